@@ -0,0 +1,270 @@
+use crate::notifiers::{self, NotifierTrait};
+use crate::LibError;
+use serde::Serialize;
+use std::cell::Cell;
+use std::fmt;
+use std::time::Duration;
+
+// Operational alerts, about failing checks rather than availability
+
+/// Why checks fail, so that the recipient of an alert knows where to look.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertKind {
+    /// Anything from DNS resolution error, to connection time out
+    Network,
+    /// The provider API answered, but with an error or an unexpected content
+    Api,
+    /// Rejected credentials, or invalid settings, which retrying does not fix
+    Configuration,
+    /// Any other failure, like a storage one
+    Other,
+    /// The provider has not been checked successfully for too long
+    Stale,
+    /// Checks succeed again, after an alert
+    Recovered,
+}
+
+impl AlertKind {
+    /// Classifies a failure from the first library error in its chain.
+    pub fn of(error: &anyhow::Error) -> Self {
+        let Some(error) = error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<LibError>())
+        else {
+            return AlertKind::Other;
+        };
+        match error {
+            LibError::RequestError { .. } => AlertKind::Network,
+            LibError::ApiStatusError { status, .. }
+                if *status == http::StatusCode::UNAUTHORIZED
+                    || *status == http::StatusCode::FORBIDDEN =>
+            {
+                AlertKind::Configuration
+            }
+            LibError::ApiError { .. }
+            | LibError::ApiStatusError { .. }
+            | LibError::JsonError { .. } => AlertKind::Api,
+            LibError::EnvError { .. }
+            | LibError::SecretFileError { .. }
+            | LibError::ValueError { .. }
+            | LibError::UnknownServer { .. }
+            | LibError::AmbiguousServer { .. }
+            | LibError::UnknownNotifier { .. }
+            | LibError::UnknownProvider { .. } => AlertKind::Configuration,
+            _ => AlertKind::Other,
+        }
+    }
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AlertKind::Network => "network",
+            AlertKind::Api => "api",
+            AlertKind::Configuration => "configuration",
+            AlertKind::Other => "other",
+            AlertKind::Stale => "stale",
+            AlertKind::Recovered => "recovered",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// OperationalAlert holds an error event of a provider, sent through
+/// `notifier::alert`, distinct from the availability notifications.
+#[derive(Serialize)]
+pub struct OperationalAlert {
+    pub provider_name: String,
+    pub kind: AlertKind,
+    /// Failed checks in a row, including the one which raised the alert,
+    /// or before the recovery
+    pub consecutive_failures: u32,
+    /// Error chain of the last failure, empty on recovery
+    pub message: String,
+}
+
+impl OperationalAlert {
+    /// Builds the alert of a failed check
+    pub fn from_error(
+        provider_name: &str,
+        error: &anyhow::Error,
+        consecutive_failures: u32,
+    ) -> Self {
+        Self {
+            provider_name: provider_name.to_string(),
+            kind: AlertKind::of(error),
+            consecutive_failures,
+            message: format!("{error:#}"),
+        }
+    }
+
+    /// Builds the alert telling that the provider was last checked successfully too long ago
+    pub fn stale(provider_name: &str, last_success: u64, consecutive_failures: u32) -> Self {
+        let age = Duration::from_secs(crate::storage::get_timestamp().saturating_sub(last_success));
+        Self {
+            provider_name: provider_name.to_string(),
+            kind: AlertKind::Stale,
+            consecutive_failures,
+            message: format!("last successful check {}", crate::format_age(age)),
+        }
+    }
+
+    /// Builds the alert telling that checks succeed again
+    pub fn recovered(provider_name: &str, consecutive_failures: u32) -> Self {
+        Self {
+            provider_name: provider_name.to_string(),
+            kind: AlertKind::Recovered,
+            consecutive_failures,
+            message: String::new(),
+        }
+    }
+
+    /// Builds an instance with dummy values for testing
+    pub(crate) fn get_dummy() -> Self {
+        Self {
+            provider_name: "dummy_provider".into(),
+            kind: AlertKind::Network,
+            consecutive_failures: 3,
+            message: "Network error".into(),
+        }
+    }
+
+    /// Tells if the alert is a recovery, which closes a previous alert
+    pub fn is_recovery(&self) -> bool {
+        self.kind == AlertKind::Recovered
+    }
+
+    /// Serializes to json
+    #[cfg(any(feature = "simple", feature = "exec"))]
+    pub(crate) fn to_json(&self) -> Result<String, LibError> {
+        Ok(serde_json::to_string(&self)?)
+    }
+}
+
+impl fmt::Display for OperationalAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let provider_name = &self.provider_name;
+        let failures = self.consecutive_failures;
+        match self.kind {
+            AlertKind::Recovered => write!(
+                f,
+                "Checks of {provider_name} succeed again, after {failures} failures"
+            ),
+            AlertKind::Stale => write!(
+                f,
+                "Data of {provider_name} is stale, after {failures} failures: {}",
+                self.message
+            ),
+            kind => write!(
+                f,
+                "Checks of {provider_name} failed {failures} times in a row ({kind} error): {}",
+                self.message
+            ),
+        }
+    }
+}
+
+/// Sends operational alerts through a dedicated notifier, once checks failed
+/// `threshold` times in a row, or right away on a permanent failure, then once
+/// they succeed again. The count is only kept for the lifetime of the instance.
+/// Optionally warns once the last successful check, which is stored, gets too old.
+pub struct AlertPolicy {
+    notifier: Box<dyn NotifierTrait>,
+    threshold: u32,
+    failures: Cell<u32>,
+    alerted: Cell<bool>,
+    stale_after: Option<Duration>,
+    stale_alerted: Cell<bool>,
+}
+
+impl AlertPolicy {
+    /// Builds an instance, verifying that the notifier can send alerts
+    pub fn new(notifier_name: &str, threshold: u32) -> Result<Self, LibError> {
+        if threshold == 0 {
+            return Err(LibError::ValueError {
+                name: "alert threshold".into(),
+                value: threshold.to_string(),
+            });
+        }
+        let notifier = notifiers::Factory::from_env_by_name(notifier_name)?;
+        if !notifier.supports_alerts() {
+            return Err(LibError::ValueError {
+                name: "notifier without operational alerts".into(),
+                value: notifier_name.to_string(),
+            });
+        }
+        Ok(Self {
+            notifier,
+            threshold,
+            failures: Cell::new(0),
+            alerted: Cell::new(false),
+            stale_after: None,
+            stale_alerted: Cell::new(false),
+        })
+    }
+
+    /// Warns when the last successful check is older than this, during an outage.
+    pub fn with_stale_after(mut self, stale_after: Option<Duration>) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// Gets the name of the notifier the alerts are sent through
+    pub fn notifier_name(&self) -> &'static str {
+        self.notifier.name()
+    }
+
+    /// Counts a failed check, and gets the alert to send, if any.
+    pub fn on_failure(
+        &self,
+        provider_name: &str,
+        error: &anyhow::Error,
+        permanent: bool,
+    ) -> Option<OperationalAlert> {
+        let failures = self.failures.get() + 1;
+        self.failures.set(failures);
+        if self.alerted.get() || (failures < self.threshold && !permanent) {
+            return None;
+        }
+        self.alerted.set(true);
+        Some(OperationalAlert::from_error(provider_name, error, failures))
+    }
+
+    /// Gets the staleness warning to send after a failed check, if any, given when
+    /// the provider was last checked successfully. It is only sent once per outage.
+    pub fn on_stale(
+        &self,
+        provider_name: &str,
+        last_success: Option<u64>,
+        now: u64,
+    ) -> Option<OperationalAlert> {
+        let stale_after = self.stale_after?;
+        let last_success = last_success?;
+        if self.stale_alerted.get() || now.saturating_sub(last_success) < stale_after.as_secs() {
+            return None;
+        }
+        self.stale_alerted.set(true);
+        Some(OperationalAlert::stale(
+            provider_name,
+            last_success,
+            self.failures.get(),
+        ))
+    }
+
+    /// Resets the count after a successful check, and gets the recovery alert to send, if any.
+    pub fn on_success(&self, provider_name: &str) -> Option<OperationalAlert> {
+        let failures = self.failures.replace(0);
+        let alerted = self.alerted.replace(false);
+        let stale_alerted = self.stale_alerted.replace(false);
+        if !alerted && !stale_alerted {
+            return None;
+        }
+        Some(OperationalAlert::recovered(provider_name, failures))
+    }
+
+    /// Sends an alert through the notifier
+    pub fn send(&self, alert: &OperationalAlert) -> Result<(), LibError> {
+        self.notifier.alert(alert)
+    }
+}
@@ -0,0 +1,237 @@
+use crate::LibError;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+
+// Shared AWS credentials resolution and request signing (signature version 4)
+
+/// Common environment variables holding static credentials.
+const ENV_AWS_ACCESS_KEY_ID: &str = "AWS_ACCESS_KEY_ID";
+const ENV_AWS_SECRET_ACCESS_KEY: &str = "AWS_SECRET_ACCESS_KEY";
+const ENV_AWS_SESSION_TOKEN: &str = "AWS_SESSION_TOKEN";
+
+/// Common environment variable to select the profile of the shared credentials file.
+const ENV_AWS_PROFILE: &str = "AWS_PROFILE";
+
+/// Common environment variable to override the location of the shared credentials file.
+const ENV_AWS_SHARED_CREDENTIALS_FILE: &str = "AWS_SHARED_CREDENTIALS_FILE";
+
+/// Profile used when none is selected.
+const AWS_DEFAULT_PROFILE: &str = "default";
+
+/// Holds the credentials used to sign requests.
+pub struct Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl Credentials {
+    /// Builds static credentials, without relying on environment variables.
+    pub fn new(access_key_id: &str, secret_access_key: &str, session_token: Option<&str>) -> Self {
+        Self {
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+            session_token: session_token.map(str::to_string),
+        }
+    }
+
+    /// Resolves credentials the way the AWS tools do, in order :
+    /// - the `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY` environment variables
+    /// - the `AWS_PROFILE` (or `default`) profile of the shared credentials file
+    pub fn from_env() -> Result<Self, LibError> {
+        if let Some(access_key_id) = crate::get_env_var_option(ENV_AWS_ACCESS_KEY_ID) {
+            return Ok(Self {
+                access_key_id,
+                secret_access_key: crate::get_env_var(ENV_AWS_SECRET_ACCESS_KEY)?,
+                session_token: crate::get_env_var_option(ENV_AWS_SESSION_TOKEN),
+            });
+        }
+
+        let profile = crate::get_env_var_default(ENV_AWS_PROFILE, AWS_DEFAULT_PROFILE);
+        Self::from_shared_file(&profile)
+    }
+
+    /// Gets the location of the shared credentials file, usually `~/.aws/credentials`.
+    fn get_shared_file_path() -> Result<PathBuf, LibError> {
+        if let Some(path) = crate::get_env_var_option(ENV_AWS_SHARED_CREDENTIALS_FILE) {
+            return Ok(PathBuf::from(path));
+        }
+        let home = crate::get_env_var("HOME").or_else(|_| crate::get_env_var("USERPROFILE"))?;
+        Ok(PathBuf::from(home).join(".aws").join("credentials"))
+    }
+
+    /// Reads the credentials of a profile from the shared credentials file (ini format).
+    fn from_shared_file(profile: &str) -> Result<Self, LibError> {
+        let path = Self::get_shared_file_path()?;
+        let content = fs::read_to_string(&path).map_err(|source| LibError::FileError {
+            path: path.clone(),
+            source,
+        })?;
+
+        let mut in_profile = false;
+        let mut access_key_id = None;
+        let mut secret_access_key = None;
+        let mut session_token = None;
+        for line in content.lines().map(str::trim) {
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                in_profile = section.trim() == profile;
+                continue;
+            }
+            if !in_profile {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = Some(value.trim().to_string());
+            match key.trim() {
+                "aws_access_key_id" => access_key_id = value,
+                "aws_secret_access_key" => secret_access_key = value,
+                "aws_session_token" => session_token = value,
+                _ => {}
+            }
+        }
+
+        match (access_key_id, secret_access_key) {
+            (Some(access_key_id), Some(secret_access_key)) => Ok(Self {
+                access_key_id,
+                secret_access_key,
+                session_token,
+            }),
+            _ => Err(LibError::ValueError {
+                name: format!("no aws credentials for profile {profile} in"),
+                value: path.display().to_string(),
+            }),
+        }
+    }
+}
+
+/// Percent-encodes a string as required by the signature (RFC 3986 unreserved characters).
+pub fn uri_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Builds an `application/x-www-form-urlencoded` body, with the encoding the signature expects.
+pub fn encode_form(params: &[(&str, &str)]) -> String {
+    params
+        .iter()
+        .map(|(key, value)| format!("{}={}", uri_encode(key), uri_encode(value)))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+/// Computes a keyed hash.
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    // hmac accepts keys of any size, so this cannot fail
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts any key size");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Converts bytes to lowercase hexadecimal.
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Signs a POST request to the root path of a regional service endpoint,
+/// and returns the headers to add to the request (the host header is set by the client).
+pub fn sign_post(
+    credentials: &Credentials,
+    service: &str,
+    region: &str,
+    host: &str,
+    content_type: &str,
+    body: &str,
+) -> Vec<(&'static str, String)> {
+    let now = Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = to_hex(&Sha256::digest(body));
+
+    // headers must be sorted by name
+    let mut headers = vec![
+        ("content-type", content_type.to_string()),
+        ("host", host.to_string()),
+        ("x-amz-date", amz_date.clone()),
+    ];
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<&str>>()
+        .join(";");
+    let canonical_request =
+        format!("POST\n/\n\n{canonical_headers}\n{signed_headers}\n{payload_hash}");
+
+    let scope = format!("{date}/{region}/{service}/aws4_request");
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        to_hex(&Sha256::digest(canonical_request))
+    );
+
+    let key = format!("AWS4{}", credentials.secret_access_key);
+    let key = hmac_sha256(key.as_bytes(), &date);
+    let key = hmac_sha256(&key, region);
+    let key = hmac_sha256(&key, service);
+    let key = hmac_sha256(&key, "aws4_request");
+    let signature = to_hex(&hmac_sha256(&key, &string_to_sign));
+
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+        credentials.access_key_id
+    );
+
+    // the host header is the only one we do not need to set ourselves
+    headers.retain(|(name, _)| *name != "host");
+    headers.push(("authorization", authorization));
+    headers
+}
+
+/// Gets the text of every `<tag>` element of a Query API response, in document order.
+/// Elements of the same name must not be nested, which holds for the responses we read.
+pub fn xml_values<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let mut values = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        let Some(end) = rest.find(&close) else {
+            break;
+        };
+        values.push(&rest[..end]);
+        rest = &rest[end + close.len()..];
+    }
+    values
+}
+
+/// Gets the text of the first `<tag>` element of a Query API response, unescaped.
+pub fn xml_value(xml: &str, tag: &str) -> Option<String> {
+    xml_values(xml, tag).first().map(|value| {
+        value
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&")
+    })
+}
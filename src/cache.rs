@@ -0,0 +1,117 @@
+use crate::LibError;
+use http::{Method, StatusCode};
+use reqwest::blocking::{Request, Response};
+use reqwest::header::{
+    HeaderName, HeaderValue, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// On-disk cache of provider HTTP responses, revalidated with conditional requests
+
+/// Process-wide cache directory, the cache being disabled by default.
+static DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Selects the directory where the responses of every request sent afterwards are cached,
+/// or disables the cache.
+pub fn set_dir(dir: Option<PathBuf>) {
+    *DIR.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = dir;
+}
+
+/// Gets the current cache directory, if the cache is enabled.
+fn get_dir() -> Option<PathBuf> {
+    DIR.lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// A cached response, with the validators the server provided for it.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CachedResponse {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    content_type: Option<String>,
+    body: String,
+}
+
+impl CachedResponse {
+    /// Loads the cached response of a request, if any. An unreadable entry is
+    /// deemed missing, so that the response is simply fetched and cached again.
+    pub(crate) fn load(path: &Path) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Asks the server to only send the response again if it changed.
+    pub(crate) fn add_validators(&self, request: &mut Request) {
+        let headers = request.headers_mut();
+        let validators = [
+            (IF_NONE_MATCH, &self.etag),
+            (IF_MODIFIED_SINCE, &self.last_modified),
+        ];
+        for (name, value) in validators {
+            if let Some(value) = value.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                headers.insert(name, value);
+            }
+        }
+    }
+
+    /// Rebuilds the response, as a successful one, for a request which was not modified.
+    pub(crate) fn into_response(self) -> Result<Response, LibError> {
+        crate::recording::build_response(StatusCode::OK.as_u16(), self.content_type, self.body)
+    }
+}
+
+/// Gets the path of the cached response of a request, if the cache is enabled.
+/// Only GET requests are cached, and they are keyed by their url.
+pub(crate) fn get_path(request: &Request) -> Option<PathBuf> {
+    if request.method() != Method::GET {
+        return None;
+    }
+    let dir = get_dir()?;
+    let url = request.url();
+    let hash = Sha256::digest(url.as_str());
+    let host = url.host_str().unwrap_or("unknown");
+    Some(dir.join(format!("{host}-{hash:x}.json")))
+}
+
+/// Caches a successful response which has validators, and gives it back to be used.
+/// Responses without any validator cannot be revalidated, so they are left as they are.
+pub(crate) fn store(path: &Path, response: Response) -> Result<Response, LibError> {
+    let header = |name: HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let etag = header(ETAG);
+    let last_modified = header(LAST_MODIFIED);
+    if !response.status().is_success() || (etag.is_none() && last_modified.is_none()) {
+        return Ok(response);
+    }
+
+    let cached = CachedResponse {
+        url: response.url().to_string(),
+        etag,
+        last_modified,
+        content_type: header(CONTENT_TYPE),
+        body: response.text()?,
+    };
+    let json = serde_json::to_string_pretty(&cached)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|source| LibError::FileError {
+            path: dir.into(),
+            source,
+        })?;
+    }
+    fs::write(path, json).map_err(|source| LibError::FileError {
+        path: path.into(),
+        source,
+    })?;
+    cached.into_response()
+}
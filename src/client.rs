@@ -0,0 +1,171 @@
+use crate::LibError;
+use reqwest::blocking::Client;
+use reqwest::{Certificate, NoProxy, Proxy};
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+// Shared HTTP client
+
+/// Common environment variable to select a proxy for plain http requests.
+const ENV_HTTP_PROXY: &str = "HTTP_PROXY";
+
+/// Common environment variable to select a proxy for https requests.
+const ENV_HTTPS_PROXY: &str = "HTTPS_PROXY";
+
+/// Common environment variable to bypass the proxies for some hosts.
+const ENV_NO_PROXY: &str = "NO_PROXY";
+
+/// Common environment variable to trust an additional PEM certificate bundle.
+const ENV_HTTP_CA_BUNDLE: &str = "HTTP_CA_BUNDLE";
+
+/// Common environment variable to bound the time to establish a connection, in seconds.
+const ENV_HTTP_CONNECT_TIMEOUT: &str = "HTTP_CONNECT_TIMEOUT";
+
+/// Common environment variable to bound the total time of a request, in seconds.
+const ENV_HTTP_TIMEOUT: &str = "HTTP_TIMEOUT";
+
+/// Common environment variable to limit the idle connections kept per host.
+const ENV_HTTP_POOL_MAX_IDLE: &str = "HTTP_POOL_MAX_IDLE";
+
+/// Process-wide opt-in to disable TLS certificate verification.
+static INSECURE: AtomicBool = AtomicBool::new(false);
+
+/// Disables (or re-enables) TLS certificate verification for every client built afterwards.
+pub fn set_insecure(insecure: bool) {
+    INSECURE.store(insecure, Ordering::Relaxed);
+}
+
+/// Gets an environment variable, falling back to its lowercase variant as curl does.
+fn get_proxy_env_var(name: &str) -> Option<String> {
+    crate::get_env_var_option(name)
+        .or_else(|| crate::get_env_var_option(&name.to_lowercase()))
+        .filter(|value| !value.is_empty())
+}
+
+/// Builds the prefix of the environment variables specific to a handler,
+/// for example `HETZNER_AUCTION` for `hetzner-auction`.
+fn get_env_prefix(name: &str) -> String {
+    name.to_uppercase().replace('-', "_")
+}
+
+/// Gets a numeric setting, the handler specific variable taking precedence over the global one.
+fn get_numeric_env_var<T: std::str::FromStr>(
+    prefix: Option<&str>,
+    name: &str,
+) -> Result<Option<T>, LibError> {
+    let specific = prefix.map(|prefix| format!("{prefix}_{name}"));
+    let found = specific
+        .as_ref()
+        .and_then(|specific| {
+            crate::get_env_var_option(specific).map(|value| (specific.as_str(), value))
+        })
+        .or_else(|| crate::get_env_var_option(name).map(|value| (name, value)));
+
+    found
+        .map(|(name, value)| {
+            value.parse::<T>().map_err(|_| LibError::ValueError {
+                name: name.to_string(),
+                value,
+            })
+        })
+        .transpose()
+}
+
+/// Holds everything needed to build a configured HTTP client.
+pub struct ClientConfig {
+    http_proxy: Option<String>,
+    https_proxy: Option<String>,
+    ca_bundle: Option<String>,
+    insecure: bool,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    pool_max_idle: Option<usize>,
+}
+
+impl ClientConfig {
+    /// Builds an instance from environment variables and the process-wide TLS mode.
+    pub fn from_env() -> Result<Self, LibError> {
+        Self::from_env_for(None)
+    }
+
+    /// Same as above, but the limits can be overridden by variables prefixed
+    /// with the handler name, for example `OVH_HTTP_TIMEOUT` over `HTTP_TIMEOUT`.
+    pub fn from_env_for(name: Option<&str>) -> Result<Self, LibError> {
+        let prefix = name.map(get_env_prefix);
+        let prefix = prefix.as_deref();
+        Ok(Self {
+            http_proxy: get_proxy_env_var(ENV_HTTP_PROXY),
+            https_proxy: get_proxy_env_var(ENV_HTTPS_PROXY),
+            ca_bundle: crate::get_env_var_option(ENV_HTTP_CA_BUNDLE),
+            insecure: INSECURE.load(Ordering::Relaxed),
+            connect_timeout: get_numeric_env_var(prefix, ENV_HTTP_CONNECT_TIMEOUT)?
+                .map(Duration::from_secs),
+            timeout: get_numeric_env_var(prefix, ENV_HTTP_TIMEOUT)?.map(Duration::from_secs),
+            pool_max_idle: get_numeric_env_var(prefix, ENV_HTTP_POOL_MAX_IDLE)?,
+        })
+    }
+
+    /// Builds a proxy for the given scheme, honoring the `NO_PROXY` exclusions.
+    fn build_proxy(
+        url: &str,
+        builder: fn(&str) -> reqwest::Result<Proxy>,
+    ) -> Result<Proxy, LibError> {
+        let no_proxy = get_proxy_env_var(ENV_NO_PROXY).and_then(|list| NoProxy::from_string(&list));
+        Ok(builder(url)?.no_proxy(no_proxy))
+    }
+
+    /// Builds the actual client.
+    pub fn build(&self) -> Result<Client, LibError> {
+        // we handle proxies ourselves so that the behaviour is the same everywhere
+        let mut builder = Client::builder().no_proxy();
+
+        if let Some(url) = &self.http_proxy {
+            builder = builder.proxy(Self::build_proxy(url, |url| Proxy::http(url))?);
+        }
+
+        if let Some(url) = &self.https_proxy {
+            builder = builder.proxy(Self::build_proxy(url, |url| Proxy::https(url))?);
+        }
+
+        if let Some(path) = &self.ca_bundle {
+            let pem = fs::read(path).map_err(|source| LibError::FileError {
+                path: path.into(),
+                source,
+            })?;
+            let certificates = Certificate::from_pem_bundle(&pem)?;
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if self.insecure {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+
+        // without any setting, the reqwest default timeout applies
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        if let Some(pool_max_idle) = self.pool_max_idle {
+            builder = builder.pool_max_idle_per_host(pool_max_idle);
+        }
+
+        Ok(builder.build()?)
+    }
+}
+
+/// Builds a client configured from the environment, to be used by every handler.
+pub fn build_client() -> Result<Client, LibError> {
+    ClientConfig::from_env()?.build()
+}
+
+/// Builds a client configured from the environment, with the limits specific to a provider.
+pub fn build_client_for(name: &str) -> Result<Client, LibError> {
+    ClientConfig::from_env_for(Some(name))?.build()
+}
@@ -0,0 +1,181 @@
+use crate::LibError;
+use std::io::{ErrorKind, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// External commands
+
+/// How often we look at the child process while waiting for it.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Runs some work on its own thread, its result being received once it is done.
+/// The thread is left behind if the result is not awaited until then.
+fn in_background<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> Receiver<T> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(work());
+    });
+    receiver
+}
+
+/// Builds a platform shell invocation, so that users can provide full command lines.
+fn shell(command_line: &str) -> Command {
+    if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", command_line]);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.args(["-c", command_line]);
+        command
+    }
+}
+
+/// Builds a platform shell invocation, passing arguments to the command line.
+/// With `sh`, they are passed as positional parameters, so that they are never interpreted.
+fn shell_with_args(command_line: &str, args: &[String]) -> Command {
+    if cfg!(windows) {
+        let quoted: Vec<String> = args
+            .iter()
+            .map(|arg| format!("\"{}\"", arg.replace('"', "")))
+            .collect();
+        shell(&format!("{command_line} {}", quoted.join(" ")))
+    } else {
+        let mut command = shell(&format!("{command_line} \"$@\""));
+        // $0 of the command line
+        command.arg("dsaw");
+        command.args(args);
+        command
+    }
+}
+
+/// Runs a command line through the shell, feeding `input` on its stdin.
+///
+/// The child is killed if it does not complete within `timeout`,
+/// and a non-zero exit status is an error including its stderr.
+///
+/// Returns the standard output of the command.
+pub fn run(
+    command_line: &str,
+    input: &str,
+    envs: &[(&str, String)],
+    timeout: Duration,
+) -> Result<String, LibError> {
+    run_command(shell(command_line), command_line, input, envs, timeout)
+}
+
+/// Same as above, with arguments appended to the command line.
+pub fn run_with_args(
+    command_line: &str,
+    args: &[String],
+    input: &str,
+    envs: &[(&str, String)],
+    timeout: Duration,
+) -> Result<String, LibError> {
+    run_command(
+        shell_with_args(command_line, args),
+        command_line,
+        input,
+        envs,
+        timeout,
+    )
+}
+
+/// Runs a shell invocation, as described above.
+fn run_command(
+    mut command: Command,
+    command_line: &str,
+    input: &str,
+    envs: &[(&str, String)],
+    timeout: Duration,
+) -> Result<String, LibError> {
+    let mut child = command
+        .envs(envs.iter().map(|(name, value)| (name, value)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // drain outputs in the background so that the child never blocks on a full pipe
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let stdout_reader = in_background(move || {
+        let mut buffer = String::new();
+        stdout.read_to_string(&mut buffer).map(|_| buffer)
+    });
+    let stderr_reader = in_background(move || {
+        let mut buffer = String::new();
+        stderr.read_to_string(&mut buffer).map(|_| buffer)
+    });
+
+    // feed the input aside, so that a child not reading it still times out,
+    // then close stdin by dropping it so that the child sees EOF
+    let started = Instant::now();
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_string();
+    let stdin_writer = in_background(move || match stdin.write_all(input.as_bytes()) {
+        // the child may legitimately exit without reading its input
+        Err(error) if error.kind() == ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    });
+
+    // wait for completion, or kill it if it takes too long
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() > timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(LibError::CommandError {
+                message: format!("`{command_line}` timed out after {timeout:?}"),
+            });
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    // background processes started by the command may hold its pipes open after it exited,
+    // so they are only waited for until the timeout, and left behind past it
+    let remaining = || timeout.saturating_sub(started.elapsed()).max(POLL_INTERVAL);
+    let held_open = || LibError::CommandError {
+        message: format!(
+            "`{command_line}` exited, but its pipes were still held open after {timeout:?} \
+            (by a background process ?)"
+        ),
+    };
+    let stdout = stdout_reader
+        .recv_timeout(remaining())
+        .map_err(|_| held_open())??;
+    let stderr = stderr_reader
+        .recv_timeout(remaining())
+        .ok()
+        .and_then(Result::ok)
+        .unwrap_or_default();
+
+    if !status.success() {
+        return Err(LibError::CommandError {
+            message: format!("`{command_line}` failed with {status}: {}", stderr.trim()),
+        });
+    }
+
+    stdin_writer
+        .recv_timeout(remaining())
+        .map_err(|_| held_open())??;
+
+    Ok(stdout)
+}
+
+/// Parses a timeout expressed in seconds.
+pub fn parse_timeout(name: &str, value: &str) -> Result<Duration, LibError> {
+    value
+        .parse::<u64>()
+        .ok()
+        .filter(|seconds| *seconds > 0)
+        .map(Duration::from_secs)
+        .ok_or_else(|| LibError::ValueError {
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+}
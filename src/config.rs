@@ -0,0 +1,354 @@
+use crate::LibError;
+use crate::{client, notifiers, providers, quiet::QuietHours, storage::CheckResultStorage};
+use anyhow::{self, Context};
+use colored::Colorize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::{env, fs, path};
+
+// Configuration helpers
+
+/// Commented example of every environment variable, grouped by handler.
+pub const EXAMPLE: &str = include_str!("../config.example.env");
+
+/// Environment variables loaded from a file, in the `KEY=VALUE` format of the example configuration.
+/// Empty lines and `#` comments are ignored, and values can be quoted.
+/// Variables following a `[name]` line belong to the profile of that name, and only apply
+/// when it is selected, overriding the variables found before any profile.
+pub struct EnvFile {
+    path: String,
+    profile: Option<String>,
+    vars: BTreeMap<String, String>,
+    /// the variables of every profile, on their own
+    profiles: BTreeMap<String, BTreeMap<String, String>>,
+    /// the variables actually set in the process environment
+    applied: BTreeSet<String>,
+}
+
+impl EnvFile {
+    /// Reads and parses a file, without touching the process environment,
+    /// with the variables of the selected profile, which must exist in the file.
+    pub fn load(path: &str, profile: Option<&str>) -> Result<Self, LibError> {
+        let text = fs::read_to_string(path).map_err(|source| LibError::FileError {
+            path: path.into(),
+            source,
+        })?;
+        let mut vars = BTreeMap::new();
+        let mut profiles: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+        let mut section: Option<&str> = None;
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                let name = name.trim();
+                if name.is_empty() {
+                    return Err(LibError::ValueError {
+                        name: format!("{path} line {}", index + 1),
+                        value: line.to_string(),
+                    });
+                }
+                profiles.entry(name.to_string()).or_default();
+                section = Some(name);
+                continue;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (name, value) = line
+                .split_once('=')
+                .map(|(name, value)| (name.trim(), value.trim()))
+                .filter(|(name, _)| {
+                    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                })
+                .ok_or_else(|| LibError::ValueError {
+                    name: format!("{path} line {}", index + 1),
+                    value: line.to_string(),
+                })?;
+            let value = Self::unquote(value).to_string();
+            match section.and_then(|section| profiles.get_mut(section)) {
+                None => vars.insert(name.to_string(), value),
+                Some(section_vars) => section_vars.insert(name.to_string(), value),
+            };
+        }
+        let mut env_file = Self {
+            path: path.to_string(),
+            profile: profile.map(str::to_string),
+            vars,
+            profiles,
+            applied: BTreeSet::new(),
+        };
+        if let Some(profile) = profile {
+            let overrides = env_file.profile_vars(profile)?.clone();
+            env_file.vars.extend(overrides);
+        }
+        Ok(env_file)
+    }
+
+    /// Gets the variables of a profile on their own, like the overrides of a single job.
+    pub fn profile_vars(&self, profile: &str) -> Result<&BTreeMap<String, String>, LibError> {
+        self.profiles
+            .get(profile)
+            .ok_or_else(|| LibError::ValueError {
+                name: format!("profile not found in {}", self.path),
+                value: profile.to_string(),
+            })
+    }
+
+    /// Removes the quotes surrounding a value, if any
+    fn unquote(value: &str) -> &str {
+        for quote in ['"', '\''] {
+            if let Some(inner) = value
+                .strip_prefix(quote)
+                .and_then(|value| value.strip_suffix(quote))
+            {
+                return inner;
+            }
+        }
+        value
+    }
+
+    /// Gets the path of the file.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Gets the selected profile, if any.
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Sets the variables in the process environment. Variables which were already defined
+    /// before the first file was applied take precedence, and are left untouched.
+    /// Variables set by the previous file, and missing from this one, are removed.
+    pub fn apply(&mut self, previous: Option<&EnvFile>) {
+        let empty = BTreeSet::new();
+        let owned = previous.map_or(&empty, |previous| &previous.applied);
+        for name in owned.iter() {
+            if !self.vars.contains_key(name) {
+                env::remove_var(name);
+            }
+        }
+        for (name, value) in self.vars.iter() {
+            if owned.contains(name) || env::var_os(name).is_none() {
+                env::set_var(name, value);
+                self.applied.insert(name.clone());
+            }
+        }
+    }
+
+    /// Displays a value, unless it is a secret
+    fn display(name: &str, value: &str) -> String {
+        match crate::is_secret_env_var(name) {
+            true => "***".to_string(),
+            false => format!("`{value}`"),
+        }
+    }
+
+    /// Describes the variables which changed since the previous file, one per line.
+    pub fn diff(&self, previous: &EnvFile) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (name, value) in self.vars.iter() {
+            match previous.vars.get(name) {
+                None => lines.push(format!("added {name}={}", Self::display(name, value))),
+                Some(old) if old != value => lines.push(format!(
+                    "changed {name}={} (was {})",
+                    Self::display(name, value),
+                    Self::display(name, old)
+                )),
+                Some(_) => {}
+            }
+        }
+        for name in previous.vars.keys() {
+            if !self.vars.contains_key(name) {
+                lines.push(format!("removed {name}"));
+            }
+        }
+        lines
+    }
+}
+
+/// An implementation for the InitRunner, which scaffolds a configuration
+pub struct InitRunner;
+
+impl InitRunner {
+    /// Writes the example configuration to the given file, or to stdout.
+    /// An existing file is never overwritten.
+    pub fn init(output: &Option<String>) -> anyhow::Result<()> {
+        let Some(output) = output else {
+            print!("{EXAMPLE}");
+            return Ok(());
+        };
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(output)
+            .with_context(|| format!("while creating configuration file {output}"))?;
+        file.write_all(EXAMPLE.as_bytes())
+            .with_context(|| format!("while writing configuration file {output}"))?;
+
+        println!("Example configuration written to {}", output.green());
+        Ok(())
+    }
+}
+
+/// An implementation for the ValidateRunner, which verifies the current configuration
+/// without querying any provider nor sending any notification.
+pub struct ValidateRunner<'a> {
+    providers: &'a Vec<String>,
+    notifiers: &'a Vec<String>,
+    storage_dir: &'a Option<String>,
+}
+
+impl<'a> ValidateRunner<'a> {
+    /// Builds an instance so that we do not endlessly repeat arguments
+    pub fn new(
+        providers: &'a Vec<String>,
+        notifiers: &'a Vec<String>,
+        storage_dir: &'a Option<String>,
+    ) -> Self {
+        Self {
+            providers,
+            notifiers,
+            storage_dir,
+        }
+    }
+
+    /// Prints the outcome of a single verification, and tells if it succeeded
+    fn report(kind: &str, name: &str, result: anyhow::Result<()>) -> bool {
+        match result {
+            Ok(()) => {
+                println!("{} {kind} {name}", "OK  ".green());
+                true
+            }
+            Err(error) => {
+                println!(
+                    "{} {kind} {name}: {}",
+                    "FAIL".red(),
+                    format!("{error:#}").red()
+                );
+                false
+            }
+        }
+    }
+
+    /// Verifies that the storage directory is an accessible and writable directory
+    fn validate_storage(storage_dir: &Option<String>) -> anyhow::Result<()> {
+        let path = match storage_dir {
+            Some(dir) => path::PathBuf::from(dir),
+            None => crate::storage::get_default_dir()
+                .context("while setting up the default storage directory")?,
+        };
+        CheckResultStorage::new(&path)?;
+        let metadata = fs::metadata(&path)?;
+        if metadata.permissions().readonly() {
+            anyhow::bail!("{} is read-only", path.display());
+        }
+        Ok(())
+    }
+
+    /// Verifies every requested item, and fails if any of them is invalid
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut valid = Self::report(
+            "http client",
+            "",
+            client::build_client().map(|_| ()).map_err(Into::into),
+        );
+
+        let default_dir = crate::dirs::get_state_dir()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_default();
+        valid &= Self::report(
+            "storage",
+            self.storage_dir.as_deref().unwrap_or(&default_dir),
+            Self::validate_storage(self.storage_dir),
+        );
+
+        for name in self.providers.iter() {
+            let result = providers::Factory::from_env_by_name(name).map(|_| ());
+            valid &= Self::report("provider", name, result.map_err(Into::into));
+        }
+
+        for name in self.notifiers.iter() {
+            let result = notifiers::Factory::from_env_by_name(name)
+                .and_then(|_| QuietHours::from_env(name))
+                .map(|_| ());
+            valid &= Self::report("notifier", name, result.map_err(Into::into));
+        }
+
+        if !valid {
+            anyhow::bail!("the configuration is invalid");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes a file of its own for a test, and gets its path
+    fn write_env_file(name: &str, content: &str) -> String {
+        let path = env::temp_dir().join(format!("dsaw-test-{}-{name}.env", std::process::id()));
+        fs::write(&path, content).unwrap();
+        path.display().to_string()
+    }
+
+    #[test]
+    fn env_file_parses_variables() {
+        let path = write_env_file(
+            "variables",
+            "# comment\n\nPLAIN=value\n  SPACED = spaced value  \nexport EXPORTED=1\n\
+             DOUBLE=\"quoted # value\"\nSINGLE='quoted'\nEMPTY=\n",
+        );
+        let env_file = EnvFile::load(&path, None).unwrap();
+        let expected: BTreeMap<String, String> = [
+            ("PLAIN", "value"),
+            ("SPACED", "spaced value"),
+            ("EXPORTED", "1"),
+            ("DOUBLE", "quoted # value"),
+            ("SINGLE", "quoted"),
+            ("EMPTY", ""),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        assert_eq!(env_file.vars, expected);
+        assert_eq!(env_file.profile(), None);
+    }
+
+    #[test]
+    fn env_file_applies_the_selected_profile() {
+        let path = write_env_file(
+            "profiles",
+            "SHARED=base\nOVERRIDDEN=base\n[staging]\nOVERRIDDEN=staging\n[production]\nONLY=prod\n",
+        );
+        let env_file = EnvFile::load(&path, None).unwrap();
+        assert_eq!(env_file.vars.len(), 2);
+        assert_eq!(env_file.vars["OVERRIDDEN"], "base");
+
+        let env_file = EnvFile::load(&path, Some("staging")).unwrap();
+        assert_eq!(env_file.vars["SHARED"], "base");
+        assert_eq!(env_file.vars["OVERRIDDEN"], "staging");
+        assert!(!env_file.vars.contains_key("ONLY"));
+        assert_eq!(env_file.profile_vars("production").unwrap()["ONLY"], "prod");
+
+        assert!(EnvFile::load(&path, Some("unknown")).is_err());
+    }
+
+    #[test]
+    fn env_file_rejects_invalid_lines() {
+        for (name, content) in [
+            ("no-equal", "NAME\n"),
+            ("no-name", "=value\n"),
+            ("bad-name", "BAD-NAME=value\n"),
+            ("empty-profile", "[ ]\n"),
+        ] {
+            let path = write_env_file(name, content);
+            assert!(EnvFile::load(&path, None).is_err(), "{content}");
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::LibError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+// Delivery reports of the notifications
+
+/// How many deliveries the history of a provider keeps.
+pub const HISTORY_LENGTH: usize = 100;
+
+/// Outcome of a notification sent through a single notifier.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeliveryReport {
+    /// Timestamp of the delivery
+    pub timestamp: u64,
+    pub notifier_name: String,
+    pub success: bool,
+    /// Time spent notifying, in milliseconds
+    pub latency_ms: u64,
+    /// HTTP status of the response, when the notifier failed with one
+    pub status: Option<u16>,
+    /// Error chain of a failed delivery
+    pub error: Option<String>,
+}
+
+impl DeliveryReport {
+    /// Describes how a notification went through a notifier.
+    pub fn new(
+        notifier_name: &str,
+        latency: Duration,
+        outcome: &anyhow::Result<()>,
+        timestamp: u64,
+    ) -> Self {
+        let error = outcome.as_ref().err();
+        Self {
+            timestamp,
+            notifier_name: notifier_name.to_string(),
+            success: error.is_none(),
+            latency_ms: latency.as_millis() as u64,
+            status: error.and_then(status_of),
+            error: error.map(|error| format!("{error:#}")),
+        }
+    }
+}
+
+/// Gets the HTTP status of a failed notification, from the first error in its chain telling it.
+fn status_of(error: &anyhow::Error) -> Option<u16> {
+    error
+        .chain()
+        .find_map(|cause| match cause.downcast_ref::<LibError>()? {
+            LibError::ApiStatusError { status, .. } => Some(status.as_u16()),
+            LibError::RequestError { source, .. } => source.status().map(|status| status.as_u16()),
+            _ => None,
+        })
+}
+
+impl fmt::Display for DeliveryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = self
+            .status
+            .map(|status| format!(", status {status}"))
+            .unwrap_or_default();
+        match &self.error {
+            None => write!(
+                f,
+                "{}: delivered in {} ms{status}",
+                self.notifier_name, self.latency_ms
+            ),
+            Some(error) => write!(
+                f,
+                "{}: failed in {} ms{status}: {error}",
+                self.notifier_name, self.latency_ms
+            ),
+        }
+    }
+}
+
+/// Adds the deliveries of a check to the history of a provider, dropping the oldest ones.
+pub fn push_history(history: &mut Vec<DeliveryReport>, reports: &[DeliveryReport]) {
+    history.extend_from_slice(reports);
+    let excess = history.len().saturating_sub(HISTORY_LENGTH);
+    history.drain(..excess);
+}
@@ -0,0 +1,239 @@
+use crate::providers::StockLevel;
+use crate::{CheckResult, LibError};
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+// Digest of notifications
+
+/// Defines when the collected changes are delivered.
+#[derive(Clone, Copy, Debug)]
+pub enum DigestWindow {
+    /// Delivers once this long has elapsed since the first collected change.
+    Duration(Duration),
+    /// Delivers once a day, at the first check after this local time.
+    Daily(NaiveTime),
+}
+
+impl DigestWindow {
+    /// Builds a window of a number of minutes, which cannot be zero.
+    pub fn from_minutes(minutes: u64) -> Result<Self, LibError> {
+        if minutes == 0 {
+            return Err(LibError::ValueError {
+                name: "digest window must be at least one minute".into(),
+                value: minutes.to_string(),
+            });
+        }
+        Ok(Self::Duration(Duration::from_secs(minutes * 60)))
+    }
+
+    /// Builds a daily window, from a local time like `18:00`.
+    pub fn from_daily(time: &str) -> Result<Self, LibError> {
+        NaiveTime::parse_from_str(time, "%H:%M")
+            .map(Self::Daily)
+            .map_err(|e| LibError::ValueError {
+                name: format!("invalid digest time ({e})"),
+                value: time.to_string(),
+            })
+    }
+
+    /// Tells if changes collected since `started` are due at `now`, both being unix timestamps.
+    pub fn is_due(&self, started: u64, now: u64) -> bool {
+        match self {
+            Self::Duration(duration) => now.saturating_sub(started) >= duration.as_secs(),
+            Self::Daily(time) => {
+                let Some(started) = Local.timestamp_opt(started as i64, 0).single() else {
+                    return true;
+                };
+                // the first occurrence of the time strictly after the start of the window
+                let mut due: Option<DateTime<Local>> = started
+                    .date_naive()
+                    .and_time(*time)
+                    .and_local_timezone(Local)
+                    .earliest();
+                if due.is_none_or(|due| due <= started) {
+                    due = (started.date_naive() + ChronoDuration::days(1))
+                        .and_time(*time)
+                        .and_local_timezone(Local)
+                        .earliest();
+                }
+                due.is_none_or(|due| now as i64 >= due.timestamp())
+            }
+        }
+    }
+}
+
+/// Changes collected for a single provider
+#[derive(Serialize, Deserialize)]
+struct DigestEntry {
+    provider_name: String,
+    available_servers: Vec<String>,
+    unavailable_servers: Vec<String>,
+    #[serde(default)]
+    last_seen_available: BTreeMap<String, u64>,
+    #[serde(default)]
+    order_urls: BTreeMap<String, String>,
+    #[serde(default)]
+    stock_levels: BTreeMap<String, StockLevel>,
+    #[serde(default)]
+    locations: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    prices: BTreeMap<String, f64>,
+    #[serde(default)]
+    previous_prices: BTreeMap<String, f64>,
+}
+
+/// Collects the changes of every check between the runners and the notifier,
+/// so that a single notification grouping them by provider is delivered per window.
+/// It is stored between invocations, so that every watcher sharing the same storage
+/// and notifier contributes to the same digest.
+#[derive(Default, Serialize, Deserialize)]
+pub struct DigestBuffer {
+    /// Timestamp of the first collected change
+    started: u64,
+    entries: Vec<DigestEntry>,
+}
+
+impl DigestBuffer {
+    /// Tells if no change was collected
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Gets the timestamp of the first collected change
+    pub fn started(&self) -> u64 {
+        self.started
+    }
+
+    /// Tells if the collected changes are due
+    pub fn is_due(&self, window: &DigestWindow, now: u64) -> bool {
+        !self.is_empty() && window.is_due(self.started, now)
+    }
+
+    /// Collects the changes of a check, the latest availability of a server replacing any previous one.
+    pub fn add(&mut self, result: &CheckResult, now: u64) {
+        if self.is_empty() {
+            self.started = now;
+        }
+
+        let index = match self
+            .entries
+            .iter()
+            .position(|entry| entry.provider_name == result.provider_name)
+        {
+            Some(index) => index,
+            None => {
+                self.entries.push(DigestEntry {
+                    provider_name: result.provider_name.clone(),
+                    available_servers: Vec::new(),
+                    unavailable_servers: Vec::new(),
+                    last_seen_available: BTreeMap::new(),
+                    order_urls: BTreeMap::new(),
+                    stock_levels: BTreeMap::new(),
+                    locations: BTreeMap::new(),
+                    prices: BTreeMap::new(),
+                    previous_prices: BTreeMap::new(),
+                });
+                self.entries.len() - 1
+            }
+        };
+        let entry = &mut self.entries[index];
+
+        for server in result.available_servers.iter() {
+            entry.unavailable_servers.retain(|known| known != server);
+            if !entry.available_servers.contains(server) {
+                entry.available_servers.push(server.clone());
+            }
+        }
+        for server in result.unavailable_servers.iter() {
+            entry.available_servers.retain(|known| known != server);
+            if !entry.unavailable_servers.contains(server) {
+                entry.unavailable_servers.push(server.clone());
+            }
+        }
+        // the first change of the window is the one which tells when the server was last seen
+        for (server, timestamp) in result.last_seen_available.iter() {
+            entry
+                .last_seen_available
+                .entry(server.clone())
+                .or_insert(*timestamp);
+        }
+        entry.order_urls.extend(result.order_urls.clone());
+        // the latest stock level is the one worth telling
+        entry.stock_levels.extend(result.stock_levels.clone());
+        entry.locations.extend(result.locations.clone());
+        entry.prices.extend(result.prices.clone());
+        // the first price of the window is the one it changed from
+        for (server, previous) in result.previous_prices.iter() {
+            entry
+                .previous_prices
+                .entry(server.clone())
+                .or_insert(*previous);
+        }
+    }
+
+    /// Builds the combined result, grouped by provider.
+    /// With several providers, servers are prefixed by their provider, like `ovh:22sk010`.
+    pub fn to_result(&self) -> CheckResult {
+        let mut entries: Vec<&DigestEntry> = self.entries.iter().collect();
+        entries.sort_by(|a, b| a.provider_name.cmp(&b.provider_name));
+
+        let names: Vec<&str> = entries
+            .iter()
+            .map(|entry| entry.provider_name.as_str())
+            .collect();
+        let mut result = CheckResult::new(&names.join(","));
+
+        let prefixed = entries.len() > 1;
+        for entry in entries {
+            let name = |server: &String| match prefixed {
+                true => format!("{}:{server}", entry.provider_name),
+                false => server.clone(),
+            };
+            result
+                .available_servers
+                .extend(entry.available_servers.iter().map(name));
+            result
+                .unavailable_servers
+                .extend(entry.unavailable_servers.iter().map(name));
+            result.last_seen_available.extend(
+                entry
+                    .last_seen_available
+                    .iter()
+                    .map(|(server, timestamp)| (name(server), *timestamp)),
+            );
+            result.order_urls.extend(
+                entry
+                    .order_urls
+                    .iter()
+                    .map(|(server, order_url)| (name(server), order_url.clone())),
+            );
+            result.stock_levels.extend(
+                entry
+                    .stock_levels
+                    .iter()
+                    .map(|(server, stock)| (name(server), *stock)),
+            );
+            result.locations.extend(
+                entry
+                    .locations
+                    .iter()
+                    .map(|(server, locations)| (name(server), locations.clone())),
+            );
+            result.prices.extend(
+                entry
+                    .prices
+                    .iter()
+                    .map(|(server, price)| (name(server), *price)),
+            );
+            result.previous_prices.extend(
+                entry
+                    .previous_prices
+                    .iter()
+                    .map(|(server, previous)| (name(server), *previous)),
+            );
+        }
+        result
+    }
+}
@@ -0,0 +1,42 @@
+use crate::LibError;
+use directories::ProjectDirs;
+use std::path::PathBuf;
+
+// Default locations of the states and of the configuration
+
+/// Name of the directory of this program, inside the platform directories.
+const APP_DIR_NAME: &str = "dsaw";
+
+/// Name of the environment file loaded by default, inside the configuration directory.
+pub const DEFAULT_ENV_FILE_NAME: &str = "dsaw.env";
+
+/// Name of the environment file loaded by default from the current directory,
+/// before the one of the configuration directory, as kept by each deployment.
+pub const LOCAL_ENV_FILE_NAME: &str = ".env";
+
+/// Gets the platform directories of this program.
+fn get_project_dirs() -> Result<ProjectDirs, LibError> {
+    ProjectDirs::from("", "", APP_DIR_NAME).ok_or_else(|| LibError::ValueError {
+        name: "home directory of the current user".into(),
+        value: String::new(),
+    })
+}
+
+/// Gets the directory where states are stored by default: `$XDG_STATE_HOME/dsaw`,
+/// which defaults to `~/.local/state/dsaw`. Platforms without a state directory use
+/// the local data one: `~/Library/Application Support/dsaw` on macOS,
+/// and `%LOCALAPPDATA%\dsaw\data` on Windows.
+pub fn get_state_dir() -> Result<PathBuf, LibError> {
+    let dirs = get_project_dirs()?;
+    Ok(dirs
+        .state_dir()
+        .unwrap_or_else(|| dirs.data_local_dir())
+        .to_path_buf())
+}
+
+/// Gets the directory where the configuration is looked for by default: `$XDG_CONFIG_HOME/dsaw`,
+/// which defaults to `~/.config/dsaw` (`~/Library/Application Support/dsaw`
+/// on macOS, and `%APPDATA%\dsaw\config` on Windows).
+pub fn get_config_dir() -> Result<PathBuf, LibError> {
+    Ok(get_project_dirs()?.config_dir().to_path_buf())
+}
@@ -0,0 +1,142 @@
+use crate::{notifiers, providers, LibError};
+use anyhow;
+use colored::Colorize;
+use std::env;
+
+// Credentials and connectivity diagnosis
+
+/// Outcome of the diagnosis of a single handler
+enum Status {
+    /// The handler answered the lightweight call
+    Ok(String),
+    /// The handler is built, but offers no call which would not notify
+    Unverified,
+    /// A required environment variable is not defined
+    NotConfigured(String),
+    /// Anything else, from invalid settings to authentication failures
+    Failed(LibError),
+}
+
+impl Status {
+    /// Classifies the failure to build a handler
+    fn from_setup_error(error: LibError) -> Self {
+        match error {
+            LibError::EnvError {
+                name,
+                source: env::VarError::NotPresent,
+            } => Status::NotConfigured(name),
+            error => Status::Failed(error),
+        }
+    }
+}
+
+/// An implementation for the DoctorRunner, which verifies credentials and connectivity
+/// of providers and notifiers without sending any notification.
+pub struct DoctorRunner<'a> {
+    providers: &'a Vec<String>,
+    notifiers: &'a Vec<String>,
+}
+
+impl<'a> DoctorRunner<'a> {
+    /// Builds an instance, every known handler being diagnosed when none is given
+    pub fn new(providers: &'a Vec<String>, notifiers: &'a Vec<String>) -> Self {
+        Self {
+            providers,
+            notifiers,
+        }
+    }
+
+    /// Builds the provider and lists its inventory, counting the known servers
+    fn diagnose_provider(name: &str) -> Status {
+        let provider = match providers::Factory::from_env_by_name(name) {
+            Ok(provider) => provider,
+            Err(error) => return Status::from_setup_error(error),
+        };
+        match provider.inventory(true) {
+            Ok(servers) => Status::Ok(format!("{} server types known", servers.len())),
+            Err(error) => Status::Failed(error),
+        }
+    }
+
+    /// Builds the notifier and verifies it, when it offers a way which does not notify
+    fn diagnose_notifier(name: &str) -> Status {
+        let notifier = match notifiers::Factory::from_env_by_name(name) {
+            Ok(notifier) => notifier,
+            Err(error) => return Status::from_setup_error(error),
+        };
+        match notifier.verify() {
+            Ok(true) => Status::Ok("credentials accepted".to_string()),
+            Ok(false) => Status::Unverified,
+            Err(error) => Status::Failed(error),
+        }
+    }
+
+    /// Prints a line of the status table, and tells if it is a failure
+    fn print_status(kind: &str, name: &str, status: Status) -> bool {
+        let failed = matches!(status, Status::Failed(_));
+        let label = format!("{kind} {name}");
+        match status {
+            Status::Ok(details) => println!("{} {label:30} {details}", "OK  ".green()),
+            Status::Unverified => println!(
+                "{} {label:30} settings are valid, but cannot be verified without notifying",
+                "OK  ".green()
+            ),
+            Status::NotConfigured(variable) => {
+                println!("{} {label:30} {variable} is not defined", "SKIP".yellow())
+            }
+            Status::Failed(error) => println!(
+                "{} {label:30} {}",
+                "FAIL".red(),
+                format!("{:#}", anyhow::Error::from(error)).red()
+            ),
+        }
+        failed
+    }
+
+    /// Turns the missing settings of an explicitly requested handler into a failure
+    fn promote(status: Status, explicit: bool) -> Status {
+        match status {
+            Status::NotConfigured(name) if explicit => Status::Failed(LibError::EnvError {
+                name,
+                source: env::VarError::NotPresent,
+            }),
+            status => status,
+        }
+    }
+
+    /// Diagnoses the handlers and prints a status table, failing if any of them failed.
+    /// Unless explicitly requested, handlers without their settings are only skipped.
+    pub fn diagnose(&self) -> anyhow::Result<()> {
+        let everything = self.providers.is_empty() && self.notifiers.is_empty();
+        let mut failed = false;
+
+        let provider_names: Vec<String> = match everything {
+            true => providers::Factory::get_available()
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            false => self.providers.clone(),
+        };
+        for name in provider_names.iter() {
+            let status = Self::promote(Self::diagnose_provider(name), !everything);
+            failed |= Self::print_status("provider", name, status);
+        }
+
+        let notifier_names: Vec<String> = match everything {
+            true => notifiers::Factory::get_available()
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            false => self.notifiers.clone(),
+        };
+        for name in notifier_names.iter() {
+            let status = Self::promote(Self::diagnose_notifier(name), !everything);
+            failed |= Self::print_status("notifier", name, status);
+        }
+
+        if failed {
+            anyhow::bail!("some handlers failed their diagnosis");
+        }
+        Ok(())
+    }
+}
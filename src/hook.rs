@@ -0,0 +1,76 @@
+use crate::{CheckResult, LibError};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+// Availability hook
+
+/// How long the hook may run before being killed.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Variables passed to the hook, in addition to the json payload on stdin.
+const ENV_DSAW_PROVIDER: &str = "DSAW_PROVIDER";
+const ENV_DSAW_SERVERS: &str = "DSAW_SERVERS";
+
+/// Runs a user-specified command line as soon as servers become available,
+/// with their references as arguments, so that it can attempt to order them.
+/// A server is not passed again until its cooldown is over, even if it flaps.
+pub struct AvailabilityHook {
+    command: String,
+    cooldown: Duration,
+}
+
+impl AvailabilityHook {
+    /// Builds a new instance, attempting to sanitize inputs
+    pub fn new(command: &str, cooldown: Duration) -> Result<Self, LibError> {
+        if command.trim().is_empty() {
+            return Err(LibError::ValueError {
+                name: "found empty hook command".into(),
+                value: command.to_string(),
+            });
+        }
+        Ok(Self {
+            command: command.to_string(),
+            cooldown,
+        })
+    }
+
+    /// Gets the available servers whose cooldown is over, given when the hook last ran for each.
+    pub fn select(
+        &self,
+        result: &CheckResult,
+        runs: &BTreeMap<String, u64>,
+        now: u64,
+    ) -> Vec<String> {
+        result
+            .available_servers
+            .iter()
+            .filter(|server| match runs.get(*server) {
+                Some(last) => now.saturating_sub(*last) >= self.cooldown.as_secs(),
+                None => true,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Describes the invocation, for dry runs.
+    pub fn describe(&self, servers: &[String]) -> String {
+        format!("{} {}", self.command, servers.join(" "))
+    }
+
+    /// Runs the command with the servers as arguments, the json serialization of the result
+    /// on its stdin, and the provider and comma-separated servers in its environment.
+    /// Returns its standard output.
+    pub fn run(&self, result: &CheckResult, servers: &[String]) -> Result<String, LibError> {
+        let envs = [
+            (ENV_DSAW_PROVIDER, result.provider_name.clone()),
+            (ENV_DSAW_SERVERS, servers.join(",")),
+        ];
+        crate::command::run_with_args(
+            &self.command,
+            servers,
+            &result.to_json()?,
+            &envs,
+            HOOK_TIMEOUT,
+        )
+    }
+}
@@ -0,0 +1,500 @@
+use crate::providers::{CheckStage, Factory, LocatedServer, ProviderTrait, ServerInfo, StockLevel};
+use crate::LibError;
+use anyhow::Context;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Saved inventories, their comparison with the current one, and their cache
+
+/// An inventory saved to a file, to be compared with a later one.
+#[derive(Serialize, Deserialize)]
+pub struct SavedInventory {
+    pub provider_name: String,
+    /// Timestamp of the inventory
+    pub timestamp: u64,
+    /// Tells if unavailable servers were listed too
+    pub all: bool,
+    pub servers: Vec<ServerInfo>,
+}
+
+impl SavedInventory {
+    /// Builds an instance from an inventory which was just listed
+    pub fn new(provider_name: &str, all: bool, servers: Vec<ServerInfo>) -> Self {
+        Self {
+            provider_name: provider_name.to_string(),
+            timestamp: crate::storage::get_timestamp(),
+            all,
+            servers,
+        }
+    }
+
+    /// Loads an inventory from a json file
+    pub fn load(path: &Path) -> Result<Self, LibError> {
+        let json = fs::read_to_string(path).map_err(|source| LibError::FileError {
+            path: path.into(),
+            source,
+        })?;
+        serde_json::from_str(&json).map_err(|source| LibError::JsonFileError {
+            path: path.into(),
+            source,
+        })
+    }
+
+    /// Saves the inventory as a json file, replacing any previous one
+    pub fn save(&self, path: &Path) -> Result<(), LibError> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json).map_err(|source| LibError::FileError {
+            path: path.into(),
+            source,
+        })
+    }
+
+    /// Finds a server by its identifier
+    pub fn find(&self, server_id: &str) -> Option<&ServerInfo> {
+        self.servers
+            .iter()
+            .find(|info| info.server_id() == server_id)
+    }
+
+    /// Compares with a later inventory, servers being matched by their identifier
+    pub fn diff<'a>(&'a self, latest: &'a SavedInventory) -> InventoryDiff<'a> {
+        InventoryDiff {
+            added: latest
+                .servers
+                .iter()
+                .filter(|info| self.find(info.server_id()).is_none())
+                .collect(),
+            removed: self
+                .servers
+                .iter()
+                .filter(|info| latest.find(info.server_id()).is_none())
+                .collect(),
+            changed: self
+                .servers
+                .iter()
+                .filter_map(|before| {
+                    latest
+                        .find(before.server_id())
+                        .filter(|after| *after != before)
+                        .map(|after| (before, after))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Differences between two inventories
+pub struct InventoryDiff<'a> {
+    pub added: Vec<&'a ServerInfo>,
+    pub removed: Vec<&'a ServerInfo>,
+    /// Servers found in both, before and after
+    pub changed: Vec<(&'a ServerInfo, &'a ServerInfo)>,
+}
+
+impl InventoryDiff<'_> {
+    /// Tells if both inventories hold the same servers
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Describes a server on a single line
+fn describe(info: &ServerInfo) -> String {
+    let price = info
+        .price
+        .map(|price| format!(" {price:.2}"))
+        .unwrap_or_default();
+    let availability = match (info.available, info.stock) {
+        (false, _) => "unavailable".to_string(),
+        (true, Some(stock)) => format!("available, {stock} stock"),
+        (true, None) => "available".to_string(),
+    };
+    let specs = info.describe_specs();
+    let specs = match specs.is_empty() {
+        true => specs,
+        false => format!(" {specs}"),
+    };
+    format!(
+        "{} {} {}{specs}{price} ({availability})",
+        info.reference, info.memory, info.storage
+    )
+}
+
+/// An implementation for the DiffRunner, which compares a saved inventory with the current one
+pub struct DiffRunner {
+    provider: Box<dyn ProviderTrait>,
+    path: PathBuf,
+    saved: SavedInventory,
+    update: bool,
+}
+
+impl DiffRunner {
+    /// Builds an instance from a saved inventory, for the provider it was saved from.
+    /// With `update`, the saved inventory is replaced by the current one after the comparison.
+    pub fn new(path: &Path, update: bool) -> anyhow::Result<Self> {
+        let saved = SavedInventory::load(path)
+            .with_context(|| format!("while loading inventory {}", path.display()))?;
+        let provider = Factory::from_env_by_name(&saved.provider_name)
+            .with_context(|| format!("while setting up provider {}", saved.provider_name))
+            .context(CheckStage::Provider)?;
+        Ok(Self {
+            provider,
+            path: path.to_path_buf(),
+            saved,
+            update,
+        })
+    }
+
+    /// Prints the servers which were added, removed or changed since the inventory was saved,
+    /// and tells if there was any. Unavailable servers are compared only if they were saved.
+    pub fn diff(&self) -> anyhow::Result<bool> {
+        println!("Working...");
+        let provider_name = self.provider.name();
+        let inventory = self
+            .provider
+            .inventory(self.saved.all)
+            .with_context(|| format!("while getting inventory for provider {provider_name}"))
+            .context(CheckStage::Provider)?;
+        let latest = SavedInventory::new(provider_name, self.saved.all, inventory);
+
+        let age = Duration::from_secs(latest.timestamp.saturating_sub(self.saved.timestamp));
+        println!(
+            "Changes of {provider_name} since {}:",
+            crate::format_age(age)
+        );
+        let diff = self.saved.diff(&latest);
+        if diff.is_empty() {
+            println!("No changes");
+        }
+        for info in diff.added.iter() {
+            println!("{} {}", "+".green(), describe(info).green());
+        }
+        for info in diff.removed.iter() {
+            println!("{} {}", "-".red(), describe(info).red());
+        }
+        for (before, after) in diff.changed.iter() {
+            println!("{} {}", "~".yellow(), describe(before).dimmed());
+            println!("  {}", describe(after).yellow());
+        }
+        let changed = !diff.is_empty();
+
+        if self.update {
+            latest
+                .save(&self.path)
+                .with_context(|| format!("while saving inventory {}", self.path.display()))?;
+        }
+        Ok(changed)
+    }
+}
+
+/// Saves the inventory of a provider as listed by `InventoryRunner`, before any filter,
+/// so that it can be compared with a later one.
+pub fn save(
+    provider_name: &str,
+    all: bool,
+    inventory: Vec<ServerInfo>,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let count = inventory.len();
+    SavedInventory::new(provider_name, all, inventory)
+        .save(path)
+        .with_context(|| format!("while saving inventory {}", path.display()))?;
+    println!("Saved {count} servers to {}", path.display());
+    Ok(())
+}
+
+/// A provider answering from an inventory saved to a file, like by `inventory --save`,
+/// without any network access nor credentials, so that a check can be reproduced.
+pub struct OfflineProvider {
+    name: &'static str,
+    inventory: SavedInventory,
+}
+
+impl OfflineProvider {
+    /// Loads an inventory which was saved from the given provider.
+    pub fn load(path: &Path, provider_name: &str) -> Result<Self, LibError> {
+        let inventory = SavedInventory::load(path)?;
+        if inventory.provider_name != provider_name {
+            return Err(LibError::ValueError {
+                name: format!("inventory of another provider than {provider_name}"),
+                value: inventory.provider_name,
+            });
+        }
+        let name = Factory::get_available()
+            .into_iter()
+            .find(|name| *name == provider_name)
+            .ok_or_else(|| LibError::UnknownProvider {
+                provider: provider_name.to_string(),
+            })?;
+        Ok(Self { name, inventory })
+    }
+}
+
+impl ProviderTrait for OfflineProvider {
+    /// Gets the name of the provider the inventory was saved from.
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Lists the saved inventory.
+    fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        Ok(self
+            .inventory
+            .servers
+            .iter()
+            .filter(|info| info.available || all)
+            .cloned()
+            .collect())
+    }
+
+    /// Checks a single server type from the saved inventory. Servers missing from
+    /// an inventory which only listed the available ones are deemed unavailable.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        match self.inventory.find(server) {
+            Some(info) => Ok(info.available),
+            None if !self.inventory.all => Ok(false),
+            None => Err(LibError::UnknownServer {
+                server: server.to_string(),
+                suggestions: Vec::new(),
+            }),
+        }
+    }
+
+    /// Checks several server types from the saved inventory.
+    fn check_many(&self, servers: &[String]) -> Result<Vec<(String, bool)>, LibError> {
+        servers
+            .iter()
+            .map(|server| Ok((server.clone(), self.check(server)?)))
+            .collect()
+    }
+
+    /// Checks several server types from the saved inventory, with their saved stock level.
+    fn check_stock(
+        &self,
+        servers: &[String],
+    ) -> Result<Vec<(String, bool, Option<StockLevel>)>, LibError> {
+        servers
+            .iter()
+            .map(|server| {
+                let available = self.check(server)?;
+                let stock = self
+                    .inventory
+                    .find(server)
+                    .and_then(|info| info.stock)
+                    .filter(|_| available);
+                Ok((server.clone(), available, stock))
+            })
+            .collect()
+    }
+
+    /// Gets the saved page where a server type can be ordered.
+    fn order_url(&self, server: &str) -> Option<String> {
+        self.inventory.find(server)?.order_url.clone()
+    }
+}
+
+/// How long inventories are reused, instead of querying the provider again.
+#[derive(Clone)]
+pub struct InventoryCache {
+    pub ttl: Duration,
+    /// Where inventories are kept between invocations, they are only kept in memory otherwise
+    pub dir: Option<PathBuf>,
+}
+
+/// Process-wide inventory cache, disabled by default.
+static CACHE: Mutex<Option<InventoryCache>> = Mutex::new(None);
+
+/// Inventories kept in memory, a single one per cache key.
+static ENTRIES: Mutex<Vec<(String, SavedInventory)>> = Mutex::new(Vec::new());
+
+/// Selects how every provider built afterwards caches its inventory, or disables the cache.
+pub fn set_cache(cache: Option<InventoryCache>) {
+    *CACHE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = cache;
+}
+
+/// Gets the current inventory cache, if it is enabled.
+fn get_cache() -> Option<InventoryCache> {
+    CACHE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Wraps a provider so that it caches its inventory, if the cache is enabled.
+pub(crate) fn with_cache(provider: Box<dyn ProviderTrait>) -> Box<dyn ProviderTrait> {
+    match get_cache() {
+        Some(cache) => {
+            // providers built with other variables, like another account, have their own inventory
+            let key = match crate::env_overrides_key() {
+                Some(overrides) => format!("{}-{overrides}", provider.name()),
+                None => provider.name().to_string(),
+            };
+            Box::new(CachedProvider {
+                provider,
+                cache,
+                key,
+            })
+        }
+        None => provider,
+    }
+}
+
+/// A provider whose whole inventory is reused for a while, by inventories and checks alike,
+/// so that they do not each query the provider within the same short window.
+struct CachedProvider {
+    provider: Box<dyn ProviderTrait>,
+    cache: InventoryCache,
+    /// Identifies the cached inventory, the provider name unless built with overrides
+    key: String,
+}
+
+impl CachedProvider {
+    /// Gets the path of the inventory kept between invocations, if any
+    fn get_path(&self) -> Option<PathBuf> {
+        let dir = self.cache.dir.as_ref()?;
+        Some(dir.join(format!("inventory-{}.json", self.key)))
+    }
+
+    /// Tells if a cached inventory can still be used
+    fn is_fresh(&self, inventory: &SavedInventory, now: u64) -> bool {
+        inventory.provider_name == self.provider.name()
+            && now.saturating_sub(inventory.timestamp) < self.cache.ttl.as_secs()
+    }
+
+    /// Gets the inventory from memory, then from disk, and only then from the provider.
+    /// Unavailable servers are always listed, so that any query can be answered.
+    fn get_inventory(&self) -> Result<Vec<ServerInfo>, LibError> {
+        let now = crate::storage::get_timestamp();
+        // not locked while querying, as an aggregate queries the cache of its providers
+        if let Some((_, entry)) = ENTRIES
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .find(|(key, entry)| *key == self.key && self.is_fresh(entry, now))
+        {
+            return Ok(entry.servers.clone());
+        }
+
+        let path = self.get_path();
+        // an unreadable inventory is deemed missing, as it is only a cache
+        let saved = path
+            .as_deref()
+            .and_then(|path| SavedInventory::load(path).ok())
+            .filter(|saved| self.is_fresh(saved, now));
+        let inventory = match saved {
+            Some(saved) => saved,
+            None => {
+                let inventory =
+                    SavedInventory::new(self.provider.name(), true, self.provider.inventory(true)?);
+                if let Some(path) = path {
+                    if let Some(dir) = path.parent() {
+                        fs::create_dir_all(dir).map_err(|source| LibError::FileError {
+                            path: dir.into(),
+                            source,
+                        })?;
+                    }
+                    inventory.save(&path)?;
+                }
+                inventory
+            }
+        };
+
+        let servers = inventory.servers.clone();
+        let mut entries = ENTRIES
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        entries.retain(|(key, _)| *key != self.key);
+        entries.push((self.key.clone(), inventory));
+        Ok(servers)
+    }
+}
+
+impl ProviderTrait for CachedProvider {
+    /// Gets the actual name of the provider.
+    fn name(&self) -> &'static str {
+        self.provider.name()
+    }
+
+    /// Collects provider inventory, from the cache if it is fresh.
+    fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        let mut inventory = self.get_inventory()?;
+        inventory.retain(|info| info.available || all);
+        Ok(inventory)
+    }
+
+    /// Checks a single server type, from the cached inventory.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        let states = self.check_stock(&[server.to_string()])?;
+        Ok(states.iter().any(|(_, available, _)| *available))
+    }
+
+    /// Checks several server types, from the cached inventory.
+    fn check_many(&self, servers: &[String]) -> Result<Vec<(String, bool)>, LibError> {
+        Ok(self
+            .check_stock(servers)?
+            .into_iter()
+            .map(|(server, available, _)| (server, available))
+            .collect())
+    }
+
+    /// Checks several server types from the cached inventory, the servers which are missing
+    /// from it being checked by the provider itself.
+    fn check_stock(
+        &self,
+        servers: &[String],
+    ) -> Result<Vec<(String, bool, Option<StockLevel>)>, LibError> {
+        let inventory = self.get_inventory()?;
+        let mut states = Vec::new();
+        let mut missing = Vec::new();
+        for server in servers {
+            match inventory.iter().find(|info| info.server_id() == server) {
+                Some(info) => states.push((
+                    server.clone(),
+                    info.available,
+                    info.stock.filter(|_| info.available),
+                )),
+                None => missing.push(server.clone()),
+            }
+        }
+        if !missing.is_empty() {
+            states.extend(self.provider.check_stock(&missing)?);
+            // keep the order of the request
+            states.sort_by_key(|(server, _, _)| servers.iter().position(|known| known == server));
+        }
+        Ok(states)
+    }
+
+    /// Checks several server types with their locations, which the cached inventory does not
+    /// hold, so that the providers which know them are queried instead.
+    fn check_locations(&self, servers: &[String]) -> Result<Vec<LocatedServer>, LibError> {
+        if self.provider.supports_locations() {
+            return self.provider.check_locations(servers);
+        }
+        Ok(self
+            .check_stock(servers)?
+            .into_iter()
+            .map(|(server, available, stock)| (server, available, stock, Vec::new()))
+            .collect())
+    }
+
+    /// Tells if the wrapped provider knows the locations of its servers.
+    fn supports_locations(&self) -> bool {
+        self.provider.supports_locations()
+    }
+
+    /// Verifies that a server reference is well formed.
+    fn validate_server(&self, server: &str) -> Result<(), LibError> {
+        self.provider.validate_server(server)
+    }
+
+    /// Gets the public page where a server type can be ordered.
+    fn order_url(&self, server: &str) -> Option<String> {
+        self.provider.order_url(server)
+    }
+}
@@ -4,62 +4,171 @@
 //! for dedicated servers inventory and availability, building `CheckResult`.
 //! It provides implementations to 'notify' about theses results, or their
 //! change compared to previous invocation.
-//! 
+//!
 //! See modules implementations for available handlers.
 
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::fmt::Display;
-use std::{env, io};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs, io};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+/// Provides the operational alerts about failing checks
+pub mod alert;
+/// Provides the shared AWS credentials resolution and request signing
+#[cfg(feature = "aws")]
+pub mod aws;
+/// Provides the on-disk cache of provider HTTP responses
+pub mod cache;
+/// Provides the shared HTTP client builder used by providers and notifiers
+pub mod client;
+/// Provides the shared external command runner used by command-based handlers
+pub mod command;
+/// Provides the scaffolding and validation of the configuration
+pub mod config;
+/// Provides the delivery reports of the notifications
+pub mod delivery;
+/// Provides the collection of changes into periodic digests
+pub mod digest;
+/// Provides the default locations of the states and of the configuration
+pub mod dirs;
+/// Provides the diagnosis of credentials and connectivity
+pub mod doctor;
+/// Provides the command run when servers become available
+pub mod hook;
+/// Provides the saved inventories, their comparison, and their cache
+pub mod inventory;
+/// Provides the latency and error metrics of provider APIs
+pub mod metrics;
 /// Provides the implementation for CheckResult notifiers
 pub mod notifiers;
+/// Provides the retries of the notifications which failed
+pub mod outbox;
+/// Provides the glob and regular expression server selection
+pub mod pattern;
 /// Provides the implementation for CheckResult providers
 pub mod providers;
+/// Provides the quiet hours during which notifications are held
+pub mod quiet;
+/// Provides the record and replay of provider HTTP traffic
+pub mod recording;
+/// Provides the configuration reload requests of long-running watches
+pub mod reload;
+/// Provides the scheduling of repeated checks
+pub mod schedule;
 /// Provides the implementation to store CheckResult hashes
 /// This is not built as a feature that could be removed, as
 /// it is at the core of the differential notification scheme.
 pub mod storage;
+/// Provides the placeholder substitution used by text templates
+pub mod template;
+/// Provides the on-demand checks requested over HTTP
+pub mod trigger;
+/// Provides the live terminal monitor of watched servers
+#[cfg(feature = "tui")]
+pub mod tui;
+/// Provides the programmatic watching of servers, for programs embedding the library
+pub mod watcher;
+
+pub use watcher::{AvailabilityEvent, Watcher};
 
 /// NotifierError enumerates all possible errors returned by this library.
 #[derive(Error, Debug)]
 pub enum LibError {
-    /// input/output errors
+    /// input/output errors, without any file involved, like socket ones
     #[error("Input/output error")]
-    // FIXME: faire marcher le #from : IOError(#[from] io::Error),
-    IOError { source: io::Error },
+    IOError {
+        #[from]
+        source: io::Error,
+    },
+
+    /// input/output errors on a file or a directory
+    #[error("File `{}` error", .path.display())]
+    FileError { path: PathBuf, source: io::Error },
 
     /// Missing or empty environment variable.
     #[error("Environment variable `{name}` error")]
     EnvError { name: String, source: env::VarError },
 
+    /// Unreadable file referenced by a `*_FILE` environment variable.
+    #[error("Secret file from variable `{name}` error")]
+    SecretFileError { name: String, source: io::Error },
+
     /// Invalid value errors
     #[error("Invalid variable `{name}` error with value `{value}`")]
     ValueError { name: String, value: String },
 
     /// Anything from DNS resolution error, to connection time out...
-    #[error("Network error")]
-    RequestError { source: reqwest::Error },
+    /// The url of the request is kept without its query, which may hold credentials.
+    #[error("Network error{}", in_context("on", .url))]
+    RequestError {
+        url: Option<String>,
+        source: reqwest::Error,
+    },
 
     /// Anything which happen on the logical request (ie. network is ok).
     #[error("API error `{message}`")]
     ApiError { message: String },
 
-    /// Anything which happen upon json serialization/deserialization.
-    #[error("Json error")]
-    JsonError { source: serde_json::Error },
+    /// Same as above, when the API answered with an unsuccessful HTTP status.
+    #[error("API error `{message}`")]
+    ApiStatusError {
+        status: http::StatusCode,
+        message: String,
+    },
+
+    /// Anything which happen upon json serialization/deserialization,
+    /// with the provider whose output could not be read, if any.
+    #[error("Json error{}", in_context("in the output of provider", .provider))]
+    JsonError {
+        provider: Option<String>,
+        source: serde_json::Error,
+    },
 
-    /// Unknown server reference.
+    /// Unreadable json from a file, like a saved inventory or a recorded response.
+    #[error("Json error in file `{}`", .path.display())]
+    JsonFileError {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+
+    /// Unknown server reference, with the closest known ones if any were found.
     #[error("Unknown server `{server}`")]
-    UnknownServer { server: String },
+    UnknownServer {
+        server: String,
+        suggestions: Vec<String>,
+    },
+
+    /// Server reference matching several servers, listed as candidates.
+    #[error("Ambiguous server `{server}`, which could be any of {candidates}")]
+    AmbiguousServer { server: String, candidates: String },
 
     // non existing handlers.
     /// Requested notifier does not exist.
     #[error("Unknown notifier `{notifier}`")]
     UnknownNotifier { notifier: String },
 
+    /// A notifier already sent as many notifications as its rate limit allows.
+    #[error("Notifier `{notifier}` rate limited")]
+    RateLimited { notifier: String },
+
+    /// External command failures, from spawning to non-zero exit.
+    #[error("Command error `{message}`")]
+    CommandError { message: String },
+
+    /// Storage directory used by another running instance.
+    #[error("Storage `{path}` is locked, another instance is already running")]
+    StorageLocked { path: String },
+
+    /// Replayed request which was never recorded.
+    #[error("No recorded response for `{method} {url}`")]
+    UnrecordedRequest { method: String, url: String },
+
     /// Requested provider does not exist.
     #[error("Unknown provider `{provider}` ")]
     UnknownProvider { provider: String },
@@ -68,21 +177,188 @@ pub enum LibError {
     #[cfg(feature = "email")]
     #[error("Email error `{message}`")]
     EmailError { message: String },
+
+    /// Mqtt error
+    #[cfg(feature = "mqtt")]
+    #[error("Mqtt error `{message}`")]
+    MqttError { message: String },
+
+    /// Kafka error
+    #[cfg(feature = "kafka")]
+    #[error("Kafka error `{message}`")]
+    KafkaError { message: String },
+
+    /// Desktop notification error
+    #[cfg(feature = "desktop")]
+    #[error("Desktop notification error `{message}`")]
+    DesktopError { message: String },
+}
+
+impl LibError {
+    /// Tells if the error is worth retrying later, like network timeouts or
+    /// provider outages, as opposed to configuration errors like invalid
+    /// credentials or unknown servers, which would fail again every time.
+    /// Errors which cannot be classified are deemed transient.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            LibError::IOError { source } | LibError::FileError { source, .. } => matches!(
+                source.kind(),
+                io::ErrorKind::Interrupted | io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+            ),
+            LibError::EnvError { .. }
+            | LibError::SecretFileError { .. }
+            | LibError::ValueError { .. }
+            | LibError::UnknownServer { .. }
+            | LibError::AmbiguousServer { .. }
+            | LibError::UnknownNotifier { .. }
+            | LibError::UnknownProvider { .. }
+            | LibError::UnrecordedRequest { .. } => false,
+            LibError::RequestError { source, .. } => !source.is_builder() && !source.is_redirect(),
+            LibError::ApiStatusError { status, .. } => {
+                status.is_server_error()
+                    || *status == http::StatusCode::REQUEST_TIMEOUT
+                    || *status == http::StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => true,
+        }
+    }
+}
+
+impl From<reqwest::Error> for LibError {
+    /// Moves the url out of the error, without its query, so that credentials
+    /// passed as parameters never show up in the messages.
+    fn from(source: reqwest::Error) -> Self {
+        let url = source.url().map(|url| {
+            let mut url = url.clone();
+            url.set_query(None);
+            let _ = url.set_password(None);
+            url.to_string()
+        });
+        LibError::RequestError {
+            url,
+            source: source.without_url(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for LibError {
+    fn from(source: serde_json::Error) -> Self {
+        LibError::JsonError {
+            provider: None,
+            source,
+        }
+    }
+}
+
+/// Formats the optional context of an error message, like ` on `url``.
+fn in_context(label: &str, value: &Option<String>) -> String {
+    match value {
+        Some(value) => format!(" {label} `{value}`"),
+        None => String::new(),
+    }
+}
+
+/// Suffixes of the secret environment variables, which can also be read from a file.
+const SECRET_ENV_VAR_SUFFIXES: &[&str] = &["_KEY", "_TOKEN", "_PASSWORD", "_SECRET"];
+
+/// Tells if an environment variable holds a secret, which should never be displayed.
+pub fn is_secret_env_var(name: &str) -> bool {
+    SECRET_ENV_VAR_SUFFIXES
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+}
+
+thread_local! {
+    /// Variables overriding the process environment on the current thread,
+    /// while building the handlers of a job.
+    static ENV_OVERRIDES: RefCell<BTreeMap<String, String>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+/// Runs `build` with variables overriding the process environment, on the current thread only,
+/// so that several jobs of the same process can build their providers and notifiers from
+/// `from_env()` with different credentials. Handlers keep what they read once built.
+pub fn with_env_overrides<T>(vars: &BTreeMap<String, String>, build: impl FnOnce() -> T) -> T {
+    let previous = ENV_OVERRIDES.with(|overrides| overrides.replace(vars.clone()));
+    let result = build();
+    ENV_OVERRIDES.with(|overrides| overrides.replace(previous));
+    result
+}
+
+/// Identifies the overrides of the current thread, if any, so that the handlers built
+/// with different variables, like other credentials, do not share what they cache.
+pub(crate) fn env_overrides_key() -> Option<String> {
+    use sha2::{Digest, Sha256};
+    ENV_OVERRIDES.with(|overrides| {
+        let overrides = overrides.borrow();
+        if overrides.is_empty() {
+            return None;
+        }
+        let mut hash = Sha256::new();
+        for (name, value) in overrides.iter() {
+            hash.update(format!("{name}={value}\n"));
+        }
+        Some(format!("{:x}", hash.finalize())[..12].to_string())
+    })
+}
+
+/// Reads an environment variable, from the overrides of the current thread first.
+fn read_env_var(name: &str) -> Result<String, env::VarError> {
+    match ENV_OVERRIDES.with(|overrides| overrides.borrow().get(name).cloned()) {
+        Some(value) => Ok(value),
+        None => env::var(name),
+    }
 }
 
-/// Utility function to get an environment variable by name and trim it
+/// Lists the names of the environment variables starting with `prefix`, overrides included, sorted.
+pub fn get_env_var_names(prefix: &str) -> Vec<String> {
+    let mut names: BTreeSet<String> = env::vars().map(|(name, _)| name).collect();
+    ENV_OVERRIDES.with(|overrides| names.extend(overrides.borrow().keys().cloned()));
+    names
+        .into_iter()
+        .filter(|name| name.starts_with(prefix))
+        .collect()
+}
+
+/// Reads a secret from the file named by the `{name}_FILE` environment variable,
+/// as container platforms mount secrets as files.
+///
+/// Returns None if the variable is not a secret, or if no file was provided.
+fn get_env_var_from_file(name: &str) -> Option<Result<String, LibError>> {
+    if !is_secret_env_var(name) {
+        return None;
+    }
+
+    let file_var = format!("{name}_FILE");
+    let path = read_env_var(&file_var).ok()?;
+    Some(
+        fs::read_to_string(path.trim())
+            .map(|content| content.trim().to_string())
+            .map_err(|source| LibError::SecretFileError {
+                name: file_var,
+                source,
+            }),
+    )
+}
+
+/// Utility function to get an environment variable by name and trim it.
+/// Secrets (`*_KEY`, `*_TOKEN`, `*_PASSWORD`, `*_SECRET`) fall back to the
+/// trimmed content of the file named by their `*_FILE` variant.
 pub fn get_env_var(name: &str) -> Result<String, LibError> {
-    env::var(name)
+    read_env_var(name)
         .map(|text| text.trim().to_string())
-        .map_err(|source| LibError::EnvError {
-            name: name.to_string(),
-            source,
+        .or_else(|source| {
+            get_env_var_from_file(name).unwrap_or_else(|| {
+                Err(LibError::EnvError {
+                    name: name.to_string(),
+                    source,
+                })
+            })
         })
 }
 
 /// Same as above, but as an option instead of an result
 pub fn get_env_var_option(name: &str) -> Option<String> {
-    get_env_var(name).map_or_else(|_| None, |o| Some(o))
+    get_env_var(name).ok()
 }
 
 /// Same as above, but provides a default value instead
@@ -90,6 +366,18 @@ pub fn get_env_var_default(name: &str, default: &str) -> String {
     get_env_var_option(name).unwrap_or(default.to_string())
 }
 
+/// Same as above, but parses the value if the variable is defined
+pub fn get_env_var_parsed<T: std::str::FromStr>(name: &str) -> Result<Option<T>, LibError> {
+    get_env_var_option(name)
+        .map(|value| {
+            value.parse::<T>().map_err(|_| LibError::ValueError {
+                name: name.to_string(),
+                value,
+            })
+        })
+        .transpose()
+}
+
 /// Splits a CSV string into tokens, and verify that no token is empty
 pub fn tokenize_optional_csv_str(csv: &Option<String>) -> Result<Vec<String>, LibError> {
     Ok(match csv {
@@ -109,13 +397,50 @@ pub fn tokenize_optional_csv_str(csv: &Option<String>) -> Result<Vec<String>, Li
     })
 }
 
+/// Formats how long ago something happened, in the largest unit
+pub fn format_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    let (count, unit) = match seconds {
+        0..60 => (seconds, "second"),
+        60..3600 => (seconds / 60, "minute"),
+        3600..86400 => (seconds / 3600, "hour"),
+        _ => (seconds / 86400, "day"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{count} {unit}{plural} ago")
+}
+
 /// CheckResult holds the data between providers and notifiers :
 /// - `provider::check` is the data source
 /// - `notifier::notify` is the data sink
-#[derive(PartialEq, Serialize)]
+///
+/// It is loaded back from its json payload, the missing fields being empty.
+#[derive(PartialEq, Serialize, Deserialize)]
 pub struct CheckResult {
     pub provider_name: String,
     pub available_servers: Vec<String>,
+    /// Servers which were checked but are not available, kept out of the payload.
+    #[serde(skip_serializing, default)]
+    pub unavailable_servers: Vec<String>,
+    /// When each notified server was last seen available before its change, as a unix
+    /// timestamp. Servers which were never seen available, or not tracked individually, are missing.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub last_seen_available: BTreeMap<String, u64>,
+    /// Public page where each available server can be ordered, when its provider has one.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub order_urls: BTreeMap<String, String>,
+    /// Stock level of each available server, when its provider tells it.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub stock_levels: BTreeMap<String, providers::StockLevel>,
+    /// Locations where each available server is, like zones or datacenters, when its provider tells them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub locations: BTreeMap<String, Vec<String>>,
+    /// Price of each server, when prices are watched and its provider tells it.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub prices: BTreeMap<String, f64>,
+    /// Previous price of each server whose price change is notified.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub previous_prices: BTreeMap<String, f64>,
 }
 
 impl CheckResult {
@@ -124,6 +449,13 @@ impl CheckResult {
         Self {
             provider_name: provider_name.to_string(),
             available_servers: Vec::<String>::new(),
+            unavailable_servers: Vec::<String>::new(),
+            last_seen_available: BTreeMap::new(),
+            order_urls: BTreeMap::new(),
+            stock_levels: BTreeMap::new(),
+            locations: BTreeMap::new(),
+            prices: BTreeMap::new(),
+            previous_prices: BTreeMap::new(),
         }
     }
 
@@ -135,12 +467,56 @@ impl CheckResult {
             "bar_server".into(),
             "baz_server".into(),
         ]);
+        result.unavailable_servers.push("qux_server".into());
+        result.last_seen_available.insert(
+            "foo_server".into(),
+            storage::get_timestamp().saturating_sub(12 * 24 * 60 * 60),
+        );
+        result
+            .stock_levels
+            .insert("baz_server".into(), providers::StockLevel::Low);
+        result.locations.insert(
+            "bar_server".into(),
+            vec!["fr-par-2".into(), "nl-ams-1".into()],
+        );
+        result.prices.insert("foo_server".into(), 45.0);
+        result.previous_prices.insert("foo_server".into(), 60.0);
+        result.order_urls.insert(
+            "bar_server".into(),
+            "https://example.com/order/bar_server".into(),
+        );
         result
     }
 
     /// Serializes to json
     fn to_json(&self) -> Result<String, LibError> {
-        serde_json::to_string(&self).map_err(|source| LibError::JsonError { source })
+        Ok(serde_json::to_string(&self)?)
+    }
+
+    /// Loads a result from a json file, like a payload which was notified
+    pub fn load(path: &Path) -> Result<Self, LibError> {
+        let json = fs::read_to_string(path).map_err(|source| LibError::FileError {
+            path: path.into(),
+            source,
+        })?;
+        serde_json::from_str(&json).map_err(|source| LibError::JsonFileError {
+            path: path.into(),
+            source,
+        })
+    }
+
+    /// Renders the label of a server as a Markdown link to its order page, if it has one
+    #[cfg(any(
+        feature = "teams",
+        feature = "mattermost",
+        feature = "rocketchat",
+        feature = "zulip"
+    ))]
+    fn to_markdown_link(&self, server: &str, label: &str) -> String {
+        match self.order_urls.get(server) {
+            Some(order_url) => format!("[{label}]({order_url})"),
+            None => label.to_string(),
+        }
     }
 }
 
@@ -152,10 +528,32 @@ impl Display for CheckResult {
             "Report of available server types for {provider_name} :\n\n"
         )?;
         if self.available_servers.is_empty() {
-            write!(f, "No server available for the selected types !\n")?;
+            writeln!(f, "No server available for the selected types !")?;
         } else {
             for server in &self.available_servers {
-                write!(f, "- {server}\n")?;
+                write!(f, "- {server}")?;
+                if let Some(timestamp) = self.last_seen_available.get(server) {
+                    let age =
+                        Duration::from_secs(storage::get_timestamp().saturating_sub(*timestamp));
+                    write!(f, " (last seen in stock {})", format_age(age))?;
+                }
+                if let Some(stock) = self.stock_levels.get(server) {
+                    write!(f, " ({stock} stock)")?;
+                }
+                if let Some(locations) = self.locations.get(server) {
+                    write!(f, " (in {})", locations.join(", "))?;
+                }
+                match (self.prices.get(server), self.previous_prices.get(server)) {
+                    (Some(price), Some(previous)) => {
+                        write!(f, " (price {price:.2}, was {previous:.2})")?
+                    }
+                    (Some(price), None) => write!(f, " (price {price:.2})")?,
+                    _ => {}
+                }
+                match self.order_urls.get(server) {
+                    Some(order_url) => writeln!(f, " : {order_url}")?,
+                    None => writeln!(f)?,
+                }
             }
         }
         Ok(())
@@ -1,12 +1,90 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
-use dedicated_server_availability_watcher::{notifiers, providers};
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand};
+use colored::Colorize;
+use dedicated_server_availability_watcher::alert::AlertPolicy;
+use dedicated_server_availability_watcher::config::EnvFile;
+use dedicated_server_availability_watcher::digest::DigestWindow;
+use dedicated_server_availability_watcher::hook::AvailabilityHook;
+use dedicated_server_availability_watcher::providers::{
+    CheckOutcome, CheckStage, InventoryFilter, InventorySort, PriceWatch,
+};
+use dedicated_server_availability_watcher::recording::RecordingMode;
+use dedicated_server_availability_watcher::schedule::{Schedule, Scheduler};
+use dedicated_server_availability_watcher::storage::{CheckResultStorage, Debounce};
+use dedicated_server_availability_watcher::{
+    cache, client, config, dirs, doctor, inventory, notifiers, providers, recording, reload,
+    storage, trigger, LibError,
+};
+#[cfg(feature = "tui")]
+use dedicated_server_availability_watcher::{tui, with_env_overrides};
+#[cfg(feature = "tui")]
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
 
 // CLAP command line arguments declaration
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
+    /// Disable TLS certificate verification (use with care)
+    #[arg(long, global = true)]
+    insecure: bool,
+
+    /// Load environment variables from this file (defaults to ./.env, then to
+    /// $XDG_CONFIG_HOME/dsaw/dsaw.env, when they exist), which `watch` reloads on SIGHUP
+    #[arg(long, global = true, value_name = "FILE")]
+    env_file: Option<String>,
+
+    /// Also load the variables of this profile of the environment file, like `home` or `work`,
+    /// which override the ones found before any profile
+    #[arg(long, global = true, value_name = "NAME", env = "DSAW_PROFILE")]
+    profile: Option<String>,
+
+    /// Save every provider response in this directory, to replay them later
+    #[arg(long, global = true, value_name = "DIR", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Load every provider response from this directory, as saved by --record, instead of querying
+    #[arg(long, global = true, value_name = "DIR")]
+    replay: Option<PathBuf>,
+
+    /// Cache provider responses in this directory, and only download them again when they
+    /// changed, for the providers which support conditional requests
+    #[arg(long, global = true, value_name = "DIR", env = "DSAW_HTTP_CACHE")]
+    http_cache: Option<PathBuf>,
+
+    /// Reuse the inventory of a provider for this many seconds, for inventories and checks alike,
+    /// instead of querying the provider every time
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECONDS",
+        env = "DSAW_INVENTORY_CACHE"
+    )]
+    inventory_cache: Option<u64>,
+
+    /// Keep the cached inventories in this directory, so that they are reused between invocations
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        requires = "inventory_cache",
+        env = "DSAW_INVENTORY_CACHE_DIR"
+    )]
+    inventory_cache_dir: Option<PathBuf>,
+
+    /// Check this many servers at once, for the providers which query each server on its own
+    #[arg(
+        long,
+        global = true,
+        value_name = "COUNT",
+        default_value_t = 1,
+        env = "DSAW_CONCURRENCY"
+    )]
+    concurrency: usize,
+
     /// Main commands
     #[command(subcommand)]
     command: Commands,
@@ -17,7 +95,7 @@ enum Commands {
     /// provider actions
     Provider {
         #[command(subcommand)]
-        subcommand: Option<ProviderCommands>,
+        subcommand: Option<Box<ProviderCommands>>,
     },
 
     /// notifier actions
@@ -25,6 +103,54 @@ enum Commands {
         #[command(subcommand)]
         subcommand: Option<NotifierCommands>,
     },
+
+    /// configuration actions
+    Config {
+        #[command(subcommand)]
+        subcommand: ConfigCommands,
+    },
+
+    /// storage actions
+    Storage {
+        #[command(subcommand)]
+        subcommand: StorageCommands,
+    },
+
+    /// live monitor of watched servers, in the terminal
+    #[cfg(feature = "tui")]
+    Tui {
+        /// Provider and servers to watch, like `ovh=24sk10,24sk20`, optionally with
+        /// the profile of the environment file to use, like `scaleway@work=...` (may be repeated)
+        #[arg(
+            short,
+            long,
+            value_name = "PROVIDER[@PROFILE]=SERVERS",
+            required = true
+        )]
+        watch: Vec<String>,
+
+        /// Optional notify handlers (may be repeated, or separated by commas)
+        #[arg(short, long, env = "DSAW_NOTIFIER", value_delimiter = ',')]
+        notifier: Vec<String>,
+
+        /// Storage directory (defaults to $XDG_STATE_HOME/dsaw)
+        #[arg(short, long, env = "DSAW_STORAGE_DIR")]
+        storage_dir: Option<String>,
+
+        #[command(flatten)]
+        schedule: ScheduleArgs,
+    },
+
+    /// verify credentials and connectivity, without notifying
+    Doctor {
+        /// Provider to diagnose (may be repeated, all configured ones by default)
+        #[arg(short, long)]
+        provider: Vec<String>,
+
+        /// Notifier to diagnose (may be repeated, all configured ones by default)
+        #[arg(short, long)]
+        notifier: Vec<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -40,27 +166,387 @@ enum ProviderCommands {
         /// List even currently unavailable types
         #[arg(short, long)]
         all: bool,
+
+        /// Show when every server was last checked and last seen available
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Storage directory to read the states from, with --verbose (defaults to $XDG_STATE_HOME/dsaw)
+        #[arg(short, long, requires = "verbose")]
+        storage_dir: Option<String>,
+
+        /// Save the inventory to this file, before filtering it, to compare it later with `diff`
+        #[arg(long, value_name = "FILE")]
+        save: Option<PathBuf>,
+
+        #[command(flatten)]
+        filter: InventoryArgs,
+    },
+
+    /// Compare the current inventory with one saved by `inventory --save`
+    Diff {
+        /// Saved inventory, which tells the provider
+        snapshot: PathBuf,
+
+        /// Replace the saved inventory with the current one, after comparing them
+        #[arg(long)]
+        update: bool,
     },
 
     /// Checks provider for server availability
     Check {
-        /// Provider
-        provider: String,
+        #[command(flatten)]
+        check: CheckArgs,
 
-        /// Storage directory (defaults to current)
-        #[arg(short, long)]
-        storage_dir: Option<String>,
+        /// Query the provider, but only print what would be stored and notified
+        #[arg(long)]
+        dry_run: bool,
+    },
 
-        /// List of server types
-        #[arg(required = true)]
-        servers: Vec<String>,
+    /// Checks provider for server availability repeatedly, as a daemon
+    Watch {
+        #[command(flatten)]
+        check: CheckArgs,
 
-        /// Optional notify handler
-        #[arg(short, long)]
-        notifier: Option<String>,
+        #[command(flatten)]
+        schedule: ScheduleArgs,
+
+        /// After every check, delete the stored states which no check confirmed for this many days
+        #[arg(long, value_name = "DAYS", env = "DSAW_PRUNE_DAYS")]
+        prune_days: Option<u64>,
+
+        #[command(flatten)]
+        trigger: TriggerArgs,
+
+        #[command(flatten)]
+        alert: AlertArgs,
     },
 }
 
+#[derive(Args)]
+struct InventoryArgs {
+    /// Only list servers with at least this much memory
+    #[arg(long, value_name = "GB")]
+    filter_memory: Option<f64>,
+
+    /// Only list servers with at least this much storage
+    #[arg(long, value_name = "GB")]
+    filter_storage: Option<f64>,
+
+    /// Only list servers whose processor model contains this text, ignoring case
+    #[arg(long, value_name = "TEXT")]
+    filter_cpu: Option<String>,
+
+    /// Only list servers with at least this many processor cores
+    #[arg(long, value_name = "N")]
+    filter_cores: Option<u32>,
+
+    /// Only list servers with at least this much bandwidth
+    #[arg(long, value_name = "MBPS")]
+    filter_bandwidth: Option<u64>,
+
+    /// Only list servers of this product range, ignoring case
+    #[arg(long, value_name = "NAME")]
+    filter_range: Option<String>,
+
+    /// Only list available (true) or unavailable (false) servers
+    #[arg(long, value_name = "BOOL")]
+    filter_available: Option<bool>,
+
+    /// Sort by reference, memory, storage, price, cores or bandwidth
+    #[arg(long, value_name = "FIELD")]
+    sort: Option<InventorySort>,
+
+    /// Only list this many servers, after filtering and sorting
+    #[arg(long, value_name = "N")]
+    limit: Option<usize>,
+}
+
+impl InventoryArgs {
+    /// Builds the filter matching the arguments
+    fn build_filter(&self) -> InventoryFilter {
+        InventoryFilter {
+            memory: self.filter_memory,
+            storage: self.filter_storage,
+            cpu: self.filter_cpu.clone(),
+            cores: self.filter_cores,
+            bandwidth: self.filter_bandwidth,
+            range: self.filter_range.clone(),
+            available: self.filter_available,
+            sort: self.sort,
+            limit: self.limit,
+        }
+    }
+}
+
+#[derive(Args)]
+struct CheckArgs {
+    /// Provider
+    #[arg(env = "DSAW_PROVIDER")]
+    provider: String,
+
+    /// Storage directory (defaults to $XDG_STATE_HOME/dsaw)
+    #[arg(short, long, env = "DSAW_STORAGE_DIR")]
+    storage_dir: Option<String>,
+
+    /// List of server types
+    #[arg(
+        required_unless_present = "all_servers",
+        env = "DSAW_SERVERS",
+        value_delimiter = ' '
+    )]
+    servers: Vec<String>,
+
+    /// Watch the whole inventory instead of a list of server types,
+    /// notifying about any server which appears or disappears
+    #[arg(
+        long,
+        conflicts_with_all = ["servers", "whole_list", "debounce_checks", "debounce_minutes"]
+    )]
+    all_servers: bool,
+
+    /// Check the inventory saved to this file by `inventory --save`, instead of querying
+    /// the provider, which needs neither network access nor credentials
+    #[arg(long, value_name = "FILE")]
+    from_file: Option<PathBuf>,
+
+    /// Optional notify handlers (may be repeated, or separated by commas),
+    /// each of them being attempted even when another one fails
+    #[arg(short, long, env = "DSAW_NOTIFIER", value_delimiter = ',')]
+    notifier: Vec<String>,
+
+    /// Notify about the whole list of available servers whenever it changes,
+    /// instead of only the servers whose availability changed
+    #[arg(long)]
+    whole_list: bool,
+
+    /// Only notify once a change has been observed for this many consecutive checks
+    #[arg(long, value_name = "CHECKS", conflicts_with = "whole_list")]
+    debounce_checks: Option<u32>,
+
+    /// Only notify once a change has been observed for this many minutes
+    #[arg(long, value_name = "MINUTES", conflicts_with = "whole_list")]
+    debounce_minutes: Option<u64>,
+
+    /// Collect the changes and notify them together, once this many minutes after the first one
+    #[arg(long, value_name = "MINUTES")]
+    digest_minutes: Option<u64>,
+
+    /// Collect the changes and notify them together, once a day at this local time
+    #[arg(long, value_name = "HH:MM", conflicts_with = "digest_minutes")]
+    digest_daily: Option<String>,
+
+    /// Retry the notifications which failed at the following checks, for up to this
+    /// many minutes (0 disables retries)
+    #[arg(long, value_name = "MINUTES", default_value_t = 1440)]
+    retry_max_age_minutes: u64,
+
+    /// Run this command line as soon as servers become available, with their references
+    /// as arguments, for example to attempt to order them
+    #[arg(long, value_name = "COMMAND", env = "DSAW_ON_AVAILABLE")]
+    on_available: Option<String>,
+
+    /// Do not run the --on-available command again for the same server within this many minutes
+    #[arg(
+        long,
+        value_name = "MINUTES",
+        default_value_t = 60,
+        requires = "on_available"
+    )]
+    on_available_cooldown: u64,
+
+    /// Exit with an error instead of waiting, when another instance is checking
+    /// with the same storage directory
+    #[arg(long)]
+    no_wait: bool,
+
+    /// Print the duration and status of the provider API calls of every check
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Also notify when the stock of an available server becomes low,
+    /// for the providers which tell it
+    #[arg(long, conflicts_with = "whole_list")]
+    notify_low_stock: bool,
+
+    /// Also notify when the price of an available server drops below this amount,
+    /// in the currency of the provider
+    #[arg(long, value_name = "AMOUNT", conflicts_with_all = ["whole_list", "all_servers"])]
+    price_below: Option<f64>,
+
+    /// Also notify whenever the price of an available server changes
+    #[arg(long, conflicts_with_all = ["whole_list", "all_servers"])]
+    notify_price_change: bool,
+}
+
+impl CheckArgs {
+    /// Builds the runner matching the arguments
+    fn build_runner(&self, dry_run: bool) -> Result<providers::CheckRunner> {
+        let digest = match (self.digest_minutes, &self.digest_daily) {
+            (Some(minutes), _) => Some(DigestWindow::from_minutes(minutes)?),
+            (None, Some(time)) => Some(DigestWindow::from_daily(time)?),
+            (None, None) => None,
+        };
+        let hook = match &self.on_available {
+            Some(command) => Some(AvailabilityHook::new(
+                command,
+                Duration::from_secs(self.on_available_cooldown * 60),
+            )?),
+            None => None,
+        };
+        let storage = CheckResultStorage::from_dir(&self.storage_dir)
+            .context("while initializing CheckResultStorage")?;
+        let debounce = Debounce {
+            checks: self.debounce_checks,
+            duration: self
+                .debounce_minutes
+                .map(|minutes| Duration::from_secs(minutes * 60)),
+        };
+        let runner = match &self.from_file {
+            None => providers::CheckRunner::new(
+                &self.provider,
+                &self.servers,
+                &self.notifier,
+                storage,
+                self.whole_list,
+                debounce,
+                dry_run,
+            ),
+            Some(path) => {
+                let provider = inventory::OfflineProvider::load(path, &self.provider)
+                    .with_context(|| format!("while loading inventory {}", path.display()))
+                    .context(CheckStage::Provider)?;
+                providers::CheckRunner::new_with_provider(
+                    Box::new(provider),
+                    &self.servers,
+                    &self.notifier,
+                    storage,
+                    self.whole_list,
+                    debounce,
+                    dry_run,
+                )
+            }
+        };
+        runner.map(|runner| {
+            runner
+                .with_digest(digest)
+                .with_retry(match self.retry_max_age_minutes {
+                    0 => None,
+                    minutes => Some(Duration::from_secs(minutes * 60)),
+                })
+                .with_wait_lock(!self.no_wait)
+                .with_hook(hook)
+                .with_verbose(self.verbose)
+                .with_low_stock(self.notify_low_stock)
+                .with_price_watch(PriceWatch {
+                    below: self.price_below,
+                    on_change: self.notify_price_change,
+                })
+        })
+    }
+}
+
+#[derive(Args)]
+struct TriggerArgs {
+    /// Listen on this address for `POST /trigger/{provider}` requests, which check right away,
+    /// and for `GET /metrics` requests. Requests must provide the token of the DSAW_TRIGGER_TOKEN variable
+    #[arg(long, value_name = "ADDRESS:PORT")]
+    listen: Option<String>,
+
+    /// Minimum number of seconds between two accepted trigger requests
+    #[arg(long, value_name = "SECONDS", default_value_t = 60)]
+    trigger_interval: u64,
+}
+
+impl TriggerArgs {
+    /// Starts listening for trigger requests, if required
+    fn listen(&self) -> Result<()> {
+        let Some(address) = &self.listen else {
+            return Ok(());
+        };
+        let token = dedicated_server_availability_watcher::get_env_var(trigger::ENV_TRIGGER_TOKEN)?;
+        trigger::listen(address, &token, Duration::from_secs(self.trigger_interval))
+            .with_context(|| format!("while listening for trigger requests on {address}"))?;
+        println!("Listening for trigger requests on {address}");
+        Ok(())
+    }
+}
+
+#[derive(Args)]
+struct AlertArgs {
+    /// Send operational alerts through this notifier when checks keep failing,
+    /// and once they succeed again
+    #[arg(long, value_name = "NOTIFIER", env = "DSAW_ALERT_NOTIFIER")]
+    alert_notifier: Option<String>,
+
+    /// Alert once this many checks failed in a row (permanent failures alert right away)
+    #[arg(
+        long,
+        value_name = "CHECKS",
+        default_value_t = 3,
+        env = "DSAW_ALERT_AFTER",
+        requires = "alert_notifier"
+    )]
+    alert_after: u32,
+
+    /// Also alert when the provider was last checked successfully this many minutes ago
+    #[arg(
+        long,
+        value_name = "MINUTES",
+        env = "DSAW_ALERT_STALE_MINUTES",
+        requires = "alert_notifier"
+    )]
+    alert_stale_minutes: Option<u64>,
+}
+
+impl AlertArgs {
+    /// Builds the alert policy matching the arguments
+    fn build_policy(&self) -> Result<Option<AlertPolicy>> {
+        let Some(notifier) = &self.alert_notifier else {
+            return Ok(None);
+        };
+        let policy = AlertPolicy::new(notifier, self.alert_after)
+            .with_context(|| format!("while setting up alert notifier {notifier}"))?
+            .with_stale_after(
+                self.alert_stale_minutes
+                    .map(|minutes| Duration::from_secs(minutes * 60)),
+            );
+        Ok(Some(policy))
+    }
+}
+
+#[derive(Args)]
+struct ScheduleArgs {
+    /// Seconds between checks, the first check happening right away
+    #[arg(
+        short,
+        long,
+        value_name = "SECONDS",
+        default_value_t = 300,
+        env = "DSAW_INTERVAL"
+    )]
+    interval: u64,
+
+    /// Cron expression selecting when to check, in local time (overrides the interval)
+    #[arg(long, value_name = "EXPRESSION", env = "DSAW_CRON")]
+    cron: Option<String>,
+
+    /// Maximum random delay added before each check, in seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = 0, env = "DSAW_JITTER")]
+    jitter: u64,
+}
+
+impl ScheduleArgs {
+    /// Builds the scheduler matching the arguments
+    fn build_scheduler(&self) -> Result<Scheduler> {
+        let schedule = match &self.cron {
+            Some(expression) => Schedule::from_cron(expression)?,
+            None => Schedule::from_interval(self.interval)?,
+        };
+        Ok(Scheduler::new(schedule, Duration::from_secs(self.jitter)))
+    }
+}
+
 #[derive(Subcommand)]
 enum NotifierCommands {
     /// List available notifiers
@@ -70,12 +556,302 @@ enum NotifierCommands {
     Test {
         /// Notifier to test
         notifier: String,
+
+        /// Send a test operational alert instead
+        #[arg(long)]
+        alert: bool,
+
+        /// Send the result held by this json file, in the format of the notified payload,
+        /// instead of dummy values
+        #[arg(long, value_name = "FILE", conflicts_with = "alert")]
+        payload: Option<PathBuf>,
     },
 }
 
-/// Main entrypoint, uses "clap" crate for argument handling
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Print a commented example configuration
+    Init {
+        /// Write to this file instead of stdout (never overwritten)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Validate the configuration, without querying providers nor notifying
+    Validate {
+        /// Provider to validate (may be repeated)
+        #[arg(short, long)]
+        provider: Vec<String>,
+
+        /// Notifier to validate (may be repeated)
+        #[arg(short, long)]
+        notifier: Vec<String>,
+
+        /// Storage directory (defaults to $XDG_STATE_HOME/dsaw)
+        #[arg(short, long)]
+        storage_dir: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum StorageCommands {
+    /// List the stored states, oldest first
+    List {
+        /// Storage directory (defaults to $XDG_STATE_HOME/dsaw)
+        #[arg(short, long)]
+        storage_dir: Option<String>,
+    },
+
+    /// Delete the stored states which no check confirmed for a while
+    Prune {
+        /// Delete the states older than this many days
+        #[arg(long, value_name = "DAYS")]
+        older_than_days: u64,
+
+        /// Storage directory (defaults to $XDG_STATE_HOME/dsaw)
+        #[arg(short, long)]
+        storage_dir: Option<String>,
+
+        /// Only print what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Converts a number of days into a duration
+fn days(days: u64) -> Duration {
+    Duration::from_secs(days * 24 * 60 * 60)
+}
+
+/// Process exit codes, so that scripts can react to the outcome of a check.
+/// Usage errors are reported by clap with the code 2.
+mod exit_codes {
+    /// Success, and nothing changed
+    pub const NO_CHANGE: u8 = 0;
+    /// Any error which is neither a provider nor a notifier failure
+    pub const FAILURE: u8 = 1;
+    /// At least one server became available
+    pub const NEW_AVAILABILITY: u8 = 10;
+    /// Something changed, but no server became available
+    pub const CHANGED: u8 = 11;
+    /// The provider could not be set up or queried
+    pub const PROVIDER_FAILURE: u8 = 20;
+    /// The notifier could not be set up or failed to notify
+    pub const NOTIFIER_FAILURE: u8 = 21;
+    /// Another instance is checking with the same storage directory
+    pub const ALREADY_RUNNING: u8 = 30;
+}
+
+/// Maps the outcome of a check to its exit code
+fn exit_code_for_outcome(outcome: CheckOutcome) -> u8 {
+    match outcome {
+        CheckOutcome::Unchanged => exit_codes::NO_CHANGE,
+        CheckOutcome::Changed => exit_codes::CHANGED,
+        CheckOutcome::NewAvailability => exit_codes::NEW_AVAILABILITY,
+    }
+}
+
+/// Maps an error to its exit code, from the failed stage found in its chain
+fn exit_code_for_error(error: &anyhow::Error) -> u8 {
+    let locked = error
+        .chain()
+        .any(|cause| matches!(cause.downcast_ref(), Some(LibError::StorageLocked { .. })));
+    if locked {
+        return exit_codes::ALREADY_RUNNING;
+    }
+    match CheckStage::of(error) {
+        Some(CheckStage::Provider) => exit_codes::PROVIDER_FAILURE,
+        Some(CheckStage::Notifier) => exit_codes::NOTIFIER_FAILURE,
+        None => exit_codes::FAILURE,
+    }
+}
+
+/// Prints the servers which were probably meant, when a server reference was unknown
+fn print_suggestions(error: &anyhow::Error) {
+    let suggestions = error.chain().find_map(|cause| match cause.downcast_ref() {
+        Some(LibError::UnknownServer { suggestions, .. }) if !suggestions.is_empty() => {
+            Some(suggestions)
+        }
+        _ => None,
+    });
+    if let Some(suggestions) = suggestions {
+        eprintln!("\nDid you mean :");
+        for suggestion in suggestions {
+            eprintln!("    {suggestion}");
+        }
+    }
+}
+
+/// Main entrypoint, reporting errors the same way returning them from main would
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => ExitCode::from(code),
+        Err(error) => {
+            eprintln!("Error: {error:?}");
+            print_suggestions(&error);
+            ExitCode::from(exit_code_for_error(&error))
+        }
+    }
+}
+
+/// Finds a global option in the raw arguments, like the environment file, as it must be
+/// loaded before parsing them, since some of them can come from the variables it holds
+fn find_arg(option: &str) -> Option<String> {
+    let prefix = format!("{option}=");
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == option {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Gets the environment file loaded when none is selected, if it exists:
+/// the `.env` file of the current directory, then the one of the configuration directory
+fn find_default_env_file() -> Option<String> {
+    if Path::new(dirs::LOCAL_ENV_FILE_NAME).is_file() {
+        return Some(dirs::LOCAL_ENV_FILE_NAME.to_string());
+    }
+    let path = dirs::get_config_dir()
+        .ok()?
+        .join(dirs::DEFAULT_ENV_FILE_NAME);
+    path.is_file().then(|| path.to_string_lossy().to_string())
+}
+
+/// Loads the environment file, if any, then parses the arguments
+fn parse_with_env_file() -> Result<(Cli, Option<EnvFile>)> {
+    let profile = find_arg("--profile").or_else(|| std::env::var("DSAW_PROFILE").ok());
+    let env_file = match find_arg("--env-file").or_else(find_default_env_file) {
+        None => {
+            if let Some(profile) = profile {
+                return Err(LibError::ValueError {
+                    name: "profile without any environment file".into(),
+                    value: profile,
+                }
+                .into());
+            }
+            None
+        }
+        Some(path) => {
+            let mut env_file = EnvFile::load(&path, profile.as_deref())
+                .with_context(|| format!("while loading environment file {path}"))?;
+            env_file.apply(None);
+            Some(env_file)
+        }
+    };
+    Ok((Cli::parse(), env_file))
+}
+
+/// Builds a watch from the arguments, which only fails on invalid configuration
+fn build_watch(cli: &Cli) -> Result<providers::WatchRunner> {
+    let Commands::Provider {
+        subcommand: Some(subcommand),
+    } = &cli.command
+    else {
+        unreachable!("only the watch command can be reloaded");
+    };
+    let ProviderCommands::Watch {
+        check,
+        schedule,
+        prune_days,
+        alert,
+        ..
+    } = subcommand.as_ref()
+    else {
+        unreachable!("only the watch command can be reloaded");
+    };
+    Ok(
+        providers::WatchRunner::new(check.build_runner(false)?, schedule.build_scheduler()?)
+            .with_prune(prune_days.map(days))
+            .with_alerts(alert.build_policy()?),
+    )
+}
+
+/// Parses the arguments again, and verifies that the watch can be built from them
+fn parse_watch() -> Result<Cli> {
+    let cli = Cli::try_parse()?;
+    build_watch(&cli)?;
+    Ok(cli)
+}
+
+/// Reloads the environment file, printing what changed, then parses the arguments again.
+/// On failure, the previous environment is restored.
+fn reload(env_file: &mut Option<EnvFile>) -> Result<Cli> {
+    let Some(previous) = env_file else {
+        return parse_watch();
+    };
+    let path = previous.path().to_string();
+    let mut loaded = EnvFile::load(&path, previous.profile())
+        .with_context(|| format!("while loading environment file {path}"))?;
+    for change in loaded.diff(previous) {
+        println!("{path}: {change}");
+    }
+    loaded.apply(Some(previous));
+
+    match parse_watch() {
+        Ok(cli) => {
+            *env_file = Some(loaded);
+            Ok(cli)
+        }
+        Err(error) => {
+            previous.apply(Some(&loaded));
+            Err(error)
+        }
+    }
+}
+
+/// Watches until a permanent error happens, reloading the configuration on SIGHUP.
+/// As states are stored, the checks go on as if the process had been restarted.
+fn watch(mut cli: Cli, mut env_file: Option<EnvFile>) -> Result<()> {
+    reload::listen();
+    // the listener is not reloaded, as it lives in its own thread
+    if let Commands::Provider {
+        subcommand: Some(subcommand),
+    } = &cli.command
+    {
+        if let ProviderCommands::Watch { trigger, .. } = subcommand.as_ref() {
+            trigger.listen()?;
+        }
+    }
+    loop {
+        build_watch(&cli)?.watch()?;
+        println!("Reloading configuration");
+        match reload(&mut env_file) {
+            Ok(reloaded) => cli = reloaded,
+            Err(error) => eprintln!(
+                "{}",
+                format!("{error:#}, keeping the previous configuration").red()
+            ),
+        }
+    }
+}
+
+/// Runs the command, uses "clap" crate for argument handling
+fn run() -> Result<u8> {
+    let (cli, env_file) = parse_with_env_file()?;
+
+    client::set_insecure(cli.insecure);
+    recording::set_mode(match (&cli.record, &cli.replay) {
+        (Some(dir), _) => RecordingMode::Record(dir.clone()),
+        (None, Some(dir)) => RecordingMode::Replay(dir.clone()),
+        (None, None) => RecordingMode::Off,
+    });
+    cache::set_dir(cli.http_cache.clone());
+    inventory::set_cache(
+        cli.inventory_cache
+            .map(|seconds| inventory::InventoryCache {
+                ttl: Duration::from_secs(seconds),
+                dir: cli.inventory_cache_dir.clone(),
+            }),
+    );
+    providers::set_concurrency(cli.concurrency);
 
     match &cli.command {
         // Notifier actions
@@ -85,8 +861,17 @@ fn main() -> Result<()> {
             Some(sub) => match sub {
                 NotifierCommands::List {} => notifiers::ListRunner::print_list()?,
 
-                NotifierCommands::Test { notifier } => {
-                    notifiers::TestRunner::new(notifier)?.test()?
+                NotifierCommands::Test {
+                    notifier,
+                    alert,
+                    payload,
+                } => {
+                    let runner = notifiers::TestRunner::new(notifier)?;
+                    match (alert, payload) {
+                        (true, _) => runner.test_alert()?,
+                        (false, Some(path)) => runner.test_payload(path)?,
+                        (false, None) => runner.test()?,
+                    }
                 }
             },
         },
@@ -95,23 +880,138 @@ fn main() -> Result<()> {
         Commands::Provider { subcommand } => match subcommand {
             None => providers::ListRunner::print_list(),
 
-            Some(sub) => match sub {
+            Some(sub) => match sub.as_ref() {
                 ProviderCommands::List {} => providers::ListRunner::print_list(),
 
-                ProviderCommands::Inventory { provider, all } => {
-                    providers::InventoryRunner::new(provider)?.list_inventory(*all)?;
-                }
-
-                ProviderCommands::Check {
+                ProviderCommands::Inventory {
                     provider,
-                    servers,
-                    notifier,
+                    all,
+                    verbose,
                     storage_dir,
-                } => providers::CheckRunner::new(provider, servers, notifier, storage_dir)?
-                    .check_once()?,
+                    save,
+                    filter,
+                } => {
+                    let history = match verbose {
+                        true => Some(
+                            CheckResultStorage::from_dir(storage_dir)
+                                .context("while initializing CheckResultStorage")?,
+                        ),
+                        false => None,
+                    };
+                    providers::InventoryRunner::new(provider)?
+                        .with_history(history)
+                        .with_save(save.clone())
+                        .list_inventory(*all, &filter.build_filter())?;
+                }
+
+                ProviderCommands::Diff { snapshot, update } => {
+                    if inventory::DiffRunner::new(snapshot, *update)?.diff()? {
+                        return Ok(exit_codes::CHANGED);
+                    }
+                }
+
+                ProviderCommands::Check { check, dry_run } => {
+                    let outcome = check.build_runner(*dry_run)?.check_once()?;
+                    return Ok(exit_code_for_outcome(outcome));
+                }
+
+                ProviderCommands::Watch { .. } => watch(cli, env_file)?,
             },
         },
+
+        // Configuration actions
+        Commands::Config { subcommand } => match subcommand {
+            ConfigCommands::Init { output } => config::InitRunner::init(output)?,
+
+            ConfigCommands::Validate {
+                provider,
+                notifier,
+                storage_dir,
+            } => config::ValidateRunner::new(provider, notifier, storage_dir).validate()?,
+        },
+
+        // Storage actions
+        Commands::Storage { subcommand } => match subcommand {
+            StorageCommands::List { storage_dir } => {
+                storage::ListRunner::new(storage_dir)?.list()?
+            }
+
+            StorageCommands::Prune {
+                older_than_days,
+                storage_dir,
+                dry_run,
+            } => {
+                storage::PruneRunner::new(storage_dir, days(*older_than_days), *dry_run)?.prune()?
+            }
+        },
+
+        // Terminal monitor
+        #[cfg(feature = "tui")]
+        Commands::Tui {
+            watch,
+            notifier,
+            storage_dir,
+            schedule,
+        } => {
+            let watches = watch
+                .iter()
+                .map(|watch| tui::parse_watch(watch))
+                .collect::<Result<Vec<_>, LibError>>()?;
+            let mut runners = Vec::new();
+            let mut storages = Vec::new();
+            for (provider, profile, servers) in watches.iter() {
+                // the variables of its profile only apply to the handlers of the job
+                let overrides = match (profile, &env_file) {
+                    (None, _) => BTreeMap::new(),
+                    (Some(profile), Some(env_file)) => env_file.profile_vars(profile)?.clone(),
+                    (Some(profile), None) => {
+                        return Err(LibError::ValueError {
+                            name: "profile without any environment file".into(),
+                            value: profile.clone(),
+                        }
+                        .into())
+                    }
+                };
+                let storage_dir = overrides
+                    .get("DSAW_STORAGE_DIR")
+                    .cloned()
+                    .or_else(|| storage_dir.clone());
+                // states are named after the provider, so other accounts would overwrite them
+                if storages.iter().any(|(known, known_profile, known_dir)| {
+                    *known == provider && *known_profile != profile && *known_dir == storage_dir
+                }) {
+                    return Err(LibError::ValueError {
+                        name: "watches of several profiles sharing a storage directory (set DSAW_STORAGE_DIR in their profiles)".into(),
+                        value: provider.clone(),
+                    }
+                    .into());
+                }
+                storages.push((provider, profile, storage_dir.clone()));
+                let runner = with_env_overrides(&overrides, || {
+                    providers::CheckRunner::new(
+                        provider,
+                        servers,
+                        notifier,
+                        CheckResultStorage::from_dir(&storage_dir)
+                            .context("while initializing CheckResultStorage")?,
+                        false,
+                        Debounce::default(),
+                        false,
+                    )
+                })
+                // the log pane shows the changes, printing them would garble the screen
+                .map(|runner| runner.with_print(false))
+                .with_context(|| format!("while setting up watch {provider}"))?;
+                runners.push(runner);
+            }
+            tui::TuiRunner::new(runners, schedule.build_scheduler()?).run()?
+        }
+
+        // Diagnosis
+        Commands::Doctor { provider, notifier } => {
+            doctor::DoctorRunner::new(provider, notifier).diagnose()?
+        }
     }
 
-    Ok(())
+    Ok(exit_codes::NO_CHANGE)
 }
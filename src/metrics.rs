@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+// Provider API metrics
+
+/// How many checks the history of a provider keeps.
+pub const HISTORY_LENGTH: usize = 100;
+
+/// A single request sent to a provider API.
+pub struct CallMetric {
+    pub method: String,
+    pub host: String,
+    pub duration: Duration,
+    /// HTTP status of the response, or None when no response was received
+    pub status: Option<u16>,
+}
+
+impl CallMetric {
+    /// Tells if the call failed, either without response or with an error status.
+    pub fn is_error(&self) -> bool {
+        self.status.is_none_or(|status| status >= 400)
+    }
+}
+
+/// Calls recorded since they were last taken, by the check which is running.
+static CALLS: Mutex<Vec<CallMetric>> = Mutex::new(Vec::new());
+
+/// Totals since the process started, per provider, for the metrics endpoint.
+static TOTALS: Mutex<BTreeMap<String, ProviderTotals>> = Mutex::new(BTreeMap::new());
+
+/// Locks a metric, even if a thread panicked while holding it.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Records a call, as every provider request goes through `recording::send()`.
+pub fn record(call: CallMetric) {
+    lock(&CALLS).push(call);
+}
+
+/// Takes the calls recorded since the last time, so that a check gets its own.
+pub fn take() -> Vec<CallMetric> {
+    std::mem::take(&mut *lock(&CALLS))
+}
+
+/// Summary of the calls of a single check, as kept in the history of a provider.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct CheckMetrics {
+    /// Timestamp of the check
+    pub timestamp: u64,
+    pub calls: u32,
+    /// Calls without response, or with an error status
+    pub errors: u32,
+    /// Total duration of the calls, in milliseconds
+    pub total_ms: u64,
+    /// Duration of the slowest call, in milliseconds
+    pub max_ms: u64,
+    /// Number of responses per status, `none` counting calls without response
+    #[serde(default)]
+    pub statuses: BTreeMap<String, u32>,
+}
+
+impl CheckMetrics {
+    /// Summarizes the calls of a check.
+    pub fn from_calls(calls: &[CallMetric], timestamp: u64) -> Self {
+        let mut metrics = Self {
+            timestamp,
+            ..Default::default()
+        };
+        for call in calls {
+            let ms = call.duration.as_millis() as u64;
+            metrics.calls += 1;
+            metrics.errors += call.is_error() as u32;
+            metrics.total_ms += ms;
+            metrics.max_ms = metrics.max_ms.max(ms);
+            *metrics
+                .statuses
+                .entry(status_label(call.status))
+                .or_default() += 1;
+        }
+        metrics
+    }
+
+    /// Gets the average duration of the calls, in milliseconds.
+    pub fn average_ms(&self) -> u64 {
+        self.total_ms.checked_div(self.calls as u64).unwrap_or(0)
+    }
+}
+
+impl fmt::Display for CheckMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let statuses: Vec<String> = self
+            .statuses
+            .iter()
+            .map(|(status, count)| format!("{status}: {count}"))
+            .collect();
+        write!(
+            f,
+            "{} calls, {} errors, {} ms average, {} ms max ({})",
+            self.calls,
+            self.errors,
+            self.average_ms(),
+            self.max_ms,
+            statuses.join(", ")
+        )
+    }
+}
+
+/// Adds the metrics of a check to the history of a provider, dropping the oldest ones.
+pub fn push_history(history: &mut Vec<CheckMetrics>, metrics: CheckMetrics) {
+    history.push(metrics);
+    let excess = history.len().saturating_sub(HISTORY_LENGTH);
+    history.drain(..excess);
+}
+
+/// Describes the history of a provider, so that a degrading API stands out.
+pub fn describe_history(history: &[CheckMetrics]) -> String {
+    let calls: u32 = history.iter().map(|metrics| metrics.calls).sum();
+    let errors: u32 = history.iter().map(|metrics| metrics.errors).sum();
+    let total_ms: u64 = history.iter().map(|metrics| metrics.total_ms).sum();
+    let error_rate = match calls {
+        0 => 0.0,
+        _ => errors as f64 * 100.0 / calls as f64,
+    };
+    format!(
+        "last {} checks: {} calls, {:.1}% errors, {} ms average",
+        history.len(),
+        calls,
+        error_rate,
+        total_ms.checked_div(calls as u64).unwrap_or(0)
+    )
+}
+
+/// Gets the label of a status, `none` for calls without response.
+fn status_label(status: Option<u16>) -> String {
+    status.map_or("none".to_string(), |status| status.to_string())
+}
+
+/// Totals of the calls of a provider.
+#[derive(Default)]
+struct ProviderTotals {
+    calls: BTreeMap<String, u64>,
+    seconds: f64,
+    max_seconds: f64,
+}
+
+/// Adds the calls of a check to the totals of its provider.
+pub fn add_totals(provider_name: &str, calls: &[CallMetric]) {
+    let mut totals = lock(&TOTALS);
+    let totals = totals.entry(provider_name.to_string()).or_default();
+    for call in calls {
+        let seconds = call.duration.as_secs_f64();
+        *totals.calls.entry(status_label(call.status)).or_default() += 1;
+        totals.seconds += seconds;
+        totals.max_seconds = totals.max_seconds.max(seconds);
+    }
+}
+
+/// Renders the totals in the Prometheus text format.
+pub fn render() -> String {
+    let totals = lock(&TOTALS);
+    let mut text = String::new();
+    text.push_str("# HELP dsaw_provider_calls_total Requests sent to provider APIs, by status.\n");
+    text.push_str("# TYPE dsaw_provider_calls_total counter\n");
+    for (provider, totals) in totals.iter() {
+        for (status, count) in totals.calls.iter() {
+            text.push_str(&format!(
+                "dsaw_provider_calls_total{{provider=\"{provider}\",status=\"{status}\"}} {count}\n"
+            ));
+        }
+    }
+    text.push_str(
+        "# HELP dsaw_provider_call_seconds_total Time spent in requests to provider APIs.\n",
+    );
+    text.push_str("# TYPE dsaw_provider_call_seconds_total counter\n");
+    for (provider, totals) in totals.iter() {
+        text.push_str(&format!(
+            "dsaw_provider_call_seconds_total{{provider=\"{provider}\"}} {}\n",
+            totals.seconds
+        ));
+    }
+    text.push_str("# HELP dsaw_provider_call_seconds_max Slowest request to provider APIs.\n");
+    text.push_str("# TYPE dsaw_provider_call_seconds_max gauge\n");
+    for (provider, totals) in totals.iter() {
+        text.push_str(&format!(
+            "dsaw_provider_call_seconds_max{{provider=\"{provider}\"}} {}\n",
+            totals.max_seconds
+        ));
+    }
+    text
+}
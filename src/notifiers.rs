@@ -1,7 +1,10 @@
 use anyhow;
 use anyhow::Context;
 use colored::Colorize;
+use std::path::Path;
 
+use crate::alert::OperationalAlert;
+use crate::providers::CheckStage;
 use crate::{CheckResult, LibError};
 
 /// Provides the implementation for IFTTT-Webhook notifiers
@@ -15,8 +18,80 @@ pub mod simple;
 #[cfg(feature = "email")]
 pub mod email;
 
+/// Provides the implementation for PagerDuty notifiers
+#[cfg(feature = "pagerduty")]
+pub mod pagerduty;
+
+/// Provides the implementation for Opsgenie notifiers
+#[cfg(feature = "opsgenie")]
+pub mod opsgenie;
+
+/// Provides the implementation for Microsoft Teams notifiers
+#[cfg(feature = "teams")]
+pub mod teams;
+
+/// Provides the implementation for Mattermost notifiers
+#[cfg(feature = "mattermost")]
+pub mod mattermost;
+
+/// Provides the implementation for Rocket.Chat notifiers
+#[cfg(feature = "rocketchat")]
+pub mod rocketchat;
+
+/// Provides the implementation for Zulip notifiers
+#[cfg(feature = "zulip")]
+pub mod zulip;
+
+/// Provides the implementation for Google Chat notifiers
+#[cfg(feature = "google-chat")]
+pub mod google_chat;
+
+/// Provides the implementation for Home Assistant notifiers
+#[cfg(feature = "home-assistant")]
+pub mod home_assistant;
+
+/// Provides the implementation for desktop notifiers
+#[cfg(feature = "desktop")]
+pub mod desktop;
+
+/// Provides the implementation for command execution notifiers
+#[cfg(feature = "exec")]
+pub mod exec;
+
+/// Provides the implementation for Twilio SMS notifiers
+#[cfg(feature = "twilio-sms")]
+pub mod twilio_sms;
+
+/// Provides the implementation for syslog notifiers
+#[cfg(feature = "syslog")]
+pub mod syslog;
+
+/// Provides the implementation for json lines file notifiers
+#[cfg(feature = "file")]
+pub mod file;
+
+/// Provides the implementation for MQTT notifiers
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+
+/// Provides the implementation for Apache Kafka notifiers
+#[cfg(feature = "kafka")]
+pub mod kafka;
+
+/// Provides the policies applied around any notifier, like rate limits
+pub mod middleware;
+
+/// Provides the implementation for the mock notifier, used for testing
+#[cfg(feature = "testing")]
+pub mod mock;
+
+/// Provides the implementation for AWS SNS notifiers
+#[cfg(feature = "aws")]
+pub mod sns;
+
 /// Defines the expected behaviour of every notifier handler.
-pub trait NotifierTrait {
+/// Notifiers can be moved along with a `Watcher` to the thread running it.
+pub trait NotifierTrait: Send {
     /// Gets the actual name of the notifier.
     fn name(&self) -> &'static str;
 
@@ -25,6 +100,25 @@ pub trait NotifierTrait {
 
     /// Does whatever is required to test the notifier.
     fn test(&self) -> Result<(), LibError>;
+
+    /// Verifies credentials and connectivity with a lightweight call, without
+    /// notifying anything. Returns false when the notifier offers no such call.
+    fn verify(&self) -> Result<bool, LibError> {
+        Ok(false)
+    }
+
+    /// Tells if the notifier can send operational alerts, through `alert()`.
+    fn supports_alerts(&self) -> bool {
+        false
+    }
+
+    /// Sends an operational alert, about failing checks rather than availability.
+    fn alert(&self, _alert: &OperationalAlert) -> Result<(), LibError> {
+        Err(LibError::ValueError {
+            name: "notifier without operational alerts".into(),
+            value: self.name().to_string(),
+        })
+    }
 }
 
 /// Defines the expected behaviour for builing the desired notifier.
@@ -44,6 +138,8 @@ static FACTORY: &[(&str, FactoryFunc)] = &[
     (simple::SIMPLE_POST_NAME, simple::SimplePost::from_env),
     #[cfg(feature = "simple-put")]
     (simple::SIMPLE_PUT_NAME, simple::SimplePut::from_env),
+    #[cfg(feature = "simple-form")]
+    (simple::SIMPLE_FORM_NAME, simple::SimpleForm::from_env),
     #[cfg(feature = "ifttt-webhook-json")]
     (
         ifttt_webhook::IFTTT_WEBHOOK_JSON_NAME,
@@ -59,6 +155,47 @@ static FACTORY: &[(&str, FactoryFunc)] = &[
         email::EMAIL_SENDMAIL_NAME,
         email::EmailViaSendmail::from_env,
     ),
+    #[cfg(feature = "email-smtp")]
+    (email::EMAIL_SMTP_NAME, email::EmailViaSmtp::from_env),
+    #[cfg(feature = "pagerduty")]
+    (pagerduty::PAGERDUTY_NAME, pagerduty::PagerDuty::from_env),
+    #[cfg(feature = "opsgenie")]
+    (opsgenie::OPSGENIE_NAME, opsgenie::Opsgenie::from_env),
+    #[cfg(feature = "mqtt")]
+    (mqtt::MQTT_NAME, mqtt::Mqtt::from_env),
+    #[cfg(feature = "teams")]
+    (teams::TEAMS_NAME, teams::Teams::from_env),
+    #[cfg(feature = "mattermost")]
+    (
+        mattermost::MATTERMOST_NAME,
+        mattermost::Mattermost::from_env,
+    ),
+    #[cfg(feature = "rocketchat")]
+    (
+        rocketchat::ROCKETCHAT_NAME,
+        rocketchat::RocketChat::from_env,
+    ),
+    #[cfg(feature = "home-assistant")]
+    (
+        home_assistant::HOME_ASSISTANT_NAME,
+        home_assistant::HomeAssistant::from_env,
+    ),
+    #[cfg(feature = "exec")]
+    (exec::EXEC_NAME, exec::Exec::from_env),
+    #[cfg(feature = "desktop")]
+    (desktop::DESKTOP_NAME, desktop::Desktop::from_env),
+    #[cfg(feature = "syslog")]
+    (syslog::SYSLOG_NAME, syslog::Syslog::from_env),
+    #[cfg(feature = "file")]
+    (file::FILE_NAME, file::File::from_env),
+    #[cfg(feature = "twilio-sms")]
+    (twilio_sms::TWILIO_SMS_NAME, twilio_sms::TwilioSms::from_env),
+    #[cfg(feature = "aws")]
+    (sns::SNS_NAME, sns::Sns::from_env),
+    #[cfg(feature = "kafka")]
+    (kafka::KAFKA_NAME, kafka::Kafka::from_env),
+    #[cfg(feature = "testing")]
+    (mock::MOCK_NAME, mock::MockNotifier::from_env),
 ];
 
 /// Trait to help create notifiers.
@@ -66,7 +203,8 @@ pub struct Factory;
 
 /// Global notifier factory, based on the reference table
 impl Factory {
-    /// Selects the desired notifier type and build it from environment variables.
+    /// Selects the desired notifier type and build it from environment variables,
+    /// wrapped with the policies configured for it.
     pub fn from_env_by_name(notifier: &str) -> Result<Box<dyn NotifierTrait>, LibError> {
         let (_, factory) = FACTORY
             .iter()
@@ -74,7 +212,7 @@ impl Factory {
             .ok_or_else(|| LibError::UnknownNotifier {
                 notifier: notifier.to_string(),
             })?;
-        factory()
+        middleware::from_env(factory()?)
     }
 
     /// Provides a list of all known notifier types.
@@ -110,7 +248,8 @@ impl TestRunner {
     pub fn new(notifier_name: &str) -> anyhow::Result<Self> {
         Ok(Self {
             notifier: Factory::from_env_by_name(notifier_name)
-                .with_context(|| format!("while setting up notifier {notifier_name}"))?,
+                .with_context(|| format!("while setting up notifier {notifier_name}"))
+                .context(CheckStage::Notifier)?,
         })
     }
 
@@ -118,8 +257,31 @@ impl TestRunner {
     pub fn test(&self) -> anyhow::Result<()> {
         self.notifier
             .test()
-            .with_context(|| format!("while testing notifier {}", self.notifier.name()))?;
+            .with_context(|| format!("while testing notifier {}", self.notifier.name()))
+            .context(CheckStage::Notifier)?;
+        println!("{}", "Notification sent".to_string().green());
+        Ok(())
+    }
+
+    /// Tests selected notifier with a result loaded from a json file, instead of dummy values.
+    pub fn test_payload(&self, path: &Path) -> anyhow::Result<()> {
+        let result = CheckResult::load(path)
+            .with_context(|| format!("while loading payload {}", path.display()))?;
+        self.notifier
+            .notify(&result)
+            .with_context(|| format!("while testing notifier {}", self.notifier.name()))
+            .context(CheckStage::Notifier)?;
         println!("{}", "Notification sent".to_string().green());
         Ok(())
     }
+
+    /// Tests operational alerts through the selected notifier, with dummy values.
+    pub fn test_alert(&self) -> anyhow::Result<()> {
+        self.notifier
+            .alert(&OperationalAlert::get_dummy())
+            .with_context(|| format!("while testing alerts of notifier {}", self.notifier.name()))
+            .context(CheckStage::Notifier)?;
+        println!("{}", "Alert sent".to_string().green());
+        Ok(())
+    }
 }
@@ -0,0 +1,66 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::{CheckResult, LibError};
+use notify_rust::Notification;
+
+// DESKTOP implementation
+
+/// Common name to identify the notifier
+pub const DESKTOP_NAME: &str = "desktop";
+
+/// Application name shown along with the notifications, where supported.
+const DESKTOP_APP_NAME: &str = "dedicated-server-availability-watcher";
+
+/// Pops a native notification on the desktop of the user running the watcher,
+/// through the notification service of the platform (D-Bus on Linux and BSD,
+/// the notification center on macOS, toasts on Windows).
+pub struct Desktop;
+
+impl Desktop {
+    /// Builds the title and the body of the notification
+    fn build_notification(result: &CheckResult) -> (String, String) {
+        let provider_name = &result.provider_name;
+        if result.available_servers.is_empty() {
+            return (
+                format!("No server available at {provider_name}"),
+                "No server available for the selected types !".to_string(),
+            );
+        }
+        (
+            format!("Servers available at {provider_name}"),
+            result.available_servers.join("\n"),
+        )
+    }
+}
+
+impl NotifierFactoryTrait for Desktop {
+    /// Builds a Desktop notifier, which does not need any configuration.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        Ok(Box::new(Desktop))
+    }
+}
+
+impl NotifierTrait for Desktop {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        DESKTOP_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let (title, body) = Self::build_notification(result);
+        Notification::new()
+            .appname(DESKTOP_APP_NAME)
+            .summary(&title)
+            .body(&body)
+            .show()
+            .map_err(|error| LibError::DesktopError {
+                message: error.to_string(),
+            })?;
+        Ok(())
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+}
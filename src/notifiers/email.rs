@@ -1,9 +1,11 @@
-use lettre::message::Mailbox;
-use lettre::SendmailTransport;
-use lettre::{Message, Transport};
+use std::str::FromStr;
+
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SendmailTransport, SmtpTransport, Transport};
 
 use crate::LibError::EmailError;
-use crate::{CheckResult, LibError};
+use crate::{template, CheckResult, LibError};
 
 use super::{NotifierFactoryTrait, NotifierTrait};
 
@@ -11,6 +13,39 @@ use super::{NotifierFactoryTrait, NotifierTrait};
 const ENV_EMAIL_FROM: &str = "EMAIL_FROM";
 const ENV_EMAIL_TO: &str = "EMAIL_TO";
 
+/// Environment variables to configure the SMTP server.
+const ENV_EMAIL_SMTP_HOST: &str = "EMAIL_SMTP_HOST";
+const ENV_EMAIL_SMTP_PORT: &str = "EMAIL_SMTP_PORT";
+const ENV_EMAIL_SMTP_SECURITY: &str = "EMAIL_SMTP_SECURITY";
+const ENV_EMAIL_SMTP_USERNAME: &str = "EMAIL_SMTP_USERNAME";
+const ENV_EMAIL_SMTP_PASSWORD: &str = "EMAIL_SMTP_PASSWORD";
+
+/// Environment variable to optionally replace the default subject, see `render_subject`.
+const ENV_EMAIL_SUBJECT: &str = "EMAIL_SUBJECT";
+
+/// Default subject template
+const DEFAULT_SUBJECT_TEMPLATE: &str = "Server availability notification for {provider}";
+
+/// Environment variable to optionally replace the built-in HTML template.
+const ENV_EMAIL_TEMPLATE_PATH: &str = "EMAIL_TEMPLATE_PATH";
+
+/// Style of the cells of the server table
+const HTML_CELL_STYLE: &str = "border: 1px solid #cccccc; padding: 4px 8px;";
+
+/// Built-in HTML template, see `render_html` for the available placeholders
+const DEFAULT_HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<body style="font-family: sans-serif; color: #333333;">
+<h2>Server availability for {provider}</h2>
+<p>{count} server type(s) available.</p>
+<table style="border-collapse: collapse;">
+<tr><th style="border: 1px solid #cccccc; padding: 4px 8px; background: #eeeeee; text-align: left;">Server</th></tr>
+{rows}
+</table>
+</body>
+</html>
+"#;
+
 /// Common functions
 fn mailbox_from_string(mailbox: &str) -> Result<Mailbox, LibError> {
     mailbox.parse::<Mailbox>().map_err(|e| EmailError {
@@ -18,33 +53,80 @@ fn mailbox_from_string(mailbox: &str) -> Result<Mailbox, LibError> {
     })
 }
 
-/// Get a destination mailbox from the environment
-fn env_mailbox_to() -> Result<Mailbox, LibError> {
-    let email = crate::get_env_var(ENV_EMAIL_TO)?;
-    mailbox_from_string(&email)
+/// Reads a custom HTML template
+fn read_template(path: &str) -> Result<String, LibError> {
+    std::fs::read_to_string(path).map_err(|e| EmailError {
+        message: format!("{e} in `{path}`"),
+    })
 }
 
-/// Maybe get an originating mailbox from the environment
-fn env_mailbox_from() -> Result<Mailbox, LibError> {
-    let email = crate::get_env_var(ENV_EMAIL_FROM)?;
-    mailbox_from_string(&email)
+/// Renders the HTML body from the template, whose placeholders are
+/// `{provider}`, `{count}`, `{servers}` (comma separated) and `{rows}` (table rows)
+fn render_html(result: &CheckResult, html_template: &str) -> String {
+    let cells = match result.available_servers.is_empty() {
+        true => vec!["No server available for the selected types !".to_string()],
+        false => result
+            .available_servers
+            .iter()
+            .map(|server| match result.order_urls.get(server) {
+                Some(order_url) => format!(
+                    "<a href=\"{}\">{}</a>",
+                    template::escape_html(order_url),
+                    template::escape_html(server)
+                ),
+                None => template::escape_html(server),
+            })
+            .collect(),
+    };
+    let rows = cells
+        .iter()
+        .map(|cell| format!("<tr><td style=\"{HTML_CELL_STYLE}\">{cell}</td></tr>"))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    template::render(
+        html_template,
+        &[
+            ("provider", &template::escape_html(&result.provider_name)),
+            ("count", &result.available_servers.len().to_string()),
+            (
+                "servers",
+                &template::escape_html(&result.available_servers.join(", ")),
+            ),
+            ("rows", &rows),
+        ],
+    )
 }
 
-/// Build a report message, using additional environment variables
-fn env_create_message(result: &CheckResult) -> Result<Message, LibError> {
-    let from = env_mailbox_from()?;
-    let to = env_mailbox_to()?;
-    create_message(result, to, from)
+/// Renders the subject from the template, whose placeholders are
+/// `{provider}`, `{count}` and `{servers}` (comma separated)
+fn render_subject(result: &CheckResult, subject_template: &str) -> String {
+    template::render(
+        subject_template,
+        &[
+            ("provider", &result.provider_name),
+            ("count", &result.available_servers.len().to_string()),
+            ("servers", &result.available_servers.join(", ")),
+        ],
+    )
 }
 
-/// Build a report message
-fn create_message(result: &CheckResult, to: Mailbox, from: Mailbox) -> Result<Message, LibError> {
-    let name = &result.provider_name;
+/// Build a report message, with both a plain text and an HTML body
+fn create_message(
+    result: &CheckResult,
+    to: Mailbox,
+    from: Mailbox,
+    subject_template: &str,
+    html_template: &str,
+) -> Result<Message, LibError> {
     Message::builder()
         .from(from)
         .to(to)
-        .subject(format!("Server availability notification for {name}"))
-        .body(result.to_string())
+        .subject(render_subject(result, subject_template))
+        .multipart(MultiPart::alternative_plain_html(
+            result.to_string(),
+            render_html(result, html_template),
+        ))
         .map_err(|e| EmailError {
             message: format!("{e} in ``"),
         })
@@ -53,9 +135,37 @@ fn create_message(result: &CheckResult, to: Mailbox, from: Mailbox) -> Result<Me
 /// Common name to identify the provider
 pub const EMAIL_SENDMAIL_NAME: &str = "email-sendmail";
 
-pub struct EmailViaSendmail {}
+/// Sends the report through the local `sendmail` binary.
+pub struct EmailViaSendmail {
+    from: Mailbox,
+    to: Mailbox,
+    subject: String,
+    template: String,
+}
 
 impl EmailViaSendmail {
+    /// Builds a new instance, from the originating and destination mailboxes
+    pub fn new(from: &str, to: &str) -> Result<Self, LibError> {
+        Ok(Self {
+            from: mailbox_from_string(from)?,
+            to: mailbox_from_string(to)?,
+            subject: DEFAULT_SUBJECT_TEMPLATE.to_string(),
+            template: DEFAULT_HTML_TEMPLATE.to_string(),
+        })
+    }
+
+    /// Replaces the default subject template
+    pub fn with_subject(mut self, subject: &str) -> Self {
+        self.subject = subject.to_string();
+        self
+    }
+
+    /// Replaces the built-in HTML template
+    pub fn with_template(mut self, template: &str) -> Self {
+        self.template = template.to_string();
+        self
+    }
+
     fn send(message: Message) -> Result<(), LibError> {
         SendmailTransport::new()
             .send(&message)
@@ -66,25 +176,311 @@ impl EmailViaSendmail {
 }
 
 impl NotifierFactoryTrait for EmailViaSendmail {
-    /// Builds a SimpleGet notifier from environment variables.
+    /// Builds an EmailViaSendmail notifier from environment variables.
     fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
-        Ok(Box::new(EmailViaSendmail {}))
+        let from = crate::get_env_var(ENV_EMAIL_FROM)?;
+        let to = crate::get_env_var(ENV_EMAIL_TO)?;
+        let mut notifier = Self::new(&from, &to)?;
+        if let Some(subject) = crate::get_env_var_option(ENV_EMAIL_SUBJECT) {
+            notifier = notifier.with_subject(&subject);
+        }
+        if let Some(path) = crate::get_env_var_option(ENV_EMAIL_TEMPLATE_PATH) {
+            notifier = notifier.with_template(&read_template(&path)?);
+        }
+        Ok(Box::new(notifier))
     }
 }
 
 impl NotifierTrait for EmailViaSendmail {
     /// Gets the actual name of the notifier.
     fn name(&self) -> &'static str {
-        return EMAIL_SENDMAIL_NAME;
+        EMAIL_SENDMAIL_NAME
+    }
+
+    /// Sends a notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        Self::send(create_message(
+            result,
+            self.to.clone(),
+            self.from.clone(),
+            &self.subject,
+            &self.template,
+        )?)
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+}
+
+/// Common name to identify the provider
+pub const EMAIL_SMTP_NAME: &str = "email-smtp";
+
+/// How the connection to the SMTP server is secured.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SmtpSecurity {
+    /// Plain text connection, for trusted internal relays
+    None,
+    /// Plain text connection upgraded with the STARTTLS command
+    #[default]
+    StartTls,
+    /// Implicit TLS from the start of the connection
+    Tls,
+}
+
+impl SmtpSecurity {
+    /// Gets the well-known port for the security mode
+    pub fn default_port(&self) -> u16 {
+        match self {
+            SmtpSecurity::None => 25,
+            SmtpSecurity::StartTls => 587,
+            SmtpSecurity::Tls => 465,
+        }
+    }
+}
+
+impl FromStr for SmtpSecurity {
+    type Err = LibError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "none" => Ok(SmtpSecurity::None),
+            "starttls" => Ok(SmtpSecurity::StartTls),
+            "tls" => Ok(SmtpSecurity::Tls),
+            _ => Err(LibError::ValueError {
+                name: ENV_EMAIL_SMTP_SECURITY.to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+}
+
+/// Sends the report through an SMTP server.
+///
+/// The transport is kept for the lifetime of the notifier, so that its pool
+/// of connections is reused across the notifications of the watch loop.
+pub struct EmailViaSmtp {
+    from: Mailbox,
+    to: Mailbox,
+    subject: String,
+    template: String,
+    host: String,
+    port: u16,
+    transport: SmtpTransport,
+}
+
+/// Builds an EmailViaSmtp notifier without relying on environment variables.
+#[derive(Default)]
+pub struct EmailViaSmtpBuilder {
+    from: String,
+    to: String,
+    subject: Option<String>,
+    template: Option<String>,
+    host: String,
+    port: Option<u16>,
+    security: SmtpSecurity,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl EmailViaSmtpBuilder {
+    /// Sets the originating mailbox.
+    pub fn from(mut self, from: &str) -> Self {
+        self.from = from.to_string();
+        self
+    }
+
+    /// Sets the destination mailbox.
+    pub fn to(mut self, to: &str) -> Self {
+        self.to = to.to_string();
+        self
+    }
+
+    /// Replaces the default subject template.
+    pub fn subject(mut self, subject: &str) -> Self {
+        self.subject = Some(subject.to_string());
+        self
+    }
+
+    /// Replaces the built-in HTML template.
+    pub fn template(mut self, template: &str) -> Self {
+        self.template = Some(template.to_string());
+        self
+    }
+
+    /// Sets the SMTP server host name.
+    pub fn host(mut self, host: &str) -> Self {
+        self.host = host.to_string();
+        self
+    }
+
+    /// Sets the SMTP server port, which otherwise depends on the security mode.
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Sets how the connection is secured, STARTTLS being the default.
+    pub fn security(mut self, security: SmtpSecurity) -> Self {
+        self.security = security;
+        self
+    }
+
+    /// Sets the username, authentication requiring both a username and a password.
+    pub fn username(mut self, username: &str) -> Self {
+        self.username = Some(username.to_string());
+        self
+    }
+
+    /// Sets the password, sending anonymously when neither it nor a username is set.
+    pub fn password(mut self, password: &str) -> Self {
+        self.password = Some(password.to_string());
+        self
+    }
+
+    /// Builds the notifier, verifying the settings.
+    pub fn build(self) -> Result<EmailViaSmtp, LibError> {
+        if self.host.is_empty() {
+            return Err(LibError::ValueError {
+                name: "found empty smtp host".into(),
+                value: self.host,
+            });
+        }
+
+        let credentials = match (self.username, self.password) {
+            (Some(username), Some(password)) => Some(Credentials::new(username, password)),
+            (None, None) => None,
+            (None, Some(_)) => {
+                return Err(LibError::ValueError {
+                    name: "found smtp password without username".into(),
+                    value: String::new(),
+                })
+            }
+            (Some(username), None) => {
+                return Err(LibError::ValueError {
+                    name: "found smtp username without password".into(),
+                    value: username,
+                })
+            }
+        };
+
+        let port = self.port.unwrap_or(self.security.default_port());
+        let transport = match self.security {
+            SmtpSecurity::None => Ok(SmtpTransport::builder_dangerous(&self.host)),
+            SmtpSecurity::StartTls => SmtpTransport::starttls_relay(&self.host),
+            SmtpSecurity::Tls => SmtpTransport::relay(&self.host),
+        }
+        .map_err(|e| EmailError {
+            message: format!("{e} for `{}`", self.host),
+        })?
+        .port(port);
+        let transport = match credentials {
+            Some(credentials) => transport.credentials(credentials),
+            None => transport,
+        }
+        .build();
+
+        Ok(EmailViaSmtp {
+            from: mailbox_from_string(&self.from)?,
+            to: mailbox_from_string(&self.to)?,
+            subject: self
+                .subject
+                .unwrap_or_else(|| DEFAULT_SUBJECT_TEMPLATE.to_string()),
+            template: self
+                .template
+                .unwrap_or_else(|| DEFAULT_HTML_TEMPLATE.to_string()),
+            host: self.host,
+            port,
+            transport,
+        })
+    }
+}
+
+impl EmailViaSmtp {
+    /// Starts building a new instance.
+    pub fn builder() -> EmailViaSmtpBuilder {
+        EmailViaSmtpBuilder::default()
+    }
+
+    /// Tells if a failed delivery may succeed on a new connection, like when
+    /// the server closed a pooled connection which was idle for too long.
+    /// Failures reported by the server itself, or occurring before any
+    /// connection was made, would only fail again.
+    fn is_reconnectable(error: &lettre::transport::smtp::Error) -> bool {
+        error.status().is_none() && !error.is_client() && !error.is_tls()
+    }
+
+    fn send(&self, message: Message) -> Result<(), LibError> {
+        self.transport
+            .send(&message)
+            .or_else(|e| match Self::is_reconnectable(&e) {
+                // the failed connection is dropped from the pool, so a new one is made
+                true => self.transport.send(&message),
+                false => Err(e),
+            })
+            .map(|_| ())
+            .map_err(|e| EmailError {
+                message: format!("{e} in `{message:?}`"),
+            })
+    }
+}
+
+impl NotifierFactoryTrait for EmailViaSmtp {
+    /// Builds an EmailViaSmtp notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let mut builder = Self::builder()
+            .from(&crate::get_env_var(ENV_EMAIL_FROM)?)
+            .to(&crate::get_env_var(ENV_EMAIL_TO)?)
+            .host(&crate::get_env_var(ENV_EMAIL_SMTP_HOST)?);
+        if let Some(subject) = crate::get_env_var_option(ENV_EMAIL_SUBJECT) {
+            builder = builder.subject(&subject);
+        }
+        if let Some(path) = crate::get_env_var_option(ENV_EMAIL_TEMPLATE_PATH) {
+            builder = builder.template(&read_template(&path)?);
+        }
+        if let Some(port) = crate::get_env_var_parsed(ENV_EMAIL_SMTP_PORT)? {
+            builder = builder.port(port);
+        }
+        if let Some(security) = crate::get_env_var_option(ENV_EMAIL_SMTP_SECURITY) {
+            builder = builder.security(security.parse()?);
+        }
+        if let Some(username) = crate::get_env_var_option(ENV_EMAIL_SMTP_USERNAME) {
+            builder = builder.username(&username);
+        }
+        if let Some(password) = crate::get_env_var_option(ENV_EMAIL_SMTP_PASSWORD) {
+            builder = builder.password(&password);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl NotifierTrait for EmailViaSmtp {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        EMAIL_SMTP_NAME
     }
 
     /// Sends a notification using the provided data.
     fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
-        Self::send(env_create_message(result)?)
+        self.send(create_message(
+            result,
+            self.to.clone(),
+            self.from.clone(),
+            &self.subject,
+            &self.template,
+        )?)
     }
 
     /// Tests by sending a notification with dummy values.
     fn test(&self) -> Result<(), LibError> {
         self.notify(&CheckResult::get_dummy())
     }
+
+    /// Verifies that the server accepts the connection and the credentials.
+    fn verify(&self) -> Result<bool, LibError> {
+        self.transport.test_connection().map_err(|e| EmailError {
+            message: format!("{e} for `{}:{}`", self.host, self.port),
+        })
+    }
 }
@@ -0,0 +1,140 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::alert::OperationalAlert;
+use crate::{CheckResult, LibError};
+use std::time::Duration;
+
+// EXEC implementation
+
+/// Common name to identify the notifier
+pub const EXEC_NAME: &str = "exec";
+
+/// Common environment variable to select the command line to run.
+const ENV_EXEC_COMMAND: &str = "EXEC_COMMAND";
+
+/// Environment variable to optionally bound the execution time, in seconds.
+const ENV_EXEC_TIMEOUT: &str = "EXEC_TIMEOUT";
+
+/// Timeout used when none is configured.
+const EXEC_DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Variables passed to the command, in addition to the json payload on stdin.
+const ENV_DSAW_PROVIDER: &str = "DSAW_PROVIDER";
+const ENV_DSAW_SERVERS: &str = "DSAW_SERVERS";
+
+/// Variables passed to the command instead of the servers, for operational alerts.
+const ENV_DSAW_ALERT_KIND: &str = "DSAW_ALERT_KIND";
+const ENV_DSAW_CONSECUTIVE_FAILURES: &str = "DSAW_CONSECUTIVE_FAILURES";
+
+/// Runs a user-specified command line for every notification.
+/// The json serialization of the result is written on its stdin,
+/// and the provider and comma-separated servers are set in its environment.
+/// Operational alerts are written the same way, with their kind and failure count instead.
+pub struct Exec {
+    command: String,
+    timeout: Duration,
+}
+
+/// Builds an Exec notifier without relying on environment variables.
+pub struct ExecBuilder {
+    command: String,
+    timeout: Duration,
+}
+
+impl Default for ExecBuilder {
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            timeout: EXEC_DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl ExecBuilder {
+    /// Sets the command line, run through the shell.
+    pub fn command(mut self, command: &str) -> Self {
+        self.command = command.to_string();
+        self
+    }
+
+    /// Sets how long the command may run before being killed (defaults to 30 seconds).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Builds the notifier, attempting to sanitize inputs
+    pub fn build(self) -> Result<Exec, LibError> {
+        if self.command.is_empty() {
+            return Err(LibError::ValueError {
+                name: "exec command".into(),
+                value: self.command,
+            });
+        }
+
+        Ok(Exec {
+            command: self.command,
+            timeout: self.timeout,
+        })
+    }
+}
+
+impl Exec {
+    /// Starts building a new instance.
+    pub fn builder() -> ExecBuilder {
+        ExecBuilder::default()
+    }
+}
+
+impl NotifierFactoryTrait for Exec {
+    /// Builds an Exec notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let command = crate::get_env_var(ENV_EXEC_COMMAND)?;
+        let mut builder = Self::builder().command(&command);
+        if let Some(timeout) = crate::get_env_var_option(ENV_EXEC_TIMEOUT) {
+            builder = builder.timeout(crate::command::parse_timeout(ENV_EXEC_TIMEOUT, &timeout)?);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl NotifierTrait for Exec {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        EXEC_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let envs = [
+            (ENV_DSAW_PROVIDER, result.provider_name.clone()),
+            (ENV_DSAW_SERVERS, result.available_servers.join(",")),
+        ];
+        // we are not interested in the actual output of the command
+        crate::command::run(&self.command, &result.to_json()?, &envs, self.timeout)?;
+        Ok(())
+    }
+
+    /// Tells that the notifier can send operational alerts.
+    fn supports_alerts(&self) -> bool {
+        true
+    }
+
+    /// Sends an operational alert using the provided data.
+    fn alert(&self, alert: &OperationalAlert) -> Result<(), LibError> {
+        let envs = [
+            (ENV_DSAW_PROVIDER, alert.provider_name.clone()),
+            (ENV_DSAW_ALERT_KIND, alert.kind.to_string()),
+            (
+                ENV_DSAW_CONSECUTIVE_FAILURES,
+                alert.consecutive_failures.to_string(),
+            ),
+        ];
+        crate::command::run(&self.command, &alert.to_json()?, &envs, self.timeout)?;
+        Ok(())
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+}
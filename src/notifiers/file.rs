@@ -0,0 +1,224 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::alert::{AlertKind, OperationalAlert};
+use crate::{CheckResult, LibError};
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+
+// FILE implementation (append-only json lines)
+
+/// Common name to identify the notifier
+pub const FILE_NAME: &str = "file";
+
+/// Common environment variable to select the path of the file.
+const ENV_FILE_PATH: &str = "FILE_PATH";
+
+/// Environment variable to optionally rotate the file above a size, in bytes.
+const ENV_FILE_MAX_SIZE: &str = "FILE_MAX_SIZE";
+
+/// Environment variable to optionally select how many rotated files are kept.
+const ENV_FILE_MAX_FILES: &str = "FILE_MAX_FILES";
+
+/// Number of rotated files kept when none is configured.
+const FILE_DEFAULT_MAX_FILES: u32 = 5;
+
+/// Used for line serialisation.
+#[derive(Serialize)]
+struct FileRecord<'a> {
+    timestamp: String,
+    provider: &'a str,
+    servers: &'a [String],
+}
+
+/// Used for line serialisation of operational alerts.
+#[derive(Serialize)]
+struct FileAlertRecord<'a> {
+    timestamp: String,
+    provider: &'a str,
+    alert: AlertKind,
+    consecutive_failures: u32,
+    message: &'a str,
+}
+
+/// Appends one json line per notification to a file, for auditing and offline analysis.
+/// When a maximum size is set, the file is rotated to `path.1`, `path.2`, ...
+/// before it would grow beyond it, and the oldest rotated file is removed.
+pub struct File {
+    path: String,
+    max_size: Option<u64>,
+    max_files: u32,
+}
+
+/// Builds a File notifier without relying on environment variables.
+pub struct FileBuilder {
+    path: String,
+    max_size: Option<u64>,
+    max_files: u32,
+}
+
+impl Default for FileBuilder {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            max_size: None,
+            max_files: FILE_DEFAULT_MAX_FILES,
+        }
+    }
+}
+
+impl FileBuilder {
+    /// Sets the path of the file, which is created if needed.
+    pub fn path(mut self, path: &str) -> Self {
+        self.path = path.to_string();
+        self
+    }
+
+    /// Rotates the file before it grows beyond this size, in bytes.
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Sets how many rotated files are kept (defaults to 5).
+    pub fn max_files(mut self, max_files: u32) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    /// Builds the notifier, attempting to sanitize inputs
+    pub fn build(self) -> Result<File, LibError> {
+        if self.path.is_empty() {
+            return Err(LibError::ValueError {
+                name: "found empty file path".into(),
+                value: self.path,
+            });
+        }
+
+        if self.max_size == Some(0) {
+            return Err(LibError::ValueError {
+                name: ENV_FILE_MAX_SIZE.to_string(),
+                value: "0".into(),
+            });
+        }
+
+        if self.max_files == 0 {
+            return Err(LibError::ValueError {
+                name: ENV_FILE_MAX_FILES.to_string(),
+                value: "0".into(),
+            });
+        }
+
+        Ok(File {
+            path: self.path,
+            max_size: self.max_size,
+            max_files: self.max_files,
+        })
+    }
+}
+
+impl File {
+    /// Starts building a new instance.
+    pub fn builder() -> FileBuilder {
+        FileBuilder::default()
+    }
+
+    /// Gets the path of a rotated file
+    fn rotated_path(&self, index: u32) -> String {
+        format!("{}.{index}", self.path)
+    }
+
+    /// Shifts the rotated files, dropping the oldest one, then rotates the current file
+    fn rotate(&self) -> io::Result<()> {
+        for index in (1..self.max_files).rev() {
+            match fs::rename(self.rotated_path(index), self.rotated_path(index + 1)) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+                _ => {}
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))
+    }
+
+    /// Appends a record as a json line
+    fn append_record<T: Serialize>(&self, record: &T) -> Result<(), LibError> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        self.append(&line).map_err(|source| LibError::FileError {
+            path: self.path.clone().into(),
+            source,
+        })
+    }
+
+    /// Appends a line, rotating the file first if it would grow too large
+    fn append(&self, line: &str) -> io::Result<()> {
+        if let Some(max_size) = self.max_size {
+            let size = match fs::metadata(&self.path) {
+                Ok(metadata) => metadata.len(),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => 0,
+                Err(error) => return Err(error),
+            };
+            if size > 0 && size + line.len() as u64 > max_size {
+                self.rotate()?;
+            }
+        }
+
+        // a single write, so that concurrent writers do not interleave lines
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())
+    }
+}
+
+impl NotifierFactoryTrait for File {
+    /// Builds a File notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let path = crate::get_env_var(ENV_FILE_PATH)?;
+        let mut builder = Self::builder().path(&path);
+        if let Some(max_size) = crate::get_env_var_parsed(ENV_FILE_MAX_SIZE)? {
+            builder = builder.max_size(max_size);
+        }
+        if let Some(max_files) = crate::get_env_var_parsed(ENV_FILE_MAX_FILES)? {
+            builder = builder.max_files(max_files);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl NotifierTrait for File {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        FILE_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        self.append_record(&FileRecord {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            provider: &result.provider_name,
+            servers: &result.available_servers,
+        })
+    }
+
+    /// Tells that the notifier can send operational alerts.
+    fn supports_alerts(&self) -> bool {
+        true
+    }
+
+    /// Sends an operational alert using the provided data.
+    fn alert(&self, alert: &OperationalAlert) -> Result<(), LibError> {
+        self.append_record(&FileAlertRecord {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            provider: &alert.provider_name,
+            alert: alert.kind,
+            consecutive_failures: alert.consecutive_failures,
+            message: &alert.message,
+        })
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+}
@@ -0,0 +1,144 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::{CheckResult, LibError};
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+
+// GOOGLE CHAT implementation (space incoming webhook)
+
+/// Common name to identify the notifier
+pub const GOOGLE_CHAT_NAME: &str = "google-chat";
+
+/// Common environment variable to input the space webhook URL.
+const ENV_GOOGLE_CHAT_WEBHOOK_URL: &str = "GOOGLE_CHAT_WEBHOOK_URL";
+
+/// Posts a cardsV2 message, with a section for the provider
+/// and a widget row per available server.
+pub struct GoogleChat {
+    client: Client,
+    url: String,
+}
+
+/// Builds a GoogleChat notifier without relying on environment variables.
+#[derive(Default)]
+pub struct GoogleChatBuilder {
+    url: String,
+}
+
+impl GoogleChatBuilder {
+    /// Sets the space webhook URL.
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    /// Builds the notifier, attempting to sanitize inputs
+    pub fn build(self) -> Result<GoogleChat, LibError> {
+        if !self.url.starts_with("https://") {
+            return Err(LibError::ValueError {
+                name: "google chat webhook url".into(),
+                value: self.url,
+            });
+        }
+
+        Ok(GoogleChat {
+            client: crate::client::build_client()?,
+            url: self.url,
+        })
+    }
+}
+
+impl GoogleChat {
+    /// Starts building a new instance.
+    pub fn builder() -> GoogleChatBuilder {
+        GoogleChatBuilder::default()
+    }
+
+    /// Builds the cardsV2 message payload
+    fn build_message(result: &CheckResult) -> Value {
+        let provider_name = &result.provider_name;
+
+        let widgets: Vec<Value> = if result.available_servers.is_empty() {
+            vec![json!({
+                "textParagraph": { "text": "No server available for the selected types !" }
+            })]
+        } else {
+            result
+                .available_servers
+                .iter()
+                .map(|server| {
+                    let mut widget = json!({
+                        "decoratedText": {
+                            "startIcon": { "knownIcon": "STAR" },
+                            "text": server,
+                        }
+                    });
+                    if let Some(order_url) = result.order_urls.get(server) {
+                        widget["decoratedText"]["onClick"] =
+                            json!({ "openLink": { "url": order_url } });
+                    }
+                    widget
+                })
+                .collect()
+        };
+
+        json!({
+            // shown in notifications and by clients which cannot render cards
+            "text": format!("Server availability notification for {provider_name}"),
+            "cardsV2": [{
+                "cardId": format!("{GOOGLE_CHAT_NAME}-{provider_name}"),
+                "card": {
+                    "header": {
+                        "title": "Server availability",
+                        "subtitle": format!("{} server(s) available", result.available_servers.len()),
+                    },
+                    "sections": [{
+                        "header": provider_name,
+                        "widgets": widgets,
+                    }],
+                },
+            }],
+        })
+    }
+}
+
+impl NotifierFactoryTrait for GoogleChat {
+    /// Builds a GoogleChat notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let url = crate::get_env_var(ENV_GOOGLE_CHAT_WEBHOOK_URL)?;
+        Ok(Box::new(Self::builder().url(&url).build()?))
+    }
+}
+
+impl NotifierTrait for GoogleChat {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        GOOGLE_CHAT_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&Self::build_message(result))
+            .send()?;
+
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        Err(LibError::ApiStatusError {
+            status: response.status(),
+            message: format!(
+                "Error {} while notifying {GOOGLE_CHAT_NAME}: {}",
+                response.status().as_str(),
+                response.text().unwrap_or_else(|error| error.to_string())
+            ),
+        })
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+}
@@ -0,0 +1,214 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::{CheckResult, LibError};
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::header::CONTENT_TYPE;
+
+// HOME-ASSISTANT implementation (webhook trigger, or events api)
+
+/// Common name to identify the notifier
+pub const HOME_ASSISTANT_NAME: &str = "home-assistant";
+
+/// Common environment variable to input the base URL of the instance.
+const ENV_HOME_ASSISTANT_URL: &str = "HOME_ASSISTANT_URL";
+
+/// Environment variable to input the id of a webhook trigger.
+const ENV_HOME_ASSISTANT_WEBHOOK_ID: &str = "HOME_ASSISTANT_WEBHOOK_ID";
+
+/// Environment variable to input a long-lived access token, to fire events instead.
+const ENV_HOME_ASSISTANT_TOKEN: &str = "HOME_ASSISTANT_TOKEN";
+
+/// Environment variable to optionally select the type of the fired events.
+const ENV_HOME_ASSISTANT_EVENT_TYPE: &str = "HOME_ASSISTANT_EVENT_TYPE";
+
+/// Event type used when none is configured.
+const HOME_ASSISTANT_DEFAULT_EVENT_TYPE: &str = "dsaw_availability";
+
+/// How the notifications reach Home Assistant.
+enum Target {
+    /// Triggers the automations of a webhook, which requires no authentication
+    Webhook { id: String },
+    /// Fires an event on the bus, through the REST API
+    Event { token: String, event_type: String },
+}
+
+/// Posts the same `json` payload as `simple-post` to a Home Assistant instance,
+/// either to a webhook trigger or as the data of a fired event, so that automations
+/// can react to the availability of servers.
+pub struct HomeAssistant {
+    client: Client,
+    url: String,
+    target: Target,
+}
+
+/// Builds a Home Assistant notifier without relying on environment variables.
+pub struct HomeAssistantBuilder {
+    url: String,
+    webhook_id: Option<String>,
+    token: Option<String>,
+    event_type: String,
+}
+
+impl Default for HomeAssistantBuilder {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            webhook_id: None,
+            token: None,
+            event_type: HOME_ASSISTANT_DEFAULT_EVENT_TYPE.to_string(),
+        }
+    }
+}
+
+impl HomeAssistantBuilder {
+    /// Sets the base URL of the instance, like `http://homeassistant.local:8123`.
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Posts to the webhook trigger with this id.
+    pub fn webhook_id(mut self, webhook_id: &str) -> Self {
+        self.webhook_id = Some(webhook_id.to_string());
+        self
+    }
+
+    /// Fires events through the REST API, authenticated with this long-lived access token.
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Sets the type of the fired events (defaults to `dsaw_availability`).
+    pub fn event_type(mut self, event_type: &str) -> Self {
+        self.event_type = event_type.to_string();
+        self
+    }
+
+    /// Builds the notifier, attempting to sanitize inputs
+    pub fn build(self) -> Result<HomeAssistant, LibError> {
+        // local instances usually do not use https
+        if !self.url.starts_with("https://") && !self.url.starts_with("http://") {
+            return Err(LibError::ValueError {
+                name: "home assistant url".into(),
+                value: self.url,
+            });
+        }
+
+        let target = match (self.webhook_id, self.token) {
+            (Some(id), None) if !id.is_empty() => Target::Webhook { id },
+            (None, Some(token)) if !token.is_empty() => {
+                let valid = !self.event_type.is_empty()
+                    && self
+                        .event_type
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if !valid {
+                    return Err(LibError::ValueError {
+                        name: "home assistant event type".into(),
+                        value: self.event_type,
+                    });
+                }
+                Target::Event {
+                    token,
+                    event_type: self.event_type,
+                }
+            }
+            _ => {
+                return Err(LibError::ValueError {
+                    name: "home assistant target (either a webhook id or a token)".into(),
+                    value: self.url,
+                })
+            }
+        };
+
+        Ok(HomeAssistant {
+            client: crate::client::build_client()?,
+            url: self.url,
+            target,
+        })
+    }
+}
+
+impl HomeAssistant {
+    /// Starts building a new instance.
+    pub fn builder() -> HomeAssistantBuilder {
+        HomeAssistantBuilder::default()
+    }
+
+    /// Sends a request and handles Home Assistant specific errors
+    fn send(&self, request: RequestBuilder) -> Result<(), LibError> {
+        let response = request.send()?;
+
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        Err(LibError::ApiStatusError {
+            status: response.status(),
+            message: format!(
+                "Error {} while notifying {HOME_ASSISTANT_NAME}: {}",
+                response.status().as_str(),
+                response.text().unwrap_or_else(|error| error.to_string())
+            ),
+        })
+    }
+}
+
+impl NotifierFactoryTrait for HomeAssistant {
+    /// Builds a Home Assistant notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let url = crate::get_env_var(ENV_HOME_ASSISTANT_URL)?;
+        let mut builder = Self::builder().url(&url);
+        if let Some(webhook_id) = crate::get_env_var_option(ENV_HOME_ASSISTANT_WEBHOOK_ID) {
+            builder = builder.webhook_id(&webhook_id);
+        }
+        if let Some(token) = crate::get_env_var_option(ENV_HOME_ASSISTANT_TOKEN) {
+            builder = builder.token(&token);
+        }
+        if let Some(event_type) = crate::get_env_var_option(ENV_HOME_ASSISTANT_EVENT_TYPE) {
+            builder = builder.event_type(&event_type);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl NotifierTrait for HomeAssistant {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        HOME_ASSISTANT_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let request = match &self.target {
+            Target::Webhook { id } => self.client.post(format!("{}/api/webhook/{id}", self.url)),
+            Target::Event { token, event_type } => self
+                .client
+                .post(format!("{}/api/events/{event_type}", self.url))
+                .bearer_auth(token),
+        };
+        self.send(
+            request
+                .header(CONTENT_TYPE, "application/json")
+                .body(result.to_json()?),
+        )
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+
+    /// Verifies the token against the REST API, webhooks offering no such call.
+    fn verify(&self) -> Result<bool, LibError> {
+        let Target::Event { token, .. } = &self.target else {
+            return Ok(false);
+        };
+        self.send(
+            self.client
+                .get(format!("{}/api/", self.url))
+                .bearer_auth(token),
+        )?;
+        Ok(true)
+    }
+}
@@ -1,8 +1,10 @@
 use super::{NotifierFactoryTrait, NotifierTrait};
 use crate::{CheckResult, LibError};
-use reqwest::blocking::Response;
+use chrono::{SecondsFormat, Utc};
+use reqwest::blocking::{Client, Response};
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::str::FromStr;
 
 // IFTTT WEBHOOK implementations
 
@@ -16,6 +18,59 @@ const ENV_NAME_IFTTT_WEBHOOK_EVENT: &str = "IFTTT_WEBHOOK_EVENT";
 /// Common environment variable to input the user API KEY.
 const ENV_NAME_IFTTT_WEBHOOK_KEY: &str = "IFTTT_WEBHOOK_KEY";
 
+/// Environment variables to optionally select what is sent in each value of the 'values' webhook.
+const ENV_NAME_IFTTT_WEBHOOK_VALUES: [&str; 3] = [
+    "IFTTT_WEBHOOK_VALUE1",
+    "IFTTT_WEBHOOK_VALUE2",
+    "IFTTT_WEBHOOK_VALUE3",
+];
+
+/// What is sent in a value of the 'values' webhook
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WebHookValue {
+    /// The name of the provider
+    Provider,
+    /// The comma-separated list of available servers
+    Servers,
+    /// The number of available servers
+    Count,
+    /// The time of the notification (RFC3339)
+    Timestamp,
+    /// Nothing, the value is not sent
+    Empty,
+}
+
+impl WebHookValue {
+    /// Renders the value from the result
+    fn render(&self, result: &CheckResult) -> Option<String> {
+        match self {
+            WebHookValue::Provider => Some(result.provider_name.clone()),
+            WebHookValue::Servers => Some(result.available_servers.join(",")),
+            WebHookValue::Count => Some(result.available_servers.len().to_string()),
+            WebHookValue::Timestamp => Some(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)),
+            WebHookValue::Empty => None,
+        }
+    }
+}
+
+impl FromStr for WebHookValue {
+    type Err = LibError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "provider" => Ok(WebHookValue::Provider),
+            "servers" => Ok(WebHookValue::Servers),
+            "count" => Ok(WebHookValue::Count),
+            "timestamp" => Ok(WebHookValue::Timestamp),
+            "none" | "" => Ok(WebHookValue::Empty),
+            _ => Err(LibError::ValueError {
+                name: "ifttt webhook value".into(),
+                value: value.to_string(),
+            }),
+        }
+    }
+}
+
 /// Used for API result deserialisation.
 #[derive(Debug, Deserialize)]
 struct IftttApiErrorMessage {
@@ -39,7 +94,7 @@ impl WebHookParameters {
     fn from_env() -> Result<Self, LibError> {
         let event = crate::get_env_var(ENV_NAME_IFTTT_WEBHOOK_EVENT)?;
         let key = crate::get_env_var(ENV_NAME_IFTTT_WEBHOOK_KEY)?;
-        Ok(Self::new(&event, &key)?)
+        Self::new(&event, &key)
     }
 
     /// Builds a new instance, attempting to sanitize inputs
@@ -72,24 +127,21 @@ impl WebHookParameters {
 
 trait WebHookPoster {
     /// Posts a request and handle Ifttt-Webhook specific errors
-    fn post(url: &str, body: &str) -> Result<Response, LibError> {
-        let client = reqwest::blocking::Client::new();
+    fn post(client: &Client, url: &str, body: &str) -> Result<Response, LibError> {
         let response = client
             .post(url)
             .header("Content-Type", "application/json")
             .body(body.to_string())
-            .send()
-            .map_err(|source| LibError::RequestError { source })?;
+            .send()?;
 
         if response.status().is_success() {
             return Ok(response);
         }
 
         // Handles known errors.
-        if response.status().is_client_error() {
-            let response: IftttApiError = response
-                .json()
-                .map_err(|source| LibError::RequestError { source })?;
+        let status = response.status();
+        if status.is_client_error() {
+            let response: IftttApiError = response.json()?;
 
             let messages = response
                 .errors
@@ -98,26 +150,34 @@ trait WebHookPoster {
                 .collect::<Vec<String>>()
                 .join(" / ");
 
-            return Err(LibError::ApiError {
+            return Err(LibError::ApiStatusError {
+                status,
                 message: format!("Error during IFTTT-WEBHOOK query: {messages}"),
             });
         }
 
         // Unhandled unknown errors.
-        return Err(LibError::ApiError {
+        Err(LibError::ApiStatusError {
+            status,
             message: "Unknown IFTTT-WEBHOOK error".to_string(),
-        });
+        })
     }
 }
 
 /// Holds the user credentials and event identifier used with the API.
 pub struct WebHookJson {
+    client: Client,
     url: String,
 }
 
 impl WebHookJson {
+    /// Builds a new instance, from the webhook event and the user key
+    pub fn new(event: &str, key: &str) -> Result<Self, LibError> {
+        Self::from_parameters(&WebHookParameters::new(event, key)?)
+    }
+
     /// Create an instance.
-    fn new(parameters: &WebHookParameters) -> Self {
+    fn from_parameters(parameters: &WebHookParameters) -> Result<Self, LibError> {
         let url = format!(
             // Builds ifttt 'json' URL.
             // - the first placeholder is for the event name
@@ -125,7 +185,10 @@ impl WebHookJson {
             "https://maker.ifttt.com/trigger/{}/json/with/key/{}",
             parameters.event, parameters.key
         );
-        Self { url }
+        Ok(Self {
+            client: crate::client::build_client()?,
+            url,
+        })
     }
 }
 
@@ -133,21 +196,21 @@ impl NotifierFactoryTrait for WebHookJson {
     /// Builds a WebHook 'json' notifier from environment variables.
     fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
         let parameters = WebHookParameters::from_env()?;
-        Ok(Box::new(Self::new(&parameters)))
+        Ok(Box::new(Self::from_parameters(&parameters)?))
     }
 }
 
 impl NotifierTrait for WebHookJson {
     /// Gets the actual name of the notifier.
     fn name(&self) -> &'static str {
-        return IFTTT_WEBHOOK_JSON_NAME;
+        IFTTT_WEBHOOK_JSON_NAME
     }
 
     /// Sends an notification using the provided data.
     fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
         let body = result.to_json()?;
         // we are not interested in the actual payload of the reply
-        Self::post(&self.url, &body)?;
+        Self::post(&self.client, &self.url, &body)?;
         Ok(())
     }
 
@@ -159,14 +222,22 @@ impl NotifierTrait for WebHookJson {
 
 impl WebHookPoster for WebHookJson {}
 
-/// Holds the user credentials and event identifier used with the API.
+/// Holds the user credentials and event identifier used with the API,
+/// and what is sent in `value1`, `value2` and `value3`.
 pub struct WebHookValues {
+    client: Client,
     url: String,
+    values: [WebHookValue; 3],
 }
 
 impl WebHookValues {
+    /// Builds a new instance, from the webhook event and the user key
+    pub fn new(event: &str, key: &str) -> Result<Self, LibError> {
+        Self::from_parameters(&WebHookParameters::new(event, key)?)
+    }
+
     /// Create an instance.
-    fn new(parameters: &WebHookParameters) -> Self {
+    fn from_parameters(parameters: &WebHookParameters) -> Result<Self, LibError> {
         let url = format!(
             // Builds ifttt 'value' URL.
             // - the first placeholder is for the event name
@@ -174,21 +245,37 @@ impl WebHookValues {
             "https://maker.ifttt.com/trigger/{}/with/key/{}",
             parameters.event, parameters.key
         );
-        Self { url }
+        Ok(Self {
+            client: crate::client::build_client()?,
+            url,
+            values: [
+                WebHookValue::Provider,
+                WebHookValue::Servers,
+                WebHookValue::Empty,
+            ],
+        })
+    }
+
+    /// Selects what is sent in `value1`, `value2` and `value3`,
+    /// which defaults to the provider, the servers, and nothing.
+    pub fn with_values(mut self, values: [WebHookValue; 3]) -> Self {
+        self.values = values;
+        self
     }
 
     /// Builds a POST body from query parameters
-    fn build_body(
-        &self,
-        provider_tag: &str,
-        server_tag: &str,
-        result: &CheckResult,
-    ) -> Result<String, LibError> {
-        let joined = result.available_servers.join(",");
-        let mut params = HashMap::new();
-        params.insert(provider_tag, &result.provider_name);
-        params.insert(server_tag, &joined);
-        serde_json::to_string(&params).map_err(|source| LibError::JsonError { source })
+    fn build_body(&self, result: &CheckResult) -> Result<String, LibError> {
+        let params: HashMap<String, String> = self
+            .values
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| {
+                value
+                    .render(result)
+                    .map(|rendered| (format!("value{}", index + 1), rendered))
+            })
+            .collect();
+        Ok(serde_json::to_string(&params)?)
     }
 }
 
@@ -196,21 +283,28 @@ impl NotifierFactoryTrait for WebHookValues {
     /// Builds a WebHook 'values' notifier from environment variables.
     fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
         let parameters = WebHookParameters::from_env()?;
-        Ok(Box::new(Self::new(&parameters)))
+        let notifier = Self::from_parameters(&parameters)?;
+        let mut values = notifier.values;
+        for (value, name) in values.iter_mut().zip(ENV_NAME_IFTTT_WEBHOOK_VALUES) {
+            if let Some(setting) = crate::get_env_var_option(name) {
+                *value = setting.parse()?;
+            }
+        }
+        Ok(Box::new(notifier.with_values(values)))
     }
 }
 
 impl NotifierTrait for WebHookValues {
     /// Gets the actual name of the notifier.
     fn name(&self) -> &'static str {
-        return IFTTT_WEBHOOK_VALUES_NAME;
+        IFTTT_WEBHOOK_VALUES_NAME
     }
 
     /// Sends an notification using the provided data.
     fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
-        let body = self.build_body("value1", "value2", result)?;
+        let body = self.build_body(result)?;
         // we are not interested in the actual payload of the reply
-        Self::post(&self.url, &body)?;
+        Self::post(&self.client, &self.url, &body)?;
         Ok(())
     }
 
@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+use kafka::producer::{Producer, Record, RequiredAcks};
+
+use crate::LibError::KafkaError;
+use crate::{CheckResult, LibError};
+
+use super::{NotifierFactoryTrait, NotifierTrait};
+
+// KAFKA implementation
+
+/// Common name to identify the notifier
+pub const KAFKA_NAME: &str = "kafka";
+
+/// Common environment variable to select the brokers, as a comma separated list of `host:port`.
+const ENV_KAFKA_BROKERS: &str = "KAFKA_BROKERS";
+
+/// Common environment variable to select the topic to produce to.
+const ENV_KAFKA_TOPIC: &str = "KAFKA_TOPIC";
+
+/// Environment variable to optionally use the provider name as record key, for partitioning.
+const ENV_KAFKA_PROVIDER_KEY: &str = "KAFKA_PROVIDER_KEY";
+
+/// Environment variable to optionally select how many acknowledgements are required.
+const ENV_KAFKA_REQUIRED_ACKS: &str = "KAFKA_REQUIRED_ACKS";
+
+/// Default value for optional settings
+const KAFKA_DEFAULT_REQUIRED_ACKS: &str = "1";
+
+/// How long we wait for the brokers before giving up
+const KAFKA_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Produces the json serialization of the result to a topic,
+/// connecting for every notification as we are usually run once.
+pub struct Kafka {
+    brokers: Vec<String>,
+    topic: String,
+    provider_key: bool,
+    required_acks: RequiredAcks,
+}
+
+/// Builds a Kafka notifier without relying on environment variables.
+pub struct KafkaBuilder {
+    brokers: Vec<String>,
+    topic: String,
+    provider_key: bool,
+    required_acks: String,
+}
+
+impl Default for KafkaBuilder {
+    fn default() -> Self {
+        Self {
+            brokers: Vec::new(),
+            topic: String::new(),
+            provider_key: false,
+            required_acks: KAFKA_DEFAULT_REQUIRED_ACKS.to_string(),
+        }
+    }
+}
+
+impl KafkaBuilder {
+    /// Sets the brokers, as `host:port`, at least one is required.
+    pub fn brokers<I, S>(mut self, brokers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.brokers = brokers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the topic to produce to.
+    pub fn topic(mut self, topic: &str) -> Self {
+        self.topic = topic.to_string();
+        self
+    }
+
+    /// Uses the provider name as record key, for partitioning (defaults to false).
+    pub fn provider_key(mut self, provider_key: bool) -> Self {
+        self.provider_key = provider_key;
+        self
+    }
+
+    /// Sets how many acknowledgements are required: `0`, `1` (default) or `all`.
+    pub fn required_acks(mut self, required_acks: &str) -> Self {
+        self.required_acks = required_acks.to_string();
+        self
+    }
+
+    /// Builds the notifier, attempting to sanitize inputs
+    pub fn build(self) -> Result<Kafka, LibError> {
+        if self.brokers.is_empty() || self.brokers.iter().any(|broker| broker.is_empty()) {
+            return Err(LibError::ValueError {
+                name: "kafka brokers".into(),
+                value: self.brokers.join(","),
+            });
+        }
+
+        if self.topic.is_empty() {
+            return Err(LibError::ValueError {
+                name: "kafka topic".into(),
+                value: self.topic,
+            });
+        }
+
+        let required_acks = match self.required_acks.as_str() {
+            "0" => RequiredAcks::None,
+            "1" => RequiredAcks::One,
+            "all" => RequiredAcks::All,
+            _ => {
+                return Err(LibError::ValueError {
+                    name: "kafka required acks (0, 1 or all)".into(),
+                    value: self.required_acks,
+                })
+            }
+        };
+
+        Ok(Kafka {
+            brokers: self.brokers,
+            topic: self.topic,
+            provider_key: self.provider_key,
+            required_acks,
+        })
+    }
+}
+
+impl Kafka {
+    /// Starts building a new instance.
+    pub fn builder() -> KafkaBuilder {
+        KafkaBuilder::default()
+    }
+
+    /// Connects and produces a single record
+    fn produce(&self, key: &str, payload: String) -> Result<(), LibError> {
+        let mut producer = Producer::from_hosts(self.brokers.clone())
+            .with_connection_idle_timeout(KAFKA_TIMEOUT)
+            .with_ack_timeout(KAFKA_TIMEOUT)
+            .with_required_acks(self.required_acks)
+            .create()
+            .map_err(|e| KafkaError {
+                message: e.to_string(),
+            })?;
+
+        let sent = if self.provider_key {
+            producer.send(&Record::from_key_value(&self.topic, key, payload))
+        } else {
+            producer.send(&Record::from_value(&self.topic, payload))
+        };
+        sent.map_err(|e| KafkaError {
+            message: e.to_string(),
+        })
+    }
+}
+
+impl NotifierFactoryTrait for Kafka {
+    /// Builds a Kafka notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let brokers = crate::get_env_var(ENV_KAFKA_BROKERS)?;
+        let topic = crate::get_env_var(ENV_KAFKA_TOPIC)?;
+        let required_acks =
+            crate::get_env_var_default(ENV_KAFKA_REQUIRED_ACKS, KAFKA_DEFAULT_REQUIRED_ACKS);
+        let mut builder = Self::builder()
+            .brokers(crate::tokenize_optional_csv_str(&Some(brokers))?)
+            .topic(&topic)
+            .required_acks(&required_acks);
+        if let Some(provider_key) = crate::get_env_var_parsed(ENV_KAFKA_PROVIDER_KEY)? {
+            builder = builder.provider_key(provider_key);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl NotifierTrait for Kafka {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        KAFKA_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        self.produce(&result.provider_name, result.to_json()?)
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+}
@@ -0,0 +1,171 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::{CheckResult, LibError};
+use reqwest::blocking::Client;
+use serde::Serialize;
+
+// MATTERMOST implementation (incoming webhook)
+
+/// Common name to identify the notifier
+pub const MATTERMOST_NAME: &str = "mattermost";
+
+/// Common environment variable to input the incoming webhook URL.
+const ENV_MATTERMOST_WEBHOOK_URL: &str = "MATTERMOST_WEBHOOK_URL";
+
+/// Environment variable to optionally post to another channel than the webhook one.
+const ENV_MATTERMOST_CHANNEL: &str = "MATTERMOST_CHANNEL";
+
+/// Environment variable to optionally override the displayed user name.
+const ENV_MATTERMOST_USERNAME: &str = "MATTERMOST_USERNAME";
+
+/// Used for API request serialisation.
+#[derive(Serialize)]
+struct MattermostMessage<'a> {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    channel: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    username: Option<&'a str>,
+}
+
+/// Posts a Markdown table of the checked servers and their availability.
+pub struct Mattermost {
+    client: Client,
+    url: String,
+    channel: Option<String>,
+    username: Option<String>,
+}
+
+/// Builds a Mattermost notifier without relying on environment variables.
+#[derive(Default)]
+pub struct MattermostBuilder {
+    url: String,
+    channel: Option<String>,
+    username: Option<String>,
+}
+
+impl MattermostBuilder {
+    /// Sets the incoming webhook URL.
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    /// Posts to this channel (by name, like `town-square`) instead of the webhook one.
+    pub fn channel(mut self, channel: &str) -> Self {
+        self.channel = Some(channel.to_string());
+        self
+    }
+
+    /// Overrides the user name displayed for the posts.
+    pub fn username(mut self, username: &str) -> Self {
+        self.username = Some(username.to_string());
+        self
+    }
+
+    /// Builds the notifier, attempting to sanitize inputs
+    pub fn build(self) -> Result<Mattermost, LibError> {
+        // self-hosted instances may not use https
+        if !self.url.starts_with("https://") && !self.url.starts_with("http://") {
+            return Err(LibError::ValueError {
+                name: "mattermost webhook url".into(),
+                value: self.url,
+            });
+        }
+
+        Ok(Mattermost {
+            client: crate::client::build_client()?,
+            url: self.url,
+            channel: self.channel,
+            username: self.username,
+        })
+    }
+}
+
+impl Mattermost {
+    /// Starts building a new instance.
+    pub fn builder() -> MattermostBuilder {
+        MattermostBuilder::default()
+    }
+
+    /// Builds the Markdown text, with a table of the servers
+    fn build_text(result: &CheckResult) -> String {
+        let provider_name = &result.provider_name;
+
+        if result.available_servers.is_empty() && result.unavailable_servers.is_empty() {
+            return format!("#### Server availability for {provider_name}\nNo server available for the selected types !");
+        }
+
+        let rows = result
+            .available_servers
+            .iter()
+            .map(|server| {
+                format!(
+                    "| {} | :white_check_mark: available |",
+                    result.to_markdown_link(server, server)
+                )
+            })
+            .chain(
+                result
+                    .unavailable_servers
+                    .iter()
+                    .map(|server| format!("| {server} | :x: unavailable |")),
+            )
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        format!(
+            "#### Server availability for {provider_name}\n\n| Server | Status |\n|:-------|:-------|\n{rows}"
+        )
+    }
+}
+
+impl NotifierFactoryTrait for Mattermost {
+    /// Builds a Mattermost notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let url = crate::get_env_var(ENV_MATTERMOST_WEBHOOK_URL)?;
+        let mut builder = Self::builder().url(&url);
+        if let Some(channel) = crate::get_env_var_option(ENV_MATTERMOST_CHANNEL) {
+            builder = builder.channel(&channel);
+        }
+        if let Some(username) = crate::get_env_var_option(ENV_MATTERMOST_USERNAME) {
+            builder = builder.username(&username);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl NotifierTrait for Mattermost {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        MATTERMOST_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let message = MattermostMessage {
+            text: Self::build_text(result),
+            channel: self.channel.as_deref(),
+            username: self.username.as_deref(),
+        };
+
+        let response = self.client.post(&self.url).json(&message).send()?;
+
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        Err(LibError::ApiStatusError {
+            status: response.status(),
+            message: format!(
+                "Error {} while notifying {MATTERMOST_NAME}: {}",
+                response.status().as_str(),
+                response.text().unwrap_or_else(|error| error.to_string())
+            ),
+        })
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+}
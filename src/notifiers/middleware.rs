@@ -0,0 +1,337 @@
+use crate::alert::OperationalAlert;
+use crate::notifiers::NotifierTrait;
+use crate::{CheckResult, LibError};
+use std::collections::{BTreeMap, VecDeque};
+use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+// Policies applied around any notifier, so that notifiers do not each implement them
+
+/// Suffix of the environment variable holding the rate limit of a notifier,
+/// like `TEAMS_RATE_LIMIT=5/60` for at most 5 notifications per hour.
+const ENV_RATE_LIMIT_SUFFIX: &str = "RATE_LIMIT";
+
+/// Suffix of the environment variable holding how long identical notifications are dropped,
+/// like `TEAMS_DEDUPE_MINUTES=30`.
+const ENV_DEDUPE_MINUTES_SUFFIX: &str = "DEDUPE_MINUTES";
+
+/// Suffix of the environment variable holding the template of the notified server names,
+/// like `TEAMS_SERVER_TEMPLATE={provider}/{server}`.
+const ENV_SERVER_TEMPLATE_SUFFIX: &str = "SERVER_TEMPLATE";
+
+/// Locks the state of a wrapper, even if a thread panicked while holding it.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// How many notifications a notifier may send over a sliding window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    pub count: u32,
+    pub window: Duration,
+}
+
+impl FromStr for RateLimit {
+    type Err = LibError;
+
+    /// Parses a limit like `5/60`, for at most 5 notifications per 60 minutes.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let error = || LibError::ValueError {
+            name: "rate limit (like 5/60 for 5 notifications per 60 minutes)".into(),
+            value: value.to_string(),
+        };
+        let (count, minutes) = value.split_once('/').ok_or_else(error)?;
+        let count: u32 = count.trim().parse().map_err(|_| error())?;
+        let minutes: u64 = minutes.trim().parse().map_err(|_| error())?;
+        if count == 0 || minutes == 0 {
+            return Err(error());
+        }
+        Ok(Self {
+            count,
+            window: Duration::from_secs(minutes * 60),
+        })
+    }
+}
+
+/// Fails the notifications exceeding a rate limit, so that they are retried later
+/// from the outbox rather than flooding the recipient. Only the notifications sent
+/// during the lifetime of the instance are counted, like during a watch.
+pub struct RateLimited {
+    notifier: Box<dyn NotifierTrait>,
+    limit: RateLimit,
+    sent: Mutex<VecDeque<Instant>>,
+}
+
+impl RateLimited {
+    /// Builds an instance around a notifier
+    pub fn new(notifier: Box<dyn NotifierTrait>, limit: RateLimit) -> Self {
+        Self {
+            notifier,
+            limit,
+            sent: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl NotifierTrait for RateLimited {
+    /// Gets the name of the wrapped notifier.
+    fn name(&self) -> &'static str {
+        self.notifier.name()
+    }
+
+    /// Notifies through the wrapped notifier, unless the limit is reached.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let mut sent = lock(&self.sent);
+        let now = Instant::now();
+        while sent
+            .front()
+            .is_some_and(|time| now.duration_since(*time) >= self.limit.window)
+        {
+            sent.pop_front();
+        }
+        if sent.len() >= self.limit.count as usize {
+            return Err(LibError::RateLimited {
+                notifier: self.name().to_string(),
+            });
+        }
+        // failed notifications count too, as they may have reached the recipient
+        sent.push_back(now);
+        self.notifier.notify(result)
+    }
+
+    /// Tests the wrapped notifier, which is never limited.
+    fn test(&self) -> Result<(), LibError> {
+        self.notifier.test()
+    }
+
+    /// Verifies the wrapped notifier.
+    fn verify(&self) -> Result<bool, LibError> {
+        self.notifier.verify()
+    }
+
+    /// Tells if the wrapped notifier can send operational alerts.
+    fn supports_alerts(&self) -> bool {
+        self.notifier.supports_alerts()
+    }
+
+    /// Sends an operational alert through the wrapped notifier, which is never limited.
+    fn alert(&self, alert: &OperationalAlert) -> Result<(), LibError> {
+        self.notifier.alert(alert)
+    }
+}
+
+/// Drops the notifications identical to one which was successfully sent recently,
+/// like when several watches share the same notifier. Only the notifications sent
+/// during the lifetime of the instance are remembered.
+pub struct Deduplicated {
+    notifier: Box<dyn NotifierTrait>,
+    window: Duration,
+    sent: Mutex<Vec<(String, Instant)>>,
+}
+
+impl Deduplicated {
+    /// Builds an instance around a notifier
+    pub fn new(notifier: Box<dyn NotifierTrait>, window: Duration) -> Self {
+        Self {
+            notifier,
+            window,
+            sent: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl NotifierTrait for Deduplicated {
+    /// Gets the name of the wrapped notifier.
+    fn name(&self) -> &'static str {
+        self.notifier.name()
+    }
+
+    /// Notifies through the wrapped notifier, unless the same payload was sent within the window.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let payload = result.to_json()?;
+        let now = Instant::now();
+        {
+            let mut sent = lock(&self.sent);
+            sent.retain(|(_, time)| now.duration_since(*time) < self.window);
+            if sent.iter().any(|(known, _)| *known == payload) {
+                return Ok(());
+            }
+        }
+        self.notifier.notify(result)?;
+        lock(&self.sent).push((payload, now));
+        Ok(())
+    }
+
+    /// Tests the wrapped notifier, which is never deduplicated.
+    fn test(&self) -> Result<(), LibError> {
+        self.notifier.test()
+    }
+
+    /// Verifies the wrapped notifier.
+    fn verify(&self) -> Result<bool, LibError> {
+        self.notifier.verify()
+    }
+
+    /// Tells if the wrapped notifier can send operational alerts.
+    fn supports_alerts(&self) -> bool {
+        self.notifier.supports_alerts()
+    }
+
+    /// Sends an operational alert through the wrapped notifier.
+    fn alert(&self, alert: &OperationalAlert) -> Result<(), LibError> {
+        self.notifier.alert(alert)
+    }
+}
+
+/// Renames the notified servers from a template, like `{server} ({provider})`,
+/// before handing the result over to the wrapped notifier.
+pub struct Transformed {
+    notifier: Box<dyn NotifierTrait>,
+    template: String,
+}
+
+impl Transformed {
+    /// Builds an instance around a notifier. The template placeholders are
+    /// `{provider}` and `{server}`.
+    pub fn new(notifier: Box<dyn NotifierTrait>, template: &str) -> Self {
+        Self {
+            notifier,
+            template: template.to_string(),
+        }
+    }
+
+    /// Renames a single server
+    fn rename(&self, provider_name: &str, server: &str) -> String {
+        crate::template::render(
+            &self.template,
+            &[("provider", provider_name), ("server", server)],
+        )
+    }
+
+    /// Renames the keys of a map of server details
+    fn rename_keys<T: Clone>(
+        &self,
+        provider_name: &str,
+        map: &BTreeMap<String, T>,
+    ) -> BTreeMap<String, T> {
+        map.iter()
+            .map(|(server, value)| (self.rename(provider_name, server), value.clone()))
+            .collect()
+    }
+
+    /// Builds the result with its servers renamed
+    fn transform(&self, result: &CheckResult) -> CheckResult {
+        let provider_name = &result.provider_name;
+        let rename = |servers: &Vec<String>| {
+            servers
+                .iter()
+                .map(|server| self.rename(provider_name, server))
+                .collect()
+        };
+        CheckResult {
+            provider_name: provider_name.clone(),
+            available_servers: rename(&result.available_servers),
+            unavailable_servers: rename(&result.unavailable_servers),
+            last_seen_available: self.rename_keys(provider_name, &result.last_seen_available),
+            order_urls: self.rename_keys(provider_name, &result.order_urls),
+            stock_levels: self.rename_keys(provider_name, &result.stock_levels),
+            locations: self.rename_keys(provider_name, &result.locations),
+            prices: self.rename_keys(provider_name, &result.prices),
+            previous_prices: self.rename_keys(provider_name, &result.previous_prices),
+        }
+    }
+}
+
+impl NotifierTrait for Transformed {
+    /// Gets the name of the wrapped notifier.
+    fn name(&self) -> &'static str {
+        self.notifier.name()
+    }
+
+    /// Notifies the renamed servers through the wrapped notifier.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        self.notifier.notify(&self.transform(result))
+    }
+
+    /// Tests the wrapped notifier.
+    fn test(&self) -> Result<(), LibError> {
+        self.notifier.test()
+    }
+
+    /// Verifies the wrapped notifier.
+    fn verify(&self) -> Result<bool, LibError> {
+        self.notifier.verify()
+    }
+
+    /// Tells if the wrapped notifier can send operational alerts.
+    fn supports_alerts(&self) -> bool {
+        self.notifier.supports_alerts()
+    }
+
+    /// Sends an operational alert through the wrapped notifier.
+    fn alert(&self, alert: &OperationalAlert) -> Result<(), LibError> {
+        self.notifier.alert(alert)
+    }
+}
+
+/// Wraps a notifier with the policies configured for it, if any. The variables are prefixed
+/// by the notifier name, uppercased with dashes as underscores, like `EMAIL_SMTP_RATE_LIMIT`.
+/// Duplicates are dropped first, so that they never count towards the rate limit, and the
+/// servers are renamed last.
+pub fn from_env(notifier: Box<dyn NotifierTrait>) -> Result<Box<dyn NotifierTrait>, LibError> {
+    let prefix = notifier.name().to_uppercase().replace('-', "_");
+    let mut notifier = notifier;
+    if let Some(template) =
+        crate::get_env_var_option(&format!("{prefix}_{ENV_SERVER_TEMPLATE_SUFFIX}"))
+    {
+        notifier = Box::new(Transformed::new(notifier, &template));
+    }
+    if let Some(limit) =
+        crate::get_env_var_parsed::<RateLimit>(&format!("{prefix}_{ENV_RATE_LIMIT_SUFFIX}"))?
+    {
+        notifier = Box::new(RateLimited::new(notifier, limit));
+    }
+    // outermost, so that duplicates never reach the rate limit
+    if let Some(minutes) =
+        crate::get_env_var_parsed::<u64>(&format!("{prefix}_{ENV_DEDUPE_MINUTES_SUFFIX}"))?
+    {
+        notifier = Box::new(Deduplicated::new(
+            notifier,
+            Duration::from_secs(minutes * 60),
+        ));
+    }
+    Ok(notifier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limit_from_str() {
+        assert_eq!(
+            "5/60".parse::<RateLimit>().unwrap(),
+            RateLimit {
+                count: 5,
+                window: Duration::from_secs(3600),
+            }
+        );
+        assert_eq!(
+            " 1 / 1 ".parse::<RateLimit>().unwrap(),
+            RateLimit {
+                count: 1,
+                window: Duration::from_secs(60),
+            }
+        );
+    }
+
+    #[test]
+    fn rate_limit_rejects_invalid_values() {
+        for value in ["", "5", "5/", "/60", "0/60", "5/0", "-1/60", "five/60"] {
+            assert!(value.parse::<RateLimit>().is_err(), "{value}");
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::alert::OperationalAlert;
+use crate::{CheckResult, LibError};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+// MOCK implementation (records calls, for testing)
+
+/// Common name to identify the notifier
+pub const MOCK_NAME: &str = "mock";
+
+/// Common environment variable to select the path of the record file.
+const ENV_MOCK_NOTIFIER_PATH: &str = "MOCK_NOTIFIER_PATH";
+
+/// Environment variable to optionally fail every call with an http status, after recording it.
+const ENV_MOCK_NOTIFIER_FAIL_STATUS: &str = "MOCK_NOTIFIER_FAIL_STATUS";
+
+/// Used for line serialisation.
+#[derive(Serialize)]
+struct MockRecord<'a> {
+    call: &'a str,
+    provider: &'a str,
+    servers: &'a [String],
+}
+
+/// Appends one json line per call to a file, so that a test can verify
+/// what would have been notified, and optionally fails to test error handling.
+pub struct MockNotifier {
+    path: String,
+    fail_status: Option<http::StatusCode>,
+}
+
+impl MockNotifier {
+    /// Builds a new instance recording its calls into a file, which is created if needed.
+    pub fn new(path: &str) -> Result<Self, LibError> {
+        if path.is_empty() {
+            return Err(LibError::ValueError {
+                name: "found empty record path".into(),
+                value: path.to_string(),
+            });
+        }
+        Ok(Self {
+            path: path.to_string(),
+            fail_status: None,
+        })
+    }
+
+    /// Makes every call fail with this status, once recorded.
+    pub fn failing(mut self, status: http::StatusCode) -> Self {
+        self.fail_status = Some(status);
+        self
+    }
+
+    /// Records a call, then fails if required.
+    fn record(&self, call: &str, provider: &str, servers: &[String]) -> Result<(), LibError> {
+        let record = MockRecord {
+            call,
+            provider,
+            servers,
+        };
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|source| LibError::FileError {
+                path: self.path.clone().into(),
+                source,
+            })?;
+
+        match self.fail_status {
+            Some(status) => Err(LibError::ApiStatusError {
+                status,
+                message: format!("mock failure for {call}"),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl NotifierFactoryTrait for MockNotifier {
+    /// Builds a MockNotifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let path = crate::get_env_var(ENV_MOCK_NOTIFIER_PATH)?;
+        let mut notifier = Self::new(&path)?;
+        if let Some(status) = crate::get_env_var_parsed::<u16>(ENV_MOCK_NOTIFIER_FAIL_STATUS)? {
+            let status = http::StatusCode::from_u16(status).map_err(|_| LibError::ValueError {
+                name: ENV_MOCK_NOTIFIER_FAIL_STATUS.to_string(),
+                value: status.to_string(),
+            })?;
+            notifier = notifier.failing(status);
+        }
+        Ok(Box::new(notifier))
+    }
+}
+
+impl NotifierTrait for MockNotifier {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        MOCK_NAME
+    }
+
+    /// Records the notification.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        self.record("notify", &result.provider_name, &result.available_servers)
+    }
+
+    /// Tests by recording a test call with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        let result = CheckResult::get_dummy();
+        self.record("test", &result.provider_name, &result.available_servers)
+    }
+
+    /// Tells that the notifier can send operational alerts.
+    fn supports_alerts(&self) -> bool {
+        true
+    }
+
+    /// Records the operational alert, under the name of its kind.
+    fn alert(&self, alert: &OperationalAlert) -> Result<(), LibError> {
+        self.record(&format!("alert-{}", alert.kind), &alert.provider_name, &[])
+    }
+}
@@ -0,0 +1,257 @@
+use std::time::Duration;
+
+use rumqttc::{Client, Connection, Event, MqttOptions, Outgoing, Packet, QoS};
+
+use crate::LibError::MqttError;
+use crate::{CheckResult, LibError};
+
+use super::{NotifierFactoryTrait, NotifierTrait};
+
+// MQTT implementation
+
+/// Common name to identify the notifier
+pub const MQTT_NAME: &str = "mqtt";
+
+/// Common environment variables to select the broker.
+const ENV_MQTT_HOST: &str = "MQTT_HOST";
+const ENV_MQTT_PORT: &str = "MQTT_PORT";
+
+/// Common environment variable to select the topic to publish to.
+const ENV_MQTT_TOPIC: &str = "MQTT_TOPIC";
+
+/// Environment variables to optionally tune the published message.
+const ENV_MQTT_QOS: &str = "MQTT_QOS";
+const ENV_MQTT_RETAIN: &str = "MQTT_RETAIN";
+
+/// Environment variables to optionally authenticate and identify against the broker.
+const ENV_MQTT_USERNAME: &str = "MQTT_USERNAME";
+const ENV_MQTT_PASSWORD: &str = "MQTT_PASSWORD";
+const ENV_MQTT_CLIENT_ID: &str = "MQTT_CLIENT_ID";
+
+/// Default values for optional settings
+const MQTT_DEFAULT_PORT: u16 = 1883;
+const MQTT_DEFAULT_QOS: u8 = 0;
+
+/// How long we wait for the broker before giving up
+const MQTT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Publishes the json serialization of the result to a broker topic,
+/// connecting for every notification as we are usually run once.
+pub struct Mqtt {
+    host: String,
+    port: u16,
+    topic: String,
+    qos: QoS,
+    retain: bool,
+    credentials: Option<(String, String)>,
+    client_id: String,
+}
+
+/// Builds a Mqtt notifier without relying on environment variables.
+pub struct MqttBuilder {
+    host: String,
+    port: u16,
+    topic: String,
+    qos: u8,
+    retain: bool,
+    credentials: Option<(String, String)>,
+    client_id: String,
+}
+
+impl Default for MqttBuilder {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: MQTT_DEFAULT_PORT,
+            topic: String::new(),
+            qos: MQTT_DEFAULT_QOS,
+            retain: false,
+            credentials: None,
+            client_id: format!("dsaw-{}", std::process::id()),
+        }
+    }
+}
+
+impl MqttBuilder {
+    /// Sets the broker host.
+    pub fn host(mut self, host: &str) -> Self {
+        self.host = host.to_string();
+        self
+    }
+
+    /// Sets the broker port (defaults to 1883).
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Sets the topic to publish to, which cannot contain wildcards.
+    pub fn topic(mut self, topic: &str) -> Self {
+        self.topic = topic.to_string();
+        self
+    }
+
+    /// Sets the quality of service: 0 (default), 1 or 2.
+    pub fn qos(mut self, qos: u8) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// Asks the broker to retain the message (defaults to false).
+    pub fn retain(mut self, retain: bool) -> Self {
+        self.retain = retain;
+        self
+    }
+
+    /// Sets the credentials to authenticate against the broker.
+    pub fn credentials(mut self, username: &str, password: &str) -> Self {
+        self.credentials = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Sets the client identifier (defaults to one based on the process id).
+    pub fn client_id(mut self, client_id: &str) -> Self {
+        self.client_id = client_id.to_string();
+        self
+    }
+
+    /// Builds the notifier, attempting to sanitize inputs
+    pub fn build(self) -> Result<Mqtt, LibError> {
+        if self.host.is_empty() {
+            return Err(LibError::ValueError {
+                name: "mqtt host".into(),
+                value: self.host,
+            });
+        }
+
+        // wildcards are only valid for subscriptions
+        if self.topic.is_empty() || self.topic.contains(['+', '#']) {
+            return Err(LibError::ValueError {
+                name: "mqtt topic".into(),
+                value: self.topic,
+            });
+        }
+
+        let qos = rumqttc::qos(self.qos).map_err(|_| LibError::ValueError {
+            name: "mqtt qos (0, 1 or 2)".into(),
+            value: self.qos.to_string(),
+        })?;
+
+        Ok(Mqtt {
+            host: self.host,
+            port: self.port,
+            topic: self.topic,
+            qos,
+            retain: self.retain,
+            credentials: self.credentials,
+            client_id: self.client_id,
+        })
+    }
+}
+
+impl Mqtt {
+    /// Starts building a new instance.
+    pub fn builder() -> MqttBuilder {
+        MqttBuilder::default()
+    }
+
+    /// Builds the connection options
+    fn options(&self) -> MqttOptions {
+        let mut options = MqttOptions::new(&self.client_id, &self.host, self.port);
+        options.set_keep_alive(MQTT_TIMEOUT);
+        if let Some((username, password)) = &self.credentials {
+            options.set_credentials(username, password);
+        }
+        options
+    }
+
+    /// Drives the connection until the expected event happens
+    fn wait_for(
+        connection: &mut Connection,
+        done: impl Fn(&Event) -> bool,
+    ) -> Result<(), LibError> {
+        loop {
+            let event = connection
+                .recv_timeout(MQTT_TIMEOUT)
+                .map_err(|e| MqttError {
+                    message: format!("{e:?} while waiting for the broker"),
+                })?
+                .map_err(|e| MqttError {
+                    message: e.to_string(),
+                })?;
+            if done(&event) {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Tells if the publication has been acknowledged according to its QoS
+    fn is_published(&self, event: &Event) -> bool {
+        match self.qos {
+            QoS::AtMostOnce => matches!(event, Event::Outgoing(Outgoing::Publish(_))),
+            QoS::AtLeastOnce => matches!(event, Event::Incoming(Packet::PubAck(_))),
+            QoS::ExactlyOnce => matches!(event, Event::Incoming(Packet::PubComp(_))),
+        }
+    }
+
+    /// Connects, publishes and disconnects
+    fn publish(&self, payload: String) -> Result<(), LibError> {
+        let (client, mut connection) = Client::new(self.options(), 10);
+
+        client
+            .publish(&self.topic, self.qos, self.retain, payload)
+            .map_err(|e| MqttError {
+                message: e.to_string(),
+            })?;
+        Self::wait_for(&mut connection, |event| self.is_published(event))?;
+
+        client.disconnect().map_err(|e| MqttError {
+            message: e.to_string(),
+        })?;
+        Self::wait_for(&mut connection, |event| {
+            matches!(event, Event::Outgoing(Outgoing::Disconnect))
+        })
+    }
+}
+
+impl NotifierFactoryTrait for Mqtt {
+    /// Builds a Mqtt notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let host = crate::get_env_var(ENV_MQTT_HOST)?;
+        let topic = crate::get_env_var(ENV_MQTT_TOPIC)?;
+        let mut builder = Self::builder().host(&host).topic(&topic);
+        if let Some(port) = crate::get_env_var_parsed(ENV_MQTT_PORT)? {
+            builder = builder.port(port);
+        }
+        if let Some(qos) = crate::get_env_var_parsed(ENV_MQTT_QOS)? {
+            builder = builder.qos(qos);
+        }
+        if let Some(retain) = crate::get_env_var_parsed(ENV_MQTT_RETAIN)? {
+            builder = builder.retain(retain);
+        }
+        if let Some(username) = crate::get_env_var_option(ENV_MQTT_USERNAME) {
+            builder = builder.credentials(&username, &crate::get_env_var(ENV_MQTT_PASSWORD)?);
+        }
+        if let Some(client_id) = crate::get_env_var_option(ENV_MQTT_CLIENT_ID) {
+            builder = builder.client_id(&client_id);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl NotifierTrait for Mqtt {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        MQTT_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        self.publish(result.to_json()?)
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+}
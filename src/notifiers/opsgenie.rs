@@ -0,0 +1,293 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::alert::OperationalAlert;
+use crate::{CheckResult, LibError};
+use reqwest::blocking::Client;
+use serde::Serialize;
+
+// OPSGENIE implementation (alert api v2)
+
+/// Common name to identify the notifier
+pub const OPSGENIE_NAME: &str = "opsgenie";
+
+/// Common environment variable to input the API integration key.
+const ENV_OPSGENIE_API_KEY: &str = "OPSGENIE_API_KEY";
+
+/// Environment variable to optionally select the priority of created alerts.
+const ENV_OPSGENIE_PRIORITY: &str = "OPSGENIE_PRIORITY";
+
+/// Environment variable to optionally select the instance region, `us` or `eu`.
+const ENV_OPSGENIE_REGION: &str = "OPSGENIE_REGION";
+
+/// Priority used when none is configured.
+const OPSGENIE_DEFAULT_PRIORITY: &str = "P3";
+
+/// Region used when none is configured.
+const OPSGENIE_DEFAULT_REGION: &str = "us";
+
+/// Opsgenie truncates alert messages to this many characters.
+const OPSGENIE_MAX_MESSAGE_LENGTH: usize = 130;
+
+/// Used for API request serialisation.
+#[derive(Serialize)]
+struct OpsgenieAlert<'a> {
+    message: String,
+    alias: String,
+    description: String,
+    priority: &'a str,
+    source: &'static str,
+    tags: Vec<&'a str>,
+}
+
+/// Used for API request serialisation.
+#[derive(Serialize)]
+struct OpsgenieClose {
+    source: &'static str,
+    note: String,
+}
+
+/// Creates an alert for every available server, and closes the alert of
+/// every checked server which is not available anymore.
+/// The alias is `provider:server`, so that Opsgenie deduplicates repeated alerts.
+/// Operational alerts create an alert aliased `provider:alert`, closed on recovery.
+pub struct Opsgenie {
+    client: Client,
+    api_key: String,
+    priority: String,
+    url: String,
+}
+
+/// Builds an Opsgenie notifier without relying on environment variables.
+pub struct OpsgenieBuilder {
+    api_key: String,
+    priority: String,
+    region: String,
+}
+
+impl Default for OpsgenieBuilder {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            priority: OPSGENIE_DEFAULT_PRIORITY.to_string(),
+            region: OPSGENIE_DEFAULT_REGION.to_string(),
+        }
+    }
+}
+
+impl OpsgenieBuilder {
+    /// Sets the API integration key.
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = api_key.to_string();
+        self
+    }
+
+    /// Sets the priority of created alerts, from `P1` to `P5` (defaults to `P3`).
+    /// The severities `critical`, `error`, `warning` and `info` are mapped
+    /// to `P1`, `P2`, `P3` and `P5` respectively.
+    pub fn priority(mut self, priority: &str) -> Self {
+        self.priority = priority.to_string();
+        self
+    }
+
+    /// Sets the region of the Opsgenie instance, `us` (default) or `eu`.
+    pub fn region(mut self, region: &str) -> Self {
+        self.region = region.to_string();
+        self
+    }
+
+    /// Builds the notifier, attempting to sanitize inputs
+    pub fn build(self) -> Result<Opsgenie, LibError> {
+        if self.api_key.is_empty() {
+            return Err(LibError::ValueError {
+                name: "opsgenie api key".into(),
+                value: self.api_key,
+            });
+        }
+
+        let priority = match self.priority.to_lowercase().as_str() {
+            "critical" => "P1",
+            "error" => "P2",
+            "warning" => "P3",
+            "info" => "P5",
+            "p1" => "P1",
+            "p2" => "P2",
+            "p3" => "P3",
+            "p4" => "P4",
+            "p5" => "P5",
+            _ => {
+                return Err(LibError::ValueError {
+                    name: "opsgenie priority".into(),
+                    value: self.priority,
+                })
+            }
+        };
+
+        let url = match self.region.to_lowercase().as_str() {
+            "us" => "https://api.opsgenie.com/v2/alerts",
+            "eu" => "https://api.eu.opsgenie.com/v2/alerts",
+            _ => {
+                return Err(LibError::ValueError {
+                    name: "opsgenie region".into(),
+                    value: self.region,
+                })
+            }
+        };
+
+        Ok(Opsgenie {
+            client: crate::client::build_client()?,
+            api_key: self.api_key,
+            priority: priority.to_string(),
+            url: url.to_string(),
+        })
+    }
+}
+
+impl Opsgenie {
+    /// Starts building a new instance.
+    pub fn builder() -> OpsgenieBuilder {
+        OpsgenieBuilder::default()
+    }
+
+    /// Builds the alias of a server, used for deduplication and closing
+    fn alias(provider_name: &str, server: &str) -> String {
+        format!("{provider_name}:{server}")
+    }
+
+    /// Builds the url closing an alert, escaping the alias as a path segment
+    fn close_url(&self, alias: &str) -> Result<String, LibError> {
+        let invalid = || LibError::ValueError {
+            name: "opsgenie url".into(),
+            value: self.url.clone(),
+        };
+        let mut url = reqwest::Url::parse(&self.url).map_err(|_| invalid())?;
+        url.path_segments_mut()
+            .map_err(|_| invalid())?
+            .push(alias)
+            .push("close");
+        url.query_pairs_mut().append_pair("identifierType", "alias");
+        Ok(url.to_string())
+    }
+
+    /// Sends a single request and handles Opsgenie specific errors
+    fn send<T: Serialize>(&self, url: &str, body: &T) -> Result<(), LibError> {
+        let response = self
+            .client
+            .post(url)
+            .header("Authorization", format!("GenieKey {}", self.api_key))
+            .json(body)
+            .send()?;
+
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        Err(LibError::ApiStatusError {
+            status: response.status(),
+            message: format!(
+                "Error {} while notifying {OPSGENIE_NAME}: {}",
+                response.status().as_str(),
+                response.text().unwrap_or_else(|error| error.to_string())
+            ),
+        })
+    }
+}
+
+impl NotifierFactoryTrait for Opsgenie {
+    /// Builds an Opsgenie notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let api_key = crate::get_env_var(ENV_OPSGENIE_API_KEY)?;
+        let priority = crate::get_env_var_default(ENV_OPSGENIE_PRIORITY, OPSGENIE_DEFAULT_PRIORITY);
+        let region = crate::get_env_var_default(ENV_OPSGENIE_REGION, OPSGENIE_DEFAULT_REGION);
+        Ok(Box::new(
+            Self::builder()
+                .api_key(&api_key)
+                .priority(&priority)
+                .region(&region)
+                .build()?,
+        ))
+    }
+}
+
+impl NotifierTrait for Opsgenie {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        OPSGENIE_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let provider_name = &result.provider_name;
+
+        for server in result.available_servers.iter() {
+            self.send(
+                &self.url,
+                &OpsgenieAlert {
+                    message: format!("Server {server} is available at {provider_name}")
+                        .chars()
+                        .take(OPSGENIE_MAX_MESSAGE_LENGTH)
+                        .collect(),
+                    alias: Self::alias(provider_name, server),
+                    description: format!(
+                        "Available servers at {provider_name}: {}",
+                        result.available_servers.join(", ")
+                    ),
+                    priority: &self.priority,
+                    source: env!("CARGO_PKG_NAME"),
+                    tags: vec![provider_name, server],
+                },
+            )?;
+        }
+
+        for server in result.unavailable_servers.iter() {
+            self.send(
+                &self.close_url(&Self::alias(provider_name, server))?,
+                &OpsgenieClose {
+                    source: env!("CARGO_PKG_NAME"),
+                    note: format!("Server {server} is not available anymore at {provider_name}"),
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+
+    /// Tells that the notifier can send operational alerts.
+    fn supports_alerts(&self) -> bool {
+        true
+    }
+
+    /// Sends an operational alert using the provided data.
+    fn alert(&self, alert: &OperationalAlert) -> Result<(), LibError> {
+        let provider_name = &alert.provider_name;
+        let alias = Self::alias(provider_name, "alert");
+        if alert.is_recovery() {
+            return self.send(
+                &self.close_url(&alias)?,
+                &OpsgenieClose {
+                    source: env!("CARGO_PKG_NAME"),
+                    note: alert.to_string(),
+                },
+            );
+        }
+        let kind = alert.kind.to_string();
+        self.send(
+            &self.url,
+            &OpsgenieAlert {
+                message: alert
+                    .to_string()
+                    .chars()
+                    .take(OPSGENIE_MAX_MESSAGE_LENGTH)
+                    .collect(),
+                alias,
+                description: alert.message.clone(),
+                priority: &self.priority,
+                source: env!("CARGO_PKG_NAME"),
+                tags: vec![provider_name.as_str(), kind.as_str()],
+            },
+        )
+    }
+}
@@ -0,0 +1,229 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::alert::OperationalAlert;
+use crate::{CheckResult, LibError};
+use reqwest::blocking::Client;
+use serde::Serialize;
+
+// PAGERDUTY implementation (events api v2)
+
+/// Common name to identify the notifier
+pub const PAGERDUTY_NAME: &str = "pagerduty";
+
+/// Common environment variable to input the integration routing key.
+const ENV_PAGERDUTY_ROUTING_KEY: &str = "PAGERDUTY_ROUTING_KEY";
+
+/// Environment variable to optionally select the severity of triggered events.
+const ENV_PAGERDUTY_SEVERITY: &str = "PAGERDUTY_SEVERITY";
+
+/// Events API v2 endpoint.
+const PAGERDUTY_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// Severity used when none is configured.
+const PAGERDUTY_DEFAULT_SEVERITY: &str = "info";
+
+/// Severity of operational alerts, which are about the watcher itself.
+const PAGERDUTY_ALERT_SEVERITY: &str = "error";
+
+/// Used for API request serialisation, only set on trigger events.
+#[derive(Serialize)]
+struct PagerDutyPayload<'a> {
+    summary: String,
+    source: &'a str,
+    severity: &'a str,
+}
+
+/// Used for API request serialisation.
+#[derive(Serialize)]
+struct PagerDutyEvent<'a> {
+    routing_key: &'a str,
+    event_action: &'a str,
+    dedup_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload: Option<PagerDutyPayload<'a>>,
+}
+
+/// Sends a `trigger` event for every available server,
+/// and a `resolve` event for every checked server which is not available.
+/// The dedup key is `provider:server` so that each server is its own incident.
+/// Operational alerts trigger an `error` event keyed `provider:alert`, resolved on recovery.
+pub struct PagerDuty {
+    client: Client,
+    routing_key: String,
+    severity: String,
+}
+
+/// Builds a PagerDuty notifier without relying on environment variables.
+pub struct PagerDutyBuilder {
+    routing_key: String,
+    severity: String,
+}
+
+impl Default for PagerDutyBuilder {
+    fn default() -> Self {
+        Self {
+            routing_key: String::new(),
+            severity: PAGERDUTY_DEFAULT_SEVERITY.to_string(),
+        }
+    }
+}
+
+impl PagerDutyBuilder {
+    /// Sets the integration routing key.
+    pub fn routing_key(mut self, routing_key: &str) -> Self {
+        self.routing_key = routing_key.to_string();
+        self
+    }
+
+    /// Sets the severity of triggered events: `critical`, `error`, `warning` or `info` (default).
+    pub fn severity(mut self, severity: &str) -> Self {
+        self.severity = severity.to_string();
+        self
+    }
+
+    /// Builds the notifier, attempting to sanitize inputs
+    pub fn build(self) -> Result<PagerDuty, LibError> {
+        if self.routing_key.is_empty() {
+            return Err(LibError::ValueError {
+                name: "pagerduty routing key".into(),
+                value: self.routing_key,
+            });
+        }
+
+        if !matches!(
+            self.severity.as_str(),
+            "critical" | "error" | "warning" | "info"
+        ) {
+            return Err(LibError::ValueError {
+                name: "pagerduty severity".into(),
+                value: self.severity,
+            });
+        }
+
+        Ok(PagerDuty {
+            client: crate::client::build_client()?,
+            routing_key: self.routing_key,
+            severity: self.severity,
+        })
+    }
+}
+
+impl PagerDuty {
+    /// Starts building a new instance.
+    pub fn builder() -> PagerDutyBuilder {
+        PagerDutyBuilder::default()
+    }
+
+    /// Builds the deduplication key of a server
+    fn dedup_key(provider_name: &str, server: &str) -> String {
+        format!("{provider_name}:{server}")
+    }
+
+    /// Sends a single event and handles PagerDuty specific errors
+    fn send(&self, event: &PagerDutyEvent) -> Result<(), LibError> {
+        let response = self.client.post(PAGERDUTY_URL).json(event).send()?;
+
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        Err(LibError::ApiStatusError {
+            status: response.status(),
+            message: format!(
+                "Error {} while notifying {PAGERDUTY_NAME}: {}",
+                response.status().as_str(),
+                response.text().unwrap_or_else(|error| error.to_string())
+            ),
+        })
+    }
+}
+
+impl NotifierFactoryTrait for PagerDuty {
+    /// Builds a PagerDuty notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let routing_key = crate::get_env_var(ENV_PAGERDUTY_ROUTING_KEY)?;
+        let severity =
+            crate::get_env_var_default(ENV_PAGERDUTY_SEVERITY, PAGERDUTY_DEFAULT_SEVERITY);
+        Ok(Box::new(
+            Self::builder()
+                .routing_key(&routing_key)
+                .severity(&severity)
+                .build()?,
+        ))
+    }
+}
+
+impl NotifierTrait for PagerDuty {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        PAGERDUTY_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let provider_name = &result.provider_name;
+
+        for server in result.available_servers.iter() {
+            self.send(&PagerDutyEvent {
+                routing_key: &self.routing_key,
+                event_action: "trigger",
+                dedup_key: Self::dedup_key(provider_name, server),
+                payload: Some(PagerDutyPayload {
+                    summary: format!("Server {server} is available at {provider_name}"),
+                    source: provider_name,
+                    severity: &self.severity,
+                }),
+            })?;
+        }
+
+        for server in result.unavailable_servers.iter() {
+            self.send(&PagerDutyEvent {
+                routing_key: &self.routing_key,
+                event_action: "resolve",
+                dedup_key: Self::dedup_key(provider_name, server),
+                payload: None,
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Tests by sending a notification with dummy values, then resolves
+    /// the incidents it triggered, so that none of them is left open.
+    fn test(&self) -> Result<(), LibError> {
+        let dummy = CheckResult::get_dummy();
+        self.notify(&dummy)?;
+        for server in dummy.available_servers.iter() {
+            self.send(&PagerDutyEvent {
+                routing_key: &self.routing_key,
+                event_action: "resolve",
+                dedup_key: Self::dedup_key(&dummy.provider_name, server),
+                payload: None,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Tells that the notifier can send operational alerts.
+    fn supports_alerts(&self) -> bool {
+        true
+    }
+
+    /// Sends an operational alert using the provided data.
+    fn alert(&self, alert: &OperationalAlert) -> Result<(), LibError> {
+        let provider_name = &alert.provider_name;
+        self.send(&PagerDutyEvent {
+            routing_key: &self.routing_key,
+            event_action: if alert.is_recovery() {
+                "resolve"
+            } else {
+                "trigger"
+            },
+            dedup_key: Self::dedup_key(provider_name, "alert"),
+            payload: (!alert.is_recovery()).then(|| PagerDutyPayload {
+                summary: alert.to_string(),
+                source: provider_name,
+                severity: PAGERDUTY_ALERT_SEVERITY,
+            }),
+        })
+    }
+}
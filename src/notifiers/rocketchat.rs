@@ -0,0 +1,228 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::{CheckResult, LibError};
+use reqwest::blocking::Client;
+use serde::Serialize;
+
+// ROCKETCHAT implementation (incoming webhook)
+
+/// Common name to identify the notifier
+pub const ROCKETCHAT_NAME: &str = "rocketchat";
+
+/// Common environment variable to input the incoming webhook URL.
+const ENV_ROCKETCHAT_WEBHOOK_URL: &str = "ROCKETCHAT_WEBHOOK_URL";
+
+/// Environment variable to optionally override the displayed name.
+const ENV_ROCKETCHAT_ALIAS: &str = "ROCKETCHAT_ALIAS";
+
+/// Environment variable to optionally override the avatar with an emoji.
+const ENV_ROCKETCHAT_EMOJI: &str = "ROCKETCHAT_EMOJI";
+
+/// Attachment color when servers are available
+const ROCKETCHAT_COLOR_AVAILABLE: &str = "#2eb886";
+
+/// Attachment color when no server is available
+const ROCKETCHAT_COLOR_UNAVAILABLE: &str = "#a0a0a0";
+
+/// Used for API request serialisation.
+#[derive(Serialize)]
+struct RocketChatMessage<'a> {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alias: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    emoji: Option<&'a str>,
+    attachments: Vec<RocketChatAttachment>,
+}
+
+/// Used for API request serialisation.
+#[derive(Serialize)]
+struct RocketChatAttachment {
+    title: String,
+    color: &'static str,
+    fields: Vec<RocketChatField>,
+}
+
+/// Used for API request serialisation.
+#[derive(Serialize)]
+struct RocketChatField {
+    short: bool,
+    title: String,
+    value: String,
+}
+
+/// Posts an attachment per availability state, listing the checked servers.
+pub struct RocketChat {
+    client: Client,
+    url: String,
+    alias: Option<String>,
+    emoji: Option<String>,
+}
+
+/// Builds a RocketChat notifier without relying on environment variables.
+#[derive(Default)]
+pub struct RocketChatBuilder {
+    url: String,
+    alias: Option<String>,
+    emoji: Option<String>,
+}
+
+impl RocketChatBuilder {
+    /// Sets the incoming webhook URL.
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    /// Overrides the name displayed for the messages.
+    pub fn alias(mut self, alias: &str) -> Self {
+        self.alias = Some(alias.to_string());
+        self
+    }
+
+    /// Overrides the avatar with an emoji, like `:computer:`.
+    pub fn emoji(mut self, emoji: &str) -> Self {
+        self.emoji = Some(emoji.to_string());
+        self
+    }
+
+    /// Builds the notifier, attempting to sanitize inputs
+    pub fn build(self) -> Result<RocketChat, LibError> {
+        // self-hosted instances may not use https
+        if !self.url.starts_with("https://") && !self.url.starts_with("http://") {
+            return Err(LibError::ValueError {
+                name: "rocketchat webhook url".into(),
+                value: self.url,
+            });
+        }
+
+        // the API expects the emoji short name between colons
+        let emoji = self.emoji.map(|emoji| match emoji.starts_with(':') {
+            true => emoji,
+            false => format!(":{}:", emoji.trim_matches(':')),
+        });
+
+        Ok(RocketChat {
+            client: crate::client::build_client()?,
+            url: self.url,
+            alias: self.alias,
+            emoji,
+        })
+    }
+}
+
+impl RocketChat {
+    /// Starts building a new instance.
+    pub fn builder() -> RocketChatBuilder {
+        RocketChatBuilder::default()
+    }
+
+    /// Builds an attachment listing servers, if there is any, linked to their order page
+    fn build_attachment(
+        result: &CheckResult,
+        title: &str,
+        color: &'static str,
+        servers: &[String],
+    ) -> Option<RocketChatAttachment> {
+        if servers.is_empty() {
+            return None;
+        }
+
+        Some(RocketChatAttachment {
+            title: format!("{title} ({})", servers.len()),
+            color,
+            fields: servers
+                .iter()
+                .map(|server| RocketChatField {
+                    short: true,
+                    title: "Server".to_string(),
+                    value: result.to_markdown_link(server, server),
+                })
+                .collect(),
+        })
+    }
+
+    /// Builds the message, with an attachment for available and unavailable servers
+    fn build_message(&self, result: &CheckResult) -> RocketChatMessage<'_> {
+        let text = match result.available_servers.is_empty() {
+            true => format!(
+                "No server available for the selected types at {}",
+                result.provider_name
+            ),
+            false => format!("Servers available at {}", result.provider_name),
+        };
+
+        let attachments = [
+            Self::build_attachment(
+                result,
+                "Available",
+                ROCKETCHAT_COLOR_AVAILABLE,
+                &result.available_servers,
+            ),
+            Self::build_attachment(
+                result,
+                "Unavailable",
+                ROCKETCHAT_COLOR_UNAVAILABLE,
+                &result.unavailable_servers,
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        RocketChatMessage {
+            text,
+            alias: self.alias.as_deref(),
+            emoji: self.emoji.as_deref(),
+            attachments,
+        }
+    }
+}
+
+impl NotifierFactoryTrait for RocketChat {
+    /// Builds a RocketChat notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let url = crate::get_env_var(ENV_ROCKETCHAT_WEBHOOK_URL)?;
+        let mut builder = Self::builder().url(&url);
+        if let Some(alias) = crate::get_env_var_option(ENV_ROCKETCHAT_ALIAS) {
+            builder = builder.alias(&alias);
+        }
+        if let Some(emoji) = crate::get_env_var_option(ENV_ROCKETCHAT_EMOJI) {
+            builder = builder.emoji(&emoji);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl NotifierTrait for RocketChat {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        ROCKETCHAT_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&self.build_message(result))
+            .send()?;
+
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        Err(LibError::ApiStatusError {
+            status: response.status(),
+            message: format!(
+                "Error {} while notifying {ROCKETCHAT_NAME}: {}",
+                response.status().as_str(),
+                response.text().unwrap_or_else(|error| error.to_string())
+            ),
+        })
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+}
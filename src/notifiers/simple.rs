@@ -1,5 +1,8 @@
 use super::{NotifierFactoryTrait, NotifierTrait};
-use crate::{CheckResult, LibError};
+use crate::alert::OperationalAlert;
+use crate::{template, CheckResult, LibError};
+use http::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use http::Method;
 use reqwest::blocking::{Client, RequestBuilder};
 use std::collections::HashMap;
 
@@ -9,32 +12,199 @@ use std::collections::HashMap;
 pub const SIMPLE_GET_NAME: &str = "simple-get";
 pub const SIMPLE_POST_NAME: &str = "simple-post";
 pub const SIMPLE_PUT_NAME: &str = "simple-put";
+pub const SIMPLE_FORM_NAME: &str = "simple-form";
 
-/// Common environment variable to select the custom URL.
+/// Common environment variable to select the custom URL, see `render_url` for placeholders.
 const ENV_SIMPLE_URL: &str = "SIMPLE_URL";
 
 /// Environment variable to optionally select the name of the query parameter for the GET request.
 const ENV_SIMPLE_GET_PARAM_NAME_PROVIDER: &str = "SIMPLE_GET_PARAM_NAME_PROVIDER";
 const ENV_SIMPLE_GET_PARAM_NAME_SERVERS: &str = "SIMPLE_GET_PARAM_NAME_SERVERS";
 
+/// Environment variable to optionally select the name of the fields for the form request.
+const ENV_SIMPLE_FORM_FIELD_NAME_PROVIDER: &str = "SIMPLE_FORM_FIELD_NAME_PROVIDER";
+const ENV_SIMPLE_FORM_FIELD_NAME_SERVERS: &str = "SIMPLE_FORM_FIELD_NAME_SERVERS";
+
+/// Field names used when none is configured.
+const SIMPLE_FORM_DEFAULT_FIELD_NAME_PROVIDER: &str = "provider";
+const SIMPLE_FORM_DEFAULT_FIELD_NAME_SERVERS: &str = "servers";
+
+/// Prefix of the environment variables adding headers, like `SIMPLE_HEADER_X_API_KEY`
+/// for an `X-Api-Key` header, underscores being replaced by dashes.
+const ENV_SIMPLE_HEADER_PREFIX: &str = "SIMPLE_HEADER_";
+
+/// Environment variables to optionally authenticate with basic credentials.
+const ENV_SIMPLE_USERNAME: &str = "SIMPLE_USERNAME";
+const ENV_SIMPLE_PASSWORD: &str = "SIMPLE_PASSWORD";
+
+/// Environment variable to optionally authenticate with a bearer token.
+const ENV_SIMPLE_BEARER_TOKEN: &str = "SIMPLE_BEARER_TOKEN";
+
+/// Environment variable to optionally replace the HTTP method of the notifier.
+const ENV_SIMPLE_METHOD: &str = "SIMPLE_METHOD";
+
+/// How requests are authenticated
+#[derive(Clone, Debug, Default)]
+enum SimpleAuth {
+    #[default]
+    None,
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer {
+        token: String,
+    },
+}
+
+/// Request settings shared by every simple notifier, so that they can
+/// talk to real-world APIs : extra headers, authentication and method.
+#[derive(Clone, Debug, Default)]
+pub struct SimpleOptions {
+    headers: HeaderMap,
+    auth: SimpleAuth,
+    method: Option<Method>,
+}
+
+impl SimpleOptions {
+    /// Adds a header to every request, replacing any default value.
+    pub fn header(mut self, name: &str, value: &str) -> Result<Self, LibError> {
+        let header_name =
+            HeaderName::from_bytes(name.as_bytes()).map_err(|_| LibError::ValueError {
+                name: "simple header name".into(),
+                value: name.to_string(),
+            })?;
+        let header_value = HeaderValue::from_str(value).map_err(|_| LibError::ValueError {
+            name: format!("simple header `{name}` value"),
+            value: value.to_string(),
+        })?;
+        self.headers.insert(header_name, header_value);
+        Ok(self)
+    }
+
+    /// Authenticates with basic credentials.
+    pub fn basic_auth(mut self, username: &str, password: &str) -> Self {
+        self.auth = SimpleAuth::Basic {
+            username: username.to_string(),
+            password: password.to_string(),
+        };
+        self
+    }
+
+    /// Authenticates with a bearer token.
+    pub fn bearer_token(mut self, token: &str) -> Self {
+        self.auth = SimpleAuth::Bearer {
+            token: token.to_string(),
+        };
+        self
+    }
+
+    /// Replaces the HTTP method of the notifier, like `PATCH`.
+    pub fn method(mut self, method: &str) -> Result<Self, LibError> {
+        let method = Method::from_bytes(method.to_uppercase().as_bytes()).map_err(|_| {
+            LibError::ValueError {
+                name: ENV_SIMPLE_METHOD.to_string(),
+                value: method.to_string(),
+            }
+        })?;
+        self.method = Some(method);
+        Ok(self)
+    }
+
+    /// Reads the options from environment variables
+    pub fn from_env() -> Result<Self, LibError> {
+        let mut options = Self::default();
+
+        // sorted so that errors are reported consistently
+        for var in crate::get_env_var_names(ENV_SIMPLE_HEADER_PREFIX) {
+            let name = var[ENV_SIMPLE_HEADER_PREFIX.len()..].replace('_', "-");
+            options = options.header(&name, &crate::get_env_var(&var)?)?;
+        }
+
+        let username = crate::get_env_var_option(ENV_SIMPLE_USERNAME);
+        let token = crate::get_env_var_option(ENV_SIMPLE_BEARER_TOKEN);
+        match (username, token) {
+            (Some(_), Some(token)) => {
+                return Err(LibError::ValueError {
+                    name: format!("both {ENV_SIMPLE_USERNAME} and {ENV_SIMPLE_BEARER_TOKEN}"),
+                    value: token,
+                })
+            }
+            (Some(username), None) => {
+                let password = crate::get_env_var(ENV_SIMPLE_PASSWORD)?;
+                options = options.basic_auth(&username, &password);
+            }
+            (None, Some(token)) => options = options.bearer_token(&token),
+            (None, None) => {}
+        }
+
+        if let Some(method) = crate::get_env_var_option(ENV_SIMPLE_METHOD) {
+            options = options.method(&method)?;
+        }
+
+        Ok(options)
+    }
+
+    /// Builds a request with the options, the method defaulting to the one of the notifier
+    fn request(&self, client: &Client, method: Method, url: &str) -> RequestBuilder {
+        let builder = client
+            .request(self.method.clone().unwrap_or(method), url)
+            .headers(self.headers.clone());
+        match &self.auth {
+            SimpleAuth::None => builder,
+            SimpleAuth::Basic { username, password } => {
+                builder.basic_auth(username, Some(password))
+            }
+            SimpleAuth::Bearer { token } => builder.bearer_auth(token),
+        }
+    }
+
+    /// Builds a request with a json body, unless another content type was set
+    fn json_request(
+        &self,
+        client: &Client,
+        method: Method,
+        url: &str,
+        json: String,
+    ) -> RequestBuilder {
+        let builder = self.request(client, method, url);
+        let builder = match self.headers.contains_key(CONTENT_TYPE) {
+            true => builder,
+            false => builder.header(CONTENT_TYPE, "application/json"),
+        };
+        builder.body(json)
+    }
+}
+
+/// Renders the URL template, replacing the `{provider}` and `{servers}` (comma separated)
+/// placeholders with their percent-encoded values, so that path-based APIs can be reached.
+/// Operational alerts have no servers, so their placeholder is empty.
+fn render_url(url: &str, provider_name: &str, servers: &[String]) -> String {
+    template::render(
+        url,
+        &[
+            ("provider", &template::escape_url(provider_name)),
+            ("servers", &template::escape_url(&servers.join(","))),
+        ],
+    )
+}
+
 /// Utility function to handle the execution of the request
 fn send_request(builder: RequestBuilder, notifier_name: &str) -> Result<(), LibError> {
-    let response = builder
-        .send()
-        .map_err(|source| LibError::RequestError { source })?;
+    let response = builder.send()?;
 
     response
         .status()
         .is_success()
         .then_some(())
-        .ok_or(LibError::ApiError {
+        .ok_or(LibError::ApiStatusError {
+            status: response.status(),
             message: format!(
                 "Error {} while notifying {notifier_name}: {}",
                 response.status().as_str(),
                 response
                     .text()
-                    .map_err(|source| LibError::RequestError { source })
-                    .unwrap_or_else(|error| error.to_string())
+                    .unwrap_or_else(|error| LibError::from(error).to_string())
             ),
         })
 }
@@ -44,9 +214,11 @@ fn send_request(builder: RequestBuilder, notifier_name: &str) -> Result<(), LibE
 /// When notifying, it provides the provider name in a parameter,
 /// and a comma-separated list of server name in the other parameter
 pub struct SimpleGet {
+    client: Client,
     url: String,
     param_provider: String,
     param_servers: String,
+    options: SimpleOptions,
 }
 
 impl NotifierFactoryTrait for SimpleGet {
@@ -55,15 +227,31 @@ impl NotifierFactoryTrait for SimpleGet {
         let url = crate::get_env_var(ENV_SIMPLE_URL)?;
         let param_provider = crate::get_env_var(ENV_SIMPLE_GET_PARAM_NAME_PROVIDER)?;
         let param_servers = crate::get_env_var(ENV_SIMPLE_GET_PARAM_NAME_SERVERS)?;
-        Ok(Box::new(SimpleGet {
-            url,
-            param_provider,
-            param_servers,
-        }))
+        Ok(Box::new(
+            Self::new(&url, &param_provider, &param_servers)?
+                .with_options(SimpleOptions::from_env()?),
+        ))
     }
 }
 
 impl SimpleGet {
+    /// Builds a new instance, from the URL and the names of the query parameters
+    pub fn new(url: &str, param_provider: &str, param_servers: &str) -> Result<Self, LibError> {
+        Ok(Self {
+            client: crate::client::build_client()?,
+            url: url.into(),
+            param_provider: param_provider.into(),
+            param_servers: param_servers.into(),
+            options: SimpleOptions::default(),
+        })
+    }
+
+    /// Sets the headers, authentication and method of the requests
+    pub fn with_options(mut self, options: SimpleOptions) -> Self {
+        self.options = options;
+        self
+    }
+
     /// Builds the query parameter from the structure's data
     fn build_query_parameters(&self, result: &CheckResult) -> HashMap<&String, String> {
         let joined = result.available_servers.join(",");
@@ -77,13 +265,20 @@ impl SimpleGet {
 impl NotifierTrait for SimpleGet {
     /// Gets the actual name of the notifier.
     fn name(&self) -> &'static str {
-        return SIMPLE_GET_NAME;
+        SIMPLE_GET_NAME
     }
 
     /// Sends an notification using the provided data.
     fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
         let params = self.build_query_parameters(result);
-        let builder = Client::new().get(&self.url).query(&params);
+        let builder = self
+            .options
+            .request(
+                &self.client,
+                Method::GET,
+                &render_url(&self.url, &result.provider_name, &result.available_servers),
+            )
+            .query(&params);
         send_request(builder, self.name())
     }
 
@@ -96,27 +291,53 @@ impl NotifierTrait for SimpleGet {
 /// Implementation of a simple POST request to a custom URL
 /// It picks the URL, and sets the body to the json serialization of the result
 pub struct SimplePost {
+    client: Client,
     url: String,
+    options: SimpleOptions,
+}
+
+impl SimplePost {
+    /// Builds a new instance, from the URL
+    pub fn new(url: &str) -> Result<Self, LibError> {
+        Ok(Self {
+            client: crate::client::build_client()?,
+            url: url.into(),
+            options: SimpleOptions::default(),
+        })
+    }
+
+    /// Sets the headers, authentication and method of the requests
+    pub fn with_options(mut self, options: SimpleOptions) -> Self {
+        self.options = options;
+        self
+    }
 }
 
 impl NotifierFactoryTrait for SimplePost {
     /// Builds a SimplePost notifier from environment variables.
     fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
         let url = crate::get_env_var(ENV_SIMPLE_URL)?;
-        Ok(Box::new(SimplePost { url }))
+        Ok(Box::new(
+            Self::new(&url)?.with_options(SimpleOptions::from_env()?),
+        ))
     }
 }
 
 impl NotifierTrait for SimplePost {
     /// Gets the actual name of the notifier.
     fn name(&self) -> &'static str {
-        return SIMPLE_POST_NAME;
+        SIMPLE_POST_NAME
     }
 
     /// Sends an notification using the provided data.
     fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
         let json = result.to_json()?;
-        let builder = Client::new().post(&self.url).body(json);
+        let builder = self.options.json_request(
+            &self.client,
+            Method::POST,
+            &render_url(&self.url, &result.provider_name, &result.available_servers),
+            json,
+        );
         send_request(builder, self.name())
     }
 
@@ -124,32 +345,169 @@ impl NotifierTrait for SimplePost {
     fn test(&self) -> Result<(), LibError> {
         self.notify(&CheckResult::get_dummy())
     }
+
+    /// Tells that the notifier can send operational alerts.
+    fn supports_alerts(&self) -> bool {
+        true
+    }
+
+    /// Sends an operational alert, with the json serialization of the alert as body.
+    fn alert(&self, alert: &OperationalAlert) -> Result<(), LibError> {
+        let builder = self.options.json_request(
+            &self.client,
+            Method::POST,
+            &render_url(&self.url, &alert.provider_name, &[]),
+            alert.to_json()?,
+        );
+        send_request(builder, self.name())
+    }
 }
 
 /// Implementation of a simple POST request to a custom URL
 /// It picks the URL, and sets the body to the json serialization of the result
 pub struct SimplePut {
+    client: Client,
     url: String,
+    options: SimpleOptions,
+}
+
+impl SimplePut {
+    /// Builds a new instance, from the URL
+    pub fn new(url: &str) -> Result<Self, LibError> {
+        Ok(Self {
+            client: crate::client::build_client()?,
+            url: url.into(),
+            options: SimpleOptions::default(),
+        })
+    }
+
+    /// Sets the headers, authentication and method of the requests
+    pub fn with_options(mut self, options: SimpleOptions) -> Self {
+        self.options = options;
+        self
+    }
 }
 
 impl NotifierFactoryTrait for SimplePut {
     /// Builds a SimplePut notifier from environment variables.
     fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
         let url = crate::get_env_var(ENV_SIMPLE_URL)?;
-        Ok(Box::new(SimplePut { url }))
+        Ok(Box::new(
+            Self::new(&url)?.with_options(SimpleOptions::from_env()?),
+        ))
     }
 }
 
 impl NotifierTrait for SimplePut {
     /// Gets the actual name of the notifier.
     fn name(&self) -> &'static str {
-        return SIMPLE_PUT_NAME;
+        SIMPLE_PUT_NAME
     }
 
     /// Sends an notification using the provided data.
     fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
         let json = result.to_json()?;
-        let builder = Client::new().put(&self.url).body(json);
+        let builder = self.options.json_request(
+            &self.client,
+            Method::PUT,
+            &render_url(&self.url, &result.provider_name, &result.available_servers),
+            json,
+        );
+        send_request(builder, self.name())
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+
+    /// Tells that the notifier can send operational alerts.
+    fn supports_alerts(&self) -> bool {
+        true
+    }
+
+    /// Sends an operational alert, with the json serialization of the alert as body.
+    fn alert(&self, alert: &OperationalAlert) -> Result<(), LibError> {
+        let builder = self.options.json_request(
+            &self.client,
+            Method::PUT,
+            &render_url(&self.url, &alert.provider_name, &[]),
+            alert.to_json()?,
+        );
+        send_request(builder, self.name())
+    }
+}
+
+/// Implementation of a simple POST request to a custom URL, with a form-encoded body
+/// It picks the URL, and the field names from environment variables
+/// When notifying, it provides the provider name in a field,
+/// and a comma-separated list of server name in the other field
+pub struct SimpleForm {
+    client: Client,
+    url: String,
+    field_provider: String,
+    field_servers: String,
+    options: SimpleOptions,
+}
+
+impl SimpleForm {
+    /// Builds a new instance, from the URL and the names of the form fields
+    pub fn new(url: &str, field_provider: &str, field_servers: &str) -> Result<Self, LibError> {
+        Ok(Self {
+            client: crate::client::build_client()?,
+            url: url.into(),
+            field_provider: field_provider.into(),
+            field_servers: field_servers.into(),
+            options: SimpleOptions::default(),
+        })
+    }
+
+    /// Sets the headers, authentication and method of the requests
+    pub fn with_options(mut self, options: SimpleOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+impl NotifierFactoryTrait for SimpleForm {
+    /// Builds a SimpleForm notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let url = crate::get_env_var(ENV_SIMPLE_URL)?;
+        let field_provider = crate::get_env_var_default(
+            ENV_SIMPLE_FORM_FIELD_NAME_PROVIDER,
+            SIMPLE_FORM_DEFAULT_FIELD_NAME_PROVIDER,
+        );
+        let field_servers = crate::get_env_var_default(
+            ENV_SIMPLE_FORM_FIELD_NAME_SERVERS,
+            SIMPLE_FORM_DEFAULT_FIELD_NAME_SERVERS,
+        );
+        Ok(Box::new(
+            Self::new(&url, &field_provider, &field_servers)?
+                .with_options(SimpleOptions::from_env()?),
+        ))
+    }
+}
+
+impl NotifierTrait for SimpleForm {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        SIMPLE_FORM_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let servers = result.available_servers.join(",");
+        let builder = self
+            .options
+            .request(
+                &self.client,
+                Method::POST,
+                &render_url(&self.url, &result.provider_name, &result.available_servers),
+            )
+            .form(&[
+                (&self.field_provider, &result.provider_name),
+                (&self.field_servers, &servers),
+            ]);
         send_request(builder, self.name())
     }
 
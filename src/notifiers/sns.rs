@@ -0,0 +1,150 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::aws::{self, Credentials};
+use crate::{CheckResult, LibError};
+use reqwest::blocking::Client;
+
+// AWS SNS implementation (topic publish)
+
+/// Common name to identify the notifier
+pub const SNS_NAME: &str = "sns";
+
+/// Common environment variable to select the topic to publish to.
+const ENV_SNS_TOPIC_ARN: &str = "SNS_TOPIC_ARN";
+
+/// Signature scope of the service.
+const SNS_SERVICE: &str = "sns";
+
+/// Content type of the Query API requests.
+const SNS_CONTENT_TYPE: &str = "application/x-www-form-urlencoded; charset=utf-8";
+
+/// Publishes the json result to a SNS topic, so that it reaches every subscriber.
+/// The region is taken from the topic ARN.
+pub struct Sns {
+    client: Client,
+    credentials: Credentials,
+    topic_arn: String,
+    region: String,
+}
+
+impl Sns {
+    /// Builds a new instance, attempting to sanitize inputs
+    pub fn new(topic_arn: &str, credentials: Credentials) -> Result<Self, LibError> {
+        // arn:partition:sns:region:account:topic
+        let fields: Vec<&str> = topic_arn.split(':').collect();
+        if fields.len() != 6 || fields[0] != "arn" || fields[2] != SNS_SERVICE {
+            return Err(LibError::ValueError {
+                name: "malformed sns topic arn".into(),
+                value: topic_arn.into(),
+            });
+        }
+
+        let region = fields[3].to_string();
+        if region.is_empty() {
+            return Err(LibError::ValueError {
+                name: "sns topic arn without region".into(),
+                value: topic_arn.into(),
+            });
+        }
+
+        Ok(Self {
+            client: crate::client::build_client_for(SNS_NAME)?,
+            credentials,
+            topic_arn: topic_arn.into(),
+            region,
+        })
+    }
+
+    /// Gets the regional endpoint host
+    fn host(&self) -> String {
+        format!("sns.{}.amazonaws.com", self.region)
+    }
+
+    /// Sends a signed Query API action, the activity being used in error messages.
+    fn send_action(&self, activity: &str, parameters: &[(&str, &str)]) -> Result<(), LibError> {
+        let body = aws::encode_form(parameters);
+
+        let host = self.host();
+        let headers = aws::sign_post(
+            &self.credentials,
+            SNS_SERVICE,
+            &self.region,
+            &host,
+            SNS_CONTENT_TYPE,
+            &body,
+        );
+
+        let mut request = self.client.post(format!("https://{host}/")).body(body);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        let response = request.send()?;
+
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        Err(LibError::ApiStatusError {
+            status: response.status(),
+            message: format!(
+                "Error {} while {activity} {SNS_NAME}: {}",
+                response.status().as_str(),
+                response.text().unwrap_or_else(|error| error.to_string())
+            ),
+        })
+    }
+}
+
+impl NotifierFactoryTrait for Sns {
+    /// Builds a Sns notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let topic_arn = crate::get_env_var(ENV_SNS_TOPIC_ARN)?;
+        let credentials = Credentials::from_env()?;
+        Ok(Box::new(Self::new(&topic_arn, credentials)?))
+    }
+}
+
+impl NotifierTrait for Sns {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        SNS_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let message = result.to_json()?;
+        // used by email subscriptions, limited to 100 characters
+        let subject: String = format!("Server availability for {}", result.provider_name)
+            .chars()
+            .take(100)
+            .collect();
+
+        self.send_action(
+            "notifying",
+            &[
+                ("Action", "Publish"),
+                ("Message", &message),
+                ("Subject", &subject),
+                ("TopicArn", &self.topic_arn),
+                ("Version", "2010-03-31"),
+            ],
+        )
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+
+    /// Verifies credentials and topic by reading its attributes, which publishes nothing.
+    fn verify(&self) -> Result<bool, LibError> {
+        self.send_action(
+            "verifying",
+            &[
+                ("Action", "GetTopicAttributes"),
+                ("TopicArn", &self.topic_arn),
+                ("Version", "2010-03-31"),
+            ],
+        )?;
+        Ok(true)
+    }
+}
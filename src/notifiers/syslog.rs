@@ -0,0 +1,317 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::{CheckResult, LibError};
+use chrono::{SecondsFormat, Utc};
+use std::io::Write;
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+// SYSLOG implementation (RFC5424)
+
+/// Common name to identify the notifier
+pub const SYSLOG_NAME: &str = "syslog";
+
+/// Environment variable to optionally select the destination,
+/// like `udp://host:514` or `tcp://host:601`, the local socket being used otherwise.
+const ENV_SYSLOG_ADDRESS: &str = "SYSLOG_ADDRESS";
+
+/// Environment variable to optionally select the facility.
+const ENV_SYSLOG_FACILITY: &str = "SYSLOG_FACILITY";
+
+/// Environment variable to optionally select the severity.
+const ENV_SYSLOG_SEVERITY: &str = "SYSLOG_SEVERITY";
+
+/// Environment variable to optionally override the application name.
+const ENV_SYSLOG_APP_NAME: &str = "SYSLOG_APP_NAME";
+
+/// Local socket of the syslog daemon.
+const SYSLOG_LOCAL_SOCKET: &str = "/dev/log";
+
+/// Facility used when none is configured.
+const SYSLOG_DEFAULT_FACILITY: &str = "daemon";
+
+/// Severity used when none is configured.
+const SYSLOG_DEFAULT_SEVERITY: &str = "notice";
+
+/// Application name used when none is configured.
+const SYSLOG_DEFAULT_APP_NAME: &str = "dedicated-server-availability-watcher";
+
+/// Identifier of the structured data element, using the enterprise number reserved for documentation.
+const SYSLOG_SD_ID: &str = "dsaw@32473";
+
+/// Timeout for remote connections and writes.
+const SYSLOG_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Facility names, in the order of their code.
+const SYSLOG_FACILITIES: &[&str] = &[
+    "kern", "user", "mail", "daemon", "auth", "syslog", "lpr", "news", "uucp", "cron", "authpriv",
+    "ftp", "ntp", "audit", "alert", "clock", "local0", "local1", "local2", "local3", "local4",
+    "local5", "local6", "local7",
+];
+
+/// Severity names, in the order of their code.
+const SYSLOG_SEVERITIES: &[&str] = &[
+    "emerg", "alert", "crit", "err", "warning", "notice", "info", "debug",
+];
+
+/// Where the messages are sent.
+#[derive(Debug, PartialEq)]
+pub enum SyslogDestination {
+    /// Unix datagram socket of the local daemon
+    Local(String),
+    /// Remote daemon, one message per datagram
+    Udp(String),
+    /// Remote daemon, with octet-counting framing (RFC6587)
+    Tcp(String),
+}
+
+impl std::str::FromStr for SyslogDestination {
+    type Err = LibError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if let Some(address) = value.strip_prefix("udp://") {
+            return Ok(SyslogDestination::Udp(address.to_string()));
+        }
+        if let Some(address) = value.strip_prefix("tcp://") {
+            return Ok(SyslogDestination::Tcp(address.to_string()));
+        }
+        if value.starts_with('/') {
+            return Ok(SyslogDestination::Local(value.to_string()));
+        }
+        Err(LibError::ValueError {
+            name: ENV_SYSLOG_ADDRESS.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// Finds the code of a facility or severity, by name or by number.
+fn parse_code(name: &str, table: &[&str], value: &str) -> Result<u8, LibError> {
+    let value = value.to_lowercase();
+    table
+        .iter()
+        .position(|item| *item == value)
+        .or_else(|| {
+            value
+                .parse::<usize>()
+                .ok()
+                .filter(|code| *code < table.len())
+        })
+        .map(|code| code as u8)
+        .ok_or_else(|| LibError::ValueError {
+            name: name.to_string(),
+            value,
+        })
+}
+
+/// Gets the host name, or the RFC5424 nil value if it cannot be found.
+fn get_hostname() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .or_else(|_| std::fs::read_to_string("/proc/sys/kernel/hostname"))
+        .map(|name| name.trim().to_string())
+        .ok()
+        .filter(|name| !name.is_empty())
+        .unwrap_or("-".to_string())
+}
+
+/// Escapes the characters which are special in structured data parameter values.
+fn escape_param_value(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+    output
+}
+
+/// Sends an RFC5424 message, with the provider, the server count and the servers
+/// as structured data, so that log pipelines do not need to parse the free text.
+pub struct Syslog {
+    destination: SyslogDestination,
+    facility: u8,
+    severity: u8,
+    app_name: String,
+    hostname: String,
+}
+
+/// Builds a Syslog notifier without relying on environment variables.
+pub struct SyslogBuilder {
+    destination: SyslogDestination,
+    facility: String,
+    severity: String,
+    app_name: String,
+}
+
+impl Default for SyslogBuilder {
+    fn default() -> Self {
+        Self {
+            destination: SyslogDestination::Local(SYSLOG_LOCAL_SOCKET.to_string()),
+            facility: SYSLOG_DEFAULT_FACILITY.to_string(),
+            severity: SYSLOG_DEFAULT_SEVERITY.to_string(),
+            app_name: SYSLOG_DEFAULT_APP_NAME.to_string(),
+        }
+    }
+}
+
+impl SyslogBuilder {
+    /// Sets where the messages are sent (defaults to the local daemon).
+    pub fn destination(mut self, destination: SyslogDestination) -> Self {
+        self.destination = destination;
+        self
+    }
+
+    /// Sets the facility, by name like `local0` or by number (defaults to `daemon`).
+    pub fn facility(mut self, facility: &str) -> Self {
+        self.facility = facility.to_string();
+        self
+    }
+
+    /// Sets the severity, by name like `warning` or by number (defaults to `notice`).
+    pub fn severity(mut self, severity: &str) -> Self {
+        self.severity = severity.to_string();
+        self
+    }
+
+    /// Sets the application name.
+    pub fn app_name(mut self, app_name: &str) -> Self {
+        self.app_name = app_name.to_string();
+        self
+    }
+
+    /// Builds the notifier, attempting to sanitize inputs
+    pub fn build(self) -> Result<Syslog, LibError> {
+        let facility = parse_code(ENV_SYSLOG_FACILITY, SYSLOG_FACILITIES, &self.facility)?;
+        let severity = parse_code(ENV_SYSLOG_SEVERITY, SYSLOG_SEVERITIES, &self.severity)?;
+
+        // RFC5424 restricts the header fields to printable ascii, without spaces
+        if self.app_name.is_empty()
+            || self.app_name.len() > 48
+            || !self.app_name.chars().all(|c| c.is_ascii_graphic())
+        {
+            return Err(LibError::ValueError {
+                name: ENV_SYSLOG_APP_NAME.to_string(),
+                value: self.app_name,
+            });
+        }
+
+        Ok(Syslog {
+            destination: self.destination,
+            facility,
+            severity,
+            app_name: self.app_name,
+            hostname: get_hostname(),
+        })
+    }
+}
+
+impl Syslog {
+    /// Starts building a new instance.
+    pub fn builder() -> SyslogBuilder {
+        SyslogBuilder::default()
+    }
+
+    /// Builds the RFC5424 message
+    fn build_message(&self, result: &CheckResult) -> String {
+        let priority = self.facility * 8 + self.severity;
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let provider_name = &result.provider_name;
+        let text = match result.available_servers.is_empty() {
+            true => format!("No server available at {provider_name}"),
+            false => format!(
+                "Servers available at {provider_name}: {}",
+                result.available_servers.join(", ")
+            ),
+        };
+        format!(
+            "<{priority}>1 {timestamp} {} {} {} availability [{SYSLOG_SD_ID} provider=\"{}\" count=\"{}\" servers=\"{}\"] {text}",
+            self.hostname,
+            self.app_name,
+            std::process::id(),
+            escape_param_value(provider_name),
+            result.available_servers.len(),
+            escape_param_value(&result.available_servers.join(",")),
+        )
+    }
+
+    /// Sends the message to the configured destination
+    fn send(&self, message: &str) -> Result<(), LibError> {
+        match &self.destination {
+            SyslogDestination::Local(path) => Self::send_local(path, message),
+            SyslogDestination::Udp(address) => {
+                let socket = UdpSocket::bind("0.0.0.0:0")?;
+                socket.send_to(message.as_bytes(), address)?;
+                Ok(())
+            }
+            SyslogDestination::Tcp(address) => {
+                let address =
+                    address
+                        .to_socket_addrs()?
+                        .next()
+                        .ok_or_else(|| LibError::ValueError {
+                            name: ENV_SYSLOG_ADDRESS.to_string(),
+                            value: address.to_string(),
+                        })?;
+                let mut stream = TcpStream::connect_timeout(&address, SYSLOG_TIMEOUT)?;
+                stream.set_write_timeout(Some(SYSLOG_TIMEOUT))?;
+                stream.write_all(format!("{} {message}", message.len()).as_bytes())?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Sends the message to the local daemon
+    #[cfg(unix)]
+    fn send_local(path: &str, message: &str) -> Result<(), LibError> {
+        let socket = std::os::unix::net::UnixDatagram::unbound()?;
+        socket.send_to(message.as_bytes(), path)?;
+        Ok(())
+    }
+
+    /// Local sockets are not available on this platform
+    #[cfg(not(unix))]
+    fn send_local(path: &str, _message: &str) -> Result<(), LibError> {
+        Err(LibError::ValueError {
+            name: ENV_SYSLOG_ADDRESS.to_string(),
+            value: path.to_string(),
+        })
+    }
+}
+
+impl NotifierFactoryTrait for Syslog {
+    /// Builds a Syslog notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let mut builder = Self::builder();
+        if let Some(address) = crate::get_env_var_option(ENV_SYSLOG_ADDRESS) {
+            builder = builder.destination(address.parse()?);
+        }
+        if let Some(facility) = crate::get_env_var_option(ENV_SYSLOG_FACILITY) {
+            builder = builder.facility(&facility);
+        }
+        if let Some(severity) = crate::get_env_var_option(ENV_SYSLOG_SEVERITY) {
+            builder = builder.severity(&severity);
+        }
+        if let Some(app_name) = crate::get_env_var_option(ENV_SYSLOG_APP_NAME) {
+            builder = builder.app_name(&app_name);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl NotifierTrait for Syslog {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        SYSLOG_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        self.send(&self.build_message(result))
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+}
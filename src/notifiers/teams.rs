@@ -0,0 +1,164 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::{CheckResult, LibError};
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+
+// MICROSOFT TEAMS implementation (incoming webhook)
+
+/// Common name to identify the notifier
+pub const TEAMS_NAME: &str = "teams";
+
+/// Common environment variable to input the incoming webhook URL.
+const ENV_TEAMS_WEBHOOK_URL: &str = "TEAMS_WEBHOOK_URL";
+
+/// Environment variable to optionally override the link of the order button.
+const ENV_TEAMS_ORDER_URL: &str = "TEAMS_ORDER_URL";
+
+/// Gets the public page where the servers of a provider can be ordered.
+fn get_provider_order_url(provider_name: &str) -> Option<&'static str> {
+    match provider_name {
+        "cherryservers" => Some("https://www.cherryservers.com/dedicated-servers"),
+        "hetzner-auction" => Some("https://www.hetzner.com/sb"),
+        "online" => Some("https://www.scaleway.com/en/dedibox/"),
+        "ovh" => Some("https://www.ovhcloud.com/en/bare-metal/"),
+        "scaleway" => Some("https://console.scaleway.com/elastic-metal/servers"),
+        _ => None,
+    }
+}
+
+/// Posts a MessageCard with the list of available servers,
+/// and a button linking to the order page of the provider.
+pub struct Teams {
+    client: Client,
+    url: String,
+    order_url: Option<String>,
+}
+
+/// Builds a Teams notifier without relying on environment variables.
+#[derive(Default)]
+pub struct TeamsBuilder {
+    url: String,
+    order_url: Option<String>,
+}
+
+impl TeamsBuilder {
+    /// Sets the incoming webhook URL.
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    /// Overrides the link of the order button, which is otherwise chosen from the provider.
+    pub fn order_url(mut self, order_url: &str) -> Self {
+        self.order_url = Some(order_url.to_string());
+        self
+    }
+
+    /// Builds the notifier, attempting to sanitize inputs
+    pub fn build(self) -> Result<Teams, LibError> {
+        if !self.url.starts_with("https://") {
+            return Err(LibError::ValueError {
+                name: "teams webhook url".into(),
+                value: self.url,
+            });
+        }
+
+        Ok(Teams {
+            client: crate::client::build_client()?,
+            url: self.url,
+            order_url: self.order_url,
+        })
+    }
+}
+
+impl Teams {
+    /// Starts building a new instance.
+    pub fn builder() -> TeamsBuilder {
+        TeamsBuilder::default()
+    }
+
+    /// Builds the MessageCard payload
+    fn build_card(&self, result: &CheckResult) -> Value {
+        let provider_name = &result.provider_name;
+
+        let text = if result.available_servers.is_empty() {
+            "No server available for the selected types !".to_string()
+        } else {
+            result
+                .available_servers
+                .iter()
+                .map(|server| format!("- {}", result.to_markdown_link(server, server)))
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+
+        let mut card = json!({
+            "@type": "MessageCard",
+            "@context": "https://schema.org/extensions",
+            "summary": format!("Server availability notification for {provider_name}"),
+            "themeColor": if result.available_servers.is_empty() { "D70000" } else { "2DC72D" },
+            "title": format!("Available server types for {provider_name}"),
+            "text": text,
+        });
+
+        let order_url = self
+            .order_url
+            .as_deref()
+            .or_else(|| get_provider_order_url(provider_name));
+        if let Some(order_url) = order_url {
+            card["potentialAction"] = json!([{
+                "@type": "OpenUri",
+                "name": format!("Order at {provider_name}"),
+                "targets": [{ "os": "default", "uri": order_url }],
+            }]);
+        }
+
+        card
+    }
+}
+
+impl NotifierFactoryTrait for Teams {
+    /// Builds a Teams notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let url = crate::get_env_var(ENV_TEAMS_WEBHOOK_URL)?;
+        let mut builder = Self::builder().url(&url);
+        if let Some(order_url) = crate::get_env_var_option(ENV_TEAMS_ORDER_URL) {
+            builder = builder.order_url(&order_url);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl NotifierTrait for Teams {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        TEAMS_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&self.build_card(result))
+            .send()?;
+
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        Err(LibError::ApiStatusError {
+            status: response.status(),
+            message: format!(
+                "Error {} while notifying {TEAMS_NAME}: {}",
+                response.status().as_str(),
+                response.text().unwrap_or_else(|error| error.to_string())
+            ),
+        })
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+}
@@ -0,0 +1,258 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::{CheckResult, LibError};
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+// TWILIO SMS implementation
+
+/// Common name to identify the notifier
+pub const TWILIO_SMS_NAME: &str = "twilio-sms";
+
+/// Common environment variables to input your Twilio credentials.
+const ENV_TWILIO_ACCOUNT_SID: &str = "TWILIO_ACCOUNT_SID";
+const ENV_TWILIO_AUTH_TOKEN: &str = "TWILIO_AUTH_TOKEN";
+
+/// Common environment variables to select the sender and the recipient.
+const ENV_TWILIO_FROM: &str = "TWILIO_FROM";
+const ENV_TWILIO_TO: &str = "TWILIO_TO";
+
+/// Environment variable to optionally change the maximum length of a message.
+const ENV_TWILIO_MAX_LENGTH: &str = "TWILIO_MAX_LENGTH";
+
+/// A single GSM-7 encoded SMS segment
+const TWILIO_DEFAULT_MAX_LENGTH: usize = 160;
+
+/// Used for API result deserialisation.
+#[derive(Deserialize)]
+struct TwilioApiError {
+    message: String,
+}
+
+/// Sends a short summary of the result as a SMS through the Twilio Messages API.
+pub struct TwilioSms {
+    client: Client,
+    account_sid: String,
+    auth_token: String,
+    from: String,
+    to: String,
+    max_length: usize,
+}
+
+/// Builds a TwilioSms notifier without relying on environment variables.
+pub struct TwilioSmsBuilder {
+    account_sid: String,
+    auth_token: String,
+    from: String,
+    to: String,
+    max_length: usize,
+}
+
+impl Default for TwilioSmsBuilder {
+    fn default() -> Self {
+        Self {
+            account_sid: String::new(),
+            auth_token: String::new(),
+            from: String::new(),
+            to: String::new(),
+            max_length: TWILIO_DEFAULT_MAX_LENGTH,
+        }
+    }
+}
+
+impl TwilioSmsBuilder {
+    /// Sets the account identifier, `AC` followed by 32 hexadecimal digits.
+    pub fn account_sid(mut self, account_sid: &str) -> Self {
+        self.account_sid = account_sid.to_string();
+        self
+    }
+
+    /// Sets the authentication token.
+    pub fn auth_token(mut self, auth_token: &str) -> Self {
+        self.auth_token = auth_token.to_string();
+        self
+    }
+
+    /// Sets the sender phone number.
+    pub fn from(mut self, from: &str) -> Self {
+        self.from = from.to_string();
+        self
+    }
+
+    /// Sets the recipient phone number.
+    pub fn to(mut self, to: &str) -> Self {
+        self.to = to.to_string();
+        self
+    }
+
+    /// Sets the maximum length of a message, at least 40 (defaults to a single SMS segment).
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Builds the notifier, attempting to sanitize inputs
+    pub fn build(self) -> Result<TwilioSms, LibError> {
+        // account identifiers are 'AC' followed by 32 hexadecimal digits
+        if !self.account_sid.starts_with("AC") || self.account_sid.len() != 34 {
+            return Err(LibError::ValueError {
+                name: "malformed twilio account sid".into(),
+                value: self.account_sid,
+            });
+        }
+
+        for (name, value) in [
+            ("auth token", &self.auth_token),
+            ("from", &self.from),
+            ("to", &self.to),
+        ] {
+            if value.is_empty() {
+                return Err(LibError::ValueError {
+                    name: format!("found empty twilio {name}"),
+                    value: value.into(),
+                });
+            }
+        }
+
+        // we need some room to write anything meaningful
+        if self.max_length < 40 {
+            return Err(LibError::ValueError {
+                name: "twilio max length (at least 40)".into(),
+                value: self.max_length.to_string(),
+            });
+        }
+
+        Ok(TwilioSms {
+            client: crate::client::build_client()?,
+            account_sid: self.account_sid,
+            auth_token: self.auth_token,
+            from: self.from,
+            to: self.to,
+            max_length: self.max_length,
+        })
+    }
+}
+
+impl TwilioSms {
+    /// Starts building a new instance.
+    pub fn builder() -> TwilioSmsBuilder {
+        TwilioSmsBuilder::default()
+    }
+
+    /// Builds a summary of the result which fits in the maximum length,
+    /// listing as many servers as possible and counting the others.
+    fn build_body(&self, result: &CheckResult) -> String {
+        let provider_name = &result.provider_name;
+        let servers = &result.available_servers;
+
+        if servers.is_empty() {
+            return format!("{provider_name}: no server available");
+        }
+
+        let mut body = format!("{provider_name}: {} available:", servers.len());
+        for (index, server) in servers.iter().enumerate() {
+            let remaining = servers.len() - index - 1;
+            let separator = if index == 0 { " " } else { ", " };
+            let suffix = if remaining > 0 {
+                format!(" +{remaining} more")
+            } else {
+                String::new()
+            };
+
+            // keep room for the count of the servers which would not fit
+            let candidate = format!("{body}{separator}{server}");
+            if candidate.chars().count() + suffix.chars().count() > self.max_length {
+                body.push_str(&format!(" +{} more", remaining + 1));
+                break;
+            }
+            body = candidate;
+        }
+
+        // a single very long server name could still overflow
+        body.chars().take(self.max_length).collect()
+    }
+}
+
+impl NotifierFactoryTrait for TwilioSms {
+    /// Builds a TwilioSms notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let account_sid = crate::get_env_var(ENV_TWILIO_ACCOUNT_SID)?;
+        let auth_token = crate::get_env_var(ENV_TWILIO_AUTH_TOKEN)?;
+        let from = crate::get_env_var(ENV_TWILIO_FROM)?;
+        let to = crate::get_env_var(ENV_TWILIO_TO)?;
+        let mut builder = Self::builder()
+            .account_sid(&account_sid)
+            .auth_token(&auth_token)
+            .from(&from)
+            .to(&to);
+        if let Some(max_length) = crate::get_env_var_parsed(ENV_TWILIO_MAX_LENGTH)? {
+            builder = builder.max_length(max_length);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl NotifierTrait for TwilioSms {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        TWILIO_SMS_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let url = format!(
+            "https://api.twilio.com/2010-04-01/Accounts/{}/Messages.json",
+            self.account_sid
+        );
+        let body = self.build_body(result);
+        let response = self
+            .client
+            .post(url)
+            .basic_auth(&self.account_sid, Some(&self.auth_token))
+            .form(&[("From", &self.from), ("To", &self.to), ("Body", &body)])
+            .send()?;
+
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        let status = response.status();
+        let message = response
+            .json::<TwilioApiError>()
+            .map_or_else(|error| error.to_string(), |error| error.message);
+        Err(LibError::ApiStatusError {
+            status,
+            message: format!("Error {status} while notifying {TWILIO_SMS_NAME}: {message}"),
+        })
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+
+    /// Verifies the credentials by fetching the account, which sends nothing.
+    fn verify(&self) -> Result<bool, LibError> {
+        let url = format!(
+            "https://api.twilio.com/2010-04-01/Accounts/{}.json",
+            self.account_sid
+        );
+        let response = self
+            .client
+            .get(url)
+            .basic_auth(&self.account_sid, Some(&self.auth_token))
+            .send()?;
+
+        if response.status().is_success() {
+            return Ok(true);
+        }
+
+        let status = response.status();
+        let message = response
+            .json::<TwilioApiError>()
+            .map_or_else(|error| error.to_string(), |error| error.message);
+        Err(LibError::ApiStatusError {
+            status,
+            message: format!("Error {status} while verifying {TWILIO_SMS_NAME}: {message}"),
+        })
+    }
+}
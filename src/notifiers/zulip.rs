@@ -0,0 +1,218 @@
+use super::{NotifierFactoryTrait, NotifierTrait};
+use crate::{CheckResult, LibError};
+use reqwest::blocking::{Client, Response};
+use serde::Deserialize;
+
+// ZULIP implementation (REST API, stream messages)
+
+/// Common name to identify the notifier
+pub const ZULIP_NAME: &str = "zulip";
+
+/// Common environment variable to input the organization URL.
+const ENV_ZULIP_SITE: &str = "ZULIP_SITE";
+
+/// Common environment variable to input the bot email.
+const ENV_ZULIP_BOT_EMAIL: &str = "ZULIP_BOT_EMAIL";
+
+/// Common environment variable to input the bot API key.
+const ENV_ZULIP_API_KEY: &str = "ZULIP_API_KEY";
+
+/// Common environment variable to input the destination stream.
+const ENV_ZULIP_STREAM: &str = "ZULIP_STREAM";
+
+/// Used for API error deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct ZulipApiError {
+    msg: String,
+}
+
+/// Sends a message to a stream, using the provider name as topic.
+pub struct Zulip {
+    client: Client,
+    site: String,
+    bot_email: String,
+    api_key: String,
+    stream: String,
+}
+
+/// Builds a Zulip notifier without relying on environment variables.
+#[derive(Default)]
+pub struct ZulipBuilder {
+    site: String,
+    bot_email: String,
+    api_key: String,
+    stream: String,
+}
+
+impl ZulipBuilder {
+    /// Sets the organization URL, like `https://example.zulipchat.com`.
+    pub fn site(mut self, site: &str) -> Self {
+        self.site = site.to_string();
+        self
+    }
+
+    /// Sets the email of the bot.
+    pub fn bot_email(mut self, bot_email: &str) -> Self {
+        self.bot_email = bot_email.to_string();
+        self
+    }
+
+    /// Sets the API key of the bot.
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = api_key.to_string();
+        self
+    }
+
+    /// Sets the stream the messages are sent to.
+    pub fn stream(mut self, stream: &str) -> Self {
+        self.stream = stream.to_string();
+        self
+    }
+
+    /// Builds the notifier, attempting to sanitize inputs
+    pub fn build(self) -> Result<Zulip, LibError> {
+        // self-hosted instances may not use https
+        if !self.site.starts_with("https://") && !self.site.starts_with("http://") {
+            return Err(LibError::ValueError {
+                name: "zulip site url".into(),
+                value: self.site,
+            });
+        }
+
+        if !self.bot_email.contains('@') {
+            return Err(LibError::ValueError {
+                name: "zulip bot email".into(),
+                value: self.bot_email,
+            });
+        }
+
+        if self.api_key.is_empty() {
+            return Err(LibError::ValueError {
+                name: "found empty zulip api key".into(),
+                value: self.api_key,
+            });
+        }
+
+        if self.stream.is_empty() {
+            return Err(LibError::ValueError {
+                name: "found empty zulip stream".into(),
+                value: self.stream,
+            });
+        }
+
+        Ok(Zulip {
+            client: crate::client::build_client()?,
+            site: self.site.trim_end_matches('/').to_string(),
+            bot_email: self.bot_email,
+            api_key: self.api_key,
+            stream: self.stream,
+        })
+    }
+}
+
+impl Zulip {
+    /// Starts building a new instance.
+    pub fn builder() -> ZulipBuilder {
+        ZulipBuilder::default()
+    }
+
+    /// Builds the Markdown content of the message
+    fn build_content(result: &CheckResult) -> String {
+        if result.available_servers.is_empty() {
+            return "No server available for the selected types !".to_string();
+        }
+
+        let servers = result
+            .available_servers
+            .iter()
+            .map(|server| {
+                format!(
+                    "* {}",
+                    result.to_markdown_link(server, &format!("`{server}`"))
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        format!("**Servers available** :\n{servers}")
+    }
+
+    /// Converts an unsuccessful response to an error
+    fn build_error(response: Response, action: &str) -> LibError {
+        let status = response.status();
+        let message = response
+            .json::<ZulipApiError>()
+            .map_or_else(|error| error.to_string(), |error| error.msg);
+        LibError::ApiStatusError {
+            status,
+            message: format!("Error {status} while {action} {ZULIP_NAME}: {message}"),
+        }
+    }
+}
+
+impl NotifierFactoryTrait for Zulip {
+    /// Builds a Zulip notifier from environment variables.
+    fn from_env() -> Result<Box<dyn NotifierTrait>, LibError> {
+        let site = crate::get_env_var(ENV_ZULIP_SITE)?;
+        let bot_email = crate::get_env_var(ENV_ZULIP_BOT_EMAIL)?;
+        let api_key = crate::get_env_var(ENV_ZULIP_API_KEY)?;
+        let stream = crate::get_env_var(ENV_ZULIP_STREAM)?;
+        Ok(Box::new(
+            Self::builder()
+                .site(&site)
+                .bot_email(&bot_email)
+                .api_key(&api_key)
+                .stream(&stream)
+                .build()?,
+        ))
+    }
+}
+
+impl NotifierTrait for Zulip {
+    /// Gets the actual name of the notifier.
+    fn name(&self) -> &'static str {
+        ZULIP_NAME
+    }
+
+    /// Sends an notification using the provided data.
+    fn notify(&self, result: &CheckResult) -> Result<(), LibError> {
+        let content = Self::build_content(result);
+        let response = self
+            .client
+            .post(format!("{}/api/v1/messages", self.site))
+            .basic_auth(&self.bot_email, Some(&self.api_key))
+            .form(&[
+                ("type", "stream"),
+                ("to", &self.stream),
+                ("topic", &result.provider_name),
+                ("content", &content),
+            ])
+            .send()?;
+
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        Err(Self::build_error(response, "notifying"))
+    }
+
+    /// Tests by sending a notification with dummy values.
+    fn test(&self) -> Result<(), LibError> {
+        self.notify(&CheckResult::get_dummy())
+    }
+
+    /// Verifies the bot credentials by fetching its own profile.
+    fn verify(&self) -> Result<bool, LibError> {
+        let response = self
+            .client
+            .get(format!("{}/api/v1/users/me", self.site))
+            .basic_auth(&self.bot_email, Some(&self.api_key))
+            .send()?;
+
+        if response.status().is_success() {
+            return Ok(true);
+        }
+
+        Err(Self::build_error(response, "verifying"))
+    }
+}
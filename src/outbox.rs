@@ -0,0 +1,65 @@
+use crate::digest::DigestBuffer;
+use crate::CheckResult;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// Retries of the notifications which failed
+
+/// Delay before the first retry, doubled after every failed one.
+const RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Longest delay between two retries.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(3600);
+
+/// Changes which could not be notified through a notifier, kept in the storage directory
+/// to be retried by later checks, so that they are not lost when the notifier is down.
+/// Changes failing in a row are merged like a digest, the latest availability of a server
+/// replacing any previous one, and are delivered together in order.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Outbox {
+    pending: DigestBuffer,
+    /// Failed deliveries so far
+    attempts: u32,
+    /// Timestamp before which no retry is attempted
+    next_attempt: u64,
+}
+
+impl Outbox {
+    /// Tells if nothing is waiting to be notified
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Adds changes to the pending ones, to be delivered with them.
+    pub fn add(&mut self, result: &CheckResult, now: u64) {
+        self.pending.add(result, now);
+    }
+
+    /// Counts a failed delivery, and delays the next one, longer after every failure.
+    pub fn failed(&mut self, now: u64) {
+        self.attempts = self.attempts.saturating_add(1);
+        let factor = 2u32.saturating_pow(self.attempts - 1);
+        let delay = RETRY_DELAY.saturating_mul(factor).min(MAX_RETRY_DELAY);
+        self.next_attempt = now + delay.as_secs();
+    }
+
+    /// Gets the failed deliveries so far
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Tells if the pending changes are due for a retry
+    pub fn is_due(&self, now: u64) -> bool {
+        !self.is_empty() && now >= self.next_attempt
+    }
+
+    /// Tells if the pending changes failed for longer than `max_age`, so that they are given up.
+    pub fn is_expired(&self, max_age: Duration, now: u64) -> bool {
+        !self.is_empty() && now.saturating_sub(self.pending.started()) >= max_age.as_secs()
+    }
+
+    /// Builds the result to deliver, as for a digest.
+    pub fn to_result(&self) -> CheckResult {
+        self.pending.to_result()
+    }
+}
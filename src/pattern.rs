@@ -0,0 +1,175 @@
+use crate::LibError;
+use regex::Regex;
+
+// Server selection patterns
+
+/// A server selection, either an exact reference, a glob like `1801sk*`,
+/// or a regular expression between slashes like `/^EM-A/`.
+///
+/// Globs support `*`, `?` and bracket expressions, and must match the whole reference.
+/// Regular expressions use the syntax of the `regex` crate,
+/// and may match anywhere in the reference unless anchored.
+#[derive(Debug)]
+pub struct ServerPattern {
+    source: String,
+    /// The compiled selection, unless it is an exact reference
+    regex: Option<Regex>,
+}
+
+impl ServerPattern {
+    /// Parses a server selection, telling regular expressions and globs from exact references
+    pub fn parse(source: &str) -> Result<Self, LibError> {
+        let expression = if source.len() >= 2 && source.starts_with('/') && source.ends_with('/') {
+            source[1..source.len() - 1].to_string()
+        } else if source.contains(['*', '?', '[']) {
+            Self::glob_to_regex(source)?
+        } else {
+            return Ok(Self {
+                source: source.to_string(),
+                regex: None,
+            });
+        };
+
+        let regex = Regex::new(&expression)
+            .map_err(|_| Self::error(source, "invalid regular expression"))?;
+        Ok(Self {
+            source: source.to_string(),
+            regex: Some(regex),
+        })
+    }
+
+    /// Tells if the selection has to be expanded against the inventory
+    pub fn is_pattern(&self) -> bool {
+        self.regex.is_some()
+    }
+
+    /// Gets the selection as it was written
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Builds the error reported for malformed patterns
+    fn error(source: &str, reason: &str) -> LibError {
+        LibError::ValueError {
+            name: format!("server pattern ({reason})"),
+            value: source.to_string(),
+        }
+    }
+
+    /// Translates a bracket expression of a glob, the opening bracket being already consumed
+    fn glob_class_to_regex(
+        source: &str,
+        chars: &mut std::iter::Peekable<std::str::Chars>,
+    ) -> Result<String, LibError> {
+        let mut class = String::from("[");
+        if chars.next_if_eq(&'!').is_some() {
+            class.push('^');
+        }
+        let mut first = true;
+        loop {
+            let c = match chars.next() {
+                None => return Err(Self::error(source, "unclosed bracket")),
+                Some(']') if !first => break,
+                Some('\\') => chars
+                    .next()
+                    .ok_or_else(|| Self::error(source, "trailing backslash"))?,
+                Some(c) => c,
+            };
+            first = false;
+            class.push_str(&regex::escape(&c.to_string()));
+
+            // a range, unless the dash closes the expression
+            let mut lookahead = chars.clone();
+            if lookahead.next() == Some('-') && lookahead.peek().is_some_and(|c| *c != ']') {
+                chars.next();
+                let high = chars.next().unwrap_or(c);
+                if high < c {
+                    return Err(Self::error(source, "reversed range"));
+                }
+                class.push('-');
+                class.push_str(&regex::escape(&high.to_string()));
+            }
+        }
+        class.push(']');
+        Ok(class)
+    }
+
+    /// Translates a glob to a regular expression matching the whole reference
+    fn glob_to_regex(source: &str) -> Result<String, LibError> {
+        let mut expression = String::from("^");
+        let mut chars = source.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' => expression.push_str(".*"),
+                '?' => expression.push('.'),
+                '[' => expression.push_str(&Self::glob_class_to_regex(source, &mut chars)?),
+                c => expression.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        expression.push('$');
+        Ok(expression)
+    }
+
+    /// Tells if the reference is selected
+    pub fn matches(&self, reference: &str) -> bool {
+        match &self.regex {
+            Some(regex) => regex.is_match(reference),
+            None => self.source == reference,
+        }
+    }
+}
+
+/// Most suggestions given for an unknown server reference.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Counts the single character insertions, deletions or substitutions turning a text into another.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Finds the known references closest to an unknown one, ignoring case, to suggest them instead.
+/// References starting with it, or which it starts with, come first,
+/// then the ones within a few typos of it, closest first.
+pub fn closest_matches<'a>(
+    server: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    let server = server.to_lowercase();
+    let chars: Vec<char> = server.chars().collect();
+    // a third of the reference may be mistyped, which is at least a typo or two
+    let max_distance = (chars.len() / 3).max(2);
+
+    let mut matches: Vec<(usize, &str)> = Vec::new();
+    for candidate in candidates {
+        if candidate.is_empty() || matches.iter().any(|(_, known)| *known == candidate) {
+            continue;
+        }
+        let lowercase = candidate.to_lowercase();
+        let score = if lowercase.starts_with(&server) || server.starts_with(&lowercase) {
+            0
+        } else {
+            let distance = edit_distance(&chars, &lowercase.chars().collect::<Vec<char>>());
+            if distance > max_distance {
+                continue;
+            }
+            distance
+        };
+        matches.push((score, candidate));
+    }
+
+    matches.sort_by_key(|(score, candidate)| (*score, *candidate));
+    matches
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
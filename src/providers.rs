@@ -1,3 +1,47 @@
+/// Provides the implementation for the "aggregate" provider
+#[cfg(feature = "aggregate")]
+pub mod aggregate;
+
+/// Provides the implementation for the "aws" provider
+#[cfg(feature = "aws")]
+pub mod aws;
+
+/// Provides the implementation for the "cherryservers" provider
+#[cfg(feature = "cherryservers")]
+pub mod cherryservers;
+
+/// Provides the implementation for the "command" provider
+#[cfg(feature = "command")]
+pub mod command;
+
+/// Provides the implementation for the "generic-html" provider
+#[cfg(feature = "scraper")]
+pub mod generic_html;
+
+/// Provides the implementation for the "hetzner-auction" provider
+#[cfg(feature = "hetzner-auction")]
+pub mod hetzner_auction;
+
+/// Provides the implementation for the "hivelocity" provider
+#[cfg(feature = "hivelocity")]
+pub mod hivelocity;
+
+/// Provides the implementation for the "ibmcloud" provider
+#[cfg(feature = "ibmcloud")]
+pub mod ibmcloud;
+
+/// Provides the implementation for the "leaseweb" provider
+#[cfg(feature = "leaseweb")]
+pub mod leaseweb;
+
+/// Provides the implementation for the "mock" provider, used for testing
+#[cfg(feature = "testing")]
+pub mod mock;
+
+/// Provides the implementation for the "oci" provider
+#[cfg(feature = "oci")]
+pub mod oci;
+
 /// Provides the implementation for the "online" provider
 #[cfg(feature = "online")]
 pub mod online;
@@ -6,30 +50,650 @@ pub mod online;
 #[cfg(feature = "ovh")]
 pub mod ovh;
 
+/// Provides the implementation for the "phoenixnap" provider
+#[cfg(feature = "phoenixnap")]
+pub mod phoenixnap;
+
 /// Provides the implementation for the "scaleway" provider
 #[cfg(feature = "scaleway")]
 pub mod scaleway;
 
+/// Provides the implementation for the "serverscom" provider
+#[cfg(feature = "serverscom")]
+pub mod serverscom;
+
+use crate::alert::{AlertPolicy, OperationalAlert};
+use crate::delivery::{self, DeliveryReport};
+use crate::digest::{DigestBuffer, DigestWindow};
+use crate::hook::AvailabilityHook;
+use crate::metrics::{self, CheckMetrics};
 use crate::notifiers;
 use crate::notifiers::NotifierTrait;
-use crate::storage::CheckResultStorage;
+use crate::outbox::Outbox;
+use crate::pattern::{self, ServerPattern};
+use crate::quiet::QuietHours;
+use crate::reload;
+use crate::schedule::Scheduler;
+use crate::storage::{CheckResultStorage, Debounce, StorageLock};
+use crate::trigger;
 use crate::CheckResult;
 use crate::LibError;
 use anyhow;
 use anyhow::Context;
 use colored::Colorize;
-use std::{env, path};
+use serde::{Deserialize, Serialize};
+use std::panic;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
 
 /// Defines the common information returned by `ProviderTrait::inventory()`.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct ServerInfo {
     pub reference: String,
     pub memory: String,
     pub storage: String,
     pub available: bool,
+    /// Monthly price in the currency of the provider, when it is known.
+    pub price: Option<f64>,
+    /// Processor model, when it is known.
+    #[serde(default)]
+    pub cpu: Option<String>,
+    /// Total count of processor cores, when it is known.
+    #[serde(default)]
+    pub cores: Option<u32>,
+    /// Network bandwidth in megabits per second, when it is known.
+    #[serde(default)]
+    pub bandwidth: Option<u64>,
+    /// Stock level of an available server, when the provider tells more than its availability.
+    #[serde(default)]
+    pub stock: Option<StockLevel>,
+    /// Public page where the server can be ordered, when the provider has one.
+    #[serde(default)]
+    pub order_url: Option<String>,
+    /// Product range, like `Start` or `Pro`, when the provider groups its servers in ranges.
+    #[serde(default)]
+    pub range: Option<String>,
+}
+
+impl ServerInfo {
+    /// Parses a human readable capacity, like `32GB`, `2x480GBSSD+2x4TBHDD`
+    /// or `ram-64g-ecc-2400`, into a total amount of gigabytes.
+    /// Only the first size of every `+` or `,` separated part is counted,
+    /// a size without any unit being deemed in gigabytes.
+    fn parse_capacity(text: &str) -> Option<f64> {
+        let mut total = None;
+        for part in text.to_lowercase().split(['+', ',']) {
+            let chars: Vec<char> = part.chars().collect();
+            let mut index = 0;
+            let mut count = 1.0;
+            while index < chars.len() {
+                if !chars[index].is_ascii_digit() {
+                    index += 1;
+                    continue;
+                }
+
+                let start = index;
+                while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.')
+                {
+                    index += 1;
+                }
+                let Ok(number) = chars[start..index]
+                    .iter()
+                    .collect::<String>()
+                    .parse::<f64>()
+                else {
+                    break;
+                };
+
+                // a multiplier, like the 2 of 2x480GB
+                if chars.get(index) == Some(&'x')
+                    && chars.get(index + 1).is_some_and(|c| c.is_ascii_digit())
+                {
+                    count = number;
+                    index += 1;
+                    continue;
+                }
+
+                let factor = match chars.get(index) {
+                    Some('t') => 1000.0,
+                    Some('m') => 0.001,
+                    _ => 1.0,
+                };
+                total = Some(total.unwrap_or(0.0) + count * number * factor);
+                break;
+            }
+        }
+        total
+    }
+
+    /// Gets the identifier to check the server with, which is the first word of the reference.
+    pub fn server_id(&self) -> &str {
+        self.reference.split_whitespace().next().unwrap_or_default()
+    }
+
+    /// Total memory in gigabytes, when it can be parsed.
+    pub fn memory_gb(&self) -> Option<f64> {
+        Self::parse_capacity(&self.memory)
+    }
+
+    /// Total storage in gigabytes, when it can be parsed.
+    pub fn storage_gb(&self) -> Option<f64> {
+        Self::parse_capacity(&self.storage)
+    }
+
+    /// Describes the processor and the bandwidth, like `Xeon E-2136 (6c) 1000Mbps`,
+    /// leaving out what is unknown.
+    pub fn describe_specs(&self) -> String {
+        let mut specs = Vec::new();
+        if let Some(cpu) = &self.cpu {
+            specs.push(cpu.clone());
+        }
+        if let Some(cores) = self.cores {
+            specs.push(format!("({cores}c)"));
+        }
+        if let Some(bandwidth) = self.bandwidth {
+            specs.push(format!("{bandwidth}Mbps"));
+        }
+        specs.join(" ")
+    }
+}
+
+/// Servers in stock up to this count are deemed low in stock.
+const LOW_STOCK_MAX: u32 = 3;
+
+/// Servers in stock from this count are deemed high in stock.
+const HIGH_STOCK_MIN: u32 = 20;
+
+/// Stock level of an available server, for the providers which tell more than its availability.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StockLevel {
+    Low,
+    Normal,
+    High,
+}
+
+impl StockLevel {
+    /// Classifies a count of servers in stock, none being unavailable rather than low.
+    pub fn from_count(count: u32) -> Option<Self> {
+        match count {
+            0 => None,
+            count if count <= LOW_STOCK_MAX => Some(StockLevel::Low),
+            count if count < HIGH_STOCK_MIN => Some(StockLevel::Normal),
+            _ => Some(StockLevel::High),
+        }
+    }
+}
+
+impl std::fmt::Display for StockLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StockLevel::Low => write!(f, "low"),
+            StockLevel::Normal => write!(f, "normal"),
+            StockLevel::High => write!(f, "high"),
+        }
+    }
+}
+
+/// Tells which price changes of an available server are notified.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PriceWatch {
+    /// Notifies when the price drops below this amount
+    pub below: Option<f64>,
+    /// Notifies whenever the price changes
+    pub on_change: bool,
+}
+
+impl PriceWatch {
+    /// Tells if prices have to be observed at all
+    pub fn is_enabled(&self) -> bool {
+        self.below.is_some() || self.on_change
+    }
+
+    /// Tells if the change from the previously observed price is worth notifying.
+    /// The first observed price is only notified when it is already below the threshold.
+    fn is_notable(&self, previous: Option<f64>, current: Option<f64>) -> bool {
+        let Some(current) = current else {
+            return false;
+        };
+        let crossed = self.below.is_some_and(|below| {
+            current < below && previous.is_none_or(|previous| previous >= below)
+        });
+        let changed = self.on_change && previous.is_some_and(|previous| previous != current);
+        crossed || changed
+    }
+}
+
+/// Tells which side of a check failed, attached as context to the errors of the runners
+/// so that callers can tell provider failures from notifier failures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckStage {
+    Provider,
+    Notifier,
+}
+
+impl std::fmt::Display for CheckStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckStage::Provider => write!(f, "provider failure"),
+            CheckStage::Notifier => write!(f, "notifier failure"),
+        }
+    }
 }
 
+impl CheckStage {
+    /// Finds the stage which failed in the error chain, if any
+    pub fn of(error: &anyhow::Error) -> Option<CheckStage> {
+        error.downcast_ref::<CheckStage>().copied()
+    }
+}
+
+/// Outcome of a single successful check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckOutcome {
+    /// Nothing changed since the previous check, so nothing was notified
+    Unchanged,
+    /// Something changed, but no server became available
+    Changed,
+    /// At least one server became available
+    NewAvailability,
+}
+
+impl CheckOutcome {
+    /// Builds the outcome of a notified result
+    fn of(result: &CheckResult) -> Self {
+        match result.available_servers.is_empty() {
+            true => CheckOutcome::Changed,
+            false => CheckOutcome::NewAvailability,
+        }
+    }
+}
+
+/// Selects the order of the inventory.
+#[derive(Clone, Copy, Debug)]
+pub enum InventorySort {
+    Reference,
+    Memory,
+    Storage,
+    Price,
+    Cores,
+    Bandwidth,
+}
+
+impl std::str::FromStr for InventorySort {
+    type Err = LibError;
+
+    /// Parses `reference`, `memory`, `storage`, `price`, `cores` or `bandwidth`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "reference" => Ok(InventorySort::Reference),
+            "memory" => Ok(InventorySort::Memory),
+            "storage" => Ok(InventorySort::Storage),
+            "price" => Ok(InventorySort::Price),
+            "cores" => Ok(InventorySort::Cores),
+            "bandwidth" => Ok(InventorySort::Bandwidth),
+            _ => Err(LibError::ValueError {
+                name: "inventory sort (reference, memory, storage, price, cores or bandwidth)"
+                    .into(),
+                value: value.into(),
+            }),
+        }
+    }
+}
+
+/// Selects what a provider does when a part of its queries fails,
+/// like a zone of a cloud, or a provider of an aggregate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// The whole query fails with the first failing part
+    #[default]
+    FailFast,
+    /// The remaining parts are still queried, the failures being reported
+    /// alongside the partial results. It fails only when every part failed.
+    BestEffort,
+}
+
+impl std::str::FromStr for FailurePolicy {
+    type Err = LibError;
+
+    /// Parses `fail-fast` or `best-effort`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "fail-fast" => Ok(FailurePolicy::FailFast),
+            "best-effort" => Ok(FailurePolicy::BestEffort),
+            _ => Err(LibError::ValueError {
+                name: "failure policy (fail-fast or best-effort)".into(),
+                value: value.into(),
+            }),
+        }
+    }
+}
+
+/// Collects the failures of the parts of a query, following a failure policy.
+#[cfg(any(feature = "aggregate", feature = "ovh", feature = "scaleway"))]
+pub(crate) struct PartialFailures<'a> {
+    provider_name: &'a str,
+    policy: FailurePolicy,
+    failures: Vec<LibError>,
+    successes: usize,
+}
+
+#[cfg(any(feature = "aggregate", feature = "ovh", feature = "scaleway"))]
+impl<'a> PartialFailures<'a> {
+    /// Builds an instance for the parts of a query of a provider
+    pub(crate) fn new(provider_name: &'a str, policy: FailurePolicy) -> Self {
+        Self {
+            provider_name,
+            policy,
+            failures: Vec::new(),
+            successes: 0,
+        }
+    }
+
+    /// Gets the result of a part, or None if it failed and the policy is best-effort,
+    /// in which case the failure is reported right away.
+    pub(crate) fn handle<T>(
+        &mut self,
+        part: &str,
+        result: Result<T, LibError>,
+    ) -> Result<Option<T>, LibError> {
+        match result {
+            Ok(value) => {
+                self.successes += 1;
+                Ok(Some(value))
+            }
+            Err(error) if self.policy == FailurePolicy::BestEffort => {
+                eprintln!(
+                    "{}",
+                    format!("Skipping {part} of {}: {error}", self.provider_name).yellow()
+                );
+                self.failures.push(error);
+                Ok(None)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Tells if a part failed, so that the results are partial
+    #[cfg(feature = "scaleway")]
+    pub(crate) fn is_partial(&self) -> bool {
+        !self.failures.is_empty()
+    }
+
+    /// Takes the first failure, for callers which cannot report partial results.
+    #[cfg(any(feature = "aggregate", feature = "scaleway"))]
+    pub(crate) fn take_failure(&mut self) -> Option<LibError> {
+        match self.failures.is_empty() {
+            true => None,
+            false => Some(self.failures.remove(0)),
+        }
+    }
+
+    /// Fails with the first failure when no part succeeded, as there is nothing to report then.
+    #[cfg(any(feature = "aggregate", feature = "scaleway"))]
+    pub(crate) fn finish(&mut self) -> Result<(), LibError> {
+        if self.successes == 0 {
+            if let Some(error) = self.take_failure() {
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Upper bound on the pages of a single listing, in case an API keeps giving pages.
+#[cfg(any(
+    feature = "aws",
+    feature = "leaseweb",
+    feature = "scaleway",
+    feature = "serverscom"
+))]
+const MAX_PAGES: u32 = 1000;
+
+/// How an API splits a listing across pages, with the names of its query parameters.
+#[cfg(any(
+    feature = "aws",
+    feature = "leaseweb",
+    feature = "scaleway",
+    feature = "serverscom"
+))]
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Pagination {
+    /// Pages are numbered from 1, each holding up to `size` items
+    Page {
+        page: &'static str,
+        per_page: &'static str,
+        size: u32,
+    },
+    /// Up to `size` items are listed, after skipping the ones already listed
+    Offset {
+        offset: &'static str,
+        limit: &'static str,
+        size: u32,
+    },
+    /// Each page gives an opaque cursor to the next one, if any (only used by `aws`)
+    #[cfg_attr(not(feature = "aws"), allow(dead_code))]
+    Cursor { cursor: &'static str },
+}
+
+#[cfg(any(
+    feature = "aws",
+    feature = "leaseweb",
+    feature = "scaleway",
+    feature = "serverscom"
+))]
+impl Pagination {
+    /// Collects every page of a listing. The query gets the parameters selecting the page,
+    /// and returns its items along with the cursor to the next page, if the API gives any.
+    /// Numbered pages stop at the first page which is not full, cursors at the last page.
+    pub(crate) fn fetch_all<T>(
+        &self,
+        mut query: impl FnMut(&[(&str, String)]) -> Result<(Vec<T>, Option<String>), LibError>,
+    ) -> Result<Vec<T>, LibError> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+        for index in 0..MAX_PAGES {
+            let parameters = match self {
+                Pagination::Page {
+                    page,
+                    per_page,
+                    size,
+                } => vec![
+                    (*page, (index + 1).to_string()),
+                    (*per_page, size.to_string()),
+                ],
+                Pagination::Offset {
+                    offset,
+                    limit,
+                    size,
+                } => vec![
+                    (*offset, items.len().to_string()),
+                    (*limit, size.to_string()),
+                ],
+                Pagination::Cursor { cursor: name } => match &cursor {
+                    Some(cursor) => vec![(*name, cursor.clone())],
+                    None => Vec::new(),
+                },
+            };
+
+            let (page, next) = query(&parameters)?;
+            let count = page.len();
+            items.extend(page);
+
+            let last = match self {
+                Pagination::Page { size, .. } | Pagination::Offset { size, .. } => {
+                    count < *size as usize
+                }
+                Pagination::Cursor { .. } => {
+                    cursor = next.filter(|next| !next.is_empty());
+                    cursor.is_none()
+                }
+            };
+            if last {
+                return Ok(items);
+            }
+        }
+        Err(LibError::ApiError {
+            message: format!("Listing goes on after {MAX_PAGES} pages"),
+        })
+    }
+}
+
+/// Process-wide number of servers checked at once, by the providers without a batch endpoint.
+static CONCURRENCY: AtomicUsize = AtomicUsize::new(1);
+
+/// Selects how many servers are checked at once by every check run afterwards,
+/// for the providers which query each server on its own. Zero is deemed one.
+pub fn set_concurrency(concurrency: usize) {
+    CONCURRENCY.store(concurrency.max(1), Ordering::Relaxed);
+}
+
+/// Checks every server with a pool of scoped threads, bounded by the selected concurrency,
+/// keeping the order of the servers. Fails with the failure of the first server in order.
+fn check_concurrently<T: Send>(
+    servers: &[String],
+    check: impl Fn(&str) -> Result<T, LibError> + Sync,
+) -> Result<Vec<T>, LibError> {
+    let workers = CONCURRENCY.load(Ordering::Relaxed).min(servers.len());
+    if workers <= 1 {
+        return servers.iter().map(|server| check(server)).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, Result<T, LibError>)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(server) = servers.get(index) else {
+                            break;
+                        };
+                        results.push((index, check(server)));
+                    }
+                    results
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|payload| panic::resume_unwind(payload))
+            })
+            .collect()
+    });
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Post-processing of the inventory, applied in order: filters, sort, then limit.
+#[derive(Default)]
+pub struct InventoryFilter {
+    /// Keeps only servers with at least this much memory, in gigabytes.
+    pub memory: Option<f64>,
+    /// Keeps only servers with at least this much storage, in gigabytes.
+    pub storage: Option<f64>,
+    /// Keeps only servers whose processor model contains this text, ignoring case.
+    pub cpu: Option<String>,
+    /// Keeps only servers with at least this many processor cores.
+    pub cores: Option<u32>,
+    /// Keeps only servers with at least this much bandwidth, in megabits per second.
+    pub bandwidth: Option<u64>,
+    /// Keeps only servers of this product range, ignoring case.
+    pub range: Option<String>,
+    /// Keeps only available (or only unavailable) servers.
+    pub available: Option<bool>,
+    /// Sorts the servers, unknown values being listed last.
+    pub sort: Option<InventorySort>,
+    /// Keeps only this many servers.
+    pub limit: Option<usize>,
+}
+
+impl InventoryFilter {
+    /// Tells if a server passes the filters, unparsable or unknown specs never passing.
+    fn is_matching(&self, info: &ServerInfo) -> bool {
+        let at_least = |value: Option<f64>, minimum: Option<f64>| match minimum {
+            Some(minimum) => value.is_some_and(|value| value >= minimum),
+            None => true,
+        };
+        at_least(info.memory_gb(), self.memory)
+            && at_least(info.storage_gb(), self.storage)
+            && at_least(info.cores.map(f64::from), self.cores.map(f64::from))
+            && at_least(
+                info.bandwidth.map(|bandwidth| bandwidth as f64),
+                self.bandwidth.map(|bandwidth| bandwidth as f64),
+            )
+            && self.cpu.as_ref().is_none_or(|cpu| {
+                info.cpu
+                    .as_ref()
+                    .is_some_and(|model| model.to_lowercase().contains(&cpu.to_lowercase()))
+            })
+            && self.range.as_ref().is_none_or(|range| {
+                info.range
+                    .as_ref()
+                    .is_some_and(|known| known.eq_ignore_ascii_case(range))
+            })
+            && self
+                .available
+                .is_none_or(|available| info.available == available)
+    }
+
+    /// Compares optional values, unknown ones being greater than any other
+    fn compare(a: Option<f64>, b: Option<f64>) -> std::cmp::Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+    }
+
+    /// Filters, sorts and truncates the inventory.
+    pub fn apply(&self, mut inventory: Vec<ServerInfo>) -> Vec<ServerInfo> {
+        inventory.retain(|info| self.is_matching(info));
+
+        match self.sort {
+            None => {}
+            Some(InventorySort::Reference) => {
+                inventory.sort_by(|a, b| a.reference.cmp(&b.reference))
+            }
+            Some(InventorySort::Memory) => {
+                inventory.sort_by(|a, b| Self::compare(a.memory_gb(), b.memory_gb()))
+            }
+            Some(InventorySort::Storage) => {
+                inventory.sort_by(|a, b| Self::compare(a.storage_gb(), b.storage_gb()))
+            }
+            Some(InventorySort::Price) => inventory.sort_by(|a, b| Self::compare(a.price, b.price)),
+            Some(InventorySort::Cores) => inventory
+                .sort_by(|a, b| Self::compare(a.cores.map(f64::from), b.cores.map(f64::from))),
+            Some(InventorySort::Bandwidth) => inventory.sort_by(|a, b| {
+                Self::compare(
+                    a.bandwidth.map(|bandwidth| bandwidth as f64),
+                    b.bandwidth.map(|bandwidth| bandwidth as f64),
+                )
+            }),
+        }
+
+        if let Some(limit) = self.limit {
+            inventory.truncate(limit);
+        }
+        inventory
+    }
+}
+
+/// A checked server type, with its availability, its stock level and the locations
+/// where it is available, as returned by `ProviderTrait::check_locations()`.
+pub type LocatedServer = (String, bool, Option<StockLevel>, Vec<String>);
+
 /// Defines the expected behaviour of every provider handler.
-pub trait ProviderTrait {
+/// Providers are shared by the threads checking several servers at once,
+/// and can be moved along with a `Watcher` to the thread running it.
+pub trait ProviderTrait: Send + Sync {
     /// Gets the actual name of the provider.
     fn name(&self) -> &'static str;
 
@@ -40,6 +704,56 @@ pub trait ProviderTrait {
 
     /// Checks the given provider for availability of a specific server type.
     fn check(&self, server: &str) -> Result<bool, LibError>;
+
+    /// Checks the given provider for availability of several server types at once.
+    /// By default, checks every server on its own, several at once as `set_concurrency()`
+    /// tells: providers which can answer from a single inventory fetch should override it.
+    fn check_many(&self, servers: &[String]) -> Result<Vec<(String, bool)>, LibError> {
+        check_concurrently(servers, |server| {
+            Ok((server.to_string(), self.check(server)?))
+        })
+    }
+
+    /// Checks several server types at once, with the stock level of the available ones,
+    /// for the providers which tell it. By default, the stock level is unknown.
+    fn check_stock(
+        &self,
+        servers: &[String],
+    ) -> Result<Vec<(String, bool, Option<StockLevel>)>, LibError> {
+        Ok(self
+            .check_many(servers)?
+            .into_iter()
+            .map(|(server, available)| (server, available, None))
+            .collect())
+    }
+
+    /// Same as `check_stock()`, with the locations where each available server is,
+    /// for the providers which tell them. By default, the locations are unknown.
+    fn check_locations(&self, servers: &[String]) -> Result<Vec<LocatedServer>, LibError> {
+        Ok(self
+            .check_stock(servers)?
+            .into_iter()
+            .map(|(server, available, stock)| (server, available, stock, Vec::new()))
+            .collect())
+    }
+
+    /// Tells if `check_locations()` tells where the servers are, so that wrappers
+    /// answering from elsewhere, like the inventory cache, do not lose them.
+    fn supports_locations(&self) -> bool {
+        false
+    }
+
+    /// Verifies that a server reference is well formed, without any query,
+    /// so that a mistyped one fails before checking. By default, any reference is accepted.
+    fn validate_server(&self, _server: &str) -> Result<(), LibError> {
+        Ok(())
+    }
+
+    /// Gets the public page where a server type can be ordered, without any query.
+    /// By default, providers do not have any.
+    fn order_url(&self, _server: &str) -> Option<String> {
+        None
+    }
 }
 
 /// Helps create providers
@@ -53,12 +767,56 @@ type FactoryFunc = fn() -> Result<Box<dyn ProviderTrait>, LibError>;
 
 /// Builds a reference table of available providers.
 static FACTORY: &[(&str, FactoryFunc)] = &[
+    #[cfg(feature = "aggregate")]
+    (aggregate::AGGREGATE_NAME, aggregate::Aggregate::from_env),
+    #[cfg(feature = "aws")]
+    (aws::AWS_NAME, aws::Aws::from_env),
+    #[cfg(feature = "cherryservers")]
+    (
+        cherryservers::CHERRYSERVERS_NAME,
+        cherryservers::CherryServers::from_env,
+    ),
+    #[cfg(feature = "command")]
+    (command::COMMAND_NAME, command::CommandProvider::from_env),
+    #[cfg(feature = "scraper")]
+    (
+        generic_html::GENERIC_HTML_NAME,
+        generic_html::GenericHtml::from_env,
+    ),
+    #[cfg(feature = "hetzner-auction")]
+    (
+        hetzner_auction::HETZNER_AUCTION_NAME,
+        hetzner_auction::HetznerAuction::from_env,
+    ),
+    #[cfg(feature = "hivelocity")]
+    (
+        hivelocity::HIVELOCITY_NAME,
+        hivelocity::Hivelocity::from_env,
+    ),
+    #[cfg(feature = "ibmcloud")]
+    (ibmcloud::IBMCLOUD_NAME, ibmcloud::IbmCloud::from_env),
+    #[cfg(feature = "leaseweb")]
+    (leaseweb::LEASEWEB_NAME, leaseweb::Leaseweb::from_env),
+    #[cfg(feature = "testing")]
+    (mock::MOCK_NAME, mock::MockProvider::from_env),
+    #[cfg(feature = "oci")]
+    (oci::OCI_NAME, oci::Oci::from_env),
     #[cfg(feature = "online")]
     (online::ONLINE_NAME, online::Online::from_env),
     #[cfg(feature = "ovh")]
     (ovh::OVH_NAME, ovh::Ovh::from_env),
+    #[cfg(feature = "phoenixnap")]
+    (
+        phoenixnap::PHOENIXNAP_NAME,
+        phoenixnap::PhoenixNap::from_env,
+    ),
     #[cfg(feature = "scaleway")]
     (scaleway::SCALEWAY_NAME, scaleway::Scaleway::from_env),
+    #[cfg(feature = "serverscom")]
+    (
+        serverscom::SERVERSCOM_NAME,
+        serverscom::ServersCom::from_env,
+    ),
 ];
 
 /// Trait to help create providers
@@ -74,7 +832,7 @@ impl Factory {
             .ok_or_else(|| LibError::UnknownProvider {
                 provider: provider.to_string(),
             })?;
-        factory()
+        Ok(crate::inventory::with_cache(factory()?))
     }
 
     /// Provides a list of all known provider types.
@@ -93,46 +851,50 @@ struct Runner;
 impl Runner {
     /// Builds an actual notifier from a notifier name
     fn build_provider(name: &str) -> anyhow::Result<Box<dyn ProviderTrait>> {
-        Ok(Factory::from_env_by_name(name)
-            .with_context(|| format!("while setting up provider {name}"))?)
+        Factory::from_env_by_name(name)
+            .with_context(|| format!("while setting up provider {name}"))
+            .context(CheckStage::Provider)
     }
 
     /// Builds an actual notifier from a notifier name
-    fn build_notifier(name: &Option<String>) -> anyhow::Result<Option<Box<dyn NotifierTrait>>> {
-        Ok(match name {
-            None => None,
-            Some(notifier) => Some(
-                notifiers::Factory::from_env_by_name(notifier)
-                    .with_context(|| format!("while setting up notifier {notifier}"))?,
-            ),
-        })
-    }
-
-    /// Builds an accessor for stored results
-    fn build_storage(storage_dir: &Option<String>) -> anyhow::Result<CheckResultStorage> {
-        let path = match storage_dir {
-            Some(dir) => path::Path::new(&dir).to_path_buf(),
-            None => env::current_dir()
-                .with_context(|| format!("Current directory is not accessible"))?,
-        };
-        Ok(CheckResultStorage::new(&path).context("while initializing CheckResultStorage")?)
+    fn notify_result(
+        notifier: &Option<Box<dyn NotifierTrait>>,
+        result: &CheckResult,
+    ) -> anyhow::Result<()> {
+        match notifier {
+            None => {
+                for srv in result.available_servers.iter() {
+                    println!("{}", srv.green());
+                }
+            }
+            Some(notifier) => {
+                notifier
+                    .notify(result)
+                    .with_context(|| format!("while notifying results through {}", notifier.name()))
+                    .context(CheckStage::Notifier)?;
+            }
+        }
+        Ok(())
     }
 
-    /// Builds an actual notifier from a notifier name
-    fn notify_result(
+    /// Prints the notification which would be sent, without sending it
+    fn print_dry_run_result(
         notifier: &Option<Box<dyn NotifierTrait>>,
         result: &CheckResult,
     ) -> anyhow::Result<()> {
         match notifier {
             None => {
+                println!("{}", "Would print:".yellow());
                 for srv in result.available_servers.iter() {
                     println!("{}", srv.green());
                 }
             }
             Some(notifier) => {
-                notifier.notify(&result).with_context(|| {
-                    format!("while notifying results through {}", notifier.name())
-                })?;
+                println!(
+                    "{}",
+                    format!("Would notify through {}:", notifier.name()).yellow()
+                );
+                println!("{}", result.to_json()?);
             }
         }
         Ok(())
@@ -155,6 +917,8 @@ impl ListRunner {
 /// An implementation for the InventoryRunner
 pub struct InventoryRunner {
     provider: Box<dyn ProviderTrait>,
+    history: Option<CheckResultStorage>,
+    save: Option<PathBuf>,
 }
 
 impl InventoryRunner {
@@ -162,20 +926,73 @@ impl InventoryRunner {
     pub fn new(provider_name: &str) -> anyhow::Result<Self> {
         Ok(Self {
             provider: Runner::build_provider(provider_name)?,
+            history: None,
+            save: None,
         })
     }
 
+    /// Saves the inventory to a file, before filtering it, to compare it later with `DiffRunner`
+    pub fn with_save(mut self, path: Option<PathBuf>) -> Self {
+        self.save = path;
+        self
+    }
+
+    /// Shows when every server was last checked and last seen available, from the stored states
+    pub fn with_history(mut self, storage: Option<CheckResultStorage>) -> Self {
+        self.history = storage;
+        self
+    }
+
+    /// Describes when a server was last checked and last seen available
+    fn describe_history(&self, info: &ServerInfo) -> anyhow::Result<String> {
+        let Some(storage) = &self.history else {
+            return Ok(String::new());
+        };
+        let server = info.server_id();
+        let state = storage
+            .get_server_state(self.provider.name(), server)
+            .with_context(|| format!("while reading state of server {server}"))?;
+        let age = |timestamp: u64| {
+            crate::format_age(Duration::from_secs(
+                crate::storage::get_timestamp().saturating_sub(timestamp),
+            ))
+        };
+        let text = match state.and_then(|state| state.last_check.map(|check| (check, state))) {
+            None => " (never checked)".to_string(),
+            Some((last_check, state)) => match state.last_available {
+                None => format!(" (checked {}, never seen available)", age(last_check)),
+                Some(last_available) => format!(
+                    " (checked {}, last seen available {})",
+                    age(last_check),
+                    age(last_available)
+                ),
+            },
+        };
+        Ok(text)
+    }
+
     /// Prints a list of every kind of server known to the provider.
     /// By default, does not include servers which are out of stock
     /// Set `all` to true to include unavailable server kinds
-    pub fn list_inventory(&self, all: bool) -> anyhow::Result<()> {
+    /// The inventory is then post-processed by the filter.
+    pub fn list_inventory(&self, all: bool, filter: &InventoryFilter) -> anyhow::Result<()> {
         println!("Working...");
-        let inventory = self.provider.inventory(all).with_context(|| {
-            format!(
-                "while getting inventory for provider {}",
-                self.provider.name()
-            )
-        })?;
+        // filtering on availability requires every server kind
+        let all = all || filter.available.is_some();
+        let inventory = self
+            .provider
+            .inventory(all)
+            .with_context(|| {
+                format!(
+                    "while getting inventory for provider {}",
+                    self.provider.name()
+                )
+            })
+            .context(CheckStage::Provider)?;
+        if let Some(path) = &self.save {
+            crate::inventory::save(self.provider.name(), all, inventory.clone(), path)?;
+        }
+        let inventory = filter.apply(inventory);
 
         if inventory.is_empty() {
             println!("No servers found");
@@ -183,21 +1000,32 @@ impl InventoryRunner {
         }
 
         println!("Known servers:");
-        for item in inventory.iter() {
-            match item {
-                info => {
-                    println!(
-                        "{} {} {}",
-                        if !info.available {
-                            info.reference.on_red()
-                        } else {
-                            info.reference.green()
-                        },
-                        info.memory.yellow(),
-                        info.storage.blue(),
-                    );
-                }
-            }
+        for info in inventory.iter() {
+            let specs = info.describe_specs();
+            println!(
+                "{}{} {} {}{}{}{}",
+                if !info.available {
+                    info.reference.on_red()
+                } else {
+                    info.reference.green()
+                },
+                match info.stock {
+                    Some(StockLevel::Low) => format!(" [{}]", StockLevel::Low).red(),
+                    Some(stock) => format!(" [{stock}]").normal(),
+                    None => "".normal(),
+                },
+                info.memory.yellow(),
+                info.storage.blue(),
+                match specs.is_empty() {
+                    true => "".normal(),
+                    false => format!(" {specs}").magenta(),
+                },
+                match info.price {
+                    Some(price) => format!(" {price:.2}").cyan(),
+                    None => "".normal(),
+                },
+                self.describe_history(info)?.dimmed(),
+            );
         }
         Ok(())
     }
@@ -205,66 +1033,1120 @@ impl InventoryRunner {
 
 impl Runner {}
 
+/// Where the changes of a check are notified: a notifier, or the standard output without any.
+/// Each one has its own quiet hours and its own digest.
+struct NotifyTarget {
+    notifier: Option<Box<dyn NotifierTrait>>,
+    quiet_hours: Option<QuietHours>,
+}
+
+impl NotifyTarget {
+    /// Builds the target printing the available servers, which is the default
+    fn stdout() -> Self {
+        Self {
+            notifier: None,
+            quiet_hours: None,
+        }
+    }
+
+    /// Builds an actual notifier from a notifier name, with its quiet hours
+    fn from_name(name: &str) -> anyhow::Result<Self> {
+        let notifier = notifiers::Factory::from_env_by_name(name)
+            .with_context(|| format!("while setting up notifier {name}"))
+            .context(CheckStage::Notifier)?;
+        let quiet_hours = QuietHours::from_env(notifier.name())
+            .with_context(|| format!("while setting up quiet hours of {name}"))?;
+        Ok(Self {
+            notifier: Some(notifier),
+            quiet_hours,
+        })
+    }
+
+    /// Gets the name under which the digest of the target is stored
+    fn name(&self) -> &'static str {
+        match &self.notifier {
+            None => "stdout",
+            Some(notifier) => notifier.name(),
+        }
+    }
+
+    /// Tells if the target is currently within its quiet hours
+    fn is_quiet(&self) -> bool {
+        self.quiet_hours
+            .as_ref()
+            .is_some_and(|quiet_hours| quiet_hours.is_quiet(chrono::Utc::now()))
+    }
+}
+
 /// An implementation for the CheckRunner
-pub struct CheckRunner<'a> {
+pub struct CheckRunner {
     provider: Box<dyn ProviderTrait>,
-    servers: &'a Vec<String>,
-    notifier: Option<Box<dyn NotifierTrait>>,
+    servers: Vec<String>,
+    patterns: Vec<ServerPattern>,
+    targets: Vec<NotifyTarget>,
     storage: CheckResultStorage,
+    whole_list: bool,
+    debounce: Debounce,
+    dry_run: bool,
+    digest: Option<DigestWindow>,
+    wait_lock: bool,
+    hook: Option<AvailabilityHook>,
+    verbose: bool,
+    notify_low_stock: bool,
+    price_watch: PriceWatch,
+    print: bool,
+    retry_max_age: Option<Duration>,
 }
 
-impl<'a> CheckRunner<'a> {
+impl CheckRunner {
+    /// Trims, deduplicates and sorts the requested servers, so that the same selection
+    /// is always checked once per server, and stored under the same name whatever its order.
+    fn normalize_servers(servers: &[String]) -> Vec<String> {
+        let mut servers: Vec<String> = servers
+            .iter()
+            .map(|server| server.trim().to_string())
+            .filter(|server| !server.is_empty())
+            .collect();
+        servers.sort();
+        servers.dedup();
+        servers
+    }
+
     /// Builds an instance so that we do not endlessly repeat arguments
+    /// An empty list of servers watches the whole inventory.
+    /// Exact server references are verified by the provider, before any query.
+    /// Without any notifier, the available servers are printed.
     pub fn new(
         provider_name: &str,
-        servers: &'a Vec<String>,
-        notifier_name: &Option<String>,
-        storage_dir: &'a Option<String>,
+        servers: &[String],
+        notifier_names: &[String],
+        storage: CheckResultStorage,
+        whole_list: bool,
+        debounce: Debounce,
+        dry_run: bool,
+    ) -> anyhow::Result<Self> {
+        Self::new_with_provider(
+            Runner::build_provider(provider_name)?,
+            servers,
+            notifier_names,
+            storage,
+            whole_list,
+            debounce,
+            dry_run,
+        )
+    }
+
+    /// Same as `new()`, around a provider which was already built, like an offline one.
+    pub fn new_with_provider(
+        provider: Box<dyn ProviderTrait>,
+        servers: &[String],
+        notifier_names: &[String],
+        storage: CheckResultStorage,
+        whole_list: bool,
+        debounce: Debounce,
+        dry_run: bool,
     ) -> anyhow::Result<Self> {
+        let mut runner = Self::from_provider(provider, servers, storage)?;
+        if !notifier_names.is_empty() {
+            runner.targets = notifier_names
+                .iter()
+                .map(|name| NotifyTarget::from_name(name))
+                .collect::<anyhow::Result<Vec<NotifyTarget>>>()?;
+        }
+        runner.whole_list = whole_list;
+        runner.debounce = debounce;
+        runner.dry_run = dry_run;
+        Ok(runner)
+    }
+
+    /// Builds an instance around a provider which was already built, like by its builder,
+    /// without any notifier: the notified changes are returned by `check_once_changes()`.
+    pub fn from_provider(
+        provider: Box<dyn ProviderTrait>,
+        servers: &[String],
+        storage: CheckResultStorage,
+    ) -> anyhow::Result<Self> {
+        let servers = Self::normalize_servers(servers);
+        let patterns = servers
+            .iter()
+            .map(|server| ServerPattern::parse(server))
+            .collect::<Result<Vec<ServerPattern>, LibError>>()
+            .context("while parsing server selection")?;
+        for pattern in patterns.iter().filter(|pattern| !pattern.is_pattern()) {
+            provider
+                .validate_server(pattern.as_str())
+                .with_context(|| format!("while validating server {}", pattern.as_str()))?;
+        }
         Ok(Self {
-            provider: Runner::build_provider(provider_name)?,
+            provider,
             servers,
-            notifier: Runner::build_notifier(notifier_name)?,
-            storage: Runner::build_storage(storage_dir)?,
+            patterns,
+            targets: vec![NotifyTarget::stdout()],
+            storage,
+            whole_list: false,
+            debounce: Debounce::default(),
+            dry_run: false,
+            digest: None,
+            wait_lock: true,
+            hook: None,
+            verbose: false,
+            notify_low_stock: false,
+            price_watch: PriceWatch::default(),
+            print: true,
+            retry_max_age: None,
         })
     }
 
+    /// Prints the notified servers when there is no notifier, which is the default, and how
+    /// the deliveries went with several notifiers. Programs handling the returned changes
+    /// themselves may not want it.
+    pub fn with_print(mut self, print: bool) -> Self {
+        self.print = print;
+        self
+    }
+
+    /// Keeps the changes which could not be notified through a notifier in the storage
+    /// directory, and retries them at the following checks, until they are older than
+    /// `max_age`. Changes are then held while earlier ones are pending, to keep their order.
+    pub fn with_retry(mut self, max_age: Option<Duration>) -> Self {
+        self.retry_max_age = max_age;
+        self
+    }
+
+    /// Prints the provider API metrics after every check.
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Also notifies about available servers whose stock level drops to low,
+    /// for the providers which tell it. It has no effect with the whole list.
+    pub fn with_low_stock(mut self, notify_low_stock: bool) -> Self {
+        self.notify_low_stock = notify_low_stock;
+        self
+    }
+
+    /// Also notifies about available servers whose price changed, following the policy.
+    /// Prices come from the inventory, which is then queried at every check,
+    /// and are only known for some providers. It has no effect with the whole list.
+    pub fn with_price_watch(mut self, price_watch: PriceWatch) -> Self {
+        self.price_watch = price_watch;
+        self
+    }
+
+    /// Stores when the provider was last queried successfully, which is the case
+    /// unless the check failed on the provider side. Dry runs store nothing.
+    fn record_success(&self, changes: &anyhow::Result<Option<CheckResult>>) -> anyhow::Result<()> {
+        let failed = changes
+            .as_ref()
+            .is_err_and(|error| CheckStage::of(error) == Some(CheckStage::Provider));
+        if failed || self.dry_run {
+            return Ok(());
+        }
+        self.storage
+            .put_last_success(self.provider.name(), crate::storage::get_timestamp())
+            .context("while storing last successful check")
+    }
+
+    /// Summarizes the provider API calls of the check, adds them to the totals of the
+    /// metrics endpoint and to the stored history, and prints them in verbose mode.
+    /// Checks which sent no request, like replayed ones, are left out.
+    fn record_metrics(&self) -> anyhow::Result<()> {
+        let calls = metrics::take();
+        if calls.is_empty() {
+            return Ok(());
+        }
+        let provider_name = self.provider.name();
+        metrics::add_totals(provider_name, &calls);
+
+        let mut history = self
+            .storage
+            .get_metrics(provider_name)
+            .context("while reading provider metrics")?
+            .unwrap_or_default();
+        let check = CheckMetrics::from_calls(&calls, crate::storage::get_timestamp());
+        if self.verbose {
+            println!("{}", format!("Provider API: {check}").dimmed());
+        }
+        metrics::push_history(&mut history, check);
+        if self.verbose {
+            println!(
+                "{}",
+                format!("Provider API, {}", metrics::describe_history(&history)).dimmed()
+            );
+        }
+
+        if self.dry_run {
+            let (path, json) = self.storage.build_metrics(provider_name, &history)?;
+            println!("Would store {} in {}", json, path.display());
+            return Ok(());
+        }
+        self.storage
+            .put_metrics(provider_name, &history)
+            .context("while storing provider metrics")
+    }
+
+    /// Runs a hook with the servers which become available, right away,
+    /// even in digest mode or during quiet hours.
+    pub fn with_hook(mut self, hook: Option<AvailabilityHook>) -> Self {
+        self.hook = hook;
+        self
+    }
+
+    /// Runs the hook with the available servers whose cooldown is over, or only describes it
+    /// in dry-run mode. Its failure is reported, but does not fail the check.
+    fn run_hook(&self, result: &CheckResult) -> anyhow::Result<()> {
+        let Some(hook) = &self.hook else {
+            return Ok(());
+        };
+        let provider_name = self.provider.name();
+        let mut runs = self
+            .storage
+            .get_hook_runs(provider_name)
+            .context("while reading hook runs")?
+            .unwrap_or_default();
+        let now = crate::storage::get_timestamp();
+        let servers = hook.select(result, &runs, now);
+        if servers.is_empty() {
+            return Ok(());
+        }
+
+        if self.dry_run {
+            println!("Would run {}", hook.describe(&servers));
+            return Ok(());
+        }
+        match hook.run(result, &servers) {
+            Ok(output) => print!("{output}"),
+            Err(error) => eprintln!(
+                "{}",
+                format!("while running hook for {}: {error}", servers.join(",")).red()
+            ),
+        }
+        // failed runs are not retried before the cooldown either, as they may have ordered
+        for server in servers {
+            runs.insert(server, now);
+        }
+        self.storage
+            .put_hook_runs(provider_name, &runs)
+            .context("while storing hook runs")
+    }
+
+    /// Gets the name of the checked provider, under which its states are stored
+    pub fn provider_name(&self) -> &'static str {
+        self.provider.name()
+    }
+
+    /// Gets the storage of the states
+    pub fn storage(&self) -> &CheckResultStorage {
+        &self.storage
+    }
+
+    /// Tells whether to wait for another instance checking the same storage directory,
+    /// or to fail right away (the default is to wait).
+    pub fn with_wait_lock(mut self, wait_lock: bool) -> Self {
+        self.wait_lock = wait_lock;
+        self
+    }
+
+    /// Locks the storage directory for the duration of a check, waiting if required.
+    /// Dry runs write nothing, so they do not need any lock.
+    fn lock_storage(&self) -> anyhow::Result<Option<StorageLock>> {
+        if self.dry_run {
+            return Ok(None);
+        }
+        let lock = match self.storage.try_lock() {
+            Err(LibError::StorageLocked { path }) if self.wait_lock => {
+                println!("Waiting for another instance to release {path}");
+                self.storage.lock()
+            }
+            result => result,
+        };
+        Ok(Some(lock.context("while locking storage")?))
+    }
+
+    /// Deletes the stored states which were not written nor confirmed by any check
+    /// for longer than `ttl`, printing what was deleted.
+    pub fn prune(&self, ttl: Duration) -> anyhow::Result<()> {
+        let _lock = self.lock_storage()?;
+        let pruned = self
+            .storage
+            .prune(ttl, self.dry_run)
+            .context("while pruning storage")?;
+        for entry in pruned.iter() {
+            println!("Pruned {}", entry.path.display());
+        }
+        Ok(())
+    }
+
+    /// Collects the changes into a digest, delivered once per window, instead of notifying each of them.
+    pub fn with_digest(mut self, digest: Option<DigestWindow>) -> Self {
+        self.digest = digest;
+        self
+    }
+
+    /// Expands the server patterns against the current inventory, keeping exact references.
+    /// The inventory is only fetched when there is at least one pattern.
+    fn expand_servers(&self) -> anyhow::Result<Vec<String>> {
+        if !self.patterns.iter().any(|pattern| pattern.is_pattern()) {
+            return Ok(self.servers.clone());
+        }
+
+        let inventory = self
+            .provider
+            .inventory(true)
+            .context("while getting inventory to expand server patterns")?;
+
+        let mut servers: Vec<String> = Vec::new();
+        for pattern in self.patterns.iter() {
+            if !pattern.is_pattern() {
+                if !servers.iter().any(|server| server == pattern.as_str()) {
+                    servers.push(pattern.as_str().to_string());
+                }
+                continue;
+            }
+
+            let mut found = false;
+            for info in inventory.iter() {
+                let server = info.server_id();
+                if pattern.matches(server) {
+                    found = true;
+                    if !servers.iter().any(|known| known == server) {
+                        servers.push(server.to_string());
+                    }
+                }
+            }
+            if !found {
+                println!(
+                    "{}",
+                    format!("No server matches pattern {}", pattern.as_str()).yellow()
+                );
+            }
+        }
+        Ok(servers)
+    }
+
+    /// Adds the closest known servers to an unknown server error, from the inventory,
+    /// unless the provider already did. The error is kept as is when the inventory fails.
+    fn suggest_servers(&self, error: LibError) -> LibError {
+        let LibError::UnknownServer {
+            server,
+            suggestions,
+        } = error
+        else {
+            return error;
+        };
+        if !suggestions.is_empty() {
+            return LibError::UnknownServer {
+                server,
+                suggestions,
+            };
+        }
+        let suggestions = self
+            .provider
+            .inventory(true)
+            .map(|inventory| {
+                pattern::closest_matches(&server, inventory.iter().map(ServerInfo::server_id))
+            })
+            .unwrap_or_default();
+        LibError::UnknownServer {
+            server,
+            suggestions,
+        }
+    }
+
     /// Checks the given provider for availability of a specific server type.
     fn check_servers(&self, result: &mut CheckResult) -> anyhow::Result<()> {
-        for server in self.servers.iter() {
-            if self
+        let servers = self.expand_servers()?;
+        let states = self
+            .provider
+            .check_locations(&servers)
+            .map_err(|error| self.suggest_servers(error))
+            .with_context(|| format!("while checking for servers {}", servers.join(",")))?;
+        for (server, available, stock, locations) in states {
+            if available {
+                if let Some(order_url) = self.provider.order_url(&server) {
+                    result.order_urls.insert(server.clone(), order_url);
+                }
+                if let Some(stock) = stock {
+                    result.stock_levels.insert(server.clone(), stock);
+                }
+                if !locations.is_empty() {
+                    result.locations.insert(server.clone(), locations);
+                }
+                result.available_servers.push(server);
+            } else {
+                result.unavailable_servers.push(server);
+            }
+        }
+
+        if self.price_watch.is_enabled() {
+            let provider_name = self.provider.name();
+            let inventory = self
                 .provider
-                .check(server)
-                .with_context(|| format!("while checking for server {server}"))?
-            {
-                result.available_servers.push(server.clone());
+                .inventory(true)
+                .with_context(|| format!("while getting prices of provider {provider_name}"))?;
+            for info in inventory {
+                let Some(price) = info.price else {
+                    continue;
+                };
+                let server = info.server_id();
+                if servers.iter().any(|known| known == server) {
+                    result.prices.insert(server.to_string(), price);
+                }
             }
         }
         Ok(())
     }
 
-    /// Checks the given provider, compare with previous result, and notify if needed
-    pub fn check_once(&self) -> anyhow::Result<()> {
+    /// Compares every server with its stored state, and stores the latest one.
+    /// Returns a result holding only the servers whose availability changed,
+    /// once the change has been observed long enough to pass the debounce policy.
+    /// A server without any stored state is deemed previously unavailable.
+    /// With low stock notifications, it also holds the available servers whose stock became low,
+    /// and with a price watch, the ones whose price changed as the policy tells.
+    fn diff_servers(&self, latest: &CheckResult) -> anyhow::Result<CheckResult> {
+        let provider_name = self.provider.name();
+        let mut changes = CheckResult::new(provider_name);
+
+        let states = latest
+            .available_servers
+            .iter()
+            .map(|server| (server, true))
+            .chain(
+                latest
+                    .unavailable_servers
+                    .iter()
+                    .map(|server| (server, false)),
+            );
+
+        let now = crate::storage::get_timestamp();
+        for (server, available) in states {
+            let mut state = self
+                .storage
+                .get_server_state(provider_name, server)
+                .with_context(|| format!("while reading state of server {server}"))?
+                .unwrap_or_default();
+
+            let stock = latest.stock_levels.get(server).copied();
+            let previous_stock = std::mem::replace(&mut state.stock, stock);
+            let changed = state.observe(available, now, &self.debounce);
+            let stock_dropped = self.notify_low_stock
+                && state.available
+                && stock == Some(StockLevel::Low)
+                && previous_stock != Some(StockLevel::Low);
+            // the last known price is kept when the current one is missing
+            let price = latest.prices.get(server).copied();
+            let previous_price = state.price;
+            state.price = price.or(previous_price);
+            let price_changed =
+                state.available && self.price_watch.is_notable(previous_price, price);
+            if changed || stock_dropped || price_changed {
+                if available {
+                    if let Some(price) = price {
+                        changes.prices.insert(server.clone(), price);
+                    }
+                    if let Some(previous_price) = previous_price.filter(|_| price_changed) {
+                        changes
+                            .previous_prices
+                            .insert(server.clone(), previous_price);
+                    }
+                    if let Some(order_url) = latest.order_urls.get(server) {
+                        changes.order_urls.insert(server.clone(), order_url.clone());
+                    }
+                    if let Some(stock) = stock {
+                        changes.stock_levels.insert(server.clone(), stock);
+                    }
+                    if let Some(locations) = latest.locations.get(server) {
+                        changes.locations.insert(server.clone(), locations.clone());
+                    }
+                    changes.available_servers.push(server.clone());
+                } else {
+                    changes.unavailable_servers.push(server.clone());
+                }
+                // a stock drop is not a change of availability
+                if let Some(timestamp) = state.last_seen_available().filter(|_| changed) {
+                    changes
+                        .last_seen_available
+                        .insert(server.clone(), timestamp);
+                }
+            }
+
+            if self.dry_run {
+                let (path, json) = self
+                    .storage
+                    .build_server_state(provider_name, server, &state)
+                    .with_context(|| format!("while building state of server {server}"))?;
+                println!("Would store {} in {}", json, path.display());
+                continue;
+            }
+
+            self.storage
+                .put_server_state(provider_name, server, &state)
+                .with_context(|| format!("while storing state of server {server}"))?;
+        }
+
+        Ok(changes)
+    }
+
+    /// Notifies about the result through every notifier, or only describes the notifications
+    /// in dry-run mode. In digest mode or during the quiet hours of a notifier, the result is
+    /// collected instead, to be notified later. A failing notifier does not prevent notifying
+    /// through the others, but the first failure is returned.
+    fn notify_result(&self, result: &CheckResult) -> anyhow::Result<()> {
+        self.run_hook(result)?;
+        let mut reports = Vec::new();
+        let mut failure = None;
+        for target in self.targets.iter() {
+            let notified = if self.digest.is_some() || target.is_quiet() {
+                self.get_digest(target).and_then(|mut digest| {
+                    digest.add(result, crate::storage::get_timestamp());
+                    self.put_digest(target, &digest)
+                })
+            } else {
+                self.deliver_or_retry(target, result, &mut reports)
+            };
+            if let Err(error) = notified {
+                failure.get_or_insert(error);
+            }
+        }
+        self.record_deliveries(&reports)?;
+        failure.map_or(Ok(()), Err)
+    }
+
+    /// Notifies about the result through a single target, or only describes the notification
+    /// in dry-run mode, and reports how it went for actual notifiers.
+    fn deliver(
+        &self,
+        target: &NotifyTarget,
+        result: &CheckResult,
+        reports: &mut Vec<DeliveryReport>,
+    ) -> anyhow::Result<()> {
+        if self.dry_run {
+            return Runner::print_dry_run_result(&target.notifier, result);
+        }
+        if target.notifier.is_none() && !self.print {
+            return Ok(());
+        }
+        let started = Instant::now();
+        let outcome = Runner::notify_result(&target.notifier, result);
+        if let Some(notifier) = &target.notifier {
+            reports.push(DeliveryReport::new(
+                notifier.name(),
+                started.elapsed(),
+                &outcome,
+                crate::storage::get_timestamp(),
+            ));
+        }
+        outcome
+    }
+
+    /// Notifies about the result through a single target, unless changes are already waiting
+    /// to be retried through it, in which case the result is added to them. A failed
+    /// notification is kept to be retried, when retries are enabled.
+    fn deliver_or_retry(
+        &self,
+        target: &NotifyTarget,
+        result: &CheckResult,
+        reports: &mut Vec<DeliveryReport>,
+    ) -> anyhow::Result<()> {
+        let Some(notifier_name) = target.notifier.as_ref().map(|notifier| notifier.name()) else {
+            return self.deliver(target, result, reports);
+        };
+        if self.retry_max_age.is_none() {
+            return self.deliver(target, result, reports);
+        }
+        let mut outbox = self.get_outbox(notifier_name)?;
+        let now = crate::storage::get_timestamp();
+        if !outbox.is_empty() {
+            outbox.add(result, now);
+            return self.put_outbox(notifier_name, &outbox);
+        }
+        self.deliver(target, result, reports).or_else(|error| {
+            outbox.add(result, now);
+            outbox.failed(now);
+            self.put_outbox(notifier_name, &outbox)?;
+            Err(error)
+        })
+    }
+
+    /// Gets the changes waiting to be retried through a notifier
+    fn get_outbox(&self, notifier_name: &str) -> anyhow::Result<Outbox> {
+        Ok(self
+            .storage
+            .get_outbox(notifier_name)
+            .context("while reading outbox")?
+            .unwrap_or_default())
+    }
+
+    /// Stores the changes waiting to be retried, or only describes them in dry-run mode
+    fn put_outbox(&self, notifier_name: &str, outbox: &Outbox) -> anyhow::Result<()> {
+        if self.dry_run {
+            let (path, json) = self.storage.build_outbox(notifier_name, outbox)?;
+            println!("Would store {} in {}", json, path.display());
+            return Ok(());
+        }
+        self.storage
+            .put_outbox(notifier_name, outbox)
+            .context("while storing outbox")
+    }
+
+    /// Retries the changes which could not be notified through every notifier, once due.
+    fn flush_outbox(&self) -> anyhow::Result<()> {
+        let Some(max_age) = self.retry_max_age else {
+            return Ok(());
+        };
+        let mut reports = Vec::new();
+        let mut failure = None;
+        for target in self.targets.iter() {
+            if let Err(error) = self.flush_target_outbox(target, max_age, &mut reports) {
+                failure.get_or_insert(error);
+            }
+        }
+        self.record_deliveries(&reports)?;
+        failure.map_or(Ok(()), Err)
+    }
+
+    /// Retries the changes which could not be notified through a target, once due,
+    /// giving them up once they are older than `max_age`.
+    fn flush_target_outbox(
+        &self,
+        target: &NotifyTarget,
+        max_age: Duration,
+        reports: &mut Vec<DeliveryReport>,
+    ) -> anyhow::Result<()> {
+        let Some(notifier_name) = target.notifier.as_ref().map(|notifier| notifier.name()) else {
+            return Ok(());
+        };
+        let mut outbox = self.get_outbox(notifier_name)?;
+        let now = crate::storage::get_timestamp();
+        if outbox.is_expired(max_age, now) {
+            let pending = outbox.to_result();
+            let count = pending.available_servers.len() + pending.unavailable_servers.len();
+            eprintln!(
+                "{}",
+                format!(
+                    "Giving up notifying {count} changes through {notifier_name}, after {} failed attempts",
+                    outbox.attempts()
+                )
+                .red()
+            );
+            return self.put_outbox(notifier_name, &Outbox::default());
+        }
+        if !outbox.is_due(now) {
+            return Ok(());
+        }
+        match self.deliver(target, &outbox.to_result(), reports) {
+            Ok(()) => self.put_outbox(notifier_name, &Outbox::default()),
+            Err(error) => {
+                outbox.failed(now);
+                self.put_outbox(notifier_name, &outbox)?;
+                Err(error.context(format!(
+                    "while retrying notification through {notifier_name}"
+                )))
+            }
+        }
+    }
+
+    /// Prints how the notifications went when there are several notifiers, or in verbose mode,
+    /// and adds them to the stored history of the provider.
+    fn record_deliveries(&self, reports: &[DeliveryReport]) -> anyhow::Result<()> {
+        if reports.is_empty() {
+            return Ok(());
+        }
+        if self.print && (self.verbose || self.targets.len() > 1) {
+            for report in reports {
+                let line = format!("Delivery: {report}");
+                match report.success {
+                    true => println!("{}", line.dimmed()),
+                    false => println!("{}", line.red()),
+                }
+            }
+        }
+        let provider_name = self.provider.name();
+        let mut history = self
+            .storage
+            .get_deliveries(provider_name)
+            .context("while reading notification deliveries")?
+            .unwrap_or_default();
+        delivery::push_history(&mut history, reports);
+        self.storage
+            .put_deliveries(provider_name, &history)
+            .context("while storing notification deliveries")
+    }
+
+    /// Gets the changes collected so far for a target
+    fn get_digest(&self, target: &NotifyTarget) -> anyhow::Result<DigestBuffer> {
+        Ok(self
+            .storage
+            .get_digest(target.name())
+            .context("while reading digest")?
+            .unwrap_or_default())
+    }
+
+    /// Stores the changes collected so far for a target, or only describes them in dry-run mode
+    fn put_digest(&self, target: &NotifyTarget, digest: &DigestBuffer) -> anyhow::Result<()> {
+        if self.dry_run {
+            let (path, json) = self.storage.build_digest(target.name(), digest)?;
+            println!("Would store {} in {}", json, path.display());
+            return Ok(());
+        }
+        self.storage
+            .put_digest(target.name(), digest)
+            .context("while storing digest")
+    }
+
+    /// Notifies about the collected changes of every target whose digest is due.
+    fn flush_digest(&self) -> anyhow::Result<()> {
+        let mut reports = Vec::new();
+        let mut failure = None;
+        for target in self.targets.iter() {
+            if let Err(error) = self.flush_target_digest(target, &mut reports) {
+                failure.get_or_insert(error);
+            }
+        }
+        self.record_deliveries(&reports)?;
+        failure.map_or(Ok(()), Err)
+    }
+
+    /// Notifies about the collected changes once the digest window is over, and empties the digest.
+    /// Without any digest window, changes held during quiet hours are notified as soon as they are over.
+    fn flush_target_digest(
+        &self,
+        target: &NotifyTarget,
+        reports: &mut Vec<DeliveryReport>,
+    ) -> anyhow::Result<()> {
+        if (self.digest.is_none() && target.quiet_hours.is_none()) || target.is_quiet() {
+            return Ok(());
+        }
+        let digest = self.get_digest(target)?;
+        let due = match &self.digest {
+            Some(window) => digest.is_due(window, crate::storage::get_timestamp()),
+            None => !digest.is_empty(),
+        };
+        if !due {
+            return Ok(());
+        }
+        self.deliver(target, &digest.to_result(), reports)?;
+        self.put_digest(target, &DigestBuffer::default())
+    }
+
+    /// Tells that nothing would be notified, in dry-run mode only
+    fn print_dry_run_nothing(&self) {
+        if self.dry_run {
+            println!("{}", "Nothing would be notified".yellow());
+        }
+    }
+
+    /// Compares the available servers of the whole inventory with the stored snapshot,
+    /// stores the latest one, and notifies about the servers which appeared or disappeared.
+    /// Without any stored snapshot, every available server is deemed new.
+    fn check_snapshot(&self) -> anyhow::Result<Option<CheckResult>> {
+        let provider_name = self.provider.name();
+
+        let inventory = self
+            .provider
+            .inventory(false)
+            .with_context(|| format!("while getting inventory for provider {provider_name}"))
+            .context(CheckStage::Provider)?;
+        let mut latest: Vec<String> = inventory
+            .iter()
+            .map(|info| info.server_id().to_string())
+            .collect();
+        latest.sort();
+        latest.dedup();
+
+        let previous = self
+            .storage
+            .get_snapshot(provider_name)
+            .context("while reading inventory snapshot")?
+            .unwrap_or_default();
+
+        let mut changes = CheckResult::new(provider_name);
+        changes.available_servers = latest
+            .iter()
+            .filter(|server| !previous.contains(server))
+            .cloned()
+            .collect();
+        changes.order_urls = inventory
+            .iter()
+            .filter(|info| {
+                changes
+                    .available_servers
+                    .iter()
+                    .any(|server| server == info.server_id())
+            })
+            .filter_map(|info| Some((info.server_id().to_string(), info.order_url.clone()?)))
+            .collect();
+        changes.unavailable_servers = previous
+            .iter()
+            .filter(|server| !latest.contains(server))
+            .cloned()
+            .collect();
+
+        if changes.available_servers.is_empty() && changes.unavailable_servers.is_empty() {
+            if !self.dry_run {
+                self.storage
+                    .touch_snapshot(provider_name)
+                    .context("while touching inventory snapshot")?;
+            }
+            self.print_dry_run_nothing();
+            return Ok(None);
+        }
+
+        if self.dry_run {
+            let (path, json) = self.storage.build_snapshot(provider_name, &latest)?;
+            println!("Would store {} in {}", json, path.display());
+        } else {
+            self.storage
+                .put_snapshot(provider_name, &latest)
+                .context("while storing inventory snapshot")?;
+        }
+
+        self.notify_result(&changes)?;
+        Ok(Some(changes))
+    }
+
+    /// Checks the given provider, compare with previous result, and notify if needed.
+    /// Without any server, the whole inventory is compared with its previous snapshot.
+    /// In digest mode, the changes are collected, and the digest is notified once its window is over,
+    /// even when the check itself failed. The same goes for changes held during quiet hours.
+    /// In dry-run mode, the storage and the notifier are left untouched,
+    /// and what would have been stored and notified is printed instead.
+    pub fn check_once(&self) -> anyhow::Result<CheckOutcome> {
+        Ok(self
+            .check_once_changes()?
+            .map_or(CheckOutcome::Unchanged, |changes| {
+                CheckOutcome::of(&changes)
+            }))
+    }
+
+    /// Same as `check_once()`, but returns the notified changes, if any.
+    pub fn check_once_changes(&self) -> anyhow::Result<Option<CheckResult>> {
+        let _lock = self.lock_storage()?;
+        // calls made outside of a check, like by a previous failed setup, are not its own
+        metrics::take();
+        let changes = self.check_changes();
+        self.record_success(&changes)?;
+        self.record_metrics()?;
+        self.flush_digest()?;
+        self.flush_outbox()?;
+        changes
+    }
+
+    /// Checks the given provider, compare with previous result, and notify if needed.
+    fn check_changes(&self) -> anyhow::Result<Option<CheckResult>> {
+        if self.servers.is_empty() {
+            return self.check_snapshot();
+        }
+
         let provider_name = self.provider.name();
 
         // get current result
         let mut latest = CheckResult::new(provider_name);
         self.check_servers(&mut latest)
-            .with_context(|| format!("while checking provider {}", provider_name))?;
+            .with_context(|| format!("while checking provider {}", provider_name))
+            .context(CheckStage::Provider)?;
+
+        if !self.whole_list {
+            // only notify about the servers whose availability changed
+            let changes = self.diff_servers(&latest)?;
+            if changes.available_servers.is_empty() && changes.unavailable_servers.is_empty() {
+                self.print_dry_run_nothing();
+                return Ok(None);
+            }
+            self.notify_result(&changes)?;
+            return Ok(Some(changes));
+        }
 
         // do nothing more if there was no change
         if self
             .storage
-            .is_equal(&provider_name, &self.servers, &latest)?
+            .is_equal(provider_name, &self.servers, &latest)?
         {
-            return Ok(());
+            if !self.dry_run {
+                self.storage.touch_hash(provider_name, &self.servers)?;
+            }
+            self.print_dry_run_nothing();
+            return Ok(None);
         }
 
         // store latest
-        self.storage
-            .put_hash(provider_name, self.servers, &latest)?;
+        if self.dry_run {
+            let (path, hash) = self
+                .storage
+                .build_hash(provider_name, &self.servers, &latest)?;
+            println!("Would store {} in {}", hash, path.display());
+        } else {
+            self.storage
+                .put_hash(provider_name, &self.servers, &latest)?;
+        }
 
         // Notify of the new
-        Runner::notify_result(&self.notifier, &latest)
+        self.notify_result(&latest)?;
+        Ok(Some(latest))
+    }
+}
+
+/// How often a watch waiting for its next check looks for a reload request.
+const RELOAD_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Why a watch waiting for its next check woke up.
+enum Wake {
+    /// The next check is due
+    Elapsed,
+    /// A configuration reload was requested
+    Reload,
+    /// An on-demand check was requested
+    Trigger,
+}
+
+/// An implementation for the WatchRunner, which is the daemon loop
+pub struct WatchRunner {
+    runner: CheckRunner,
+    scheduler: Scheduler,
+    prune_ttl: Option<Duration>,
+    alerts: Option<AlertPolicy>,
+}
+
+impl WatchRunner {
+    /// Builds an instance around a CheckRunner
+    pub fn new(runner: CheckRunner, scheduler: Scheduler) -> Self {
+        Self {
+            runner,
+            scheduler,
+            prune_ttl: None,
+            alerts: None,
+        }
+    }
+
+    /// Sends operational alerts about failing checks, distinct from the availability notifications.
+    pub fn with_alerts(mut self, alerts: Option<AlertPolicy>) -> Self {
+        self.alerts = alerts;
+        self
+    }
+
+    /// Sends an operational alert, if any. Its failure is reported, but does not stop the watch.
+    fn send_alert(alerts: &AlertPolicy, alert: Option<OperationalAlert>) {
+        let Some(alert) = alert else {
+            return;
+        };
+        println!("{}", format!("Alerting: {alert}").yellow());
+        if let Err(error) = alerts.send(&alert) {
+            eprintln!(
+                "{}",
+                format!(
+                    "while sending alert through {}: {error}",
+                    alerts.notifier_name()
+                )
+                .red()
+            );
+        }
+    }
+
+    /// Prunes the stored states older than `ttl` after every check,
+    /// so that a long running watch does not accumulate the states of forgotten servers.
+    pub fn with_prune(mut self, ttl: Option<Duration>) -> Self {
+        self.prune_ttl = ttl;
+        self
+    }
+
+    /// Tells if a failed check is worth retrying, from the first library error in its chain.
+    pub(crate) fn is_transient(error: &anyhow::Error) -> bool {
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<LibError>())
+            .is_none_or(LibError::is_transient)
+    }
+
+    /// Waits until a deadline, unless a reload or a check is requested meanwhile.
+    fn sleep(deadline: Instant) -> Wake {
+        loop {
+            if reload::take() {
+                return Wake::Reload;
+            }
+            if trigger::take() {
+                return Wake::Trigger;
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Wake::Elapsed;
+            }
+            thread::sleep(remaining.min(RELOAD_POLL_INTERVAL));
+        }
+    }
+
+    /// Warns when the last successful check of the provider is too old, after a failed check.
+    fn alert_stale(&self, alerts: &AlertPolicy) {
+        let provider_name = self.runner.provider_name();
+        match self.runner.storage().get_last_success(provider_name) {
+            Ok(last_success) => Self::send_alert(
+                alerts,
+                alerts.on_stale(provider_name, last_success, crate::storage::get_timestamp()),
+            ),
+            Err(error) => eprintln!(
+                "{}",
+                format!("while reading last successful check: {error}").red()
+            ),
+        }
+    }
+
+    /// Checks once, alerting about consecutive failures if required, then prunes if required.
+    fn check(&self) -> anyhow::Result<()> {
+        let provider_name = self.runner.provider_name();
+        match self.runner.check_once() {
+            Ok(_) => {
+                if let Some(alerts) = &self.alerts {
+                    Self::send_alert(alerts, alerts.on_success(provider_name));
+                }
+            }
+            Err(error) => {
+                let permanent = !Self::is_transient(&error);
+                if let Some(alerts) = &self.alerts {
+                    Self::send_alert(alerts, alerts.on_failure(provider_name, &error, permanent));
+                    self.alert_stale(alerts);
+                }
+                if permanent {
+                    return Err(error.context("permanent error, giving up"));
+                }
+                eprintln!("{}", format!("{error:#}").red());
+            }
+        }
+
+        if let Some(ttl) = self.prune_ttl {
+            if let Err(error) = self.runner.prune(ttl) {
+                eprintln!("{}", format!("{error:#}").red());
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks until a reload is requested, waiting for the scheduler between checks.
+    /// The transient failure of a single check is reported, and does not stop the loop,
+    /// while a permanent one (invalid credentials, unknown server, ...) aborts it.
+    /// As the states are stored, the watch can be resumed right away with a new configuration.
+    /// Checks requested through the trigger listener happen right away, outside the schedule.
+    pub fn watch(&self) -> anyhow::Result<()> {
+        trigger::set_target(self.runner.provider_name());
+        let mut first = true;
+        loop {
+            let delay = self
+                .scheduler
+                .next_delay(first)
+                .context("while computing the next check time")?;
+            first = false;
+
+            let deadline = Instant::now() + delay;
+            loop {
+                match Self::sleep(deadline) {
+                    Wake::Reload => return Ok(()),
+                    Wake::Trigger => {
+                        println!("Checking on demand");
+                        self.check()?;
+                    }
+                    Wake::Elapsed => break,
+                }
+            }
+            self.check()?;
+        }
     }
 }
@@ -0,0 +1,205 @@
+use super::{
+    Factory, FailurePolicy, LocatedServer, PartialFailures, ProviderFactoryTrait, ProviderTrait,
+    ServerInfo, StockLevel,
+};
+use crate::LibError;
+
+// AGGREGATE implementation (several providers as one)
+
+/// Common name to identify the provider
+pub const AGGREGATE_NAME: &str = "aggregate";
+
+/// Common environment variable to select the aggregated providers.
+const ENV_AGGREGATE_PROVIDERS: &str = "AGGREGATE_PROVIDERS";
+
+/// Environment variable to optionally go on with the other providers when one fails.
+const ENV_AGGREGATE_FAILURE_POLICY: &str = "AGGREGATE_FAILURE_POLICY";
+
+/// Separates the name of a provider from its own server reference, like `ovh/1801sk12`.
+const AGGREGATE_SEPARATOR: char = '/';
+
+/// Wraps several providers, so that a single check and notifier can watch all of them.
+/// Their server references are prefixed by their name, like `ovh/1801sk12`.
+pub struct Aggregate {
+    providers: Vec<Box<dyn ProviderTrait>>,
+    failure_policy: FailurePolicy,
+}
+
+impl Aggregate {
+    /// Builds a new instance from providers which are already built.
+    pub fn new(providers: Vec<Box<dyn ProviderTrait>>) -> Result<Self, LibError> {
+        if providers.is_empty() {
+            return Err(LibError::ValueError {
+                name: "found no aggregated provider".into(),
+                value: String::new(),
+            });
+        }
+        Ok(Self {
+            providers,
+            failure_policy: FailurePolicy::default(),
+        })
+    }
+
+    /// Selects what happens when a provider fails (defaults to fail-fast).
+    pub fn with_failure_policy(mut self, failure_policy: FailurePolicy) -> Self {
+        self.failure_policy = failure_policy;
+        self
+    }
+
+    /// Builds a new instance from provider names, each of them being built from environment variables.
+    pub fn from_names(names: &[String]) -> Result<Self, LibError> {
+        let mut providers: Vec<Box<dyn ProviderTrait>> = Vec::new();
+        for name in names {
+            // an aggregate would build itself endlessly
+            if name == AGGREGATE_NAME || providers.iter().any(|known| known.name() == name) {
+                return Err(LibError::ValueError {
+                    name: ENV_AGGREGATE_PROVIDERS.to_string(),
+                    value: names.join(","),
+                });
+            }
+            providers.push(Factory::from_env_by_name(name)?);
+        }
+        Self::new(providers)
+    }
+
+    /// Finds the provider of a prefixed server reference, and its own reference.
+    fn split<'a>(&self, server: &'a str) -> Result<(&dyn ProviderTrait, &'a str), LibError> {
+        server
+            .split_once(AGGREGATE_SEPARATOR)
+            .and_then(|(name, reference)| {
+                self.providers
+                    .iter()
+                    .find(|provider| provider.name() == name)
+                    .map(|provider| (provider.as_ref(), reference))
+            })
+            .ok_or(LibError::UnknownServer {
+                server: server.to_string(),
+                suggestions: Vec::new(),
+            })
+    }
+}
+
+impl ProviderFactoryTrait for Aggregate {
+    /// Builds an Aggregate provider from environment variables.
+    fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
+        let names_csv = crate::get_env_var(ENV_AGGREGATE_PROVIDERS)?;
+        let names = crate::tokenize_optional_csv_str(&Some(names_csv))?;
+        let failure_policy =
+            crate::get_env_var_parsed::<FailurePolicy>(ENV_AGGREGATE_FAILURE_POLICY)?
+                .unwrap_or_default();
+        Ok(Box::new(
+            Self::from_names(&names)?.with_failure_policy(failure_policy),
+        ))
+    }
+}
+
+impl ProviderTrait for Aggregate {
+    /// Gets the actual name of the provider.
+    fn name(&self) -> &'static str {
+        AGGREGATE_NAME
+    }
+
+    /// Collects the inventory of every provider, prefixing the references.
+    fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        let mut inventory = Vec::new();
+        let mut failures = PartialFailures::new(AGGREGATE_NAME, self.failure_policy);
+        for provider in self.providers.iter() {
+            let part = format!("provider {}", provider.name());
+            let Some(infos) = failures.handle(&part, provider.inventory(all))? else {
+                continue;
+            };
+            for mut info in infos {
+                info.reference =
+                    format!("{}{AGGREGATE_SEPARATOR}{}", provider.name(), info.reference);
+                inventory.push(info);
+            }
+        }
+        failures.finish()?;
+        Ok(inventory)
+    }
+
+    /// Checks the provider of a prefixed server reference.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        let (provider, reference) = self.split(server)?;
+        provider.check(reference)
+    }
+
+    /// Checks the servers of every provider at once, so that each can use a single query.
+    /// The servers of failed providers are left out, with the best-effort policy.
+    fn check_many(&self, servers: &[String]) -> Result<Vec<(String, bool)>, LibError> {
+        Ok(self
+            .check_stock(servers)?
+            .into_iter()
+            .map(|(server, available, _)| (server, available))
+            .collect())
+    }
+
+    /// Same as `check_many()`, with the stock level of the available servers.
+    fn check_stock(
+        &self,
+        servers: &[String],
+    ) -> Result<Vec<(String, bool, Option<StockLevel>)>, LibError> {
+        Ok(self
+            .check_locations(servers)?
+            .into_iter()
+            .map(|(server, available, stock, _)| (server, available, stock))
+            .collect())
+    }
+
+    /// Same as `check_stock()`, with the locations of the available servers.
+    fn check_locations(&self, servers: &[String]) -> Result<Vec<LocatedServer>, LibError> {
+        let mut states: Vec<LocatedServer> = Vec::new();
+        let mut failures = PartialFailures::new(AGGREGATE_NAME, self.failure_policy);
+        for provider in self.providers.iter() {
+            let mut references = Vec::new();
+            for server in servers {
+                let (owner, reference) = self.split(server)?;
+                if owner.name() == provider.name() {
+                    references.push(reference.to_string());
+                }
+            }
+            if references.is_empty() {
+                continue;
+            }
+            let part = format!("provider {}", provider.name());
+            let Some(provider_states) =
+                failures.handle(&part, provider.check_locations(&references))?
+            else {
+                continue;
+            };
+            for (reference, available, stock, locations) in provider_states {
+                states.push((
+                    format!("{}{AGGREGATE_SEPARATOR}{reference}", provider.name()),
+                    available,
+                    stock,
+                    locations,
+                ));
+            }
+        }
+        failures.finish()?;
+
+        // keep the order of the request
+        states.sort_by_key(|(server, _, _, _)| servers.iter().position(|known| known == server));
+        Ok(states)
+    }
+
+    /// Tells if any of the providers knows the locations of its servers.
+    fn supports_locations(&self) -> bool {
+        self.providers
+            .iter()
+            .any(|provider| provider.supports_locations())
+    }
+
+    /// Verifies that the server reference is prefixed by an aggregated provider,
+    /// and that this provider accepts its own reference.
+    fn validate_server(&self, server: &str) -> Result<(), LibError> {
+        let (provider, reference) = self.split(server)?;
+        provider.validate_server(reference)
+    }
+
+    /// Gets the order page from the provider of a prefixed server reference.
+    fn order_url(&self, server: &str) -> Option<String> {
+        let (provider, reference) = self.split(server).ok()?;
+        provider.order_url(reference)
+    }
+}
@@ -0,0 +1,437 @@
+use super::{Pagination, ProviderFactoryTrait, ProviderTrait, ServerInfo};
+use crate::aws::{self as sigv4, Credentials};
+use crate::LibError;
+use http::StatusCode;
+use reqwest::blocking::Client;
+
+// AWS EC2 Dedicated Hosts implementation (Query API)
+
+/// Common name to identify the provider
+pub const AWS_NAME: &str = "aws";
+
+/// Common environment variables selecting the region, as the AWS tools do.
+const ENV_AWS_REGION: &str = "AWS_REGION";
+const ENV_AWS_DEFAULT_REGION: &str = "AWS_DEFAULT_REGION";
+
+/// Common environment variable to eventually restrict the instance families.
+const ENV_AWS_INSTANCE_FAMILIES: &str = "AWS_INSTANCE_FAMILIES";
+
+/// Common environment variable to eventually restrict the availability zones.
+const ENV_AWS_AVAILABILITY_ZONES: &str = "AWS_AVAILABILITY_ZONES";
+
+/// Signature scope of the service.
+const EC2_SERVICE: &str = "ec2";
+
+/// Version of the Query API.
+const EC2_VERSION: &str = "2016-11-15";
+
+/// Content type of the Query API requests.
+const EC2_CONTENT_TYPE: &str = "application/x-www-form-urlencoded; charset=utf-8";
+
+/// Error code of a dry run which would have succeeded.
+const EC2_DRY_RUN_SUCCESS: &str = "DryRunOperation";
+
+/// Error codes of a dry run which tells that no host can be allocated.
+const EC2_UNAVAILABLE_CODES: &[&str] = &[
+    "InsufficientHostCapacity",
+    "InsufficientCapacity",
+    "Unsupported",
+    "UnsupportedHostConfiguration",
+];
+
+/// Offerings are listed by pages, each giving a token to the next one.
+const AWS_PAGINATION: Pagination = Pagination::Cursor {
+    cursor: "NextToken",
+};
+
+/// Average number of hours in a month, to compare hourly and upfront prices.
+const HOURS_PER_MONTH: f64 = 730.0;
+
+/// A reservation offering, with only interesting fields kept.
+struct AwsOffering {
+    instance_family: String,
+    /// Monthly cost, upfront payment included
+    monthly_price: Option<f64>,
+}
+
+impl AwsOffering {
+    /// Reads an `<item>` of the `offeringSet`
+    fn from_xml(item: &str) -> Option<Self> {
+        let instance_family = sigv4::xml_value(item, "instanceFamily")?;
+        let number = |tag: &str| {
+            sigv4::xml_value(item, tag)
+                .and_then(|value| value.parse::<f64>().ok())
+                .unwrap_or_default()
+        };
+        let hours = number("duration") / 3600.0;
+        let monthly_price = match hours > 0.0 {
+            true => {
+                Some((number("upfrontPrice") / hours + number("hourlyPrice")) * HOURS_PER_MONTH)
+            }
+            false => None,
+        };
+        Some(Self {
+            instance_family,
+            monthly_price,
+        })
+    }
+}
+
+/// Joins an instance family with the availability zones where a host could be allocated.
+struct AwsServerType {
+    instance_family: String,
+    monthly_price: Option<f64>,
+    zones: Vec<String>,
+}
+
+impl AwsServerType {
+    /// Convenience function to detemine availability
+    fn is_available(&self) -> bool {
+        !self.zones.is_empty()
+    }
+
+    /// Tells if the server type matches the requested server reference
+    fn is_matching(&self, server: &str) -> bool {
+        self.instance_family == server
+    }
+}
+
+// I prefer the From trait, as i can pass references
+impl From<&AwsServerType> for ServerInfo {
+    /// Extracts only interesting information which is common to all providers
+    fn from(server_type: &AwsServerType) -> Self {
+        ServerInfo {
+            reference: format!(
+                "{} (dedicated host@{})",
+                server_type.instance_family,
+                server_type.zones.join(",")
+            ),
+            memory: "N/A".to_string(),
+            storage: "N/A".to_string(),
+            available: server_type.is_available(),
+            price: server_type.monthly_price,
+            cpu: None,
+            cores: None,
+            bandwidth: None,
+            stock: None,
+            order_url: None,
+            range: None,
+        }
+    }
+}
+
+/// Gets the dedicated host instance families offered for reservation, and tells
+/// in which availability zones a host could be allocated, through dry runs.
+pub struct Aws {
+    client: Client,
+    credentials: Credentials,
+    region: String,
+    instance_families: Vec<String>,
+    availability_zones: Vec<String>,
+}
+
+/// Builds an Aws provider without relying on environment variables.
+#[derive(Default)]
+pub struct AwsBuilder {
+    credentials: Option<Credentials>,
+    region: String,
+    instance_families: Vec<String>,
+    availability_zones: Vec<String>,
+}
+
+impl AwsBuilder {
+    /// Sets the credentials used to sign the requests.
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Sets the region, like `eu-west-3`.
+    pub fn region(mut self, region: &str) -> Self {
+        self.region = region.to_string();
+        self
+    }
+
+    /// Sets the instance families to keep, like `m5`, all offered ones being used when none is set.
+    pub fn instance_families<I, S>(mut self, instance_families: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.instance_families = instance_families.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the availability zones to try, like `eu-west-3a`, all of the region being used when none is set.
+    pub fn availability_zones<I, S>(mut self, availability_zones: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.availability_zones = availability_zones.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Builds the provider, verifying the settings.
+    pub fn build(self) -> Result<Aws, LibError> {
+        let credentials = self.credentials.ok_or_else(|| LibError::ValueError {
+            name: "missing aws credentials".into(),
+            value: String::new(),
+        })?;
+
+        if self.region.is_empty() {
+            return Err(LibError::ValueError {
+                name: "found empty aws region".into(),
+                value: self.region,
+            });
+        }
+
+        // names are lower case in the API
+        let lower = |values: Vec<String>| -> Vec<String> {
+            values.iter().map(|value| value.to_lowercase()).collect()
+        };
+
+        // construct the object if everything is ok
+        Ok(Aws {
+            client: crate::client::build_client_for(AWS_NAME)?,
+            credentials,
+            region: self.region,
+            instance_families: lower(self.instance_families),
+            availability_zones: lower(self.availability_zones),
+        })
+    }
+}
+
+impl Aws {
+    /// Starts building a new instance.
+    pub fn builder() -> AwsBuilder {
+        AwsBuilder::default()
+    }
+
+    /// Sends a signed Query API action, and returns the status and the body of the response.
+    fn send_action(&self, parameters: &[(&str, &str)]) -> Result<(StatusCode, String), LibError> {
+        let body = sigv4::encode_form(parameters);
+
+        let host = format!("{EC2_SERVICE}.{}.amazonaws.com", self.region);
+        let headers = sigv4::sign_post(
+            &self.credentials,
+            EC2_SERVICE,
+            &self.region,
+            &host,
+            EC2_CONTENT_TYPE,
+            &body,
+        );
+
+        let mut request = self.client.post(format!("https://{host}/")).body(body);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        let response = crate::recording::send(&self.client, request)?;
+
+        let status = response.status();
+        let text = response.text()?;
+        Ok((status, text))
+    }
+
+    /// Builds the error of a failed action, from the code and message of the response
+    fn build_error(status: StatusCode, text: &str) -> LibError {
+        LibError::ApiStatusError {
+            status,
+            message: format!(
+                "Error during AWS query: {} {}",
+                sigv4::xml_value(text, "Code").unwrap_or_else(|| status.to_string()),
+                sigv4::xml_value(text, "Message").unwrap_or_default()
+            ),
+        }
+    }
+
+    /// Sends an action which must succeed, and returns the body of its response.
+    fn query(&self, parameters: &[(&str, &str)]) -> Result<String, LibError> {
+        let (status, text) = self.send_action(parameters)?;
+        if !status.is_success() {
+            return Err(Self::build_error(status, &text));
+        }
+        Ok(text)
+    }
+
+    /// Gets the names of the availability zones to try.
+    fn get_availability_zones(&self) -> Result<Vec<String>, LibError> {
+        if !self.availability_zones.is_empty() {
+            return Ok(self.availability_zones.clone());
+        }
+
+        let text = self.query(&[
+            ("Action", "DescribeAvailabilityZones"),
+            ("Filter.1.Name", "zone-type"),
+            ("Filter.1.Value.1", "availability-zone"),
+            ("Version", EC2_VERSION),
+        ])?;
+        Ok(sigv4::xml_values(&text, "zoneName")
+            .into_iter()
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Gets every reservation offering, following the pagination.
+    fn get_offerings(&self) -> Result<Vec<AwsOffering>, LibError> {
+        AWS_PAGINATION.fetch_all(|pagination| {
+            let mut parameters = vec![
+                ("Action", "DescribeHostReservationOfferings"),
+                ("MaxResults", "500"),
+                ("Version", EC2_VERSION),
+            ];
+            parameters.extend(
+                pagination
+                    .iter()
+                    .map(|(name, value)| (*name, value.as_str())),
+            );
+            let text = self.query(&parameters)?;
+
+            let offerings = sigv4::xml_values(&text, "item")
+                .into_iter()
+                .filter_map(AwsOffering::from_xml)
+                .collect();
+            Ok((offerings, sigv4::xml_value(&text, "nextToken")))
+        })
+    }
+
+    /// Tells if a host of the instance family could be allocated in the availability zone,
+    /// through a dry run which never allocates anything.
+    fn can_allocate(&self, instance_family: &str, zone: &str) -> Result<bool, LibError> {
+        let (status, text) = self.send_action(&[
+            ("Action", "AllocateHosts"),
+            ("AvailabilityZone", zone),
+            ("DryRun", "true"),
+            ("InstanceFamily", instance_family),
+            ("Quantity", "1"),
+            ("Version", EC2_VERSION),
+        ])?;
+
+        match sigv4::xml_value(&text, "Code").as_deref() {
+            Some(EC2_DRY_RUN_SUCCESS) => Ok(true),
+            Some(code) if EC2_UNAVAILABLE_CODES.contains(&code) => Ok(false),
+            _ => Err(Self::build_error(status, &text)),
+        }
+    }
+
+    /// Gets the offered instance families, with the availability zones where a host could be allocated.
+    /// When families are configured, only those are tried, even if they are not offered for reservation.
+    fn get_server_types(&self) -> Result<Vec<AwsServerType>, LibError> {
+        let mut server_types: Vec<AwsServerType> = Vec::new();
+        for offering in self.get_offerings()? {
+            match server_types
+                .iter_mut()
+                .find(|server_type| server_type.instance_family == offering.instance_family)
+            {
+                // keep the cheapest offering of every family
+                Some(server_type) => {
+                    if let Some(price) = offering.monthly_price {
+                        if server_type.monthly_price.is_none_or(|known| price < known) {
+                            server_type.monthly_price = Some(price);
+                        }
+                    }
+                }
+                None => server_types.push(AwsServerType {
+                    instance_family: offering.instance_family,
+                    monthly_price: offering.monthly_price,
+                    zones: Vec::new(),
+                }),
+            }
+        }
+
+        if !self.instance_families.is_empty() {
+            server_types.retain(|server_type| {
+                self.instance_families
+                    .contains(&server_type.instance_family)
+            });
+            for instance_family in self.instance_families.iter() {
+                if !server_types
+                    .iter()
+                    .any(|server_type| server_type.is_matching(instance_family))
+                {
+                    server_types.push(AwsServerType {
+                        instance_family: instance_family.clone(),
+                        monthly_price: None,
+                        zones: Vec::new(),
+                    });
+                }
+            }
+        }
+        server_types.sort_by(|a, b| a.instance_family.cmp(&b.instance_family));
+
+        let zones = self.get_availability_zones()?;
+        for server_type in server_types.iter_mut() {
+            for zone in zones.iter() {
+                if self.can_allocate(&server_type.instance_family, zone)? {
+                    server_type.zones.push(zone.clone());
+                }
+            }
+        }
+        Ok(server_types)
+    }
+}
+
+impl ProviderFactoryTrait for Aws {
+    /// Builds an Aws provider from environment variables.
+    fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
+        let region = crate::get_env_var(ENV_AWS_REGION)
+            .or_else(|_| crate::get_env_var(ENV_AWS_DEFAULT_REGION))?;
+        let families_csv = crate::get_env_var_option(ENV_AWS_INSTANCE_FAMILIES);
+        let zones_csv = crate::get_env_var_option(ENV_AWS_AVAILABILITY_ZONES);
+        Ok(Box::new(
+            Self::builder()
+                .credentials(Credentials::from_env()?)
+                .region(&region)
+                .instance_families(crate::tokenize_optional_csv_str(&families_csv)?)
+                .availability_zones(crate::tokenize_optional_csv_str(&zones_csv)?)
+                .build()?,
+        ))
+    }
+}
+
+impl ProviderTrait for Aws {
+    /// Gets the actual name of the provider.
+    fn name(&self) -> &'static str {
+        AWS_NAME
+    }
+
+    /// Collects provider inventory.
+    fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        Ok(self
+            .get_server_types()?
+            .iter()
+            .filter(|server_type| server_type.is_available() || all)
+            .map(|server_type| server_type.into())
+            .collect())
+    }
+
+    /// Checks provider for the availability of a given instance family.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        self.get_server_types()?
+            .iter()
+            .find(|server_type| server_type.is_matching(server))
+            .map(|server_type| server_type.is_available())
+            .ok_or(LibError::UnknownServer {
+                server: server.to_string(),
+                suggestions: Vec::new(),
+            })
+    }
+
+    /// Checks several instance families from a single round of dry runs.
+    fn check_many(&self, servers: &[String]) -> Result<Vec<(String, bool)>, LibError> {
+        let server_types = self.get_server_types()?;
+        servers
+            .iter()
+            .map(|server| {
+                server_types
+                    .iter()
+                    .find(|server_type| server_type.is_matching(server))
+                    .map(|server_type| (server.clone(), server_type.is_available()))
+                    .ok_or(LibError::UnknownServer {
+                        server: server.to_string(),
+                        suggestions: Vec::new(),
+                    })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,316 @@
+use super::{ProviderFactoryTrait, ProviderTrait, ServerInfo};
+use crate::LibError;
+use http::Method;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde::Deserialize;
+
+// Cherry Servers implementation
+
+/// Common name to identify the provider
+pub const CHERRYSERVERS_NAME: &str = "cherryservers";
+
+/// Common environment variable to input your Cherry Servers API key.
+const ENV_CHERRYSERVERS_API_KEY: &str = "CHERRYSERVERS_API_KEY";
+
+/// Common environment variable to input your Cherry Servers team identifier.
+const ENV_CHERRYSERVERS_TEAM_ID: &str = "CHERRYSERVERS_TEAM_ID";
+
+/// Common environment variable to eventually filter the regions.
+const ENV_CHERRYSERVERS_REGIONS: &str = "CHERRYSERVERS_REGIONS";
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct CherryServersPlan {
+    id: u64,
+    name: String,
+    slug: String,
+    specs: CherryServersPlanSpecs,
+    #[serde(default)]
+    available_regions: Vec<CherryServersPlanRegion>,
+}
+
+impl CherryServersPlan {
+    /// Convenience function to detemine availability
+    fn is_available(&self) -> bool {
+        self.available_regions
+            .iter()
+            .any(|region| region.stock_qty > 0)
+    }
+
+    /// Tells if the plan matches the requested server reference
+    fn is_matching(&self, server: &str) -> bool {
+        self.slug == server || self.id.to_string() == server
+    }
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct CherryServersPlanSpecs {
+    cpus: Option<CherryServersPlanCpus>,
+    memory: Option<CherryServersPlanMemory>,
+    #[serde(default)]
+    storage: Vec<CherryServersPlanStorage>,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct CherryServersPlanCpus {
+    count: u32,
+    name: String,
+    /// Cores of every processor
+    cores: Option<u32>,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct CherryServersPlanMemory {
+    total: u32,
+    unit: String,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct CherryServersPlanStorage {
+    count: u32,
+    size: f64,
+    unit: String,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct CherryServersPlanRegion {
+    slug: String,
+    stock_qty: u32,
+}
+
+// I prefer the From trait, as i can pass references
+impl From<&CherryServersPlan> for ServerInfo {
+    /// Extracts only interesting information which is common to all providers
+    fn from(plan: &CherryServersPlan) -> Self {
+        let cpu = match &plan.specs.cpus {
+            Some(cpus) => format!("{}x{}", cpus.count, cpus.name),
+            None => "N/A".to_string(),
+        };
+
+        let memory = match &plan.specs.memory {
+            Some(memory) => format!("{}{}", memory.total, memory.unit),
+            None => "N/A".to_string(),
+        };
+
+        let mut storage = plan
+            .specs
+            .storage
+            .iter()
+            .map(|disk| {
+                format!(
+                    "{}x{}{}{}",
+                    disk.count,
+                    disk.size,
+                    disk.unit,
+                    disk.kind.as_deref().unwrap_or_default()
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("+");
+        storage.retain(|c| !c.is_whitespace());
+        if storage.is_empty() {
+            storage = "N/A".to_string();
+        }
+
+        let regions = plan
+            .available_regions
+            .iter()
+            .map(|region| region.slug.clone())
+            .collect::<Vec<String>>()
+            .join(",");
+
+        ServerInfo {
+            reference: format!("{} ({} {}@{})", plan.slug, plan.name, cpu, regions),
+            memory,
+            storage,
+            available: plan.is_available(),
+            price: None,
+            cpu: plan.specs.cpus.as_ref().map(|cpus| cpus.name.clone()),
+            cores: plan
+                .specs
+                .cpus
+                .as_ref()
+                .and_then(|cpus| Some(cpus.count * cpus.cores?)),
+            bandwidth: None,
+            stock: None,
+            order_url: None,
+            range: None,
+        }
+    }
+}
+
+/// Gets server inventory and availability.
+pub struct CherryServers {
+    client: Client,
+    api_key: String,
+    team_id: String,
+    regions: Vec<String>,
+}
+
+/// Builds a CherryServers provider without relying on environment variables.
+#[derive(Default)]
+pub struct CherryServersBuilder {
+    api_key: String,
+    team_id: String,
+    regions: Vec<String>,
+}
+
+impl CherryServersBuilder {
+    /// Sets the API key.
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = api_key.to_string();
+        self
+    }
+
+    /// Sets the numeric team identifier.
+    pub fn team_id(mut self, team_id: &str) -> Self {
+        self.team_id = team_id.to_string();
+        self
+    }
+
+    /// Sets the regions to keep, all of them being used when none is set.
+    pub fn regions<I, S>(mut self, regions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.regions = regions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Builds the provider, verifying the settings.
+    pub fn build(self) -> Result<CherryServers, LibError> {
+        if self.api_key.is_empty() {
+            return Err(LibError::ValueError {
+                name: "found empty cherryservers api key".into(),
+                value: self.api_key,
+            });
+        }
+
+        // team identifiers are numeric
+        if self.team_id.parse::<u64>().is_err() {
+            return Err(LibError::ValueError {
+                name: "malformed cherryservers team id".into(),
+                value: self.team_id,
+            });
+        }
+
+        // construct the object if everything is ok
+        Ok(CherryServers {
+            client: crate::client::build_client_for(CHERRYSERVERS_NAME)?,
+            api_key: self.api_key,
+            team_id: self.team_id,
+            regions: self.regions,
+        })
+    }
+}
+
+impl CherryServers {
+    /// Starts building a new instance.
+    pub fn builder() -> CherryServersBuilder {
+        CherryServersBuilder::default()
+    }
+
+    /// Wrapper for automatic handling of authentication
+    fn create_authenticated_request_builder(&self, method: Method, url: &str) -> RequestBuilder {
+        self.client
+            .request(method, url)
+            .header("Authorization", format!("Bearer {}", &self.api_key))
+    }
+
+    /// Fallback error handler for queries
+    fn do_error_if_not_successful(response: &Response) -> Result<(), LibError> {
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        Err(LibError::ApiStatusError {
+            status: response.status(),
+            message: format!(
+                "Error during Cherry Servers query: code {}",
+                response.status()
+            ),
+        })
+    }
+
+    /// Gets all baremetal plans, keeping only the desired regions.
+    fn get_plans(&self) -> Result<Vec<CherryServersPlan>, LibError> {
+        let url = format!(
+            "https://api.cherryservers.com/v1/teams/{}/plans",
+            self.team_id
+        );
+        let response = crate::recording::send(
+            &self.client,
+            self.create_authenticated_request_builder(Method::GET, &url)
+                .query(&[("type", "baremetal")]),
+        )?;
+
+        // fallback error handler
+        Self::do_error_if_not_successful(&response)?;
+
+        // reqwest deserialize
+        let mut plans = response.json::<Vec<CherryServersPlan>>()?;
+
+        // if we do not filter on regions, any of them will be fine
+        if !self.regions.is_empty() {
+            for plan in plans.iter_mut() {
+                plan.available_regions
+                    .retain(|region| self.regions.contains(&region.slug));
+            }
+        }
+
+        Ok(plans)
+    }
+}
+
+impl ProviderFactoryTrait for CherryServers {
+    /// Builds a CherryServers provider from environment variables.
+    fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
+        let api_key = crate::get_env_var(ENV_CHERRYSERVERS_API_KEY)?;
+        let team_id = crate::get_env_var(ENV_CHERRYSERVERS_TEAM_ID)?;
+        let regions_csv = crate::get_env_var_option(ENV_CHERRYSERVERS_REGIONS);
+        Ok(Box::new(
+            Self::builder()
+                .api_key(&api_key)
+                .team_id(&team_id)
+                .regions(crate::tokenize_optional_csv_str(&regions_csv)?)
+                .build()?,
+        ))
+    }
+}
+
+impl ProviderTrait for CherryServers {
+    /// Gets the actual name of the provider.
+    fn name(&self) -> &'static str {
+        CHERRYSERVERS_NAME
+    }
+
+    /// Collects provider inventory.
+    fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        Ok(self
+            .get_plans()?
+            .iter()
+            .filter(|plan| plan.is_available() || all)
+            .map(|plan| plan.into())
+            .collect())
+    }
+
+    /// Checks provider for the availability of a given server type.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        self.get_plans()?
+            .iter()
+            .find(|plan| plan.is_matching(server))
+            .map(|plan| plan.is_available())
+            .ok_or(LibError::UnknownServer {
+                server: server.to_string(),
+                suggestions: Vec::new(),
+            })
+    }
+}
@@ -0,0 +1,165 @@
+use super::{ProviderFactoryTrait, ProviderTrait, ServerInfo, StockLevel};
+use crate::LibError;
+use serde::Deserialize;
+use std::time::Duration;
+
+// Command (external plugin) implementation
+
+/// Common name to identify the provider
+pub const COMMAND_NAME: &str = "command";
+
+/// Common environment variable to select the command line printing the inventory.
+const ENV_COMMAND_PROVIDER_EXECUTABLE: &str = "COMMAND_PROVIDER_EXECUTABLE";
+
+/// Environment variable to optionally bound the execution time, in seconds.
+const ENV_COMMAND_PROVIDER_TIMEOUT: &str = "COMMAND_PROVIDER_TIMEOUT";
+
+/// Timeout used when none is configured.
+const COMMAND_PROVIDER_DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Used for command output deserialisation
+#[derive(Deserialize)]
+struct CommandServerInfo {
+    reference: String,
+    memory: Option<String>,
+    storage: Option<String>,
+    available: bool,
+    price: Option<f64>,
+    cpu: Option<String>,
+    cores: Option<u32>,
+    bandwidth: Option<u64>,
+    stock: Option<StockLevel>,
+    order_url: Option<String>,
+    range: Option<String>,
+}
+
+// I prefer the From trait, as i can pass references
+impl From<&CommandServerInfo> for ServerInfo {
+    /// Extracts only interesting information which is common to all providers
+    fn from(info: &CommandServerInfo) -> Self {
+        ServerInfo {
+            reference: info.reference.clone(),
+            memory: info.memory.clone().unwrap_or("N/A".to_string()),
+            storage: info.storage.clone().unwrap_or("N/A".to_string()),
+            available: info.available,
+            price: info.price,
+            cpu: info.cpu.clone(),
+            cores: info.cores,
+            bandwidth: info.bandwidth,
+            stock: info.stock,
+            order_url: info.order_url.clone(),
+            range: info.range.clone(),
+        }
+    }
+}
+
+/// Gets server inventory and availability from an external executable,
+/// which must print a json array of server information on its stdout.
+pub struct CommandProvider {
+    command: String,
+    timeout: Duration,
+}
+
+/// Builds a CommandProvider without relying on environment variables.
+pub struct CommandProviderBuilder {
+    command: String,
+    timeout: Duration,
+}
+
+impl Default for CommandProviderBuilder {
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            timeout: COMMAND_PROVIDER_DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl CommandProviderBuilder {
+    /// Sets the command line, run through the shell.
+    pub fn command(mut self, command: &str) -> Self {
+        self.command = command.to_string();
+        self
+    }
+
+    /// Sets how long the command may run before being killed (defaults to 60 seconds).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Builds the provider, verifying the settings.
+    pub fn build(self) -> Result<CommandProvider, LibError> {
+        if self.command.is_empty() {
+            return Err(LibError::ValueError {
+                name: "found empty provider command".into(),
+                value: self.command,
+            });
+        }
+
+        Ok(CommandProvider {
+            command: self.command,
+            timeout: self.timeout,
+        })
+    }
+}
+
+impl CommandProvider {
+    /// Starts building a new instance.
+    pub fn builder() -> CommandProviderBuilder {
+        CommandProviderBuilder::default()
+    }
+
+    /// Runs the command and parses its inventory.
+    fn get_servers(&self) -> Result<Vec<CommandServerInfo>, LibError> {
+        let output = crate::command::run(&self.command, "", &[], self.timeout)?;
+        serde_json::from_str(&output).map_err(|source| LibError::JsonError {
+            provider: Some(COMMAND_NAME.to_string()),
+            source,
+        })
+    }
+}
+
+impl ProviderFactoryTrait for CommandProvider {
+    /// Builds a CommandProvider from environment variables.
+    fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
+        let command = crate::get_env_var(ENV_COMMAND_PROVIDER_EXECUTABLE)?;
+        let mut builder = Self::builder().command(&command);
+        if let Some(timeout) = crate::get_env_var_option(ENV_COMMAND_PROVIDER_TIMEOUT) {
+            builder = builder.timeout(crate::command::parse_timeout(
+                ENV_COMMAND_PROVIDER_TIMEOUT,
+                &timeout,
+            )?);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl ProviderTrait for CommandProvider {
+    /// Gets the actual name of the provider.
+    fn name(&self) -> &'static str {
+        COMMAND_NAME
+    }
+
+    /// Collects provider inventory.
+    fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        Ok(self
+            .get_servers()?
+            .iter()
+            .filter(|info| info.available || all)
+            .map(|info| info.into())
+            .collect())
+    }
+
+    /// Checks provider for the availability of a given server type.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        self.get_servers()?
+            .iter()
+            .find(|info| info.reference == server)
+            .map(|info| info.available)
+            .ok_or(LibError::UnknownServer {
+                server: server.to_string(),
+                suggestions: Vec::new(),
+            })
+    }
+}
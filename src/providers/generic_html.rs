@@ -0,0 +1,403 @@
+use super::{ProviderFactoryTrait, ProviderTrait, ServerInfo};
+use crate::LibError;
+use reqwest::blocking::Client;
+use scraper::{ElementRef, Html, Selector};
+
+// Generic HTML page implementation (CSS selectors)
+
+/// Common name to identify the provider
+pub const GENERIC_HTML_NAME: &str = "generic-html";
+
+/// Common environment variable to select the page listing the offers.
+const ENV_GENERIC_HTML_URL: &str = "GENERIC_HTML_URL";
+
+/// Common environment variable to select the rows of the offers.
+const ENV_GENERIC_HTML_ROW_SELECTOR: &str = "GENERIC_HTML_ROW_SELECTOR";
+
+/// Common environment variable to select the name of an offer, within its row.
+const ENV_GENERIC_HTML_NAME_SELECTOR: &str = "GENERIC_HTML_NAME_SELECTOR";
+
+/// Common environment variable to eventually select the stock of an offer, within its row.
+const ENV_GENERIC_HTML_STOCK_SELECTOR: &str = "GENERIC_HTML_STOCK_SELECTOR";
+
+/// Common environment variables holding the text telling that an offer is (or is not) in stock.
+const ENV_GENERIC_HTML_IN_STOCK_TEXT: &str = "GENERIC_HTML_IN_STOCK_TEXT";
+const ENV_GENERIC_HTML_OUT_OF_STOCK_TEXT: &str = "GENERIC_HTML_OUT_OF_STOCK_TEXT";
+
+/// Common environment variables to eventually select more details of an offer, within its row.
+const ENV_GENERIC_HTML_MEMORY_SELECTOR: &str = "GENERIC_HTML_MEMORY_SELECTOR";
+const ENV_GENERIC_HTML_STORAGE_SELECTOR: &str = "GENERIC_HTML_STORAGE_SELECTOR";
+const ENV_GENERIC_HTML_PRICE_SELECTOR: &str = "GENERIC_HTML_PRICE_SELECTOR";
+
+/// Tells from the text of the stock of an offer if it is available.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StockPredicate {
+    /// Available when the text contains this, case insensitively
+    InStockText(String),
+    /// Available unless the text contains this, case insensitively
+    OutOfStockText(String),
+}
+
+impl StockPredicate {
+    /// Applies the predicate to the text of the stock
+    fn is_available(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        match self {
+            Self::InStockText(expected) => text.contains(&expected.to_lowercase()),
+            Self::OutOfStockText(expected) => !text.contains(&expected.to_lowercase()),
+        }
+    }
+}
+
+/// Parses a CSS selector, as supported by browsers.
+fn parse_selector(selector: &str) -> Result<Selector, LibError> {
+    Selector::parse(selector).map_err(|error| LibError::ValueError {
+        name: format!("css selector ({error})"),
+        value: selector.to_string(),
+    })
+}
+
+/// Gets the text of an element and its descendants, with whitespace collapsed.
+/// Words of adjacent elements, like cells, are kept apart.
+fn element_text(element: ElementRef) -> String {
+    element
+        .text()
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Parses the first number of a price, like `1 234,56 €` or `$1,234.56`.
+/// The last separator followed by one or two digits is deemed the decimal one.
+fn parse_price(text: &str) -> Option<f64> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let number: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || matches!(c, '.' | ',' | ' ' | '\u{a0}'))
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let number = number.trim_end_matches(['.', ',']);
+
+    let (integer, decimals) = match number.rfind(['.', ',']) {
+        Some(position) if (1..=2).contains(&(number.len() - position - 1)) => {
+            (&number[..position], &number[position + 1..])
+        }
+        _ => (number, "0"),
+    };
+    let integer: String = integer.chars().filter(char::is_ascii_digit).collect();
+    format!("{integer}.{decimals}").parse().ok()
+}
+
+/// An offer found on the page
+struct GenericHtmlOffer {
+    name: String,
+    memory: Option<String>,
+    storage: Option<String>,
+    price: Option<f64>,
+    available: bool,
+}
+
+impl GenericHtmlOffer {
+    /// Gets the identifier to check the offer with, whitespace being replaced
+    /// so that it remains the first word of the reference.
+    fn id(&self) -> String {
+        self.name
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join("_")
+    }
+
+    /// Tells if the offer matches the requested server reference
+    fn is_matching(&self, server: &str) -> bool {
+        self.id() == server
+    }
+}
+
+// I prefer the From trait, as i can pass references
+impl From<&GenericHtmlOffer> for ServerInfo {
+    /// Extracts only interesting information which is common to all providers
+    fn from(offer: &GenericHtmlOffer) -> Self {
+        let na = || "N/A".to_string();
+        ServerInfo {
+            reference: offer.id(),
+            memory: offer.memory.clone().unwrap_or_else(na),
+            storage: offer.storage.clone().unwrap_or_else(na),
+            available: offer.available,
+            price: offer.price,
+            cpu: None,
+            cores: None,
+            bandwidth: None,
+            stock: None,
+            order_url: None,
+            range: None,
+        }
+    }
+}
+
+/// Gets the offers of any host which only publishes its stock on an HTML page,
+/// by selecting the rows of the offers, then their name and stock within each row.
+pub struct GenericHtml {
+    client: Client,
+    url: String,
+    row: Selector,
+    name: Selector,
+    stock: Option<Selector>,
+    predicate: StockPredicate,
+    memory: Option<Selector>,
+    storage: Option<Selector>,
+    price: Option<Selector>,
+}
+
+/// Builds a GenericHtml provider without relying on environment variables.
+#[derive(Default)]
+pub struct GenericHtmlBuilder {
+    url: String,
+    row: String,
+    name: String,
+    stock: Option<String>,
+    predicate: Option<StockPredicate>,
+    memory: Option<String>,
+    storage: Option<String>,
+    price: Option<String>,
+}
+
+impl GenericHtmlBuilder {
+    /// Sets the URL of the page listing the offers.
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    /// Sets the selector of the rows, one per offer.
+    pub fn row(mut self, selector: &str) -> Self {
+        self.row = selector.to_string();
+        self
+    }
+
+    /// Sets the selector of the name, within a row.
+    pub fn name(mut self, selector: &str) -> Self {
+        self.name = selector.to_string();
+        self
+    }
+
+    /// Sets the selector of the stock, within a row (defaults to the whole row).
+    pub fn stock(mut self, selector: &str) -> Self {
+        self.stock = Some(selector.to_string());
+        self
+    }
+
+    /// Sets how the availability is told from the text of the stock.
+    pub fn predicate(mut self, predicate: StockPredicate) -> Self {
+        self.predicate = Some(predicate);
+        self
+    }
+
+    /// Sets the selector of the memory, within a row.
+    pub fn memory(mut self, selector: &str) -> Self {
+        self.memory = Some(selector.to_string());
+        self
+    }
+
+    /// Sets the selector of the storage, within a row.
+    pub fn storage(mut self, selector: &str) -> Self {
+        self.storage = Some(selector.to_string());
+        self
+    }
+
+    /// Sets the selector of the monthly price, within a row.
+    pub fn price(mut self, selector: &str) -> Self {
+        self.price = Some(selector.to_string());
+        self
+    }
+
+    /// Builds the provider, verifying the settings.
+    pub fn build(self) -> Result<GenericHtml, LibError> {
+        if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+            return Err(LibError::ValueError {
+                name: "generic-html url must be http or https".into(),
+                value: self.url,
+            });
+        }
+
+        let predicate = self.predicate.ok_or_else(|| LibError::ValueError {
+            name: format!(
+                "generic-html requires {ENV_GENERIC_HTML_IN_STOCK_TEXT} or {ENV_GENERIC_HTML_OUT_OF_STOCK_TEXT}"
+            ),
+            value: String::new(),
+        })?;
+
+        let optional = |selector: Option<String>| -> Result<Option<Selector>, LibError> {
+            selector.as_deref().map(parse_selector).transpose()
+        };
+
+        // construct the object if everything is ok
+        Ok(GenericHtml {
+            client: crate::client::build_client_for(GENERIC_HTML_NAME)?,
+            url: self.url,
+            row: parse_selector(&self.row)?,
+            name: parse_selector(&self.name)?,
+            stock: optional(self.stock)?,
+            predicate,
+            memory: optional(self.memory)?,
+            storage: optional(self.storage)?,
+            price: optional(self.price)?,
+        })
+    }
+}
+
+impl GenericHtml {
+    /// Starts building a new instance.
+    pub fn builder() -> GenericHtmlBuilder {
+        GenericHtmlBuilder::default()
+    }
+
+    /// Gets the text of the first element matching the selector within a row, if any
+    fn select_text(row: ElementRef, selector: &Selector) -> Option<String> {
+        row.select(selector)
+            .next()
+            .map(element_text)
+            .filter(|text| !text.is_empty())
+    }
+
+    /// Fetches the page and extracts its offers.
+    /// Offers listed several times are merged, being available if any row is.
+    fn get_offers(&self) -> Result<Vec<GenericHtmlOffer>, LibError> {
+        let response = crate::recording::send(&self.client, self.client.get(&self.url))?;
+
+        if !response.status().is_success() {
+            return Err(LibError::ApiStatusError {
+                status: response.status(),
+                message: format!(
+                    "Error during generic-html query: code {}",
+                    response.status()
+                ),
+            });
+        }
+
+        let html = response.text()?;
+        let document = Html::parse_document(&html);
+
+        let mut offers: Vec<GenericHtmlOffer> = Vec::new();
+        for row in document.select(&self.row) {
+            // rows without a name are headers or decorations
+            let Some(name) = Self::select_text(row, &self.name) else {
+                continue;
+            };
+            let stock = match &self.stock {
+                Some(selector) => Self::select_text(row, selector).unwrap_or_default(),
+                None => element_text(row),
+            };
+            let offer = GenericHtmlOffer {
+                name,
+                memory: self
+                    .memory
+                    .as_ref()
+                    .and_then(|selector| Self::select_text(row, selector)),
+                storage: self
+                    .storage
+                    .as_ref()
+                    .and_then(|selector| Self::select_text(row, selector)),
+                price: self
+                    .price
+                    .as_ref()
+                    .and_then(|selector| Self::select_text(row, selector))
+                    .and_then(|text| parse_price(&text)),
+                available: self.predicate.is_available(&stock),
+            };
+
+            match offers.iter_mut().find(|known| known.id() == offer.id()) {
+                Some(known) => known.available |= offer.available,
+                None => offers.push(offer),
+            }
+        }
+        Ok(offers)
+    }
+}
+
+impl ProviderFactoryTrait for GenericHtml {
+    /// Builds a GenericHtml provider from environment variables.
+    fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
+        let mut builder = Self::builder()
+            .url(&crate::get_env_var(ENV_GENERIC_HTML_URL)?)
+            .row(&crate::get_env_var(ENV_GENERIC_HTML_ROW_SELECTOR)?)
+            .name(&crate::get_env_var(ENV_GENERIC_HTML_NAME_SELECTOR)?);
+
+        match (
+            crate::get_env_var_option(ENV_GENERIC_HTML_IN_STOCK_TEXT),
+            crate::get_env_var_option(ENV_GENERIC_HTML_OUT_OF_STOCK_TEXT),
+        ) {
+            (Some(_), Some(text)) => {
+                return Err(LibError::ValueError {
+                    name: format!(
+                        "{ENV_GENERIC_HTML_OUT_OF_STOCK_TEXT} conflicts with {ENV_GENERIC_HTML_IN_STOCK_TEXT}"
+                    ),
+                    value: text,
+                })
+            }
+            (Some(text), None) => builder = builder.predicate(StockPredicate::InStockText(text)),
+            (None, Some(text)) => builder = builder.predicate(StockPredicate::OutOfStockText(text)),
+            (None, None) => {}
+        }
+
+        if let Some(selector) = crate::get_env_var_option(ENV_GENERIC_HTML_STOCK_SELECTOR) {
+            builder = builder.stock(&selector);
+        }
+        if let Some(selector) = crate::get_env_var_option(ENV_GENERIC_HTML_MEMORY_SELECTOR) {
+            builder = builder.memory(&selector);
+        }
+        if let Some(selector) = crate::get_env_var_option(ENV_GENERIC_HTML_STORAGE_SELECTOR) {
+            builder = builder.storage(&selector);
+        }
+        if let Some(selector) = crate::get_env_var_option(ENV_GENERIC_HTML_PRICE_SELECTOR) {
+            builder = builder.price(&selector);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl ProviderTrait for GenericHtml {
+    /// Gets the actual name of the provider.
+    fn name(&self) -> &'static str {
+        GENERIC_HTML_NAME
+    }
+
+    /// Collects provider inventory.
+    fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        Ok(self
+            .get_offers()?
+            .iter()
+            .filter(|offer| offer.available || all)
+            .map(|offer| offer.into())
+            .collect())
+    }
+
+    /// Checks provider for the availability of a given offer.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        self.get_offers()?
+            .iter()
+            .find(|offer| offer.is_matching(server))
+            .map(|offer| offer.available)
+            .ok_or(LibError::UnknownServer {
+                server: server.to_string(),
+                suggestions: Vec::new(),
+            })
+    }
+
+    /// Checks several offers from a single fetch of the page.
+    fn check_many(&self, servers: &[String]) -> Result<Vec<(String, bool)>, LibError> {
+        let offers = self.get_offers()?;
+        servers
+            .iter()
+            .map(|server| {
+                offers
+                    .iter()
+                    .find(|offer| offer.is_matching(server))
+                    .map(|offer| (server.clone(), offer.available))
+                    .ok_or(LibError::UnknownServer {
+                        server: server.to_string(),
+                        suggestions: Vec::new(),
+                    })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,265 @@
+use super::{ProviderFactoryTrait, ProviderTrait, ServerInfo};
+use crate::LibError;
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+// Hetzner server auction implementation
+
+/// Common name to identify the provider
+pub const HETZNER_AUCTION_NAME: &str = "hetzner-auction";
+
+/// Environment variables to eventually filter the auction listing.
+const ENV_HETZNER_AUCTION_MAX_PRICE: &str = "HETZNER_AUCTION_MAX_PRICE";
+const ENV_HETZNER_AUCTION_MIN_RAM: &str = "HETZNER_AUCTION_MIN_RAM";
+const ENV_HETZNER_AUCTION_MIN_DISK: &str = "HETZNER_AUCTION_MIN_DISK";
+const ENV_HETZNER_AUCTION_CPU: &str = "HETZNER_AUCTION_CPU";
+
+/// Public listing of the server auction, no authentication required.
+const HETZNER_AUCTION_URL: &str =
+    "https://www.hetzner.com/_resources/app/data/app/live_data_sb_EUR.json";
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct HetznerAuctionListing {
+    server: Vec<HetznerAuctionServer>,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct HetznerAuctionServer {
+    id: u64,
+    cpu: String,
+    ram_size: u32,
+    price: f64,
+    #[serde(default)]
+    hdd_hr: Vec<String>,
+    #[serde(default)]
+    hdd_arr: Vec<String>,
+    hdd_size: Option<u32>,
+    hdd_count: Option<u32>,
+    datacenter: String,
+    /// In megabits per second
+    bandwidth: Option<u64>,
+}
+
+impl HetznerAuctionServer {
+    /// Total disk capacity in GB, as the listing only provides the size of a single disk
+    fn total_disk(&self) -> u32 {
+        self.hdd_size.unwrap_or(0) * self.hdd_count.unwrap_or(0)
+    }
+}
+
+// I prefer the From trait, as i can pass references
+impl From<&HetznerAuctionServer> for ServerInfo {
+    /// Extracts only interesting information which is common to all providers
+    fn from(server: &HetznerAuctionServer) -> Self {
+        // prefer the human readable description, which includes the disk kind
+        let disks = if server.hdd_hr.is_empty() {
+            &server.hdd_arr
+        } else {
+            &server.hdd_hr
+        };
+        let mut storage = disks.join("+");
+        storage.retain(|c| !c.is_whitespace());
+
+        ServerInfo {
+            reference: format!(
+                "{} ({}@{}) {:.2}EUR",
+                server.id, server.cpu, server.datacenter, server.price
+            ),
+            memory: format!("{}GB", server.ram_size),
+            storage,
+            // every listed auction can be ordered
+            available: true,
+            price: Some(server.price),
+            cpu: Some(server.cpu.clone()),
+            cores: None,
+            bandwidth: server.bandwidth,
+            stock: None,
+            order_url: None,
+            range: None,
+        }
+    }
+}
+
+/// Filters applied to the auction listing, as interesting deals are rare.
+#[derive(Default)]
+struct HetznerAuctionFilters {
+    max_price: Option<f64>,
+    min_ram: Option<u32>,
+    min_disk: Option<u32>,
+    cpu: Option<String>,
+}
+
+impl HetznerAuctionFilters {
+    /// Parses an optional numeric filter
+    fn parse<T: std::str::FromStr>(
+        name: &str,
+        value: &Option<String>,
+    ) -> Result<Option<T>, LibError> {
+        value
+            .as_ref()
+            .map(|value| {
+                value.parse::<T>().map_err(|_| LibError::ValueError {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                })
+            })
+            .transpose()
+    }
+
+    /// Builds an instance from environment variables.
+    fn from_env() -> Result<Self, LibError> {
+        Ok(Self {
+            max_price: Self::parse(
+                ENV_HETZNER_AUCTION_MAX_PRICE,
+                &crate::get_env_var_option(ENV_HETZNER_AUCTION_MAX_PRICE),
+            )?,
+            min_ram: Self::parse(
+                ENV_HETZNER_AUCTION_MIN_RAM,
+                &crate::get_env_var_option(ENV_HETZNER_AUCTION_MIN_RAM),
+            )?,
+            min_disk: Self::parse(
+                ENV_HETZNER_AUCTION_MIN_DISK,
+                &crate::get_env_var_option(ENV_HETZNER_AUCTION_MIN_DISK),
+            )?,
+            cpu: crate::get_env_var_option(ENV_HETZNER_AUCTION_CPU)
+                .filter(|cpu| !cpu.is_empty())
+                .map(|cpu| cpu.to_lowercase()),
+        })
+    }
+
+    /// Tells if an auction matches every configured filter
+    fn is_matching(&self, server: &HetznerAuctionServer) -> bool {
+        self.max_price.is_none_or(|max| server.price <= max)
+            && self.min_ram.is_none_or(|min| server.ram_size >= min)
+            && self.min_disk.is_none_or(|min| server.total_disk() >= min)
+            && self
+                .cpu
+                .as_ref()
+                .is_none_or(|cpu| server.cpu.to_lowercase().contains(cpu))
+    }
+}
+
+/// Gets server inventory and availability.
+pub struct HetznerAuction {
+    client: Client,
+    filters: HetznerAuctionFilters,
+}
+
+/// Builds a HetznerAuction provider without relying on environment variables.
+/// Every filter is optional, the whole listing being used when none is set.
+#[derive(Default)]
+pub struct HetznerAuctionBuilder {
+    filters: HetznerAuctionFilters,
+}
+
+impl HetznerAuctionBuilder {
+    /// Keeps only the auctions up to this monthly price, in euros.
+    pub fn max_price(mut self, max_price: f64) -> Self {
+        self.filters.max_price = Some(max_price);
+        self
+    }
+
+    /// Keeps only the auctions with at least this RAM, in GB.
+    pub fn min_ram(mut self, min_ram: u32) -> Self {
+        self.filters.min_ram = Some(min_ram);
+        self
+    }
+
+    /// Keeps only the auctions with at least this total disk capacity, in GB.
+    pub fn min_disk(mut self, min_disk: u32) -> Self {
+        self.filters.min_disk = Some(min_disk);
+        self
+    }
+
+    /// Keeps only the auctions whose CPU model contains this case-insensitive text.
+    pub fn cpu(mut self, cpu: &str) -> Self {
+        self.filters.cpu = Some(cpu.to_lowercase()).filter(|cpu| !cpu.is_empty());
+        self
+    }
+
+    /// Builds the provider.
+    pub fn build(self) -> Result<HetznerAuction, LibError> {
+        Ok(HetznerAuction {
+            client: crate::client::build_client_for(HETZNER_AUCTION_NAME)?,
+            filters: self.filters,
+        })
+    }
+}
+
+impl HetznerAuction {
+    /// Starts building a new instance.
+    pub fn builder() -> HetznerAuctionBuilder {
+        HetznerAuctionBuilder::default()
+    }
+
+    /// Gets every auction matching the filters.
+    fn get_auctions(&self) -> Result<Vec<HetznerAuctionServer>, LibError> {
+        let response = crate::recording::send(&self.client, self.client.get(HETZNER_AUCTION_URL))?;
+
+        if !response.status().is_success() {
+            return Err(LibError::ApiStatusError {
+                status: response.status(),
+                message: format!(
+                    "Error during Hetzner auction query: code {}",
+                    response.status()
+                ),
+            });
+        }
+
+        // Deserialization
+        let listing: HetznerAuctionListing = response.json()?;
+
+        Ok(listing
+            .server
+            .into_iter()
+            .filter(|server| self.filters.is_matching(server))
+            .collect())
+    }
+}
+
+impl ProviderFactoryTrait for HetznerAuction {
+    /// Builds a HetznerAuction provider from environment variables.
+    fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
+        let filters = HetznerAuctionFilters::from_env()?;
+        Ok(Box::new(HetznerAuctionBuilder { filters }.build()?))
+    }
+}
+
+impl ProviderTrait for HetznerAuction {
+    /// Gets the actual name of the provider.
+    fn name(&self) -> &'static str {
+        HETZNER_AUCTION_NAME
+    }
+
+    /// Collects provider inventory.
+    /// Auctions are removed from the listing once sold, so `all` changes nothing.
+    fn inventory(&self, _all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        Ok(self
+            .get_auctions()?
+            .iter()
+            .map(|server| server.into())
+            .collect())
+    }
+
+    /// Checks provider for the availability of a given server type.
+    /// A sold auction simply disappears, so an unknown id is not an error.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        Ok(self
+            .get_auctions()?
+            .iter()
+            .any(|auction| auction.id.to_string() == server))
+    }
+
+    /// Verifies that the server is an auction number.
+    fn validate_server(&self, server: &str) -> Result<(), LibError> {
+        match server.parse::<u64>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(LibError::ValueError {
+                name: "malformed hetzner auction id".into(),
+                value: server.to_string(),
+            }),
+        }
+    }
+}
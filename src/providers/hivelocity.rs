@@ -0,0 +1,268 @@
+use super::{ProviderFactoryTrait, ProviderTrait, ServerInfo};
+use crate::LibError;
+use http::Method;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+// Hivelocity implementation
+
+/// Common name to identify the provider
+pub const HIVELOCITY_NAME: &str = "hivelocity";
+
+/// Common environment variable to input your Hivelocity API key.
+const ENV_HIVELOCITY_API_KEY: &str = "HIVELOCITY_API_KEY";
+
+/// Common environment variable to eventually filter the locations.
+const ENV_HIVELOCITY_LOCATIONS: &str = "HIVELOCITY_LOCATIONS";
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HivelocityProduct {
+    product_id: u64,
+    product_name: String,
+    product_cpu: Option<String>,
+    product_memory: Option<String>,
+    product_drive: Option<String>,
+    #[serde(default)]
+    product_on_demand: bool,
+    #[serde(default)]
+    quantity: u32,
+}
+
+/// Aggregates a product across every facility it is stocked in.
+struct HivelocityDevice {
+    product: HivelocityProduct,
+    quantities: BTreeMap<String, u32>,
+}
+
+impl HivelocityDevice {
+    /// Convenience function to detemine availability
+    fn is_available(&self) -> bool {
+        self.quantities.values().any(|quantity| *quantity > 0)
+    }
+
+    /// Tells if the device matches the requested server reference
+    fn is_matching(&self, server: &str) -> bool {
+        self.product.product_id.to_string() == server
+    }
+}
+
+// I prefer the From trait, as i can pass references
+impl From<&HivelocityDevice> for ServerInfo {
+    /// Extracts only interesting information which is common to all providers
+    fn from(device: &HivelocityDevice) -> Self {
+        let product = &device.product;
+
+        let quantities = device
+            .quantities
+            .iter()
+            .map(|(facility, quantity)| format!("{facility}:{quantity}"))
+            .collect::<Vec<String>>()
+            .join(",");
+
+        let mut storage = product.product_drive.clone().unwrap_or_default();
+        storage.retain(|c| !c.is_whitespace());
+        if storage.is_empty() {
+            storage = "N/A".to_string();
+        }
+
+        ServerInfo {
+            reference: format!(
+                "{} ({} {}@{})",
+                product.product_id,
+                product.product_name,
+                product.product_cpu.as_deref().unwrap_or("N/A"),
+                quantities
+            ),
+            memory: product
+                .product_memory
+                .as_deref()
+                .unwrap_or("N/A")
+                .replace(' ', ""),
+            storage,
+            available: device.is_available(),
+            price: None,
+            cpu: product.product_cpu.clone(),
+            cores: None,
+            bandwidth: None,
+            stock: None,
+            order_url: None,
+            range: None,
+        }
+    }
+}
+
+/// Gets server inventory and availability.
+pub struct Hivelocity {
+    client: Client,
+    api_key: String,
+    locations: Vec<String>,
+}
+
+/// Builds a Hivelocity provider without relying on environment variables.
+#[derive(Default)]
+pub struct HivelocityBuilder {
+    api_key: String,
+    locations: Vec<String>,
+}
+
+impl HivelocityBuilder {
+    /// Sets the API key.
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = api_key.to_string();
+        self
+    }
+
+    /// Sets the facility codes to keep, all of them being used when none is set.
+    pub fn locations<I, S>(mut self, locations: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.locations = locations.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Builds the provider, verifying the settings.
+    pub fn build(self) -> Result<Hivelocity, LibError> {
+        if self.api_key.is_empty() {
+            return Err(LibError::ValueError {
+                name: "found empty hivelocity api key".into(),
+                value: self.api_key,
+            });
+        }
+
+        // facility codes are upper case in the API
+        let locations = self
+            .locations
+            .iter()
+            .map(|location| location.to_uppercase())
+            .collect();
+
+        // construct the object if everything is ok
+        Ok(Hivelocity {
+            client: crate::client::build_client_for(HIVELOCITY_NAME)?,
+            api_key: self.api_key,
+            locations,
+        })
+    }
+}
+
+impl Hivelocity {
+    /// Starts building a new instance.
+    pub fn builder() -> HivelocityBuilder {
+        HivelocityBuilder::default()
+    }
+
+    /// Wrapper for automatic handling of authentication
+    fn create_authenticated_request_builder(&self, method: Method, url: &str) -> RequestBuilder {
+        self.client
+            .request(method, url)
+            .header("X-API-KEY", &self.api_key)
+    }
+
+    /// Fallback error handler for queries
+    fn do_error_if_not_successful(response: &Response) -> Result<(), LibError> {
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        Err(LibError::ApiStatusError {
+            status: response.status(),
+            message: format!("Error during Hivelocity query: code {}", response.status()),
+        })
+    }
+
+    /// Gets the instant-deploy products of every facility.
+    fn get_facility_products(&self) -> Result<HashMap<String, Vec<HivelocityProduct>>, LibError> {
+        let response = crate::recording::send(
+            &self.client,
+            self.create_authenticated_request_builder(
+                Method::GET,
+                "https://core.hivelocity.net/api/v2/inventory/product",
+            )
+            .query(&[("group_by", "facility")]),
+        )?;
+
+        // fallback error handler
+        Self::do_error_if_not_successful(&response)?;
+
+        // reqwest deserialize
+        Ok(response.json::<HashMap<String, Vec<HivelocityProduct>>>()?)
+    }
+
+    /// Gets all devices, with their quantities per desired facility. Devices stocked elsewhere,
+    /// or which cannot be deployed instantly, are kept without any quantity, so that they are
+    /// known but unavailable.
+    fn get_devices(&self) -> Result<Vec<HivelocityDevice>, LibError> {
+        let mut map: HashMap<u64, HivelocityDevice> = HashMap::new();
+
+        for (facility, products) in self.get_facility_products()? {
+            // if we do not filter on locations, any of them will be fine
+            let desired =
+                self.locations.is_empty() || self.locations.contains(&facility.to_uppercase());
+            for product in products {
+                // only instant-deploy devices can be watched for stock
+                let stocked = desired && product.product_on_demand;
+                let quantity = product.quantity;
+                let device = map
+                    .entry(product.product_id)
+                    .or_insert_with(|| HivelocityDevice {
+                        product,
+                        quantities: BTreeMap::new(),
+                    });
+                if stocked {
+                    device.quantities.insert(facility.clone(), quantity);
+                }
+            }
+        }
+
+        // Builds result by moving the values from the map into the vec
+        Ok(Vec::from_iter(map.into_values()))
+    }
+}
+
+impl ProviderFactoryTrait for Hivelocity {
+    /// Builds a Hivelocity provider from environment variables.
+    fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
+        let api_key = crate::get_env_var(ENV_HIVELOCITY_API_KEY)?;
+        let locations_csv = crate::get_env_var_option(ENV_HIVELOCITY_LOCATIONS);
+        Ok(Box::new(
+            Self::builder()
+                .api_key(&api_key)
+                .locations(crate::tokenize_optional_csv_str(&locations_csv)?)
+                .build()?,
+        ))
+    }
+}
+
+impl ProviderTrait for Hivelocity {
+    /// Gets the actual name of the provider.
+    fn name(&self) -> &'static str {
+        HIVELOCITY_NAME
+    }
+
+    /// Collects provider inventory.
+    fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        Ok(self
+            .get_devices()?
+            .iter()
+            .filter(|device| device.is_available() || all)
+            .map(|device| device.into())
+            .collect())
+    }
+
+    /// Checks provider for the availability of a given server type.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        self.get_devices()?
+            .iter()
+            .find(|device| device.is_matching(server))
+            .map(|device| device.is_available())
+            .ok_or(LibError::UnknownServer {
+                server: server.to_string(),
+                suggestions: Vec::new(),
+            })
+    }
+}
@@ -0,0 +1,435 @@
+use super::{ProviderFactoryTrait, ProviderTrait, ServerInfo};
+use crate::LibError;
+use reqwest::blocking::{Client, Response};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+// IBM Cloud classic infrastructure implementation
+
+/// Common name to identify the provider
+pub const IBMCLOUD_NAME: &str = "ibmcloud";
+
+/// Common environment variable to input your IBM Cloud IAM API key.
+const ENV_IBMCLOUD_API_KEY: &str = "IBMCLOUD_API_KEY";
+
+/// Common environment variable to eventually filter the datacenters.
+const ENV_IBMCLOUD_DATACENTERS: &str = "IBMCLOUD_DATACENTERS";
+
+/// Common environment variable to eventually select another product package.
+const ENV_IBMCLOUD_PACKAGE: &str = "IBMCLOUD_PACKAGE";
+
+/// Endpoint exchanging an API key for an IAM access token.
+const IBMCLOUD_TOKEN_URL: &str = "https://iam.cloud.ibm.com/identity/token";
+
+/// Base URL of the classic infrastructure (SoftLayer) REST API.
+const IBMCLOUD_API_URL: &str = "https://api.softlayer.com/rest/v3.1";
+
+/// Package holding the fast provisioning bare metal presets.
+const IBMCLOUD_DEFAULT_PACKAGE: &str = "BARE_METAL_SERVER";
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct IbmCloudToken {
+    access_token: String,
+}
+
+/// Used for API error deserialisation
+#[derive(Deserialize)]
+struct IbmCloudError {
+    error: String,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct IbmCloudPackage {
+    id: u64,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct IbmCloudLocation {
+    name: String,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IbmCloudPackageLocation {
+    #[serde(default)]
+    is_available: u8,
+    location: IbmCloudLocation,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IbmCloudPreset {
+    key_name: String,
+    description: Option<String>,
+    #[serde(default)]
+    locations: Vec<IbmCloudLocation>,
+    #[serde(default)]
+    configuration: Vec<IbmCloudPresetConfiguration>,
+    /// Floats are sometimes serialized as strings by the API
+    total_minimum_recurring_fee: Option<serde_json::Value>,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct IbmCloudPresetConfiguration {
+    category: IbmCloudCategory,
+    price: IbmCloudPrice,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IbmCloudCategory {
+    category_code: String,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct IbmCloudPrice {
+    item: IbmCloudItem,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct IbmCloudItem {
+    description: String,
+}
+
+/// Joins a preset configuration with the datacenters where it can be ordered.
+struct IbmCloudServerType {
+    preset: IbmCloudPreset,
+    datacenters: Vec<String>,
+}
+
+impl IbmCloudServerType {
+    /// Convenience function to detemine availability
+    fn is_available(&self) -> bool {
+        !self.datacenters.is_empty()
+    }
+
+    /// Tells if the server type matches the requested server reference
+    fn is_matching(&self, server: &str) -> bool {
+        self.preset.key_name == server
+    }
+
+    /// Gets the description of the processor item, as is.
+    fn cpu(&self) -> Option<String> {
+        self.preset
+            .configuration
+            .iter()
+            .find(|configuration| configuration.category.category_code == "server")
+            .map(|configuration| configuration.price.item.description.clone())
+    }
+
+    /// Gets the descriptions of the items of the given categories, without spaces.
+    /// Categories are matched by prefix, so that `disk` finds `disk0`, `disk1`...
+    fn describe(&self, category: &str) -> String {
+        let mut items: Vec<String> = self
+            .preset
+            .configuration
+            .iter()
+            .filter(|configuration| configuration.category.category_code.starts_with(category))
+            .map(|configuration| configuration.price.item.description.replace(' ', ""))
+            .collect();
+        items.sort();
+        match items.is_empty() {
+            true => "N/A".to_string(),
+            false => items.join("+"),
+        }
+    }
+}
+
+// I prefer the From trait, as i can pass references
+impl From<&IbmCloudServerType> for ServerInfo {
+    /// Extracts only interesting information which is common to all providers
+    fn from(server_type: &IbmCloudServerType) -> Self {
+        let description = server_type.preset.description.clone().unwrap_or_default();
+
+        let price = match &server_type.preset.total_minimum_recurring_fee {
+            Some(serde_json::Value::Number(fee)) => fee.as_f64(),
+            Some(serde_json::Value::String(fee)) => fee.parse().ok(),
+            _ => None,
+        };
+
+        ServerInfo {
+            reference: format!(
+                "{} ({}@{})",
+                server_type.preset.key_name,
+                description,
+                server_type.datacenters.join(",")
+            ),
+            memory: server_type.describe("ram"),
+            storage: server_type.describe("disk"),
+            available: server_type.is_available(),
+            price,
+            cpu: server_type.cpu(),
+            cores: None,
+            bandwidth: None,
+            stock: None,
+            order_url: None,
+            range: None,
+        }
+    }
+}
+
+/// Gets server inventory and availability.
+pub struct IbmCloud {
+    client: Client,
+    api_key: String,
+    package: String,
+    datacenters: Vec<String>,
+}
+
+/// Builds an IbmCloud provider without relying on environment variables.
+pub struct IbmCloudBuilder {
+    api_key: String,
+    package: String,
+    datacenters: Vec<String>,
+}
+
+impl Default for IbmCloudBuilder {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            package: IBMCLOUD_DEFAULT_PACKAGE.to_string(),
+            datacenters: Vec::new(),
+        }
+    }
+}
+
+impl IbmCloudBuilder {
+    /// Sets the IAM API key.
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = api_key.to_string();
+        self
+    }
+
+    /// Sets the key name of the product package holding the presets.
+    pub fn package(mut self, package: &str) -> Self {
+        self.package = package.to_string();
+        self
+    }
+
+    /// Sets the datacenters to keep, like `dal10`, all of them being used when none is set.
+    pub fn datacenters<I, S>(mut self, datacenters: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.datacenters = datacenters.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Builds the provider, verifying the settings.
+    pub fn build(self) -> Result<IbmCloud, LibError> {
+        for (name, value) in [("api key", &self.api_key), ("package", &self.package)] {
+            if value.is_empty() {
+                return Err(LibError::ValueError {
+                    name: format!("found empty ibmcloud {name}"),
+                    value: value.into(),
+                });
+            }
+        }
+
+        // datacenter names are lower case in the API, and package key names upper case
+        let datacenters = self
+            .datacenters
+            .iter()
+            .map(|datacenter| datacenter.to_lowercase())
+            .collect();
+
+        // construct the object if everything is ok
+        Ok(IbmCloud {
+            client: crate::client::build_client_for(IBMCLOUD_NAME)?,
+            api_key: self.api_key,
+            package: self.package.to_uppercase(),
+            datacenters,
+        })
+    }
+}
+
+impl IbmCloud {
+    /// Starts building a new instance.
+    pub fn builder() -> IbmCloudBuilder {
+        IbmCloudBuilder::default()
+    }
+
+    /// Fallback error handler for queries, using the error message of the API if any
+    fn do_error_if_not_successful(response: Response) -> Result<Response, LibError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let message = match response.json::<IbmCloudError>() {
+            Ok(error) => format!("Error during IBM Cloud query: {}", error.error),
+            Err(_) => format!("Error during IBM Cloud query: code {status}"),
+        };
+        Err(LibError::ApiStatusError { status, message })
+    }
+
+    /// Exchanges the API key for an IAM access token.
+    fn get_access_token(&self) -> Result<String, LibError> {
+        let response = crate::recording::send(
+            &self.client,
+            self.client.post(IBMCLOUD_TOKEN_URL).form(&[
+                ("grant_type", "urn:ibm:params:oauth:grant-type:apikey"),
+                ("apikey", self.api_key.as_str()),
+            ]),
+        )?;
+
+        // reqwest deserialize
+        Ok(Self::do_error_if_not_successful(response)?
+            .json::<IbmCloudToken>()?
+            .access_token)
+    }
+
+    /// Executes authenticated get queries, and deserializes the result.
+    fn get_api<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        token: &str,
+    ) -> Result<T, LibError> {
+        let response = crate::recording::send(
+            &self.client,
+            self.client
+                .get(format!("{IBMCLOUD_API_URL}/{path}"))
+                .bearer_auth(token)
+                .query(query),
+        )?;
+
+        // reqwest deserialize
+        Ok(Self::do_error_if_not_successful(response)?.json::<T>()?)
+    }
+
+    /// Gets all presets of the package, with the datacenters where they can be ordered.
+    fn get_server_types(&self) -> Result<Vec<IbmCloudServerType>, LibError> {
+        let token = self.get_access_token()?;
+
+        let filter = format!(r#"{{"keyName":{{"operation":"{}"}}}}"#, self.package);
+        let package_id = self
+            .get_api::<Vec<IbmCloudPackage>>(
+                "SoftLayer_Product_Package/getAllObjects.json",
+                &[("objectFilter", &filter), ("objectMask", "mask[id]")],
+                &token,
+            )?
+            .first()
+            .map(|package| package.id)
+            .ok_or_else(|| LibError::ValueError {
+                name: ENV_IBMCLOUD_PACKAGE.to_string(),
+                value: self.package.clone(),
+            })?;
+
+        // datacenters where the package can currently be ordered
+        let available: Vec<String> = self
+            .get_api::<Vec<IbmCloudPackageLocation>>(
+                &format!("SoftLayer_Product_Package/{package_id}/getAvailableLocations.json"),
+                &[("objectMask", "mask[isAvailable,location[name]]")],
+                &token,
+            )?
+            .into_iter()
+            .filter(|location| location.is_available != 0)
+            .map(|location| location.location.name.to_lowercase())
+            .filter(|name| self.datacenters.is_empty() || self.datacenters.contains(name))
+            .collect();
+
+        let presets: Vec<IbmCloudPreset> = self.get_api(
+            &format!("SoftLayer_Product_Package/{package_id}/getActivePresets.json"),
+            &[(
+                "objectMask",
+                "mask[keyName,description,totalMinimumRecurringFee,locations[name],configuration[category[categoryCode],price[item[description]]]]",
+            )],
+            &token,
+        )?;
+
+        Ok(presets
+            .into_iter()
+            .map(|preset| {
+                // presets without locations can be ordered wherever the package can
+                let datacenters = available
+                    .iter()
+                    .filter(|datacenter| {
+                        preset.locations.is_empty()
+                            || preset
+                                .locations
+                                .iter()
+                                .any(|location| location.name.to_lowercase() == **datacenter)
+                    })
+                    .cloned()
+                    .collect();
+                IbmCloudServerType {
+                    preset,
+                    datacenters,
+                }
+            })
+            .collect())
+    }
+}
+
+impl ProviderFactoryTrait for IbmCloud {
+    /// Builds an IbmCloud provider from environment variables.
+    fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
+        let api_key = crate::get_env_var(ENV_IBMCLOUD_API_KEY)?;
+        let datacenters_csv = crate::get_env_var_option(ENV_IBMCLOUD_DATACENTERS);
+        let mut builder = Self::builder()
+            .api_key(&api_key)
+            .datacenters(crate::tokenize_optional_csv_str(&datacenters_csv)?);
+        if let Some(package) = crate::get_env_var_option(ENV_IBMCLOUD_PACKAGE) {
+            builder = builder.package(&package);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl ProviderTrait for IbmCloud {
+    /// Gets the actual name of the provider.
+    fn name(&self) -> &'static str {
+        IBMCLOUD_NAME
+    }
+
+    /// Collects provider inventory.
+    fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        Ok(self
+            .get_server_types()?
+            .iter()
+            .filter(|server_type| server_type.is_available() || all)
+            .map(|server_type| server_type.into())
+            .collect())
+    }
+
+    /// Checks provider for the availability of a given server type.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        self.get_server_types()?
+            .iter()
+            .find(|server_type| server_type.is_matching(server))
+            .map(|server_type| server_type.is_available())
+            .ok_or(LibError::UnknownServer {
+                server: server.to_string(),
+                suggestions: Vec::new(),
+            })
+    }
+
+    /// Checks several presets from a single listing of the package.
+    fn check_many(&self, servers: &[String]) -> Result<Vec<(String, bool)>, LibError> {
+        let server_types = self.get_server_types()?;
+        servers
+            .iter()
+            .map(|server| {
+                server_types
+                    .iter()
+                    .find(|server_type| server_type.is_matching(server))
+                    .map(|server_type| (server.clone(), server_type.is_available()))
+                    .ok_or(LibError::UnknownServer {
+                        server: server.to_string(),
+                        suggestions: Vec::new(),
+                    })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,330 @@
+use super::{Pagination, ProviderFactoryTrait, ProviderTrait, ServerInfo};
+use crate::LibError;
+use http::Method;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde::Deserialize;
+
+// Leaseweb implementation
+
+/// Common name to identify the provider
+pub const LEASEWEB_NAME: &str = "leaseweb";
+
+/// Common environment variable to input your Leaseweb API key.
+const ENV_LEASEWEB_API_KEY: &str = "LEASEWEB_API_KEY";
+
+/// Common environment variable to eventually filter the sites.
+const ENV_LEASEWEB_SITES: &str = "LEASEWEB_SITES";
+
+/// Offers are listed by pages, of the largest size accepted by the API.
+const LEASEWEB_PAGINATION: Pagination = Pagination::Offset {
+    offset: "offset",
+    limit: "limit",
+    size: 50,
+};
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct LeasewebOffers {
+    offers: Vec<LeasewebOffer>,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct LeasewebOffer {
+    id: String,
+    name: Option<String>,
+    cpu: Option<LeasewebCpu>,
+    ram: Option<LeasewebCapacity>,
+    #[serde(default)]
+    hdd: Vec<LeasewebDisk>,
+    #[serde(default)]
+    stock: Vec<LeasewebStock>,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct LeasewebCpu {
+    quantity: Option<u32>,
+    #[serde(rename = "type")]
+    model: Option<String>,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct LeasewebCapacity {
+    size: u32,
+    unit: String,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct LeasewebDisk {
+    amount: u32,
+    size: u32,
+    unit: String,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct LeasewebStock {
+    site: String,
+    #[serde(default)]
+    quantity: u32,
+}
+
+impl LeasewebOffer {
+    /// Convenience function to detemine availability
+    fn is_available(&self) -> bool {
+        self.stock.iter().any(|stock| stock.quantity > 0)
+    }
+
+    /// Tells if the offer matches the requested server reference
+    fn is_matching(&self, server: &str) -> bool {
+        self.id == server
+    }
+}
+
+// I prefer the From trait, as i can pass references
+impl From<&LeasewebOffer> for ServerInfo {
+    /// Extracts only interesting information which is common to all providers
+    fn from(offer: &LeasewebOffer) -> Self {
+        let mut cpu = match &offer.cpu {
+            Some(cpu) => format!(
+                "{}x{}",
+                cpu.quantity.unwrap_or(1),
+                cpu.model.as_deref().unwrap_or("N/A")
+            ),
+            None => "N/A".to_string(),
+        };
+        cpu.retain(|c| !c.is_whitespace());
+
+        let memory = match &offer.ram {
+            Some(ram) => format!("{}{}", ram.size, ram.unit),
+            None => "N/A".to_string(),
+        };
+
+        let storage = match offer.hdd.is_empty() {
+            true => "N/A".to_string(),
+            false => offer
+                .hdd
+                .iter()
+                .map(|disk| {
+                    format!(
+                        "{}x{}{}{}",
+                        disk.amount,
+                        disk.size,
+                        disk.unit,
+                        disk.kind.as_deref().unwrap_or_default()
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("+"),
+        };
+
+        let sites = offer
+            .stock
+            .iter()
+            .map(|stock| format!("{}:{}", stock.site, stock.quantity))
+            .collect::<Vec<String>>()
+            .join(",");
+
+        ServerInfo {
+            reference: format!(
+                "{} ({} {}@{})",
+                offer.id,
+                offer.name.as_deref().unwrap_or("N/A"),
+                cpu,
+                sites
+            ),
+            memory,
+            storage,
+            available: offer.is_available(),
+            price: None,
+            cpu: offer.cpu.as_ref().and_then(|cpu| cpu.model.clone()),
+            cores: None,
+            bandwidth: None,
+            stock: None,
+            order_url: None,
+            range: None,
+        }
+    }
+}
+
+/// Gets server inventory and availability.
+pub struct Leaseweb {
+    client: Client,
+    api_key: String,
+    sites: Vec<String>,
+}
+
+/// Builds a Leaseweb provider without relying on environment variables.
+#[derive(Default)]
+pub struct LeasewebBuilder {
+    api_key: String,
+    sites: Vec<String>,
+}
+
+impl LeasewebBuilder {
+    /// Sets the API key.
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = api_key.to_string();
+        self
+    }
+
+    /// Sets the sites to keep, all of them being used when none is set.
+    pub fn sites<I, S>(mut self, sites: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.sites = sites.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Builds the provider, verifying the settings.
+    pub fn build(self) -> Result<Leaseweb, LibError> {
+        if self.api_key.is_empty() {
+            return Err(LibError::ValueError {
+                name: "found empty leaseweb api key".into(),
+                value: self.api_key,
+            });
+        }
+
+        // site codes are upper case in the API
+        let sites = self.sites.iter().map(|site| site.to_uppercase()).collect();
+
+        // construct the object if everything is ok
+        Ok(Leaseweb {
+            client: crate::client::build_client_for(LEASEWEB_NAME)?,
+            api_key: self.api_key,
+            sites,
+        })
+    }
+}
+
+impl Leaseweb {
+    /// Starts building a new instance.
+    pub fn builder() -> LeasewebBuilder {
+        LeasewebBuilder::default()
+    }
+
+    /// Wrapper for automatic handling of authentication
+    fn create_authenticated_request_builder(&self, method: Method, url: &str) -> RequestBuilder {
+        self.client
+            .request(method, url)
+            .header("X-LSW-Auth", &self.api_key)
+    }
+
+    /// Fallback error handler for queries
+    fn do_error_if_not_successful(response: &Response) -> Result<(), LibError> {
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        Err(LibError::ApiStatusError {
+            status: response.status(),
+            message: format!("Error during Leaseweb query: code {}", response.status()),
+        })
+    }
+
+    /// Gets a page of offers.
+    fn get_offers_page(
+        &self,
+        parameters: &[(&str, String)],
+    ) -> Result<Vec<LeasewebOffer>, LibError> {
+        let response = crate::recording::send(
+            &self.client,
+            self.create_authenticated_request_builder(
+                Method::GET,
+                "https://api.leaseweb.com/bareMetals/v2/offers",
+            )
+            .query(parameters),
+        )?;
+
+        // fallback error handler
+        Self::do_error_if_not_successful(&response)?;
+
+        // reqwest deserialize
+        Ok(response.json::<LeasewebOffers>()?.offers)
+    }
+
+    /// Gets all offers, with their stock in the desired sites.
+    fn get_offers(&self) -> Result<Vec<LeasewebOffer>, LibError> {
+        let mut offers = LEASEWEB_PAGINATION
+            .fetch_all(|parameters| Ok((self.get_offers_page(parameters)?, None)))?;
+
+        // if we do not filter on sites, any of them will be fine
+        if !self.sites.is_empty() {
+            for offer in offers.iter_mut() {
+                offer
+                    .stock
+                    .retain(|stock| self.sites.contains(&stock.site.to_uppercase()));
+            }
+        }
+
+        Ok(offers)
+    }
+}
+
+impl ProviderFactoryTrait for Leaseweb {
+    /// Builds a Leaseweb provider from environment variables.
+    fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
+        let api_key = crate::get_env_var(ENV_LEASEWEB_API_KEY)?;
+        let sites_csv = crate::get_env_var_option(ENV_LEASEWEB_SITES);
+        Ok(Box::new(
+            Self::builder()
+                .api_key(&api_key)
+                .sites(crate::tokenize_optional_csv_str(&sites_csv)?)
+                .build()?,
+        ))
+    }
+}
+
+impl ProviderTrait for Leaseweb {
+    /// Gets the actual name of the provider.
+    fn name(&self) -> &'static str {
+        LEASEWEB_NAME
+    }
+
+    /// Collects provider inventory.
+    fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        Ok(self
+            .get_offers()?
+            .iter()
+            .filter(|offer| offer.is_available() || all)
+            .map(|offer| offer.into())
+            .collect())
+    }
+
+    /// Checks provider for the availability of a given server type.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        self.get_offers()?
+            .iter()
+            .find(|offer| offer.is_matching(server))
+            .map(|offer| offer.is_available())
+            .ok_or(LibError::UnknownServer {
+                server: server.to_string(),
+                suggestions: Vec::new(),
+            })
+    }
+
+    /// Checks several offers from a single query.
+    fn check_many(&self, servers: &[String]) -> Result<Vec<(String, bool)>, LibError> {
+        let offers = self.get_offers()?;
+        servers
+            .iter()
+            .map(|server| {
+                offers
+                    .iter()
+                    .find(|offer| offer.is_matching(server))
+                    .map(|offer| (server.clone(), offer.is_available()))
+                    .ok_or(LibError::UnknownServer {
+                        server: server.to_string(),
+                        suggestions: Vec::new(),
+                    })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,202 @@
+use super::{ProviderFactoryTrait, ProviderTrait, ServerInfo, StockLevel};
+use crate::LibError;
+use serde::Deserialize;
+use std::fs;
+
+// MOCK implementation (fixture driven, for testing)
+
+/// Common name to identify the provider
+pub const MOCK_NAME: &str = "mock";
+
+/// Environment variable to select the path of a json fixture file.
+const ENV_MOCK_PROVIDER_FIXTURE: &str = "MOCK_PROVIDER_FIXTURE";
+
+/// Environment variable to provide the json fixture inline.
+const ENV_MOCK_PROVIDER_INVENTORY: &str = "MOCK_PROVIDER_INVENTORY";
+
+/// Used for fixture deserialisation, in the same format as the command provider
+#[derive(Deserialize)]
+struct MockServerInfo {
+    reference: String,
+    memory: Option<String>,
+    storage: Option<String>,
+    available: bool,
+    price: Option<f64>,
+    cpu: Option<String>,
+    cores: Option<u32>,
+    bandwidth: Option<u64>,
+    stock: Option<StockLevel>,
+    order_url: Option<String>,
+    range: Option<String>,
+}
+
+// I prefer the From trait, as i can pass references
+impl From<&MockServerInfo> for ServerInfo {
+    /// Extracts only interesting information which is common to all providers
+    fn from(info: &MockServerInfo) -> Self {
+        ServerInfo {
+            reference: info.reference.clone(),
+            memory: info.memory.clone().unwrap_or("N/A".to_string()),
+            storage: info.storage.clone().unwrap_or("N/A".to_string()),
+            available: info.available,
+            price: info.price,
+            cpu: info.cpu.clone(),
+            cores: info.cores,
+            bandwidth: info.bandwidth,
+            stock: info.stock,
+            order_url: info.order_url.clone(),
+            range: info.range.clone(),
+        }
+    }
+}
+
+/// Where the fixture comes from.
+enum MockSource {
+    /// Read again at every query, so that a test can change it between runs
+    File(String),
+    /// Fixed for the lifetime of the provider
+    Inline(String),
+}
+
+/// Gets server inventory and availability from a json fixture, without any network access,
+/// in order to test the whole check, storage and notification chain.
+pub struct MockProvider {
+    source: MockSource,
+}
+
+impl MockProvider {
+    /// Builds a new instance reading its fixture from a file at every query.
+    pub fn from_file(path: &str) -> Result<Self, LibError> {
+        if path.is_empty() {
+            return Err(LibError::ValueError {
+                name: "found empty fixture path".into(),
+                value: path.to_string(),
+            });
+        }
+        Ok(Self {
+            source: MockSource::File(path.to_string()),
+        })
+    }
+
+    /// Builds a new instance from an inline fixture, which is verified right away.
+    pub fn from_json(json: &str) -> Result<Self, LibError> {
+        let provider = Self {
+            source: MockSource::Inline(json.to_string()),
+        };
+        provider.get_servers()?;
+        Ok(provider)
+    }
+
+    /// Loads and parses the fixture.
+    fn get_servers(&self) -> Result<Vec<MockServerInfo>, LibError> {
+        let json = match &self.source {
+            MockSource::File(path) => {
+                fs::read_to_string(path).map_err(|source| LibError::FileError {
+                    path: path.into(),
+                    source,
+                })?
+            }
+            MockSource::Inline(json) => json.clone(),
+        };
+        serde_json::from_str(&json).map_err(|source| LibError::JsonError {
+            provider: Some(MOCK_NAME.to_string()),
+            source,
+        })
+    }
+}
+
+impl ProviderFactoryTrait for MockProvider {
+    /// Builds a MockProvider from environment variables.
+    fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
+        let fixture = crate::get_env_var_option(ENV_MOCK_PROVIDER_FIXTURE);
+        let inventory = crate::get_env_var_option(ENV_MOCK_PROVIDER_INVENTORY);
+        let provider = match (fixture, inventory) {
+            (Some(path), None) => Self::from_file(&path)?,
+            (None, Some(json)) => Self::from_json(&json)?,
+            (None, None) => {
+                return Err(LibError::ValueError {
+                    name: ENV_MOCK_PROVIDER_FIXTURE.to_string(),
+                    value: String::new(),
+                })
+            }
+            (Some(path), Some(_)) => {
+                return Err(LibError::ValueError {
+                    name: format!("{ENV_MOCK_PROVIDER_FIXTURE} and {ENV_MOCK_PROVIDER_INVENTORY}"),
+                    value: path,
+                })
+            }
+        };
+        Ok(Box::new(provider))
+    }
+}
+
+impl ProviderTrait for MockProvider {
+    /// Gets the actual name of the provider.
+    fn name(&self) -> &'static str {
+        MOCK_NAME
+    }
+
+    /// Collects provider inventory.
+    fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        Ok(self
+            .get_servers()?
+            .iter()
+            .filter(|info| info.available || all)
+            .map(|info| info.into())
+            .collect())
+    }
+
+    /// Checks provider for the availability of a given server type.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        self.get_servers()?
+            .iter()
+            .find(|info| info.reference == server)
+            .map(|info| info.available)
+            .ok_or(LibError::UnknownServer {
+                server: server.to_string(),
+                suggestions: Vec::new(),
+            })
+    }
+
+    /// Checks many servers with a single read of the fixture.
+    fn check_many(&self, servers: &[String]) -> Result<Vec<(String, bool)>, LibError> {
+        Ok(self
+            .check_stock(servers)?
+            .into_iter()
+            .map(|(server, available, _)| (server, available))
+            .collect())
+    }
+
+    /// Same as `check_many()`, with the stock level of the fixture for available servers.
+    fn check_stock(
+        &self,
+        servers: &[String],
+    ) -> Result<Vec<(String, bool, Option<StockLevel>)>, LibError> {
+        let infos = self.get_servers()?;
+        servers
+            .iter()
+            .map(|server| {
+                infos
+                    .iter()
+                    .find(|info| &info.reference == server)
+                    .map(|info| {
+                        let stock = info.stock.filter(|_| info.available);
+                        (server.clone(), info.available, stock)
+                    })
+                    .ok_or(LibError::UnknownServer {
+                        server: server.clone(),
+                        suggestions: Vec::new(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Gets the order page of a server from the fixture, if it has any.
+    fn order_url(&self, server: &str) -> Option<String> {
+        self.get_servers()
+            .ok()?
+            .into_iter()
+            .find(|info| info.reference == server)?
+            .order_url
+    }
+}
@@ -0,0 +1,693 @@
+use super::{ProviderFactoryTrait, ProviderTrait, ServerInfo};
+use crate::LibError;
+use chrono::Utc;
+use http::Method;
+use openssl::base64;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Private};
+use openssl::sign::Signer;
+use reqwest::blocking::{Client, Response};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+
+// Oracle Cloud Infrastructure implementation (compute capacity reports)
+
+/// Common name to identify the provider
+pub const OCI_NAME: &str = "oci";
+
+/// Common environment variables holding the API signing key settings.
+const ENV_OCI_TENANCY: &str = "OCI_TENANCY";
+const ENV_OCI_USER: &str = "OCI_USER";
+const ENV_OCI_FINGERPRINT: &str = "OCI_FINGERPRINT";
+const ENV_OCI_PRIVATE_KEY: &str = "OCI_PRIVATE_KEY";
+const ENV_OCI_REGION: &str = "OCI_REGION";
+
+/// Common environment variable to select the profile of the configuration file.
+const ENV_OCI_CLI_PROFILE: &str = "OCI_CLI_PROFILE";
+
+/// Common environment variable to override the location of the configuration file.
+const ENV_OCI_CLI_CONFIG_FILE: &str = "OCI_CLI_CONFIG_FILE";
+
+/// Common environment variable to select the watched shapes.
+const ENV_OCI_SHAPES: &str = "OCI_SHAPES";
+
+/// Common environment variable to eventually select another compartment than the tenancy.
+const ENV_OCI_COMPARTMENT: &str = "OCI_COMPARTMENT";
+
+/// Common environment variable to eventually filter the availability domains.
+const ENV_OCI_AVAILABILITY_DOMAINS: &str = "OCI_AVAILABILITY_DOMAINS";
+
+/// Profile used when none is selected.
+const OCI_DEFAULT_PROFILE: &str = "DEFAULT";
+
+/// Status of a shape which can currently be launched.
+const OCI_AVAILABLE_STATUS: &str = "AVAILABLE";
+
+/// Holds the settings of the API signing key.
+pub struct OciCredentials {
+    tenancy: String,
+    user: String,
+    fingerprint: String,
+    private_key: String,
+    region: String,
+}
+
+impl OciCredentials {
+    /// Builds credentials, without relying on environment variables.
+    pub fn new(
+        tenancy: &str,
+        user: &str,
+        fingerprint: &str,
+        private_key: &str,
+        region: &str,
+    ) -> Self {
+        Self {
+            tenancy: tenancy.to_string(),
+            user: user.to_string(),
+            fingerprint: fingerprint.to_string(),
+            private_key: private_key.to_string(),
+            region: region.to_string(),
+        }
+    }
+
+    /// Resolves credentials, in order :
+    /// - the `OCI_TENANCY`, `OCI_USER`, `OCI_FINGERPRINT`, `OCI_PRIVATE_KEY` and `OCI_REGION` environment variables
+    /// - the `OCI_CLI_PROFILE` (or `DEFAULT`) profile of the configuration file of the OCI tools
+    pub fn from_env() -> Result<Self, LibError> {
+        if let Some(user) = crate::get_env_var_option(ENV_OCI_USER) {
+            return Ok(Self {
+                tenancy: crate::get_env_var(ENV_OCI_TENANCY)?,
+                user,
+                fingerprint: crate::get_env_var(ENV_OCI_FINGERPRINT)?,
+                private_key: crate::get_env_var(ENV_OCI_PRIVATE_KEY)?,
+                region: crate::get_env_var(ENV_OCI_REGION)?,
+            });
+        }
+
+        let profile = crate::get_env_var_default(ENV_OCI_CLI_PROFILE, OCI_DEFAULT_PROFILE);
+        Self::from_config_file(&profile)
+    }
+
+    /// Gets the home directory, for the configuration file and the paths it holds.
+    fn get_home() -> Result<PathBuf, LibError> {
+        crate::get_env_var("HOME")
+            .or_else(|_| crate::get_env_var("USERPROFILE"))
+            .map(PathBuf::from)
+    }
+
+    /// Gets the location of the configuration file, usually `~/.oci/config`.
+    fn get_config_file_path() -> Result<PathBuf, LibError> {
+        if let Some(path) = crate::get_env_var_option(ENV_OCI_CLI_CONFIG_FILE) {
+            return Ok(PathBuf::from(path));
+        }
+        Ok(Self::get_home()?.join(".oci").join("config"))
+    }
+
+    /// Reads the settings of a profile from the configuration file (ini format),
+    /// and the private key from the file it references.
+    fn from_config_file(profile: &str) -> Result<Self, LibError> {
+        let path = Self::get_config_file_path()?;
+        let content = fs::read_to_string(&path).map_err(|source| LibError::FileError {
+            path: path.clone(),
+            source,
+        })?;
+
+        let mut in_profile = false;
+        let (mut tenancy, mut user, mut fingerprint, mut key_file, mut region) =
+            (None, None, None, None, None);
+        for line in content.lines().map(str::trim) {
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                in_profile = section.trim() == profile;
+                continue;
+            }
+            if !in_profile {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = Some(value.trim().to_string());
+            match key.trim() {
+                "tenancy" => tenancy = value,
+                "user" => user = value,
+                "fingerprint" => fingerprint = value,
+                "key_file" => key_file = value,
+                "region" => region = value,
+                _ => {}
+            }
+        }
+
+        let (Some(tenancy), Some(user), Some(fingerprint), Some(key_file), Some(region)) =
+            (tenancy, user, fingerprint, key_file, region)
+        else {
+            return Err(LibError::ValueError {
+                name: format!("incomplete oci profile {profile} in"),
+                value: path.display().to_string(),
+            });
+        };
+
+        let key_file = match key_file.strip_prefix("~/") {
+            Some(relative) => Self::get_home()?.join(relative),
+            None => PathBuf::from(key_file),
+        };
+        let private_key = fs::read_to_string(&key_file).map_err(|source| LibError::FileError {
+            path: key_file,
+            source,
+        })?;
+
+        Ok(Self {
+            tenancy,
+            user,
+            fingerprint,
+            private_key,
+            region,
+        })
+    }
+}
+
+/// A shape to watch, with its configuration when it is flexible, like `VM.Standard.A1.Flex:4:24`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OciShape {
+    name: String,
+    ocpus: Option<f64>,
+    memory_gb: Option<f64>,
+}
+
+impl std::str::FromStr for OciShape {
+    type Err = LibError;
+
+    /// Parses `NAME`, or `NAME:OCPUS:MEMORY_GB` for flexible shapes.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let error = || LibError::ValueError {
+            name: "oci shape (like BM.Standard.E4.128 or VM.Standard.A1.Flex:4:24)".into(),
+            value: value.to_string(),
+        };
+        let mut parts = value.split(':');
+        let name = parts.next().unwrap_or_default().trim().to_string();
+        if name.is_empty() {
+            return Err(error());
+        }
+        let shape = match (parts.next(), parts.next(), parts.next()) {
+            (None, None, None) => Self {
+                name,
+                ocpus: None,
+                memory_gb: None,
+            },
+            (Some(ocpus), Some(memory_gb), None) => Self {
+                name,
+                ocpus: Some(ocpus.trim().parse().map_err(|_| error())?),
+                memory_gb: Some(memory_gb.trim().parse().map_err(|_| error())?),
+            },
+            _ => return Err(error()),
+        };
+        Ok(shape)
+    }
+}
+
+/// Used for API request serialisation
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OciCapacityReportRequest<'a> {
+    compartment_id: &'a str,
+    availability_domain: &'a str,
+    shape_availabilities: Vec<OciShapeRequest<'a>>,
+}
+
+/// Used for API request serialisation
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OciShapeRequest<'a> {
+    instance_shape: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance_shape_config: Option<OciShapeConfig>,
+}
+
+/// Used for API request serialisation
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OciShapeConfig {
+    ocpus: f64,
+    #[serde(rename = "memoryInGBs")]
+    memory_in_gbs: f64,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct OciAvailabilityDomain {
+    name: String,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OciCapacityReport {
+    #[serde(default)]
+    shape_availabilities: Vec<OciShapeAvailability>,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OciShapeAvailability {
+    instance_shape: String,
+    availability_status: Option<String>,
+    available_count: Option<u64>,
+}
+
+/// Used for API error deserialisation
+#[derive(Deserialize)]
+struct OciError {
+    message: String,
+}
+
+/// Availability of a shape in a single availability domain.
+struct OciDomainStatus {
+    domain: String,
+    status: String,
+    count: Option<u64>,
+}
+
+/// Joins a watched shape with its availability in every availability domain.
+struct OciServerType<'a> {
+    shape: &'a OciShape,
+    domains: Vec<OciDomainStatus>,
+}
+
+impl OciServerType<'_> {
+    /// Convenience function to detemine availability
+    fn is_available(&self) -> bool {
+        self.domains
+            .iter()
+            .any(|domain| domain.status == OCI_AVAILABLE_STATUS)
+    }
+
+    /// Tells if the server type matches the requested server reference
+    fn is_matching(&self, server: &str) -> bool {
+        self.shape.name == server
+    }
+}
+
+// I prefer the From trait, as i can pass references
+impl From<&OciServerType<'_>> for ServerInfo {
+    /// Extracts only interesting information which is common to all providers
+    fn from(server_type: &OciServerType) -> Self {
+        let ocpus = match server_type.shape.ocpus {
+            Some(ocpus) => format!("{ocpus}ocpu "),
+            None => String::new(),
+        };
+
+        // the short name of the domain, like AD-1, is enough
+        let domains = server_type
+            .domains
+            .iter()
+            .map(|domain| {
+                let name = domain.domain.rsplit('-').take(2).collect::<Vec<&str>>();
+                let name = name.into_iter().rev().collect::<Vec<&str>>().join("-");
+                match domain.count {
+                    Some(count) => format!("{name}:{}:{count}", domain.status),
+                    None => format!("{name}:{}", domain.status),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(",");
+
+        ServerInfo {
+            reference: format!("{} ({ocpus}@{domains})", server_type.shape.name),
+            memory: match server_type.shape.memory_gb {
+                Some(memory_gb) => format!("{memory_gb}G"),
+                None => "N/A".to_string(),
+            },
+            storage: "N/A".to_string(),
+            available: server_type.is_available(),
+            price: None,
+            cpu: None,
+            cores: None,
+            bandwidth: None,
+            stock: None,
+            order_url: None,
+            range: None,
+        }
+    }
+}
+
+/// Gets the availability of the watched shapes, through compute capacity reports.
+pub struct Oci {
+    client: Client,
+    credentials: OciCredentials,
+    private_key: PKey<Private>,
+    compartment: String,
+    shapes: Vec<OciShape>,
+    availability_domains: Vec<String>,
+}
+
+/// Builds an Oci provider without relying on environment variables.
+#[derive(Default)]
+pub struct OciBuilder {
+    credentials: Option<OciCredentials>,
+    compartment: Option<String>,
+    shapes: Vec<OciShape>,
+    availability_domains: Vec<String>,
+}
+
+impl OciBuilder {
+    /// Sets the settings of the API signing key.
+    pub fn credentials(mut self, credentials: OciCredentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Sets the compartment of the reports (defaults to the tenancy).
+    pub fn compartment(mut self, compartment: &str) -> Self {
+        self.compartment = Some(compartment.to_string());
+        self
+    }
+
+    /// Sets the shapes to watch.
+    pub fn shapes(mut self, shapes: Vec<OciShape>) -> Self {
+        self.shapes = shapes;
+        self
+    }
+
+    /// Sets the availability domains to keep, like `AD-1`, all of them being used when none is set.
+    pub fn availability_domains<I, S>(mut self, availability_domains: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.availability_domains = availability_domains.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Builds the provider, verifying the settings.
+    pub fn build(self) -> Result<Oci, LibError> {
+        let credentials = self.credentials.ok_or_else(|| LibError::ValueError {
+            name: "missing oci credentials".into(),
+            value: String::new(),
+        })?;
+
+        for (name, value) in [
+            ("tenancy", &credentials.tenancy),
+            ("user", &credentials.user),
+            ("fingerprint", &credentials.fingerprint),
+            ("region", &credentials.region),
+        ] {
+            if value.is_empty() {
+                return Err(LibError::ValueError {
+                    name: format!("found empty oci {name}"),
+                    value: value.into(),
+                });
+            }
+        }
+
+        if self.shapes.is_empty() {
+            return Err(LibError::ValueError {
+                name: "found no oci shape".into(),
+                value: String::new(),
+            });
+        }
+
+        // parse the key right away, so that a wrong one is reported before any check
+        let private_key =
+            PKey::private_key_from_pem(credentials.private_key.as_bytes()).map_err(|e| {
+                LibError::ValueError {
+                    name: format!("invalid oci private key ({e})"),
+                    value: ENV_OCI_PRIVATE_KEY.to_string(),
+                }
+            })?;
+
+        // availability domains are upper case in the API
+        let availability_domains = self
+            .availability_domains
+            .iter()
+            .map(|domain| domain.to_uppercase())
+            .collect();
+
+        // construct the object if everything is ok
+        Ok(Oci {
+            client: crate::client::build_client_for(OCI_NAME)?,
+            compartment: self
+                .compartment
+                .unwrap_or_else(|| credentials.tenancy.clone()),
+            credentials,
+            private_key,
+            shapes: self.shapes,
+            availability_domains,
+        })
+    }
+}
+
+impl Oci {
+    /// Starts building a new instance.
+    pub fn builder() -> OciBuilder {
+        OciBuilder::default()
+    }
+
+    /// Fallback error handler for queries, using the error message of the API if any
+    fn do_error_if_not_successful(response: Response) -> Result<Response, LibError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let message = match response.json::<OciError>() {
+            Ok(error) => format!("Error during OCI query: {}", error.message),
+            Err(_) => format!("Error during OCI query: code {status}"),
+        };
+        Err(LibError::ApiStatusError { status, message })
+    }
+
+    /// Signs a request with the API key (draft-cavage-http-signatures, as OCI expects it),
+    /// and returns the headers to add to the request (the host header is set by the client).
+    fn sign(
+        &self,
+        method: &Method,
+        host: &str,
+        path_and_query: &str,
+        body: Option<&str>,
+    ) -> Result<Vec<(&'static str, String)>, LibError> {
+        let mut headers = vec![
+            (
+                "date",
+                Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+            ),
+            (
+                "(request-target)",
+                format!("{} {path_and_query}", method.as_str().to_lowercase()),
+            ),
+            ("host", host.to_string()),
+        ];
+        if let Some(body) = body {
+            headers.push(("content-length", body.len().to_string()));
+            headers.push(("content-type", "application/json".to_string()));
+            headers.push((
+                "x-content-sha256",
+                base64::encode_block(&Sha256::digest(body)),
+            ));
+        }
+
+        let signing_string = headers
+            .iter()
+            .map(|(name, value)| format!("{name}: {value}"))
+            .collect::<Vec<String>>()
+            .join("\n");
+        let signature = Signer::new(MessageDigest::sha256(), &self.private_key)
+            .and_then(|mut signer| {
+                signer.update(signing_string.as_bytes())?;
+                signer.sign_to_vec()
+            })
+            .map_err(|e| LibError::ValueError {
+                name: format!("could not sign oci request ({e})"),
+                value: path_and_query.to_string(),
+            })?;
+
+        let authorization = format!(
+            r#"Signature version="1",keyId="{}/{}/{}",algorithm="rsa-sha256",headers="{}",signature="{}""#,
+            self.credentials.tenancy,
+            self.credentials.user,
+            self.credentials.fingerprint,
+            headers
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<&str>>()
+                .join(" "),
+            base64::encode_block(&signature),
+        );
+
+        // the pseudo header and the ones set by the client are not sent by us
+        headers
+            .retain(|(name, _)| !matches!(*name, "(request-target)" | "host" | "content-length"));
+        headers.push(("authorization", authorization));
+        Ok(headers)
+    }
+
+    /// Executes a signed query, and deserializes the result.
+    fn query<T: serde::de::DeserializeOwned>(
+        &self,
+        method: Method,
+        service: &str,
+        path_and_query: &str,
+        body: Option<String>,
+    ) -> Result<T, LibError> {
+        let host = format!("{service}.{}.oraclecloud.com", self.credentials.region);
+        let headers = self.sign(&method, &host, path_and_query, body.as_deref())?;
+
+        let mut request = self
+            .client
+            .request(method, format!("https://{host}{path_and_query}"));
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+        let response = crate::recording::send(&self.client, request)?;
+
+        // reqwest deserialize
+        Ok(Self::do_error_if_not_successful(response)?.json::<T>()?)
+    }
+
+    /// Gets the names of the availability domains to report on.
+    fn get_availability_domains(&self) -> Result<Vec<String>, LibError> {
+        let path = format!(
+            "/20160918/availabilityDomains?compartmentId={}",
+            crate::template::escape_url(&self.credentials.tenancy)
+        );
+        Ok(self
+            .query::<Vec<OciAvailabilityDomain>>(Method::GET, "identity", &path, None)?
+            .into_iter()
+            .map(|domain| domain.name)
+            .filter(|name| {
+                self.availability_domains.is_empty()
+                    || self
+                        .availability_domains
+                        .iter()
+                        .any(|filter| name.to_uppercase().ends_with(filter))
+            })
+            .collect())
+    }
+
+    /// Gets the availability of every watched shape, in every availability domain.
+    fn get_server_types(&self) -> Result<Vec<OciServerType<'_>>, LibError> {
+        let mut server_types: Vec<OciServerType> = self
+            .shapes
+            .iter()
+            .map(|shape| OciServerType {
+                shape,
+                domains: Vec::new(),
+            })
+            .collect();
+
+        // a report covers every shape, but a single availability domain
+        for domain in self.get_availability_domains()? {
+            let request = OciCapacityReportRequest {
+                compartment_id: &self.compartment,
+                availability_domain: &domain,
+                shape_availabilities: self
+                    .shapes
+                    .iter()
+                    .map(|shape| OciShapeRequest {
+                        instance_shape: &shape.name,
+                        instance_shape_config: match (shape.ocpus, shape.memory_gb) {
+                            (Some(ocpus), Some(memory_gb)) => Some(OciShapeConfig {
+                                ocpus,
+                                memory_in_gbs: memory_gb,
+                            }),
+                            _ => None,
+                        },
+                    })
+                    .collect(),
+            };
+            let body = serde_json::to_string(&request)?;
+            let report: OciCapacityReport = self.query(
+                Method::POST,
+                "iaas",
+                "/20160918/computeCapacityReports",
+                Some(body),
+            )?;
+
+            for availability in report.shape_availabilities {
+                let Some(server_type) = server_types
+                    .iter_mut()
+                    .find(|server_type| server_type.shape.name == availability.instance_shape)
+                else {
+                    continue;
+                };
+                server_type.domains.push(OciDomainStatus {
+                    domain: domain.clone(),
+                    status: availability.availability_status.unwrap_or_default(),
+                    count: availability.available_count,
+                });
+            }
+        }
+
+        Ok(server_types)
+    }
+}
+
+impl ProviderFactoryTrait for Oci {
+    /// Builds an Oci provider from environment variables.
+    fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
+        let shapes = crate::tokenize_optional_csv_str(&Some(crate::get_env_var(ENV_OCI_SHAPES)?))?
+            .iter()
+            .map(|shape| shape.parse())
+            .collect::<Result<Vec<OciShape>, LibError>>()?;
+        let domains_csv = crate::get_env_var_option(ENV_OCI_AVAILABILITY_DOMAINS);
+        let mut builder = Self::builder()
+            .credentials(OciCredentials::from_env()?)
+            .shapes(shapes)
+            .availability_domains(crate::tokenize_optional_csv_str(&domains_csv)?);
+        if let Some(compartment) = crate::get_env_var_option(ENV_OCI_COMPARTMENT) {
+            builder = builder.compartment(&compartment);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+impl ProviderTrait for Oci {
+    /// Gets the actual name of the provider.
+    fn name(&self) -> &'static str {
+        OCI_NAME
+    }
+
+    /// Collects provider inventory.
+    fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        Ok(self
+            .get_server_types()?
+            .iter()
+            .filter(|server_type| server_type.is_available() || all)
+            .map(|server_type| server_type.into())
+            .collect())
+    }
+
+    /// Checks provider for the availability of a given shape.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        self.get_server_types()?
+            .iter()
+            .find(|server_type| server_type.is_matching(server))
+            .map(|server_type| server_type.is_available())
+            .ok_or(LibError::UnknownServer {
+                server: server.to_string(),
+                suggestions: Vec::new(),
+            })
+    }
+
+    /// Checks several shapes from a single round of reports.
+    fn check_many(&self, servers: &[String]) -> Result<Vec<(String, bool)>, LibError> {
+        let server_types = self.get_server_types()?;
+        servers
+            .iter()
+            .map(|server| {
+                server_types
+                    .iter()
+                    .find(|server_type| server_type.is_matching(server))
+                    .map(|server_type| (server.clone(), server_type.is_available()))
+                    .ok_or(LibError::UnknownServer {
+                        server: server.to_string(),
+                        suggestions: Vec::new(),
+                    })
+            })
+            .collect()
+    }
+}
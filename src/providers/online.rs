@@ -1,10 +1,10 @@
-use super::{ProviderFactoryTrait, ProviderTrait, ServerInfo};
+use super::{ProviderFactoryTrait, ProviderTrait, ServerInfo, StockLevel};
 use crate::LibError;
 use array_tool::vec::Intersect;
 use http::Method;
 use reqwest::blocking::{Client, RequestBuilder, Response};
 use serde::Deserialize;
-use serde_json::Value;
+use std::collections::BTreeMap;
 
 // Online implementation
 
@@ -17,6 +17,12 @@ const ENV_ONLINE_PRIVATE_TOKEN: &str = "ONLINE_PRIVATE_TOKEN";
 /// Common environment variable to input your Online API key.
 const ENV_ONLINE_DATACENTERS: &str = "ONLINE_DATACENTERS";
 
+/// Dedibox order form, where every server is listed.
+const ONLINE_ORDER_URL: &str = "https://console.online.net/en/order/server";
+
+/// Used for API result deserialisation, products being grouped by range, then by identifier
+type OnlineDediboxPlans = BTreeMap<String, BTreeMap<String, OnlineDediboxProduct>>;
+
 /// Used for API result deserialisation, with only interesting fields implemented
 #[derive(Deserialize)]
 struct OnlineDediboxProduct {
@@ -24,6 +30,9 @@ struct OnlineDediboxProduct {
     slug: String,
     specs: OnlineDediboxProductSpecs,
     stocks: Vec<OnlineDediboxProductStock>,
+    /// Name of the range holding the product, like `Start`, `Pro` or `Core`
+    #[serde(skip)]
+    range: String,
 }
 
 impl OnlineDediboxProduct {
@@ -34,7 +43,12 @@ impl OnlineDediboxProduct {
                 return true;
             }
         }
-        return false;
+        false
+    }
+
+    /// Counts the servers in stock, across the listed datacenters
+    fn stock_count(&self) -> u32 {
+        self.stocks.iter().map(|stock| stock.stock).sum()
     }
 }
 
@@ -86,7 +100,7 @@ impl From<&OnlineDediboxProduct> for ServerInfo {
             .collect::<Vec<String>>()
             .join(",");
 
-        let available_quantity = product.stocks.iter().map(|p| p.stock).sum::<u32>();
+        let available_quantity = product.stock_count();
 
         let reference = format!("{} ({}@{})", product.id, product.slug, datacenters);
 
@@ -95,40 +109,75 @@ impl From<&OnlineDediboxProduct> for ServerInfo {
             memory,
             storage,
             available: available_quantity > 0,
+            price: None,
+            cpu: Some(cpu),
+            cores: None,
+            bandwidth: None,
+            stock: StockLevel::from_count(available_quantity),
+            order_url: Some(ONLINE_ORDER_URL.to_string()),
+            range: Some(product.range.clone()),
         }
     }
 }
 
 /// Gets server inventory and availability.
 pub struct Online {
+    client: Client,
     api_token: String,
     datacenters: Vec<String>,
 }
 
-impl Online {
-    /// Builds a new instance.
-    fn new(api_token: &str, dc_csv: &Option<String>) -> Result<Self, LibError> {
-        let api_token = api_token.to_string();
-        if api_token.is_empty() {
+/// Builds an Online provider without relying on environment variables.
+#[derive(Default)]
+pub struct OnlineBuilder {
+    api_token: String,
+    datacenters: Vec<String>,
+}
+
+impl OnlineBuilder {
+    /// Sets the private API token.
+    pub fn api_token(mut self, api_token: &str) -> Self {
+        self.api_token = api_token.to_string();
+        self
+    }
+
+    /// Sets the datacenters to keep, all of them being used when none is set.
+    pub fn datacenters<I, S>(mut self, datacenters: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.datacenters = datacenters.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Builds the provider, verifying the settings.
+    pub fn build(self) -> Result<Online, LibError> {
+        if self.api_token.is_empty() {
             return Err(LibError::ValueError {
                 name: "found empty online api token".into(),
-                value: api_token.into(),
+                value: self.api_token,
             });
         }
 
-        // verify datacenter variable
-        let datacenters: Vec<String> = crate::tokenize_optional_csv_str(dc_csv)?;
-
         // construct the object if everything is ok
-        Ok(Self {
-            api_token,
-            datacenters,
+        Ok(Online {
+            client: crate::client::build_client_for(ONLINE_NAME)?,
+            api_token: self.api_token,
+            datacenters: self.datacenters,
         })
     }
+}
+
+impl Online {
+    /// Starts building a new instance.
+    pub fn builder() -> OnlineBuilder {
+        OnlineBuilder::default()
+    }
 
     /// Wrapper for automatic handling of authentication
     fn create_authenticated_request_builder(&self, method: Method, url: &str) -> RequestBuilder {
-        Client::new()
+        self.client
             .request(method, url)
             .header("Authorization", format!("Bearer {}", &self.api_token))
     }
@@ -139,7 +188,8 @@ impl Online {
             return Ok(());
         }
 
-        Err(LibError::ApiError {
+        Err(LibError::ApiStatusError {
+            status: response.status(),
             message: format!(
                 "Error during Online dedibox query: code {}",
                 response.status()
@@ -149,52 +199,39 @@ impl Online {
 
     /// Executes simple authenticated get queries which fails only on transport errors
     fn get_api_authenticated(&self, url: &str) -> Result<Response, LibError> {
-        let response = self
-            .create_authenticated_request_builder(Method::GET, url)
-            .send()
-            .map_err(|source| LibError::RequestError { source })?;
+        let response = crate::recording::send(
+            &self.client,
+            self.create_authenticated_request_builder(Method::GET, url),
+        )?;
 
         Ok(response)
     }
 
-    // Extract the enum value from a serde_json Value::Object variant
-    fn extract_serde_value_object_variant_value(
-        name: &str,
-        value: serde_json::Value,
-    ) -> Result<serde_json::Map<String, Value>, LibError> {
-        match value {
-            Value::Object(value) => Ok(value),
-            _ => Err(LibError::ApiError {
-                message: format!("Dedibox value {name} is not a json object"),
-            }),
-        }
-    }
-
-    /// Gets all plans, with produc ranges and actual products
+    /// Gets all plans, with produc ranges and actual products,
+    /// with only the stocks of the desired datacenters if any
     fn get_plans(&self) -> Result<Vec<OnlineDediboxProduct>, LibError> {
         let url = "https://api.online.net/api/v1/dedibox/plans";
-        let response = self.get_api_authenticated(&url)?;
+        let response = self.get_api_authenticated(url)?;
 
         // fallback error handler
         Self::do_error_if_not_successful(&response)?;
 
-        // reqwest generic deserialize
-        let ranges = response
-            .json::<Value>()
-            .map_err(|source| LibError::RequestError { source })?;
-
-        // extract enum value
-        let ranges = Self::extract_serde_value_object_variant_value("root", ranges)?;
+        // reqwest deserialize
+        let ranges = response.json::<OnlineDediboxPlans>()?;
 
         let mut results: Vec<OnlineDediboxProduct> = Vec::new();
-        for (range_name, products) in ranges.into_iter() {
-            // convert range Value into its map
-            let products = Self::extract_serde_value_object_variant_value(&range_name, products)?;
-
-            for (_, product) in products.into_iter() {
-                // deserialize product Value
-                let product: OnlineDediboxProduct = serde_json::from_value(product)
-                    .map_err(|source| LibError::JsonError { source })?;
+        for (range, products) in ranges.into_iter() {
+            for (_, mut product) in products.into_iter() {
+                product.range = range.clone();
+
+                // if we do not filter on datacenters, any of them will be fine
+                if !self.datacenters.is_empty() {
+                    product.stocks.retain(|stock| {
+                        self.datacenters
+                            .iter()
+                            .any(|name| name.eq_ignore_ascii_case(&stock.datacenter.name))
+                    });
+                }
 
                 // add to collection
                 results.push(product);
@@ -213,18 +250,16 @@ impl Online {
         Self::do_error_if_not_successful(&response)?;
 
         // reqwest deserialize and check
-        let result = response
-            .json::<OnlineDediboxProductAvailability>()
-            .map_err(|source| LibError::RequestError { source })?;
+        let result = response.json::<OnlineDediboxProductAvailability>()?;
 
         // if we do not filter on datacenters, any of them will be fine
-        if self.datacenters.len() == 0 {
+        if self.datacenters.is_empty() {
             return Ok(result.available);
         }
 
         // extract available datacenter names, and find if any are in common with desired ones
         let result: Vec<String> = result.datacenters.iter().map(|d| d.name.clone()).collect();
-        Ok(self.datacenters.intersect(result).len() > 0)
+        Ok(!self.datacenters.intersect(result).is_empty())
     }
 }
 
@@ -233,14 +268,19 @@ impl ProviderFactoryTrait for Online {
     fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
         let api_token = crate::get_env_var(ENV_ONLINE_PRIVATE_TOKEN)?;
         let dc_csv = crate::get_env_var_option(ENV_ONLINE_DATACENTERS);
-        Ok(Box::new(Self::new(&api_token, &dc_csv)?))
+        Ok(Box::new(
+            Self::builder()
+                .api_token(&api_token)
+                .datacenters(crate::tokenize_optional_csv_str(&dc_csv)?)
+                .build()?,
+        ))
     }
 }
 
 impl ProviderTrait for Online {
     /// Gets the actual name of the provider.
     fn name(&self) -> &'static str {
-        return ONLINE_NAME;
+        ONLINE_NAME
     }
 
     /// Collects provider inventory.
@@ -257,4 +297,54 @@ impl ProviderTrait for Online {
     fn check(&self, server: &str) -> Result<bool, LibError> {
         self.get_product_availability(server)
     }
+
+    /// Checks several server types from a single listing of the plans.
+    fn check_many(&self, servers: &[String]) -> Result<Vec<(String, bool)>, LibError> {
+        Ok(self
+            .check_stock(servers)?
+            .into_iter()
+            .map(|(server, available, _)| (server, available))
+            .collect())
+    }
+
+    /// Checks several server types from a single listing of the plans,
+    /// the stock level following the count of servers in stock.
+    fn check_stock(
+        &self,
+        servers: &[String],
+    ) -> Result<Vec<(String, bool, Option<StockLevel>)>, LibError> {
+        let products = self.get_plans()?;
+        servers
+            .iter()
+            .map(|server| {
+                products
+                    .iter()
+                    .find(|product| &product.id.to_string() == server)
+                    .map(|product| {
+                        let stock = StockLevel::from_count(product.stock_count());
+                        (server.clone(), stock.is_some(), stock)
+                    })
+                    .ok_or(LibError::UnknownServer {
+                        server: server.to_string(),
+                        suggestions: Vec::new(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Verifies that the server is a product number.
+    fn validate_server(&self, server: &str) -> Result<(), LibError> {
+        match server.parse::<u32>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(LibError::ValueError {
+                name: "malformed online product id".into(),
+                value: server.to_string(),
+            }),
+        }
+    }
+
+    /// Gets the Dedibox order form.
+    fn order_url(&self, _server: &str) -> Option<String> {
+        Some(ONLINE_ORDER_URL.to_string())
+    }
 }
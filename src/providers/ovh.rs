@@ -1,6 +1,10 @@
-use super::{ProviderFactoryTrait, ProviderTrait, ServerInfo};
+use super::{FailurePolicy, PartialFailures, ProviderFactoryTrait, ProviderTrait, ServerInfo};
 use crate::LibError;
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::Url;
 use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
 
 // OVH implementation
 
@@ -10,9 +14,132 @@ pub const OVH_NAME: &str = "ovh";
 /// Common environment variable to eventually filter the queries.
 const ENV_NAME_OVH_EXCLUDE_DATACENTER: &str = "OVH_EXCLUDE_DATACENTER";
 
+/// Environment variables to optionally sign the queries with an application of your account.
+const ENV_NAME_OVH_APP_KEY: &str = "OVH_APP_KEY";
+const ENV_NAME_OVH_APP_SECRET: &str = "OVH_APP_SECRET";
+const ENV_NAME_OVH_CONSUMER_KEY: &str = "OVH_CONSUMER_KEY";
+
+/// Common environment variable to select the subsidiary whose catalog gives the prices.
+const ENV_NAME_OVH_SUBSIDIARY: &str = "OVH_SUBSIDIARY";
+
+/// Subsidiary used when none is selected.
+const OVH_DEFAULT_SUBSIDIARY: &str = "FR";
+
 /// Provider API endpoint.
 const OVH_URL: &str = "https://api.ovh.com/1.0/dedicated/server/datacenter/availabilities";
 
+/// Order catalogs holding the plans of dedicated servers, the eco ranges and the others.
+const OVH_CATALOGS: &[&str] = &["eco", "baremetalServers"];
+
+/// Catalog prices are given in hundred millionths of the currency.
+const OVH_CATALOG_PRICE_FACTOR: f64 = 100000000.0;
+
+/// Gets the express order page of a server, which is selected by its plan code.
+fn get_order_url(server: &str) -> String {
+    format!("https://www.ovh.com/fr/order/express/#/express/review?products=~(~(planCode~'{server}~productId~'dedicated))")
+}
+
+/// Credentials of an application of your account, created on https://api.ovh.com/createToken/
+/// with at least the `GET /dedicated/*` right, so that availabilities are those of your account.
+#[derive(Clone)]
+pub struct OvhCredentials {
+    pub app_key: String,
+    pub app_secret: String,
+    pub consumer_key: String,
+}
+
+impl OvhCredentials {
+    /// Reads the credentials, if an application key is set, in which case the others are required
+    fn from_env() -> Result<Option<Self>, LibError> {
+        let Some(app_key) = crate::get_env_var_option(ENV_NAME_OVH_APP_KEY) else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            app_key,
+            app_secret: crate::get_env_var(ENV_NAME_OVH_APP_SECRET)?,
+            consumer_key: crate::get_env_var(ENV_NAME_OVH_CONSUMER_KEY)?,
+        }))
+    }
+
+    /// Signs a query the way the API expects it, which is
+    /// `$1$` followed by the hex SHA-1 of `secret+consumer+method+url+body+timestamp`.
+    fn sign(&self, method: &str, url: &str, body: &str, timestamp: u64) -> String {
+        let data = format!(
+            "{}+{}+{method}+{url}+{body}+{timestamp}",
+            self.app_secret, self.consumer_key
+        );
+        format!("$1${:x}", Sha1::digest(data))
+    }
+
+    /// Adds the authentication headers to a GET query of the url.
+    fn authenticate(&self, builder: RequestBuilder, url: &Url) -> RequestBuilder {
+        // the server rejects queries whose timestamp is too far from its own clock
+        let timestamp = crate::storage::get_timestamp();
+        builder
+            .header("X-Ovh-Application", &self.app_key)
+            .header("X-Ovh-Consumer", &self.consumer_key)
+            .header("X-Ovh-Timestamp", timestamp.to_string())
+            .header(
+                "X-Ovh-Signature",
+                self.sign("GET", url.as_str(), "", timestamp),
+            )
+    }
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct OvhCatalog {
+    plans: Vec<OvhCatalogPlan>,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct OvhCatalogPlan {
+    #[serde(rename = "planCode")]
+    plan_code: String,
+    #[serde(rename = "invoiceName")]
+    invoice_name: String,
+    #[serde(default)]
+    pricings: Vec<OvhCatalogPricing>,
+}
+
+impl OvhCatalogPlan {
+    /// Gets the commercial name, like `KS-4`, without the description following it
+    fn name(&self) -> &str {
+        self.invoice_name
+            .split('|')
+            .next()
+            .unwrap_or_default()
+            .trim()
+    }
+
+    /// Gets the price of a monthly renewal, which is the one paid over time
+    fn monthly_price(&self) -> Option<f64> {
+        self.pricings
+            .iter()
+            .find(|pricing| {
+                pricing
+                    .capacities
+                    .iter()
+                    .any(|capacity| capacity == "renew")
+                    && pricing.interval_unit == "month"
+                    && pricing.interval == 1
+            })
+            .map(|pricing| pricing.price as f64 / OVH_CATALOG_PRICE_FACTOR)
+    }
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct OvhCatalogPricing {
+    #[serde(default)]
+    capacities: Vec<String>,
+    interval: u32,
+    #[serde(rename = "intervalUnit")]
+    interval_unit: String,
+    price: u64,
+}
+
 /// Used for API result deserialisation, with only interesting fields implemented
 #[derive(Deserialize)]
 struct OvhDedicatedServerInformation {
@@ -20,9 +147,40 @@ struct OvhDedicatedServerInformation {
     memory: Option<String>,
     storage: Option<String>,
     server: String,
+    #[serde(rename = "planCode")]
+    plan_code: Option<String>,
 }
 
 impl OvhDedicatedServerInformation {
+    /// Gets the plan code to look the server up in the catalog, which is usually its name
+    fn plan_code(&self) -> &str {
+        self.plan_code.as_deref().unwrap_or(&self.server)
+    }
+
+    /// Lists the datacenters, like `bhs,gra,rbx`
+    fn datacenter_list(&self) -> String {
+        self.datacenters
+            .iter()
+            .map(|d| d.datacenter.clone())
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    /// Describes the server with its commercial name and its monthly price, when it is in the catalog
+    fn to_server_info(&self, plan: Option<&OvhCatalogPlan>) -> ServerInfo {
+        let mut info: ServerInfo = self.into();
+        if let Some(plan) = plan {
+            info.reference = format!(
+                "{} ({} @{})",
+                self.server,
+                plan.name(),
+                self.datacenter_list()
+            );
+            info.price = plan.monthly_price();
+        }
+        info
+    }
+
     /// Convenience function to detemine availability
     fn is_available(&self) -> bool {
         for datacenter in self.datacenters.iter() {
@@ -30,7 +188,7 @@ impl OvhDedicatedServerInformation {
                 return true;
             }
         }
-        return false;
+        false
     }
 }
 
@@ -44,10 +202,7 @@ struct OvhDedicatedServerDatacenterAvailability {
 impl OvhDedicatedServerDatacenterAvailability {
     /// Convenience function to detemine availability
     fn is_available(&self) -> bool {
-        match self.availability.as_str() {
-            "unavailable" | "unknown" => return false,
-            _ => return true,
-        }
+        !matches!(self.availability.as_str(), "unavailable" | "unknown")
     }
 }
 
@@ -56,15 +211,7 @@ impl From<&OvhDedicatedServerInformation> for ServerInfo {
     /// Extracts only interesting information which is common to all providers
     fn from(info: &OvhDedicatedServerInformation) -> Self {
         ServerInfo {
-            reference: format!(
-                "{} (@{})",
-                info.server,
-                info.datacenters
-                    .iter()
-                    .map(|d| d.datacenter.clone())
-                    .collect::<Vec<String>>()
-                    .join(",")
-            ),
+            reference: format!("{} (@{})", info.server, info.datacenter_list()),
             memory: info
                 .memory
                 .as_ref()
@@ -76,25 +223,78 @@ impl From<&OvhDedicatedServerInformation> for ServerInfo {
                 .unwrap_or(&"N/A".to_string())
                 .to_string(),
             available: info.is_available(),
+            price: None,
+            cpu: None,
+            cores: None,
+            bandwidth: None,
+            stock: None,
+            order_url: Some(get_order_url(&info.server)),
+            range: None,
         }
     }
 }
 
 /// Gets server inventory and availability.
 pub struct Ovh {
+    client: Client,
     /// Used to exclude datacenters by their id.
     /// Examples : ["ca","bhs","fr","gra","rbx","sbg"]
     excluded_datacenters: Vec<String>,
+    /// Used to sign the queries, which are anonymous otherwise.
+    credentials: Option<OvhCredentials>,
+    /// Subsidiary whose catalog gives the commercial names and prices, like `FR` or `CA`.
+    subsidiary: String,
 }
 
-impl Ovh {
-    /// Builds a new instance.
-    fn new(excluded_datacenters: &Option<String>) -> Result<Self, LibError> {
-        let excluded_datacenters = crate::tokenize_optional_csv_str(&excluded_datacenters)?;
-        Ok(Self {
-            excluded_datacenters,
+/// Builds an Ovh provider without relying on environment variables.
+#[derive(Default)]
+pub struct OvhBuilder {
+    excluded_datacenters: Vec<String>,
+    credentials: Option<OvhCredentials>,
+    subsidiary: Option<String>,
+}
+
+impl OvhBuilder {
+    /// Sets the datacenters (or countries) to exclude, none being excluded when none is set.
+    pub fn excluded_datacenters<I, S>(mut self, excluded_datacenters: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.excluded_datacenters = excluded_datacenters.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the credentials of an application of your account, queries being anonymous otherwise.
+    pub fn credentials(mut self, credentials: Option<OvhCredentials>) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Sets the subsidiary whose catalog gives the prices, `FR` being used when none is set.
+    pub fn subsidiary(mut self, subsidiary: &str) -> Self {
+        self.subsidiary = Some(subsidiary.to_uppercase());
+        self
+    }
+
+    /// Builds the provider.
+    pub fn build(self) -> Result<Ovh, LibError> {
+        Ok(Ovh {
+            client: crate::client::build_client_for(OVH_NAME)?,
+            excluded_datacenters: self.excluded_datacenters,
+            credentials: self.credentials,
+            subsidiary: self
+                .subsidiary
+                .unwrap_or_else(|| OVH_DEFAULT_SUBSIDIARY.to_string()),
         })
     }
+}
+
+impl Ovh {
+    /// Starts building a new instance.
+    pub fn builder() -> OvhBuilder {
+        OvhBuilder::default()
+    }
 
     /// Gets availability for specified server types.
     /// `server`: optionally used to query for a single server type.
@@ -117,45 +317,99 @@ impl Ovh {
             query.push(("server", server.into()));
         }
 
-        // Actual request
-        let response = reqwest::blocking::Client::new()
-            .get(OVH_URL)
-            .query(&query)
-            .send()
-            .map_err(|source| LibError::RequestError { source })?;
+        // Actual request, signed as a whole url if credentials are set
+        let url = Url::parse_with_params(OVH_URL, &query).map_err(|_| LibError::ValueError {
+            name: "OVH query".into(),
+            value: OVH_URL.into(),
+        })?;
+        let mut builder = self.client.get(url.clone());
+        if let Some(credentials) = &self.credentials {
+            builder = credentials.authenticate(builder, &url);
+        }
+        let response = crate::recording::send(&self.client, builder)?;
 
         if !response.status().is_success() {
-            return Err(LibError::ApiError {
+            return Err(LibError::ApiStatusError {
+                status: response.status(),
                 message: format!("Error during OVH query: code {}", response.status()),
             });
         }
 
         // Deserialization
-        let results: Vec<OvhDedicatedServerInformation> = response
-            .json()
-            .map_err(|source| LibError::RequestError { source })?;
+        let results: Vec<OvhDedicatedServerInformation> = response.json()?;
 
         Ok(results)
     }
+
+    /// Gets the plans of an order catalog.
+    fn api_get_order_catalog(&self, catalog: &str) -> Result<Vec<OvhCatalogPlan>, LibError> {
+        let url = format!("https://api.ovh.com/1.0/order/catalog/public/{catalog}");
+        let response = crate::recording::send(
+            &self.client,
+            self.client
+                .get(url)
+                .query(&[("ovhSubsidiary", &self.subsidiary)]),
+        )?;
+
+        if !response.status().is_success() {
+            return Err(LibError::ApiStatusError {
+                status: response.status(),
+                message: format!("Error during OVH catalog query: code {}", response.status()),
+            });
+        }
+
+        let catalog: OvhCatalog = response.json()?;
+
+        Ok(catalog.plans)
+    }
+
+    /// Gets the plans of every catalog, by their plan code. As they only describe the servers,
+    /// a catalog which cannot be queried is skipped, with a warning.
+    fn get_plans(&self) -> Result<HashMap<String, OvhCatalogPlan>, LibError> {
+        let mut failures = PartialFailures::new(OVH_NAME, FailurePolicy::BestEffort);
+        let mut plans = HashMap::new();
+        for catalog in OVH_CATALOGS {
+            let part = format!("catalog {catalog}");
+            let Some(catalog) = failures.handle(&part, self.api_get_order_catalog(catalog))? else {
+                continue;
+            };
+            plans.extend(
+                catalog
+                    .into_iter()
+                    .map(|plan| (plan.plan_code.clone(), plan)),
+            );
+        }
+        Ok(plans)
+    }
 }
 
 impl ProviderFactoryTrait for Ovh {
     /// Builds an Ovh provider from environment variables.
     fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
         let excluded_datacenters = crate::get_env_var_option(ENV_NAME_OVH_EXCLUDE_DATACENTER);
-        Ok(Box::new(Ovh::new(&excluded_datacenters)?))
+        Ok(Box::new(
+            Self::builder()
+                .excluded_datacenters(crate::tokenize_optional_csv_str(&excluded_datacenters)?)
+                .credentials(OvhCredentials::from_env()?)
+                .subsidiary(&crate::get_env_var_default(
+                    ENV_NAME_OVH_SUBSIDIARY,
+                    OVH_DEFAULT_SUBSIDIARY,
+                ))
+                .build()?,
+        ))
     }
 }
 
 impl ProviderTrait for Ovh {
     /// Gets the actual name of the provider.
     fn name(&self) -> &'static str {
-        return OVH_NAME;
+        OVH_NAME
     }
 
     /// Collects provider inventory.
     fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
         let results = self.api_get_dedicated_server_datacenter_availabilities(None)?;
+        let plans = self.get_plans()?;
 
         let mut infos = Vec::new();
 
@@ -165,7 +419,7 @@ impl ProviderTrait for Ovh {
                 continue;
             }
 
-            infos.push(server.into());
+            infos.push(server.to_server_info(plans.get(server.plan_code())));
         }
 
         Ok(infos)
@@ -177,9 +431,29 @@ impl ProviderTrait for Ovh {
         // Server ids can have duplicates (location, specs, ...)
         for result in results {
             if result.is_available() {
-                return Ok(true)
+                return Ok(true);
             }
         }
         Ok(false)
     }
+
+    /// Checks several server types from a single query.
+    fn check_many(&self, servers: &[String]) -> Result<Vec<(String, bool)>, LibError> {
+        let results = self.api_get_dedicated_server_datacenter_availabilities(None)?;
+        // Server ids can have duplicates (location, specs, ...)
+        Ok(servers
+            .iter()
+            .map(|server| {
+                let available = results
+                    .iter()
+                    .any(|result| &result.server == server && result.is_available());
+                (server.clone(), available)
+            })
+            .collect())
+    }
+
+    /// Gets the express order page of the server.
+    fn order_url(&self, server: &str) -> Option<String> {
+        Some(get_order_url(server))
+    }
 }
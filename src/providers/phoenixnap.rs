@@ -0,0 +1,364 @@
+use super::{ProviderFactoryTrait, ProviderTrait, ServerInfo};
+use crate::LibError;
+use http::Method;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// PhoenixNAP Bare Metal Cloud implementation
+
+/// Common name to identify the provider
+pub const PHOENIXNAP_NAME: &str = "phoenixnap";
+
+/// Common environment variable to input your PhoenixNAP OAuth2 client identifier.
+const ENV_PHOENIXNAP_CLIENT_ID: &str = "PHOENIXNAP_CLIENT_ID";
+
+/// Common environment variable to input your PhoenixNAP OAuth2 client secret.
+const ENV_PHOENIXNAP_CLIENT_SECRET: &str = "PHOENIXNAP_CLIENT_SECRET";
+
+/// Common environment variable to eventually filter the locations.
+const ENV_PHOENIXNAP_LOCATIONS: &str = "PHOENIXNAP_LOCATIONS";
+
+/// Endpoint delivering access tokens for the client credentials grant.
+const PHOENIXNAP_TOKEN_URL: &str =
+    "https://auth.phoenixnap.com/auth/realms/BMC/protocol/openid-connect/token";
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct PhoenixNapToken {
+    access_token: String,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PhoenixNapProduct {
+    product_code: String,
+    metadata: Option<PhoenixNapProductMetadata>,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PhoenixNapProductMetadata {
+    ram_in_gb: Option<f64>,
+    cpu: Option<String>,
+    cpu_count: Option<u32>,
+    cores_per_cpu: Option<u32>,
+    storage: Option<String>,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PhoenixNapProductAvailability {
+    product_code: String,
+    #[serde(default)]
+    location_availability_details: Vec<PhoenixNapLocationAvailability>,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PhoenixNapLocationAvailability {
+    location: String,
+    #[serde(default)]
+    available_quantity: u32,
+}
+
+/// Joins a server type with its availability in every location.
+struct PhoenixNapServerType {
+    product: PhoenixNapProduct,
+    locations: Vec<PhoenixNapLocationAvailability>,
+}
+
+impl PhoenixNapServerType {
+    /// Convenience function to detemine availability
+    fn is_available(&self) -> bool {
+        self.locations
+            .iter()
+            .any(|location| location.available_quantity > 0)
+    }
+
+    /// Tells if the server type matches the requested server reference
+    fn is_matching(&self, server: &str) -> bool {
+        self.product.product_code == server
+    }
+}
+
+// I prefer the From trait, as i can pass references
+impl From<&PhoenixNapServerType> for ServerInfo {
+    /// Extracts only interesting information which is common to all providers
+    fn from(server_type: &PhoenixNapServerType) -> Self {
+        let metadata = server_type.product.metadata.as_ref();
+
+        let cpu = match metadata.and_then(|metadata| metadata.cpu.as_ref()) {
+            Some(cpu) => format!(
+                "{}x{}c {}",
+                metadata.and_then(|m| m.cpu_count).unwrap_or(1),
+                metadata.and_then(|m| m.cores_per_cpu).unwrap_or_default(),
+                cpu
+            ),
+            None => "N/A".to_string(),
+        };
+
+        let memory = match metadata.and_then(|metadata| metadata.ram_in_gb) {
+            Some(ram) => format!("{ram}G"),
+            None => "N/A".to_string(),
+        };
+
+        let mut storage = metadata
+            .and_then(|metadata| metadata.storage.clone())
+            .unwrap_or_default();
+        storage.retain(|c| !c.is_whitespace());
+        if storage.is_empty() {
+            storage = "N/A".to_string();
+        }
+
+        let locations = server_type
+            .locations
+            .iter()
+            .map(|location| format!("{}:{}", location.location, location.available_quantity))
+            .collect::<Vec<String>>()
+            .join(",");
+
+        ServerInfo {
+            reference: format!(
+                "{} ({}@{})",
+                server_type.product.product_code, cpu, locations
+            ),
+            memory,
+            storage,
+            available: server_type.is_available(),
+            price: None,
+            cpu: metadata.and_then(|metadata| metadata.cpu.clone()),
+            cores: metadata.and_then(|metadata| {
+                Some(metadata.cpu_count.unwrap_or(1) * metadata.cores_per_cpu?)
+            }),
+            bandwidth: None,
+            stock: None,
+            order_url: None,
+            range: None,
+        }
+    }
+}
+
+/// Gets server inventory and availability.
+pub struct PhoenixNap {
+    client: Client,
+    client_id: String,
+    client_secret: String,
+    locations: Vec<String>,
+}
+
+/// Builds a PhoenixNap provider without relying on environment variables.
+#[derive(Default)]
+pub struct PhoenixNapBuilder {
+    client_id: String,
+    client_secret: String,
+    locations: Vec<String>,
+}
+
+impl PhoenixNapBuilder {
+    /// Sets the OAuth2 client identifier.
+    pub fn client_id(mut self, client_id: &str) -> Self {
+        self.client_id = client_id.to_string();
+        self
+    }
+
+    /// Sets the OAuth2 client secret.
+    pub fn client_secret(mut self, client_secret: &str) -> Self {
+        self.client_secret = client_secret.to_string();
+        self
+    }
+
+    /// Sets the locations to keep, all of them being used when none is set.
+    pub fn locations<I, S>(mut self, locations: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.locations = locations.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Builds the provider, verifying the settings.
+    pub fn build(self) -> Result<PhoenixNap, LibError> {
+        for (name, value) in [
+            ("client id", &self.client_id),
+            ("client secret", &self.client_secret),
+        ] {
+            if value.is_empty() {
+                return Err(LibError::ValueError {
+                    name: format!("found empty phoenixnap {name}"),
+                    value: value.into(),
+                });
+            }
+        }
+
+        // location codes are upper case in the API
+        let locations = self
+            .locations
+            .iter()
+            .map(|location| location.to_uppercase())
+            .collect();
+
+        // construct the object if everything is ok
+        Ok(PhoenixNap {
+            client: crate::client::build_client_for(PHOENIXNAP_NAME)?,
+            client_id: self.client_id,
+            client_secret: self.client_secret,
+            locations,
+        })
+    }
+}
+
+impl PhoenixNap {
+    /// Starts building a new instance.
+    pub fn builder() -> PhoenixNapBuilder {
+        PhoenixNapBuilder::default()
+    }
+
+    /// Fallback error handler for queries
+    fn do_error_if_not_successful(response: &Response) -> Result<(), LibError> {
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        Err(LibError::ApiStatusError {
+            status: response.status(),
+            message: format!("Error during PhoenixNAP query: code {}", response.status()),
+        })
+    }
+
+    /// Gets an access token through the OAuth2 client credentials grant.
+    fn get_access_token(&self) -> Result<String, LibError> {
+        let response = crate::recording::send(
+            &self.client,
+            self.client
+                .post(PHOENIXNAP_TOKEN_URL)
+                .basic_auth(&self.client_id, Some(&self.client_secret))
+                .form(&[("grant_type", "client_credentials")]),
+        )?;
+
+        // fallback error handler
+        Self::do_error_if_not_successful(&response)?;
+
+        // reqwest deserialize
+        Ok(response.json::<PhoenixNapToken>()?.access_token)
+    }
+
+    /// Wrapper for automatic handling of authentication
+    fn create_authenticated_request_builder(
+        &self,
+        method: Method,
+        url: &str,
+        token: &str,
+    ) -> RequestBuilder {
+        self.client.request(method, url).bearer_auth(token)
+    }
+
+    /// Executes authenticated get queries for server products, and deserializes the result.
+    fn get_server_products<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        token: &str,
+    ) -> Result<Vec<T>, LibError> {
+        let response = crate::recording::send(
+            &self.client,
+            self.create_authenticated_request_builder(Method::GET, url, token)
+                .query(&[("productCategory", "SERVER")]),
+        )?;
+
+        // fallback error handler
+        Self::do_error_if_not_successful(&response)?;
+
+        // reqwest deserialize
+        Ok(response.json::<Vec<T>>()?)
+    }
+
+    /// Gets all server types, with their availability in the desired locations.
+    fn get_server_types(&self) -> Result<Vec<PhoenixNapServerType>, LibError> {
+        let token = self.get_access_token()?;
+
+        let products: Vec<PhoenixNapProduct> =
+            self.get_server_products("https://api.phoenixnap.com/billing/v1/products", &token)?;
+
+        let mut availabilities: HashMap<String, Vec<PhoenixNapLocationAvailability>> = self
+            .get_server_products::<PhoenixNapProductAvailability>(
+                "https://api.phoenixnap.com/billing/v1/product-availability",
+                &token,
+            )?
+            .into_iter()
+            .map(|availability| {
+                (
+                    availability.product_code,
+                    availability.location_availability_details,
+                )
+            })
+            .collect();
+
+        Ok(products
+            .into_iter()
+            .map(|product| {
+                let mut locations = availabilities
+                    .remove(&product.product_code)
+                    .unwrap_or_default();
+
+                // if we do not filter on locations, any of them will be fine
+                if !self.locations.is_empty() {
+                    locations.retain(|location| {
+                        self.locations.contains(&location.location.to_uppercase())
+                    });
+                }
+
+                PhoenixNapServerType { product, locations }
+            })
+            .collect())
+    }
+}
+
+impl ProviderFactoryTrait for PhoenixNap {
+    /// Builds a PhoenixNap provider from environment variables.
+    fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
+        let client_id = crate::get_env_var(ENV_PHOENIXNAP_CLIENT_ID)?;
+        let client_secret = crate::get_env_var(ENV_PHOENIXNAP_CLIENT_SECRET)?;
+        let locations_csv = crate::get_env_var_option(ENV_PHOENIXNAP_LOCATIONS);
+        Ok(Box::new(
+            Self::builder()
+                .client_id(&client_id)
+                .client_secret(&client_secret)
+                .locations(crate::tokenize_optional_csv_str(&locations_csv)?)
+                .build()?,
+        ))
+    }
+}
+
+impl ProviderTrait for PhoenixNap {
+    /// Gets the actual name of the provider.
+    fn name(&self) -> &'static str {
+        PHOENIXNAP_NAME
+    }
+
+    /// Collects provider inventory.
+    fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        Ok(self
+            .get_server_types()?
+            .iter()
+            .filter(|server_type| server_type.is_available() || all)
+            .map(|server_type| server_type.into())
+            .collect())
+    }
+
+    /// Checks provider for the availability of a given server type.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        self.get_server_types()?
+            .iter()
+            .find(|server_type| server_type.is_matching(server))
+            .map(|server_type| server_type.is_available())
+            .ok_or(LibError::UnknownServer {
+                server: server.to_string(),
+                suggestions: Vec::new(),
+            })
+    }
+}
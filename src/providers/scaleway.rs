@@ -1,4 +1,7 @@
-use super::{ProviderFactoryTrait, ProviderTrait, ServerInfo};
+use super::{
+    FailurePolicy, LocatedServer, Pagination, PartialFailures, ProviderFactoryTrait, ProviderTrait,
+    ServerInfo, StockLevel,
+};
 use crate::LibError;
 use http::{Method, StatusCode};
 use reqwest::blocking::{Client, RequestBuilder, Response};
@@ -17,6 +20,19 @@ const ENV_SCALEWAY_SECRET_KEY: &str = "SCALEWAY_SECRET_KEY";
 /// Common environment variable to input your Scaleway API key.
 const ENV_SCALEWAY_BAREMETAL_ZONES: &str = "SCALEWAY_BAREMETAL_ZONES";
 
+/// Environment variable to optionally go on with the other zones when one fails.
+const ENV_SCALEWAY_FAILURE_POLICY: &str = "SCALEWAY_FAILURE_POLICY";
+
+/// Console page where Elastic Metal servers are ordered.
+const SCALEWAY_ORDER_URL: &str = "https://console.scaleway.com/elastic-metal/servers/create";
+
+/// Offers are listed by numbered pages, of the largest size accepted by the API.
+const SCALEWAY_PAGINATION: Pagination = Pagination::Page {
+    page: "page",
+    per_page: "page_size",
+    size: 100,
+};
+
 /// Used for API result deserialisation, with only interesting fields implemented
 #[derive(Deserialize)]
 struct ScalewayBaremetalOffers {
@@ -29,6 +45,13 @@ struct ScalewayBaremetalOfferMemory {
     capacity: u64,
 }
 
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize, Clone)]
+struct ScalewayBaremetalOfferCpu {
+    name: String,
+    core_count: u32,
+}
+
 /// Used for API result deserialisation, with only interesting fields implemented
 #[derive(Deserialize, Clone)]
 struct ScalewayBaremetalOfferDisk {
@@ -44,12 +67,40 @@ struct ScalewayBaremetalOffer {
     disks: Vec<ScalewayBaremetalOfferDisk>,
     enable: bool,
     memories: Vec<ScalewayBaremetalOfferMemory>,
+    #[serde(default)]
+    cpus: Vec<ScalewayBaremetalOfferCpu>,
+    /// In bits per second
+    bandwidth: Option<u64>,
+    /// Zones where the offer is available, filled once the offers of every zone are merged
+    #[serde(skip)]
+    zones: Vec<String>,
 }
 
 impl ScalewayBaremetalOffer {
     /// Convenience function to detemine availability
     fn is_available(&self) -> bool {
-        return self.enable && self.stock != "empty";
+        self.enable && self.stock != "empty"
+    }
+
+    /// Gets the stock level, Scaleway only telling low stock apart
+    fn stock_level(&self) -> Option<StockLevel> {
+        match (self.is_available(), self.stock.as_str()) {
+            (false, _) => None,
+            (true, "low") => Some(StockLevel::Low),
+            (true, _) => Some(StockLevel::Normal),
+        }
+    }
+
+    /// Merges the same offer from another zone, keeping the "better" availability
+    /// and the zones where it is available.
+    fn merge(&mut self, offer: &ScalewayBaremetalOffer, zone: &str) {
+        if offer.stock_level() > self.stock_level() {
+            self.enable = offer.enable;
+            self.stock = offer.stock.clone();
+        }
+        if offer.is_available() {
+            self.zones.push(zone.to_string());
+        }
     }
 }
 
@@ -59,48 +110,98 @@ impl From<&ScalewayBaremetalOffer> for ServerInfo {
     fn from(offer: &ScalewayBaremetalOffer) -> Self {
         let memory = offer.memories.iter().map(|mem| mem.capacity).sum::<u64>() / 1000000000;
         let storage = offer.disks.iter().map(|disk| disk.capacity).sum::<u64>() / 1000000000;
+        let cores = offer.cpus.iter().map(|cpu| cpu.core_count).sum::<u32>();
 
         ServerInfo {
             reference: format!("{} ({})", offer.id, offer.name),
             memory: format!("{memory}G"),
             storage: format!("{storage}G"),
             available: offer.is_available(),
+            price: None,
+            cpu: offer.cpus.first().map(|cpu| cpu.name.clone()),
+            cores: (cores > 0).then_some(cores),
+            bandwidth: offer.bandwidth.map(|bandwidth| bandwidth / 1000000),
+            stock: offer.stock_level(),
+            order_url: Some(SCALEWAY_ORDER_URL.to_string()),
+            range: None,
         }
     }
 }
 
 /// Gets server inventory and availability.
 pub struct Scaleway {
+    client: Client,
     secret_key: String,
     zones: Vec<String>,
+    failure_policy: FailurePolicy,
 }
 
-impl Scaleway {
-    /// Builds a new instance.
-    fn new(secret_key: &str, zones_csv: &str) -> Result<Self, LibError> {
+/// Builds a Scaleway provider without relying on environment variables.
+#[derive(Default)]
+pub struct ScalewayBuilder {
+    secret_key: String,
+    zones: Vec<String>,
+    failure_policy: FailurePolicy,
+}
+
+impl ScalewayBuilder {
+    /// Sets the API secret key, which is a UUID.
+    pub fn secret_key(mut self, secret_key: &str) -> Self {
+        self.secret_key = secret_key.to_string();
+        self
+    }
+
+    /// Sets the baremetal zones to query, at least one is required.
+    pub fn zones<I, S>(mut self, zones: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.zones = zones.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Selects what happens when a zone fails (defaults to fail-fast).
+    pub fn failure_policy(mut self, failure_policy: FailurePolicy) -> Self {
+        self.failure_policy = failure_policy;
+        self
+    }
+
+    /// Builds the provider, verifying the settings.
+    pub fn build(self) -> Result<Scaleway, LibError> {
         // Secret key is a UUID
-        let secret_key = secret_key.to_string();
-        Uuid::parse_str(&secret_key).map_err(|source| LibError::ValueError {
+        Uuid::parse_str(&self.secret_key).map_err(|source| LibError::ValueError {
             name: "malformed scaleway secret key".to_string(),
             value: source.to_string(),
         })?;
 
-        // split zones and verify that no zones is empty
-        let zones: Vec<String> = zones_csv.split(',').map(|s| s.trim().to_string()).collect();
-        if zones.iter().find(|i| i.is_empty()).is_some() {
+        // verify that there is at least one zone and that no zone is empty
+        if self.zones.is_empty() || self.zones.iter().any(|zone| zone.is_empty()) {
             return Err(LibError::ValueError {
                 name: "found empty scaleway zone".into(),
-                value: zones_csv.into(),
+                value: self.zones.join(","),
             });
         }
 
         // construct the object if everything is ok
-        Ok(Self { secret_key, zones })
+        Ok(Scaleway {
+            client: crate::client::build_client_for(SCALEWAY_NAME)?,
+            secret_key: self.secret_key,
+            zones: self.zones,
+            failure_policy: self.failure_policy,
+        })
+    }
+}
+
+impl Scaleway {
+    /// Starts building a new instance.
+    pub fn builder() -> ScalewayBuilder {
+        ScalewayBuilder::default()
     }
 
     /// Wrapper for automatic handling of authentication
     fn create_authenticated_request_builder(&self, method: Method, url: &str) -> RequestBuilder {
-        Client::new()
+        self.client
             .request(method, url)
             .header("X-Auth-Token", &self.secret_key)
     }
@@ -111,7 +212,8 @@ impl Scaleway {
             return Ok(());
         }
 
-        Err(LibError::ApiError {
+        Err(LibError::ApiStatusError {
+            status: response.status(),
             message: format!(
                 "Error during Scaleway baremetal query: code {}",
                 response.status()
@@ -120,61 +222,106 @@ impl Scaleway {
     }
 
     /// Executes simple authenticated get queries which fails only on transport errors
-    fn get_api_authenticated(&self, url: &str) -> Result<Response, LibError> {
-        let response = self
-            .create_authenticated_request_builder(Method::GET, url)
-            .send()
-            .map_err(|source| LibError::RequestError { source })?;
+    fn get_api_authenticated(
+        &self,
+        url: &str,
+        parameters: &[(&str, String)],
+    ) -> Result<Response, LibError> {
+        let response = crate::recording::send(
+            &self.client,
+            self.create_authenticated_request_builder(Method::GET, url)
+                .query(parameters),
+        )?;
 
         Ok(response)
     }
 
-    /// Gets all offers in specified zone.
-    fn get_zone_offers(&self, zone: &str) -> Result<ScalewayBaremetalOffers, LibError> {
+    /// Gets all offers in specified zone, across every page.
+    fn get_zone_offers(&self, zone: &str) -> Result<Vec<ScalewayBaremetalOffer>, LibError> {
         let url = format!("https://api.scaleway.com/baremetal/v1/zones/{zone}/offers");
-        let response = self.get_api_authenticated(&url)?;
+        SCALEWAY_PAGINATION.fetch_all(|parameters| {
+            let response = self.get_api_authenticated(&url, parameters)?;
 
-        // fallback error handler
-        Self::do_error_if_not_successful(&response)?;
+            // fallback error handler
+            Self::do_error_if_not_successful(&response)?;
 
-        // reqwest deserialize and check
-        response
-            .json::<ScalewayBaremetalOffers>()
-            .map_err(|source| LibError::RequestError { source })
+            // reqwest deserialize and check
+            let offers = response.json::<ScalewayBaremetalOffers>()?;
+            Ok((offers.offers, None))
+        })
     }
 
-    /// Inserts an offer into map if not already present, or override its availability if available
+    /// Inserts an offer into map if not already present, or merges its availability in the zone
     fn insert_or_update_offer(
         map: &mut HashMap<String, ScalewayBaremetalOffer>,
         offer: &ScalewayBaremetalOffer,
+        zone: &str,
     ) {
         map.entry(offer.id.clone())
-            // update stored availability if current offer is "better"
-            .and_modify(|info| {
-                if offer.is_available() {
-                    info.enable = offer.enable;
-                    info.stock = offer.stock.clone();
-                }
-            })
             // insert offer if not already present (and only then does it copy)
-            .or_insert(offer.clone());
+            .or_insert_with(|| offer.clone())
+            // update stored availability if current offer is "better"
+            .merge(offer, zone);
     }
 
-    /// Gets all offers.
-    fn get_offers(&self) -> Result<Vec<ScalewayBaremetalOffer>, LibError> {
+    /// Gets all offers, and tells if some zones failed, so that offers may be missing.
+    fn get_offers(&self) -> Result<(Vec<ScalewayBaremetalOffer>, bool), LibError> {
         let mut map: HashMap<String, ScalewayBaremetalOffer> = HashMap::new();
+        let mut failures = PartialFailures::new(SCALEWAY_NAME, self.failure_policy);
 
         for zone in &self.zones {
             // get all offers for specific zone
-            let result = self.get_zone_offers(&zone)?;
-            for offer in result.offers.iter() {
+            let Some(result) =
+                failures.handle(&format!("zone {zone}"), self.get_zone_offers(zone))?
+            else {
+                continue;
+            };
+            for offer in result.iter() {
                 // update offer availability across all zones
-                Self::insert_or_update_offer(&mut map, offer);
+                Self::insert_or_update_offer(&mut map, offer, zone);
             }
         }
+        failures.finish()?;
 
         // Builds result by moving the values from the map into the vec
-        Ok(Vec::from_iter(map.into_values()))
+        Ok((Vec::from_iter(map.into_values()), failures.is_partial()))
+    }
+
+    /// Finds the offer of a server, given either its identifier or its commercial name,
+    /// like `EM-A315X-SSD`, ignoring case. A name matching no offer may be the beginning of one,
+    /// like `EM-A315X`, as long as it is the beginning of a single one.
+    fn find_offer<'a>(
+        offers: &'a [ScalewayBaremetalOffer],
+        server: &str,
+    ) -> Result<Option<&'a ScalewayBaremetalOffer>, LibError> {
+        if let Some(offer) = offers.iter().find(|offer| offer.id == server) {
+            return Ok(Some(offer));
+        }
+
+        let name = server.to_uppercase();
+        let mut matches: Vec<&ScalewayBaremetalOffer> = offers
+            .iter()
+            .filter(|offer| offer.name.to_uppercase() == name)
+            .collect();
+        if matches.is_empty() {
+            matches = offers
+                .iter()
+                .filter(|offer| offer.name.to_uppercase().starts_with(&name))
+                .collect();
+        }
+
+        match matches.as_slice() {
+            [] => Ok(None),
+            [offer] => Ok(Some(*offer)),
+            _ => Err(LibError::AmbiguousServer {
+                server: server.to_string(),
+                candidates: matches
+                    .iter()
+                    .map(|offer| format!("{} ({})", offer.id, offer.name))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            }),
+        }
     }
 
     /// Gets a specific offer in specified zone
@@ -184,7 +331,7 @@ impl Scaleway {
         offer_id: &str,
     ) -> Result<Option<ScalewayBaremetalOffer>, LibError> {
         let url = format!("https://api.scaleway.com/baremetal/v1/zones/{zone}/offers/{offer_id}");
-        let response = self.get_api_authenticated(&url)?;
+        let response = self.get_api_authenticated(&url, &[])?;
 
         // the API returns 404 if 'offer_id' is not found, and we do not want to error out
         if response.status() == StatusCode::NOT_FOUND {
@@ -195,20 +342,21 @@ impl Scaleway {
         Self::do_error_if_not_successful(&response)?;
 
         // reqwest deserialize and check
-        Ok(Some(
-            response
-                .json::<ScalewayBaremetalOffer>()
-                .map_err(|source| LibError::RequestError { source })?,
-        ))
+        Ok(Some(response.json::<ScalewayBaremetalOffer>()?))
     }
 
     /// Gets a specific offer.
     fn get_offer(&self, offer_id: &str) -> Result<ScalewayBaremetalOffer, LibError> {
         // Start with no result
         let mut result: Option<ScalewayBaremetalOffer> = None;
+        let mut failures = PartialFailures::new(SCALEWAY_NAME, self.failure_policy);
 
         for zone in &self.zones {
-            match self.get_zone_offer(&zone, offer_id)? {
+            let part = format!("zone {zone}");
+            let Some(offer) = failures.handle(&part, self.get_zone_offer(zone, offer_id))? else {
+                continue;
+            };
+            match offer {
                 // skip if we did not find an offer for this id
                 None => continue,
 
@@ -216,41 +364,52 @@ impl Scaleway {
                     // fill result if it was previously empty, so only the first makes an actual clone
                     let info = result.get_or_insert(offer.clone());
                     // if offer availability is 'better' than current value, update it
-                    if !info.is_available() && offer.is_available() {
-                        info.enable = offer.enable;
-                        info.stock = offer.stock;
-                    }
+                    info.merge(&offer, zone);
                 }
             }
         }
 
         // We could have return an Option if on offer was found.
-        // By choice, we chose to produce an error in that case.
-        result.ok_or(LibError::UnknownServer {
-            server: offer_id.to_string(),
-        })
+        // By choice, we chose to produce an error in that case,
+        // unless the offer may be in a failed zone.
+        match result {
+            Some(offer) => Ok(offer),
+            None => Err(failures.take_failure().unwrap_or(LibError::UnknownServer {
+                server: offer_id.to_string(),
+                suggestions: Vec::new(),
+            })),
+        }
     }
 }
 
 impl ProviderFactoryTrait for Scaleway {
-    /// Builds an Ovh provider from environment variables.
+    /// Builds a Scaleway provider from environment variables.
     fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
         let secret_key = crate::get_env_var(ENV_SCALEWAY_SECRET_KEY)?;
         let zones_csv = crate::get_env_var(ENV_SCALEWAY_BAREMETAL_ZONES)?;
-        Ok(Box::new(Self::new(&secret_key, &zones_csv)?))
+        let failure_policy =
+            crate::get_env_var_parsed::<FailurePolicy>(ENV_SCALEWAY_FAILURE_POLICY)?
+                .unwrap_or_default();
+        Ok(Box::new(
+            Self::builder()
+                .secret_key(&secret_key)
+                .zones(zones_csv.split(',').map(str::trim))
+                .failure_policy(failure_policy)
+                .build()?,
+        ))
     }
 }
 
 impl ProviderTrait for Scaleway {
     /// Gets the actual name of the provider.
     fn name(&self) -> &'static str {
-        return SCALEWAY_NAME;
+        SCALEWAY_NAME
     }
 
     /// Collects provider inventory.
     fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
-        Ok(self
-            .get_offers()?
+        let (offers, _) = self.get_offers()?;
+        Ok(offers
             .iter()
             .filter(|offer| offer.is_available() || all)
             .map(|offer| offer.into())
@@ -258,8 +417,70 @@ impl ProviderTrait for Scaleway {
     }
 
     /// Checks provider for the availability of a given server type.
+    /// Commercial names are resolved from the listing of every zone.
     fn check(&self, server: &str) -> Result<bool, LibError> {
+        if Uuid::parse_str(server).is_err() {
+            let states = self.check_stock(&[server.to_string()])?;
+            return Ok(states.iter().any(|(_, available, _)| *available));
+        }
         let offer = self.get_offer(server)?;
         Ok(offer.is_available())
     }
+
+    /// Checks several server types from a single listing of every zone.
+    /// When some zones failed, the servers which were not found are left out,
+    /// as they may be in these zones.
+    fn check_many(&self, servers: &[String]) -> Result<Vec<(String, bool)>, LibError> {
+        Ok(self
+            .check_stock(servers)?
+            .into_iter()
+            .map(|(server, available, _)| (server, available))
+            .collect())
+    }
+
+    /// Same as `check_many()`, with the stock level of the available servers.
+    fn check_stock(
+        &self,
+        servers: &[String],
+    ) -> Result<Vec<(String, bool, Option<StockLevel>)>, LibError> {
+        Ok(self
+            .check_locations(servers)?
+            .into_iter()
+            .map(|(server, available, stock, _)| (server, available, stock))
+            .collect())
+    }
+
+    /// Same as `check_stock()`, with the zones where the servers are available.
+    fn check_locations(&self, servers: &[String]) -> Result<Vec<LocatedServer>, LibError> {
+        let (offers, partial) = self.get_offers()?;
+        let mut states = Vec::new();
+        for server in servers {
+            match Self::find_offer(&offers, server)? {
+                Some(offer) => states.push((
+                    server.clone(),
+                    offer.is_available(),
+                    offer.stock_level(),
+                    offer.zones.clone(),
+                )),
+                None if partial => continue,
+                None => {
+                    return Err(LibError::UnknownServer {
+                        server: server.to_string(),
+                        suggestions: Vec::new(),
+                    })
+                }
+            }
+        }
+        Ok(states)
+    }
+
+    /// Tells that the zones of the servers are known.
+    fn supports_locations(&self) -> bool {
+        true
+    }
+
+    /// Gets the console page where the servers are ordered.
+    fn order_url(&self, _server: &str) -> Option<String> {
+        Some(SCALEWAY_ORDER_URL.to_string())
+    }
 }
@@ -0,0 +1,297 @@
+use super::{Pagination, ProviderFactoryTrait, ProviderTrait, ServerInfo};
+use crate::LibError;
+use http::Method;
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+// Servers.com implementation
+
+/// Common name to identify the provider
+pub const SERVERSCOM_NAME: &str = "serverscom";
+
+/// Common environment variable to input your Servers.com API token.
+const ENV_SERVERSCOM_API_TOKEN: &str = "SERVERSCOM_API_TOKEN";
+
+/// Common environment variable to eventually filter the locations.
+const ENV_SERVERSCOM_LOCATIONS: &str = "SERVERSCOM_LOCATIONS";
+
+/// Lists are split in numbered pages, of the largest size accepted by the API.
+const SERVERSCOM_PAGINATION: Pagination = Pagination::Page {
+    page: "page",
+    per_page: "per_page",
+    size: 100,
+};
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct ServersComLocation {
+    id: u64,
+    code: String,
+}
+
+/// Used for API result deserialisation, with only interesting fields implemented
+#[derive(Deserialize)]
+struct ServersComServerModel {
+    id: u64,
+    name: String,
+    cpu_name: Option<String>,
+    cpu_count: Option<u32>,
+    cpu_cores_count: Option<u32>,
+    /// Default memory, in GB
+    ram: Option<u32>,
+    /// Maximum memory, in GB
+    max_ram: Option<u32>,
+    drive_slots_count: Option<u32>,
+}
+
+/// Aggregates a server model across every location it can be ordered in.
+struct ServersComConfiguration {
+    model: ServersComServerModel,
+    locations: BTreeSet<String>,
+}
+
+impl ServersComConfiguration {
+    /// Convenience function to detemine availability
+    fn is_available(&self) -> bool {
+        !self.locations.is_empty()
+    }
+
+    /// Tells if the configuration matches the requested server reference
+    fn is_matching(&self, server: &str) -> bool {
+        self.model.id.to_string() == server
+    }
+}
+
+// I prefer the From trait, as i can pass references
+impl From<&ServersComConfiguration> for ServerInfo {
+    /// Extracts only interesting information which is common to all providers
+    fn from(configuration: &ServersComConfiguration) -> Self {
+        let model = &configuration.model;
+
+        let mut cpu = match &model.cpu_name {
+            Some(name) => format!(
+                "{}x{}c {}",
+                model.cpu_count.unwrap_or(1),
+                model.cpu_cores_count.unwrap_or_default(),
+                name
+            ),
+            None => "N/A".to_string(),
+        };
+        cpu.retain(|c| !c.is_whitespace());
+
+        let memory = match (model.ram, model.max_ram) {
+            (Some(ram), Some(max_ram)) if max_ram > ram => format!("{ram}G(max{max_ram}G)"),
+            (Some(ram), _) => format!("{ram}G"),
+            (None, _) => "N/A".to_string(),
+        };
+
+        let storage = match model.drive_slots_count {
+            Some(slots) => format!("{slots}xslots"),
+            None => "N/A".to_string(),
+        };
+
+        let locations = configuration
+            .locations
+            .iter()
+            .cloned()
+            .collect::<Vec<String>>()
+            .join(",");
+
+        let mut chassis = model.name.clone();
+        chassis.retain(|c| !c.is_whitespace());
+
+        ServerInfo {
+            reference: format!("{} ({} {}@{})", model.id, chassis, cpu, locations),
+            memory,
+            storage,
+            available: configuration.is_available(),
+            price: None,
+            cpu: model.cpu_name.clone(),
+            cores: model
+                .cpu_cores_count
+                .map(|cores| model.cpu_count.unwrap_or(1) * cores),
+            bandwidth: None,
+            stock: None,
+            order_url: None,
+            range: None,
+        }
+    }
+}
+
+/// Gets server inventory and availability.
+pub struct ServersCom {
+    client: Client,
+    api_token: String,
+    locations: Vec<String>,
+}
+
+/// Builds a ServersCom provider without relying on environment variables.
+#[derive(Default)]
+pub struct ServersComBuilder {
+    api_token: String,
+    locations: Vec<String>,
+}
+
+impl ServersComBuilder {
+    /// Sets the API token.
+    pub fn api_token(mut self, api_token: &str) -> Self {
+        self.api_token = api_token.to_string();
+        self
+    }
+
+    /// Sets the location codes to keep, all of them being used when none is set.
+    pub fn locations<I, S>(mut self, locations: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.locations = locations.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Builds the provider, verifying the settings.
+    pub fn build(self) -> Result<ServersCom, LibError> {
+        if self.api_token.is_empty() {
+            return Err(LibError::ValueError {
+                name: "found empty serverscom api token".into(),
+                value: self.api_token,
+            });
+        }
+
+        // location codes are upper case in the API
+        let locations = self
+            .locations
+            .iter()
+            .map(|location| location.to_uppercase())
+            .collect();
+
+        // construct the object if everything is ok
+        Ok(ServersCom {
+            client: crate::client::build_client_for(SERVERSCOM_NAME)?,
+            api_token: self.api_token,
+            locations,
+        })
+    }
+}
+
+impl ServersCom {
+    /// Starts building a new instance.
+    pub fn builder() -> ServersComBuilder {
+        ServersComBuilder::default()
+    }
+
+    /// Wrapper for automatic handling of authentication
+    fn create_authenticated_request_builder(&self, method: Method, url: &str) -> RequestBuilder {
+        self.client
+            .request(method, url)
+            .bearer_auth(&self.api_token)
+    }
+
+    /// Fallback error handler for queries
+    fn do_error_if_not_successful(response: &Response) -> Result<(), LibError> {
+        if response.status().is_success() {
+            return Ok(());
+        }
+
+        Err(LibError::ApiStatusError {
+            status: response.status(),
+            message: format!("Error during Servers.com query: code {}", response.status()),
+        })
+    }
+
+    /// Executes authenticated get queries across every page, and deserializes the result.
+    fn get_list<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<Vec<T>, LibError> {
+        SERVERSCOM_PAGINATION.fetch_all(|parameters| {
+            let response = crate::recording::send(
+                &self.client,
+                self.create_authenticated_request_builder(Method::GET, url)
+                    .query(parameters),
+            )?;
+
+            // fallback error handler
+            Self::do_error_if_not_successful(&response)?;
+
+            // reqwest deserialize
+            let items = response.json::<Vec<T>>()?;
+            Ok((items, None))
+        })
+    }
+
+    /// Gets the desired locations.
+    fn get_locations(&self) -> Result<Vec<ServersComLocation>, LibError> {
+        let mut locations: Vec<ServersComLocation> =
+            self.get_list("https://api.servers.com/v1/locations")?;
+
+        // if we do not filter on locations, any of them will be fine
+        if !self.locations.is_empty() {
+            locations.retain(|location| self.locations.contains(&location.code.to_uppercase()));
+        }
+
+        Ok(locations)
+    }
+
+    /// Gets all server configurations, with the locations they can be ordered in.
+    fn get_configurations(&self) -> Result<Vec<ServersComConfiguration>, LibError> {
+        let mut map: BTreeMap<u64, ServersComConfiguration> = BTreeMap::new();
+
+        for location in self.get_locations()? {
+            let url = format!(
+                "https://api.servers.com/v1/locations/{}/order_options/server_models",
+                location.id
+            );
+            for model in self.get_list::<ServersComServerModel>(&url)? {
+                map.entry(model.id)
+                    .or_insert_with(|| ServersComConfiguration {
+                        model,
+                        locations: BTreeSet::new(),
+                    })
+                    .locations
+                    .insert(location.code.clone());
+            }
+        }
+
+        // Builds result by moving the values from the map into the vec
+        Ok(Vec::from_iter(map.into_values()))
+    }
+}
+
+impl ProviderFactoryTrait for ServersCom {
+    /// Builds a ServersCom provider from environment variables.
+    fn from_env() -> Result<Box<dyn ProviderTrait>, LibError> {
+        let api_token = crate::get_env_var(ENV_SERVERSCOM_API_TOKEN)?;
+        let locations_csv = crate::get_env_var_option(ENV_SERVERSCOM_LOCATIONS);
+        Ok(Box::new(
+            Self::builder()
+                .api_token(&api_token)
+                .locations(crate::tokenize_optional_csv_str(&locations_csv)?)
+                .build()?,
+        ))
+    }
+}
+
+impl ProviderTrait for ServersCom {
+    /// Gets the actual name of the provider.
+    fn name(&self) -> &'static str {
+        SERVERSCOM_NAME
+    }
+
+    /// Collects provider inventory.
+    fn inventory(&self, all: bool) -> Result<Vec<ServerInfo>, LibError> {
+        Ok(self
+            .get_configurations()?
+            .iter()
+            .filter(|configuration| configuration.is_available() || all)
+            .map(|configuration| configuration.into())
+            .collect())
+    }
+
+    /// Checks provider for the availability of a given server type.
+    /// A server model which cannot be ordered in any desired location is unavailable.
+    fn check(&self, server: &str) -> Result<bool, LibError> {
+        Ok(self
+            .get_configurations()?
+            .iter()
+            .any(|configuration| configuration.is_matching(server) && configuration.is_available()))
+    }
+}
@@ -0,0 +1,168 @@
+use crate::LibError;
+use chrono::{DateTime, FixedOffset, Local, NaiveTime, Utc};
+
+// Quiet hours of notifiers
+
+/// Suffix of the environment variable holding the quiet hours of a notifier,
+/// like `PUSHOVER_QUIET_HOURS=23:00-07:00,12:00-13:30`.
+const ENV_QUIET_HOURS_SUFFIX: &str = "QUIET_HOURS";
+
+/// Suffix of the environment variable holding the timezone of the quiet hours,
+/// like `PUSHOVER_QUIET_HOURS_TIMEZONE=+01:00`.
+const ENV_QUIET_HOURS_TIMEZONE_SUFFIX: &str = "QUIET_HOURS_TIMEZONE";
+
+/// Timezone in which the quiet hours are expressed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum QuietTimezone {
+    /// Local time of the host, which honours the `TZ` environment variable
+    Local,
+    /// Fixed offset from UTC, `UTC` being a zero offset
+    Fixed(FixedOffset),
+}
+
+impl std::str::FromStr for QuietTimezone {
+    type Err = LibError;
+
+    /// Parses `local`, `UTC`, or an offset like `+02:00`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "local" => Ok(Self::Local),
+            "utc" | "z" => Ok(Self::Fixed(FixedOffset::east_opt(0).unwrap())),
+            _ => value
+                .parse::<FixedOffset>()
+                .map(Self::Fixed)
+                .map_err(|_| LibError::ValueError {
+                    name: "quiet hours timezone (local, UTC or an offset like +02:00)".into(),
+                    value: value.to_string(),
+                }),
+        }
+    }
+}
+
+impl QuietTimezone {
+    /// Gets the time of day of a timestamp in this timezone
+    fn time_of(&self, now: DateTime<Utc>) -> NaiveTime {
+        match self {
+            Self::Local => now.with_timezone(&Local).time(),
+            Self::Fixed(offset) => now.with_timezone(offset).time(),
+        }
+    }
+}
+
+/// A range of time of day, which wraps around midnight when it ends before it starts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuietRange {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl std::str::FromStr for QuietRange {
+    type Err = LibError;
+
+    /// Parses a range like `23:00-07:00`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let error = || LibError::ValueError {
+            name: "quiet hours range (like 23:00-07:00)".into(),
+            value: value.to_string(),
+        };
+        let (start, end) = value.split_once('-').ok_or_else(error)?;
+        let parse = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M");
+        let start = parse(start).map_err(|_| error())?;
+        let end = parse(end).map_err(|_| error())?;
+        if start == end {
+            return Err(error());
+        }
+        Ok(Self { start, end })
+    }
+}
+
+impl QuietRange {
+    /// Tells if the time of day is within the range, its end being excluded
+    fn contains(&self, time: NaiveTime) -> bool {
+        match self.start < self.end {
+            true => self.start <= time && time < self.end,
+            false => self.start <= time || time < self.end,
+        }
+    }
+}
+
+/// Periods of the day during which a notifier must not be used,
+/// the notifications being held until the first check after they are over.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuietHours {
+    ranges: Vec<QuietRange>,
+    timezone: QuietTimezone,
+}
+
+impl QuietHours {
+    /// Builds quiet hours from ranges, expressed in the given timezone.
+    pub fn new(ranges: Vec<QuietRange>, timezone: QuietTimezone) -> Self {
+        Self { ranges, timezone }
+    }
+
+    /// Parses a comma-separated list of ranges, like `23:00-07:00,12:00-13:30`.
+    pub fn parse(ranges: &str, timezone: QuietTimezone) -> Result<Self, LibError> {
+        let ranges = crate::tokenize_optional_csv_str(&Some(ranges.to_string()))?
+            .iter()
+            .map(|range| range.parse())
+            .collect::<Result<Vec<QuietRange>, LibError>>()?;
+        Ok(Self::new(ranges, timezone))
+    }
+
+    /// Builds the quiet hours of a notifier from environment variables, if any.
+    /// The variables are prefixed by the notifier name, uppercased with dashes as underscores,
+    /// like `EMAIL_SMTP_QUIET_HOURS`, and the timezone defaults to the local one.
+    pub fn from_env(notifier_name: &str) -> Result<Option<Self>, LibError> {
+        let prefix = notifier_name.to_uppercase().replace('-', "_");
+        let Some(ranges) = crate::get_env_var_option(&format!("{prefix}_{ENV_QUIET_HOURS_SUFFIX}"))
+        else {
+            return Ok(None);
+        };
+        let timezone =
+            crate::get_env_var_parsed(&format!("{prefix}_{ENV_QUIET_HOURS_TIMEZONE_SUFFIX}"))?
+                .unwrap_or(QuietTimezone::Local);
+        Self::parse(&ranges, timezone).map(Some)
+    }
+
+    /// Tells if the notifier must be quiet at the given time
+    pub fn is_quiet(&self, now: DateTime<Utc>) -> bool {
+        let time = self.timezone.time_of(now);
+        self.ranges.iter().any(|range| range.contains(time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(value: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(value, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn range_contains_within_the_day() {
+        let range: QuietRange = "12:00-13:30".parse().unwrap();
+        assert!(!range.contains(time("11:59")));
+        assert!(range.contains(time("12:00")));
+        assert!(range.contains(time("13:29")));
+        assert!(!range.contains(time("13:30")));
+    }
+
+    #[test]
+    fn range_contains_across_midnight() {
+        let range: QuietRange = "23:00-07:00".parse().unwrap();
+        assert!(!range.contains(time("22:59")));
+        assert!(range.contains(time("23:00")));
+        assert!(range.contains(time("00:00")));
+        assert!(range.contains(time("06:59")));
+        assert!(!range.contains(time("07:00")));
+        assert!(!range.contains(time("12:00")));
+    }
+
+    #[test]
+    fn range_rejects_invalid_values() {
+        for value in ["", "23:00", "23:00-23:00", "25:00-07:00", "23:00-7h"] {
+            assert!(value.parse::<QuietRange>().is_err(), "{value}");
+        }
+    }
+}
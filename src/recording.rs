@@ -0,0 +1,177 @@
+use crate::cache::{self, CachedResponse};
+use crate::metrics::{self, CallMetric};
+use crate::LibError;
+use reqwest::blocking::{Client, Request, RequestBuilder, Response};
+use reqwest::header::CONTENT_TYPE;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+use std::{fs, io};
+
+// Record/replay of provider HTTP traffic
+
+/// What happens to the HTTP requests sent by providers.
+#[derive(Clone)]
+pub enum RecordingMode {
+    /// Requests are sent, nothing is kept
+    Off,
+    /// Requests are sent, and every response is saved in this directory
+    Record(PathBuf),
+    /// Nothing is sent, every response is loaded from this directory
+    Replay(PathBuf),
+}
+
+/// Process-wide recording mode, off by default.
+static MODE: Mutex<RecordingMode> = Mutex::new(RecordingMode::Off);
+
+/// Selects the recording mode for every request sent afterwards.
+pub fn set_mode(mode: RecordingMode) {
+    *MODE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = mode;
+}
+
+/// Gets the current recording mode.
+fn get_mode() -> RecordingMode {
+    MODE.lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// A recorded response, with the request it answered to ease debugging.
+/// Request headers and bodies are never saved, as they hold the credentials.
+#[derive(Serialize, Deserialize)]
+struct Recording {
+    method: String,
+    url: String,
+    status: u16,
+    content_type: Option<String>,
+    body: String,
+}
+
+/// Gets the path of the recording of a request, named after its host and a hash
+/// of its method, url and body, so that the same request always finds it back.
+fn get_recording_path(dir: &Path, method: &str, url: &reqwest::Url, body: &[u8]) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(method);
+    hasher.update(" ");
+    hasher.update(url.as_str());
+    hasher.update("\n");
+    hasher.update(body);
+    let hash = hasher.finalize();
+    let host = url.host_str().unwrap_or("unknown");
+    dir.join(format!("{host}-{hash:x}.json"))
+}
+
+/// Rebuilds a response which providers can use as if it came from the network.
+pub(crate) fn build_response(
+    status: u16,
+    content_type: Option<String>,
+    body: String,
+) -> Result<Response, LibError> {
+    let status = http::StatusCode::from_u16(status).map_err(|_| LibError::ValueError {
+        name: "recorded status".into(),
+        value: status.to_string(),
+    })?;
+    let mut builder = http::Response::builder().status(status);
+    if let Some(content_type) = &content_type {
+        builder = builder.header(CONTENT_TYPE, content_type);
+    }
+    let response = builder.body(body).map_err(|error| LibError::ValueError {
+        name: "recorded response".into(),
+        value: error.to_string(),
+    })?;
+    Ok(Response::from(response))
+}
+
+/// Sends a request with the client, and records its duration and status in the metrics.
+fn execute(client: &Client, request: Request) -> Result<Response, LibError> {
+    let method = request.method().to_string();
+    let host = request.url().host_str().unwrap_or("unknown").to_string();
+    let start = Instant::now();
+    let result = client.execute(request);
+    metrics::record(CallMetric {
+        method,
+        host,
+        duration: start.elapsed(),
+        status: result
+            .as_ref()
+            .ok()
+            .map(|response| response.status().as_u16()),
+    });
+    Ok(result?)
+}
+
+/// Sends a request with the client, revalidating its cached response if the HTTP cache
+/// is enabled: a response which was not modified is then served from the cache.
+fn fetch(client: &Client, mut request: Request) -> Result<Response, LibError> {
+    let Some(path) = cache::get_path(&request) else {
+        return execute(client, request);
+    };
+    let cached = CachedResponse::load(&path);
+    if let Some(cached) = &cached {
+        cached.add_validators(&mut request);
+    }
+    let response = execute(client, request)?;
+    match cached {
+        Some(cached) if response.status() == http::StatusCode::NOT_MODIFIED => {
+            cached.into_response()
+        }
+        _ => cache::store(&path, response),
+    }
+}
+
+/// Sends a request with the client, unless it is replayed, and records its response if required.
+/// Every provider sends its requests through here, so that a real run can be recorded,
+/// then replayed later to investigate a notification or to test deserialization.
+/// Only the requests actually sent are counted in the metrics.
+pub fn send(client: &Client, builder: RequestBuilder) -> Result<Response, LibError> {
+    let request = builder.build()?;
+    let (dir, replay) = match get_mode() {
+        RecordingMode::Off => return fetch(client, request),
+        RecordingMode::Record(dir) => (dir, false),
+        RecordingMode::Replay(dir) => (dir, true),
+    };
+
+    let method = request.method().to_string();
+    let url = request.url().clone();
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .unwrap_or_default();
+    let path = get_recording_path(&dir, &method, &url, body);
+
+    if replay {
+        let json = fs::read_to_string(&path).map_err(|source| match source.kind() {
+            io::ErrorKind::NotFound => LibError::UnrecordedRequest {
+                method,
+                url: url.to_string(),
+            },
+            _ => LibError::FileError {
+                path: path.clone(),
+                source,
+            },
+        })?;
+        let recording: Recording = serde_json::from_str(&json)
+            .map_err(|source| LibError::JsonFileError { path, source })?;
+        return build_response(recording.status, recording.content_type, recording.body);
+    }
+
+    let response = fetch(client, request)?;
+    let recording = Recording {
+        method,
+        url: url.to_string(),
+        status: response.status().as_u16(),
+        content_type: response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+        body: response.text()?,
+    };
+
+    let json = serde_json::to_string_pretty(&recording)?;
+    fs::create_dir_all(&dir).map_err(|source| LibError::FileError { path: dir, source })?;
+    fs::write(&path, json).map_err(|source| LibError::FileError { path, source })?;
+    build_response(recording.status, recording.content_type, recording.body)
+}
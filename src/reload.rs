@@ -0,0 +1,35 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Configuration reload requests
+
+/// Set by the signal handler, cleared once the reload has been noticed.
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Signal handler, which can only do async-signal-safe things like storing an atomic.
+#[cfg(unix)]
+extern "C" fn on_sighup(_: libc::c_int) {
+    REQUESTED.store(true, Ordering::Relaxed);
+}
+
+/// Requests a reload whenever the process receives SIGHUP, instead of terminating.
+/// There is no such signal on other platforms, where this does nothing.
+pub fn listen() {
+    #[cfg(unix)]
+    // SAFETY: the handler only stores an atomic boolean
+    unsafe {
+        libc::signal(
+            libc::SIGHUP,
+            on_sighup as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+}
+
+/// Requests a reload, as receiving SIGHUP does.
+pub fn request() {
+    REQUESTED.store(true, Ordering::Relaxed);
+}
+
+/// Tells if a reload was requested since the last call.
+pub fn take() -> bool {
+    REQUESTED.swap(false, Ordering::Relaxed)
+}
@@ -0,0 +1,89 @@
+use crate::LibError;
+use chrono::Local;
+use croner::Cron;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::str::FromStr;
+use std::time::Duration;
+
+// Scheduling of repeated checks
+
+/// Defines when the next check should happen.
+pub enum Schedule {
+    /// Checks at a fixed interval.
+    Interval(Duration),
+    /// Checks at the times matching a cron expression, in local time.
+    Cron(Box<Cron>),
+}
+
+impl Schedule {
+    /// Builds a fixed interval schedule, which cannot be zero.
+    pub fn from_interval(seconds: u64) -> Result<Self, LibError> {
+        if seconds == 0 {
+            return Err(LibError::ValueError {
+                name: "interval must be at least one second".into(),
+                value: seconds.to_string(),
+            });
+        }
+        Ok(Self::Interval(Duration::from_secs(seconds)))
+    }
+
+    /// Builds a cron schedule, from the usual 5 fields expression
+    /// (or 6 fields, when seconds are included).
+    pub fn from_cron(expression: &str) -> Result<Self, LibError> {
+        let cron = Cron::from_str(expression).map_err(|e| LibError::ValueError {
+            name: format!("invalid cron expression ({e})"),
+            value: expression.to_string(),
+        })?;
+        Ok(Self::Cron(Box::new(cron)))
+    }
+
+    /// Computes how long to wait from now until the next scheduled check.
+    /// Interval schedules check right away the first time, cron schedules always wait.
+    fn next_delay(&self, first: bool) -> Result<Duration, LibError> {
+        match self {
+            Self::Interval(_) if first => Ok(Duration::ZERO),
+            Self::Interval(interval) => Ok(*interval),
+            Self::Cron(cron) => {
+                let now = Local::now();
+                let next =
+                    cron.find_next_occurrence(&now, false)
+                        .map_err(|e| LibError::ValueError {
+                            name: format!("cron expression never matches ({e})"),
+                            value: cron.pattern.to_string(),
+                        })?;
+                // a negative duration can only happen on clock changes
+                Ok((next - now).to_std().unwrap_or_default())
+            }
+        }
+    }
+}
+
+/// Gets a random duration between zero and `max`, using the randomly seeded std hasher
+/// as we do not need anything better to spread requests.
+fn get_random_duration(max: Duration) -> Duration {
+    if max.is_zero() {
+        return max;
+    }
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_millis(random % (max.as_millis() as u64 + 1))
+}
+
+/// Combines a schedule with a random jitter, to avoid thundering-herd patterns
+/// against provider APIs when many users run the tool with the same schedule.
+pub struct Scheduler {
+    schedule: Schedule,
+    jitter: Duration,
+}
+
+impl Scheduler {
+    /// Builds a new instance.
+    pub fn new(schedule: Schedule, jitter: Duration) -> Self {
+        Self { schedule, jitter }
+    }
+
+    /// Computes how long to wait until the next check, jitter included.
+    pub fn next_delay(&self, first: bool) -> Result<Duration, LibError> {
+        Ok(self.schedule.next_delay(first)? + get_random_duration(self.jitter))
+    }
+}
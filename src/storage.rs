@@ -1,17 +1,83 @@
+use crate::delivery::DeliveryReport;
+use crate::digest::DigestBuffer;
+use crate::metrics::CheckMetrics;
+use crate::outbox::Outbox;
+use crate::providers::StockLevel;
 use crate::{CheckResult, LibError};
-use serde::Serialize;
+use anyhow::{self, Context};
+use colored::Colorize;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::{fs, path};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{fmt, fs, path, process};
+
+/// Provides the in-memory storage backend, for library consumers
+pub mod memory;
 
 // Storage
 
-/// Structure to access disk storage, and store CheckResult hashes
+/// Name of the lock file, in the storage directory
+const LOCK_FILE_NAME: &str = "dsaw.lock";
+
+/// Exclusive access to a storage, released when dropped
+pub struct StorageLock {
+    _file: Option<fs::File>,
+}
+
+impl StorageLock {
+    /// Builds a lock which holds nothing, for backends which need none
+    pub fn none() -> Self {
+        Self { _file: None }
+    }
+}
+
+/// Defines the expected behaviour of every storage backend, which holds named entries
+/// of text content. Naming, serializing and validating the entries is left to CheckResultStorage.
+/// Backends can be moved along with a `Watcher` to the thread running it.
+pub trait StorageTrait: Send {
+    /// Gets where an entry is stored, for messages.
+    fn location(&self, name: &str) -> path::PathBuf;
+
+    /// Reads an entry, or None if it does not exist.
+    fn read(&self, name: &str) -> Result<Option<String>, LibError>;
+
+    /// Writes an entry, replacing any previous content.
+    fn write(&self, name: &str, content: &str) -> Result<(), LibError>;
+
+    /// Marks an existing entry as still in use, without changing its content.
+    fn touch(&self, name: &str) -> Result<(), LibError>;
+
+    /// Deletes an entry, which may not exist.
+    fn remove(&self, name: &str) -> Result<(), LibError>;
+
+    /// Lists every entry, with the last time it was written or touched.
+    fn list(&self) -> Result<Vec<(String, SystemTime)>, LibError>;
+
+    /// Locks the storage, or fails right away if another instance holds the lock.
+    fn try_lock(&self) -> Result<StorageLock, LibError>;
+
+    /// Same as above, but waits for the other instance to release the lock.
+    fn lock(&self) -> Result<StorageLock, LibError>;
+}
+
+/// Stores every entry as a file of a directory
+pub struct FileStorage {
+    path: path::PathBuf,
+}
+
+/// Structure to access storage, and store CheckResult hashes
 ///
-/// path: the base directory for relative storage
+/// backend: where the entries actually go, a directory by default
 pub struct CheckResultStorage {
-    path: path::PathBuf,
+    backend: Box<dyn StorageTrait>,
 }
 
+/// Length of an hexadecimal SHA256 hash-string
+const SHA256_HEX_LENGTH: usize = 64;
+
 /// Generates a SHA256 hash-string of the argument
 ///
 /// we use json serialization as an intermediary data, because
@@ -21,13 +87,306 @@ pub struct CheckResultStorage {
 /// We use the convenience function for Sha256 as we work blocking and data is small
 ///
 fn get_sha256_string<T: Serialize>(value: &T) -> Result<String, LibError> {
-    let json = serde_json::to_string(&value).map_err(|source| LibError::JsonError { source })?;
+    let json = serde_json::to_string(&value)?;
     let hash = Sha256::digest(json);
     Ok(format!("{hash:x}"))
 }
 
-impl CheckResultStorage {
-    /// Builds a new storage
+/// Gets the current time as seconds since the unix epoch
+pub fn get_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Gets the default storage directory, creating it if needed, unlike the ones which are
+/// explicitly selected, as nobody would otherwise create it.
+pub fn get_default_dir() -> Result<path::PathBuf, LibError> {
+    let path = crate::dirs::get_state_dir()?;
+    fs::create_dir_all(&path).map_err(|source| LibError::FileError {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(path)
+}
+
+/// Writes a file atomically: the content goes to a temporary file in the same directory,
+/// which is then renamed over the target, so that a crash mid-write never leaves a truncated file.
+fn write_atomic(path: &path::Path, content: &str) -> Result<(), LibError> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = path.with_file_name(format!(".{file_name}.{}.tmp", process::id()));
+    let result = fs::File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        // best effort, the original error is the one worth reporting
+        let _ = fs::remove_file(&temp_path);
+    }
+    result.map_err(|source| LibError::FileError {
+        path: path.into(),
+        source,
+    })
+}
+
+/// Warns about a corrupted stored entry, which is then ignored as if it did not exist
+fn warn_corrupted(path: &path::Path, reason: &str) {
+    eprintln!(
+        "{}",
+        format!(
+            "Ignoring corrupted {} ({reason}), as if there was no previous state",
+            path.display()
+        )
+        .yellow()
+    );
+}
+
+/// Anti-flapping policy: how long a new availability must be observed before it is confirmed.
+/// Without any threshold, every change is confirmed immediately.
+/// With both thresholds, the first one reached confirms the change.
+#[derive(Default)]
+pub struct Debounce {
+    pub checks: Option<u32>,
+    pub duration: Option<Duration>,
+}
+
+/// Availability state of a single server, as stored between invocations
+#[derive(Serialize, Deserialize)]
+pub struct ServerState {
+    /// Confirmed availability, the one which was notified
+    pub available: bool,
+    /// Latest observed availability, which may not be confirmed yet
+    #[serde(default)]
+    pub observed: bool,
+    /// Number of consecutive checks with the same observed availability
+    #[serde(default)]
+    pub streak: u32,
+    /// Timestamp of the first check with the same observed availability
+    #[serde(default)]
+    pub since: u64,
+    /// Timestamp of the latest check
+    #[serde(default)]
+    pub last_check: Option<u64>,
+    /// Timestamp of the latest check which observed the server available
+    #[serde(default)]
+    pub last_available: Option<u64>,
+    /// Timestamp of the latest check which observed the server available,
+    /// before the current streak of available observations
+    #[serde(default)]
+    pub previous_available: Option<u64>,
+    /// Latest observed stock level, when the provider tells it
+    #[serde(default)]
+    pub stock: Option<StockLevel>,
+    /// Latest observed price, when prices are watched and the provider tells it
+    #[serde(default)]
+    pub price: Option<f64>,
+}
+
+impl ServerState {
+    /// Builds the state of a server which was never seen before, deemed unavailable
+    pub fn new() -> Self {
+        Self {
+            available: false,
+            observed: false,
+            streak: 0,
+            since: get_timestamp(),
+            last_check: None,
+            last_available: None,
+            previous_available: None,
+            stock: None,
+            price: None,
+        }
+    }
+
+    /// Gets when the server was last seen available before its latest change of availability:
+    /// before the current streak when it is available, or at the end of the previous one otherwise.
+    pub fn last_seen_available(&self) -> Option<u64> {
+        match self.observed {
+            true => self.previous_available,
+            false => self.last_available,
+        }
+    }
+
+    /// Records an observation, and returns true if the confirmed availability changed
+    pub fn observe(&mut self, available: bool, now: u64, debounce: &Debounce) -> bool {
+        // update the history of consecutive observations
+        if self.streak == 0 || self.observed != available {
+            if available {
+                self.previous_available = self.last_available;
+            }
+            self.observed = available;
+            self.streak = 0;
+            self.since = now;
+        }
+        self.streak = self.streak.saturating_add(1);
+        self.last_check = Some(now);
+        if available {
+            self.last_available = Some(now);
+        }
+
+        // nothing to confirm if the observation matches the confirmed availability
+        if self.available == available {
+            return false;
+        }
+
+        let confirmed = match (debounce.checks, debounce.duration) {
+            (None, None) => true,
+            (checks, duration) => {
+                checks.is_some_and(|checks| self.streak >= checks)
+                    || duration.is_some_and(|duration| {
+                        now.saturating_sub(self.since) >= duration.as_secs()
+                    })
+            }
+        };
+
+        if confirmed {
+            self.available = available;
+        }
+        confirmed
+    }
+}
+
+/// A server state as stored, with the server name so that the state files can be listed
+#[derive(Serialize)]
+struct StoredServerState<'a> {
+    server: &'a str,
+    #[serde(flatten)]
+    state: &'a ServerState,
+}
+
+/// Used to read the server name back, which older state files do not have
+#[derive(Deserialize)]
+struct StoredServerName {
+    server: Option<String>,
+}
+
+/// Kinds of files found in a storage directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoredKind {
+    /// Hash of the available servers of a provider/servers combo (`--whole-list`)
+    Hash,
+    /// Availability state of a single server
+    ServerState,
+    /// Available servers of the whole inventory of a provider (`--all-servers`)
+    Snapshot,
+    /// Changes collected for a notifier, which are never pruned
+    Digest,
+    /// Last runs of the availability hook, per server
+    Hook,
+    /// Provider API metrics of the latest checks
+    Metrics,
+    /// Last successful check of a provider
+    LastSuccess,
+    /// Outcome of the latest notifications of a provider, per notifier
+    Deliveries,
+    /// Changes which could not be notified, to be retried, which are never pruned
+    Outbox,
+}
+
+impl fmt::Display for StoredKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StoredKind::Hash => "whole-list",
+            StoredKind::ServerState => "server",
+            StoredKind::Snapshot => "all-servers",
+            StoredKind::Digest => "digest",
+            StoredKind::Hook => "hook",
+            StoredKind::Metrics => "metrics",
+            StoredKind::LastSuccess => "last-success",
+            StoredKind::Deliveries => "deliveries",
+            StoredKind::Outbox => "outbox",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// An entry found in a storage, describing what it tracks
+pub struct StoredEntry {
+    /// name of the entry, which is its file name in a directory
+    pub name: String,
+    /// where the entry is stored
+    pub path: path::PathBuf,
+    pub kind: StoredKind,
+    /// provider name, or notifier name for digests and outboxes
+    pub handler: String,
+    /// server name, when known
+    pub server: Option<String>,
+    /// last time the entry was written or confirmed by a check
+    pub modified: SystemTime,
+}
+
+impl StoredEntry {
+    /// Recognizes what an entry tracks from its name, other entries being ignored
+    fn from_name(
+        storage: &CheckResultStorage,
+        name: String,
+        modified: SystemTime,
+    ) -> Result<Option<Self>, LibError> {
+        let (stem, extension) = name.rsplit_once('.').unwrap_or((&name, ""));
+        let (kind, handler) = match extension {
+            // provider names can hold dashes, but hashes cannot
+            "sha256" => (
+                StoredKind::Hash,
+                stem.rsplit_once('-').map(|(name, _)| name),
+            ),
+            "state" => (
+                StoredKind::ServerState,
+                stem.rsplit_once('-').map(|(name, _)| name),
+            ),
+            "snapshot" => (StoredKind::Snapshot, Some(stem)),
+            "json" => [
+                ("hook-", StoredKind::Hook),
+                ("metrics-", StoredKind::Metrics),
+                ("success-", StoredKind::LastSuccess),
+                ("deliveries-", StoredKind::Deliveries),
+                ("outbox-", StoredKind::Outbox),
+            ]
+            .into_iter()
+            .find_map(|(prefix, kind)| stem.strip_prefix(prefix).map(|name| (kind, Some(name))))
+            .unwrap_or((StoredKind::Digest, stem.strip_prefix("digest-"))),
+            _ => return Ok(None),
+        };
+        let Some(handler) = handler.map(str::to_string) else {
+            return Ok(None);
+        };
+
+        let server = match kind {
+            StoredKind::ServerState => storage
+                .read_json::<StoredServerName>(&name)?
+                .and_then(|stored| stored.server),
+            _ => None,
+        };
+        Ok(Some(Self {
+            path: storage.backend.location(&name),
+            name,
+            kind,
+            handler,
+            server,
+            modified,
+        }))
+    }
+
+    /// Gets how long ago the entry was last written or confirmed
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.modified)
+            .unwrap_or_default()
+    }
+}
+
+impl Default for ServerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileStorage {
+    /// Builds a new storage in an existing directory
     pub fn new(path: &path::PathBuf) -> Result<Self, LibError> {
         if !path.is_dir() {
             return Err(LibError::ValueError {
@@ -38,17 +397,235 @@ impl CheckResultStorage {
         Ok(Self { path: path.into() })
     }
 
-    /// Builds the storage path for a provided provider/servers combo
-    fn get_path(
+    /// Opens the lock file of the storage directory, creating it if needed
+    fn open_lock_file(&self) -> Result<(fs::File, path::PathBuf), LibError> {
+        let path = self.path.join(LOCK_FILE_NAME);
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|source| LibError::FileError {
+                path: path.clone(),
+                source,
+            })?;
+        Ok((file, path))
+    }
+}
+
+impl StorageTrait for FileStorage {
+    /// Gets the path of the file of an entry.
+    fn location(&self, name: &str) -> path::PathBuf {
+        self.path.join(name)
+    }
+
+    /// Reads a file as a string
+    ///
+    /// Returns None if the file was simply not found, and an Err if it could not be read.
+    fn read(&self, name: &str) -> Result<Option<String>, LibError> {
+        let path = self.location(name);
+        match fs::read_to_string(&path) {
+            Err(err) => match err.kind() {
+                // not being able to read the file IF IT DOES NOT EXIST is NOT a problem.
+                io::ErrorKind::NotFound => Ok(None),
+                // any other reason we could not get a string IS a problem.
+                _ => Err(LibError::FileError { path, source: err }),
+            },
+            Ok(content) => Ok(Some(content)),
+        }
+    }
+
+    /// Writes a file atomically.
+    fn write(&self, name: &str, content: &str) -> Result<(), LibError> {
+        write_atomic(&self.location(name), content)
+    }
+
+    /// Sets the modification time of a file, so that it is not pruned
+    /// while its check keeps finding nothing new.
+    fn touch(&self, name: &str) -> Result<(), LibError> {
+        let path = self.location(name);
+        match fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+        {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(LibError::FileError {
+                path,
+                source: error,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Deletes a file.
+    fn remove(&self, name: &str) -> Result<(), LibError> {
+        let path = self.location(name);
+        match fs::remove_file(&path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => Err(LibError::FileError {
+                path,
+                source: error,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Lists the files of the directory, with their modification time.
+    fn list(&self) -> Result<Vec<(String, SystemTime)>, LibError> {
+        let error = |source| LibError::FileError {
+            path: self.path.clone(),
+            source,
+        };
+        let mut entries = Vec::new();
+        for dir_entry in fs::read_dir(&self.path).map_err(error)? {
+            let dir_entry = dir_entry.map_err(error)?;
+            let Ok(name) = dir_entry.file_name().into_string() else {
+                continue;
+            };
+            let modified = dir_entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .map_err(|source| LibError::FileError {
+                    path: dir_entry.path(),
+                    source,
+                })?;
+            entries.push((name, modified));
+        }
+        Ok(entries)
+    }
+
+    /// Locks the storage directory through a `dsaw.lock` file, so that concurrent checks
+    /// do not race on its files, or fails right away if another instance holds the lock.
+    ///
+    /// The lock is advisory: it only protects against other instances of this program.
+    fn try_lock(&self) -> Result<StorageLock, LibError> {
+        let (file, path) = self.open_lock_file()?;
+        match file.try_lock() {
+            Ok(()) => Ok(StorageLock { _file: Some(file) }),
+            Err(fs::TryLockError::WouldBlock) => Err(LibError::StorageLocked {
+                path: path.to_string_lossy().to_string(),
+            }),
+            Err(fs::TryLockError::Error(source)) => Err(LibError::FileError { path, source }),
+        }
+    }
+
+    /// Same as above, but waits for the other instance to release the lock.
+    fn lock(&self) -> Result<StorageLock, LibError> {
+        let (file, path) = self.open_lock_file()?;
+        file.lock()
+            .map_err(|source| LibError::FileError { path, source })?;
+        Ok(StorageLock { _file: Some(file) })
+    }
+}
+
+impl CheckResultStorage {
+    /// Builds a new storage in an existing directory
+    pub fn new(path: &path::PathBuf) -> Result<Self, LibError> {
+        Ok(Self::from_backend(Box::new(FileStorage::new(path)?)))
+    }
+
+    /// Builds a storage in the selected directory, or in the default one
+    pub fn from_dir(storage_dir: &Option<String>) -> Result<Self, LibError> {
+        let path = match storage_dir {
+            Some(dir) => path::PathBuf::from(dir),
+            None => get_default_dir()?,
+        };
+        Self::new(&path)
+    }
+
+    /// Builds a storage on top of any backend, like a MemoryStorage
+    pub fn from_backend(backend: Box<dyn StorageTrait>) -> Self {
+        Self { backend }
+    }
+
+    /// Gets where the storage holds its entries, for messages
+    pub fn location(&self) -> path::PathBuf {
+        self.backend.location("")
+    }
+
+    /// Locks the storage, or fails right away if another instance holds the lock.
+    pub fn try_lock(&self) -> Result<StorageLock, LibError> {
+        self.backend.try_lock()
+    }
+
+    /// Same as above, but waits for the other instance to release the lock.
+    pub fn lock(&self) -> Result<StorageLock, LibError> {
+        self.backend.lock()
+    }
+
+    /// Reads and parses a stored json entry
+    ///
+    /// Returns None if the entry was not found, or if it is corrupted.
+    fn read_json<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, LibError> {
+        let Some(content) = self.backend.read(name)? else {
+            return Ok(None);
+        };
+        match serde_json::from_str(&content) {
+            Ok(value) => Ok(Some(value)),
+            Err(error) => {
+                warn_corrupted(&self.backend.location(name), &error.to_string());
+                Ok(None)
+            }
+        }
+    }
+
+    /// Lists the entries tracking something in the storage, oldest first
+    pub fn list_entries(&self) -> Result<Vec<StoredEntry>, LibError> {
+        let mut entries = Vec::new();
+        for (name, modified) in self.backend.list()? {
+            if let Some(entry) = StoredEntry::from_name(self, name, modified)? {
+                entries.push(entry);
+            }
+        }
+        entries.sort_by_key(|entry| entry.modified);
+        Ok(entries)
+    }
+
+    /// Lists the entries which were neither written nor confirmed for longer than `ttl`,
+    /// and deletes them unless `dry_run` is set. Digests and outboxes are never pruned,
+    /// as they hold changes which were not notified yet.
+    pub fn prune(&self, ttl: Duration, dry_run: bool) -> Result<Vec<StoredEntry>, LibError> {
+        let expired: Vec<StoredEntry> = self
+            .list_entries()?
+            .into_iter()
+            .filter(|entry| {
+                !matches!(entry.kind, StoredKind::Digest | StoredKind::Outbox) && entry.age() > ttl
+            })
+            .collect();
+        if !dry_run {
+            for entry in expired.iter() {
+                self.backend.remove(&entry.name)?;
+            }
+        }
+        Ok(expired)
+    }
+
+    /// Marks the hash of a provided provider/servers combo as still in use
+    pub fn touch_hash(&self, provider_name: &str, servers: &Vec<String>) -> Result<(), LibError> {
+        self.backend
+            .touch(&Self::get_hash_name(provider_name, servers)?)
+    }
+
+    /// Marks the snapshot of the whole inventory of a provider as still in use
+    pub fn touch_snapshot(&self, provider_name: &str) -> Result<(), LibError> {
+        self.backend.touch(&Self::get_snapshot_name(provider_name))
+    }
+
+    /// Builds the entry name for a provided provider/servers combo
+    fn get_hash_name(provider_name: &str, servers: &Vec<String>) -> Result<String, LibError> {
+        let hash = get_sha256_string(servers)?;
+        Ok(format!("{provider_name}-{hash}.sha256"))
+    }
+
+    /// Builds the path and the hash which would be stored for a provided provider/servers combo
+    pub fn build_hash(
         &self,
         provider_name: &str,
         servers: &Vec<String>,
-    ) -> Result<path::PathBuf, LibError> {
-        let hash = get_sha256_string(servers)?;
-        let file_name = format!("{provider_name}-{hash}.sha256");
-        let mut path = self.path.clone();
-        path.push(file_name);
-        Ok(path)
+        check_result: &CheckResult,
+    ) -> Result<(path::PathBuf, String), LibError> {
+        let name = Self::get_hash_name(provider_name, servers)?;
+        let available_server_hash = get_sha256_string(&check_result.available_servers)?;
+        Ok((self.backend.location(&name), available_server_hash))
     }
 
     /// Stores the hash of a provided provider/servers combo
@@ -58,9 +635,9 @@ impl CheckResultStorage {
         servers: &Vec<String>,
         check_result: &CheckResult,
     ) -> Result<(), LibError> {
-        let path = self.get_path(&provider_name, &servers)?;
+        let name = Self::get_hash_name(provider_name, servers)?;
         let available_server_hash = get_sha256_string(&check_result.available_servers)?;
-        fs::write(path, available_server_hash).map_err(|source| LibError::IOError { source })
+        self.backend.write(&name, &available_server_hash)
     }
 
     /// Gets the hash of a provided provider/servers combo
@@ -73,12 +650,13 @@ impl CheckResultStorage {
     /// - not having permission to read the underlying file
     /// - any kind of text encoding error while converting the content to a string
     ///
-    /// Returns None if the file was simply not found
+    /// Returns None if the file was simply not found, or if its content is not
+    /// a valid hash (like a file truncated by a crash), with a warning
     ///
     /// Returns Some(String) if a string has been read successfully from the file
     ///
     /// Example:
-    /// ```
+    /// ```ignore
     /// match self.get_check_result_hash(provider_name, servers)? { // Err on critical
     ///   None => Ok(false),                                        // file not found
     ///   Some(stored_hash) => Ok(true),                            // string read and trimmed
@@ -89,19 +667,18 @@ impl CheckResultStorage {
         provider_name: &str,
         servers: &Vec<String>,
     ) -> Result<Option<String>, LibError> {
-        // not being able to build the file path is a problem, so we might return an Err
-        let path = self.get_path(&provider_name, &servers)?;
-        // handle the result of reading the file as a textual string
-        match fs::read_to_string(path) {
-            Err(err) => match err.kind() {
-                // not being able to read the file IF IT DOES NOT EXIST is NOT a problem.
-                std::io::ErrorKind::NotFound => Ok(None),
-                // any other reason we could not get a string IS a problem.
-                _ => Err(LibError::IOError { source: err }),
-            },
-            // if the string was read successfully, trim it to remove any whitespace and newlines
-            Ok(content) => Ok(Some(content.trim().to_string())),
+        // not being able to build the entry name is a problem, so we might return an Err
+        let name = Self::get_hash_name(provider_name, servers)?;
+        let Some(content) = self.backend.read(&name)? else {
+            return Ok(None);
+        };
+        // trim it to remove any whitespace and newlines
+        let hash = content.trim();
+        if hash.len() != SHA256_HEX_LENGTH || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            warn_corrupted(&self.backend.location(&name), "invalid hash");
+            return Ok(None);
         }
+        Ok(Some(hash.to_string()))
     }
 
     /// Compares the provided check_result by building its hash and comparing to the one stored
@@ -119,10 +696,361 @@ impl CheckResultStorage {
             None => Ok(false),
             // otherwise, compute the current check_result and compare it to the stored one
             Some(stored_hash) => {
-                let available_server_hash =
-                    get_sha256_string(&check_result.available_servers)?;
+                let available_server_hash = get_sha256_string(&check_result.available_servers)?;
                 Ok(available_server_hash == stored_hash)
             }
         }
     }
+
+    /// Builds the entry name for a provided provider/server state
+    fn get_state_name(provider_name: &str, server: &str) -> Result<String, LibError> {
+        let hash = get_sha256_string(&server)?;
+        Ok(format!("{provider_name}-{hash}.state"))
+    }
+
+    /// Serializes a single server state, with its server name
+    fn serialize_server_state(server: &str, state: &ServerState) -> Result<String, LibError> {
+        let stored = StoredServerState { server, state };
+        Ok(serde_json::to_string(&stored)?)
+    }
+
+    /// Builds the path and the content which would be stored for a single server state
+    pub fn build_server_state(
+        &self,
+        provider_name: &str,
+        server: &str,
+        state: &ServerState,
+    ) -> Result<(path::PathBuf, String), LibError> {
+        let name = Self::get_state_name(provider_name, server)?;
+        let json = Self::serialize_server_state(server, state)?;
+        Ok((self.backend.location(&name), json))
+    }
+
+    /// Stores the availability state of a single server
+    pub fn put_server_state(
+        &self,
+        provider_name: &str,
+        server: &str,
+        state: &ServerState,
+    ) -> Result<(), LibError> {
+        let name = Self::get_state_name(provider_name, server)?;
+        let json = Self::serialize_server_state(server, state)?;
+        self.backend.write(&name, &json)
+    }
+
+    /// Gets the availability state of a single server
+    ///
+    /// Same behaviour as `get_hash()`: None if the file was simply not found,
+    /// or could not be parsed, and an Err if it could not be read.
+    pub fn get_server_state(
+        &self,
+        provider_name: &str,
+        server: &str,
+    ) -> Result<Option<ServerState>, LibError> {
+        let name = Self::get_state_name(provider_name, server)?;
+        self.read_json(&name)
+    }
+
+    /// Builds the entry name for the snapshot of the whole inventory of a provider
+    fn get_snapshot_name(provider_name: &str) -> String {
+        format!("{provider_name}.snapshot")
+    }
+
+    /// Builds the path and the content which would be stored for an inventory snapshot
+    pub fn build_snapshot(
+        &self,
+        provider_name: &str,
+        servers: &Vec<String>,
+    ) -> Result<(path::PathBuf, String), LibError> {
+        let name = Self::get_snapshot_name(provider_name);
+        let json = serde_json::to_string(servers)?;
+        Ok((self.backend.location(&name), json))
+    }
+
+    /// Stores the available servers of the whole inventory of a provider
+    pub fn put_snapshot(&self, provider_name: &str, servers: &Vec<String>) -> Result<(), LibError> {
+        let json = serde_json::to_string(servers)?;
+        self.backend
+            .write(&Self::get_snapshot_name(provider_name), &json)
+    }
+
+    /// Gets the available servers of the whole inventory of a provider
+    ///
+    /// Same behaviour as `get_hash()`: None if the file was simply not found,
+    /// or could not be parsed, and an Err if it could not be read.
+    pub fn get_snapshot(&self, provider_name: &str) -> Result<Option<Vec<String>>, LibError> {
+        self.read_json(&Self::get_snapshot_name(provider_name))
+    }
+
+    /// Builds the entry name for the digest delivered through a notifier
+    fn get_digest_name(notifier_name: &str) -> String {
+        format!("digest-{notifier_name}.json")
+    }
+
+    /// Builds the path and the content which would be stored for a digest
+    pub fn build_digest(
+        &self,
+        notifier_name: &str,
+        digest: &DigestBuffer,
+    ) -> Result<(path::PathBuf, String), LibError> {
+        let name = Self::get_digest_name(notifier_name);
+        let json = serde_json::to_string(digest)?;
+        Ok((self.backend.location(&name), json))
+    }
+
+    /// Stores the changes collected for a notifier
+    pub fn put_digest(&self, notifier_name: &str, digest: &DigestBuffer) -> Result<(), LibError> {
+        let json = serde_json::to_string(digest)?;
+        self.backend
+            .write(&Self::get_digest_name(notifier_name), &json)
+    }
+
+    /// Gets the changes collected for a notifier
+    ///
+    /// Same behaviour as `get_hash()`: None if the file was simply not found,
+    /// or could not be parsed, and an Err if it could not be read.
+    pub fn get_digest(&self, notifier_name: &str) -> Result<Option<DigestBuffer>, LibError> {
+        self.read_json(&Self::get_digest_name(notifier_name))
+    }
+
+    /// Builds the entry name for the changes which could not be notified through a notifier
+    fn get_outbox_name(notifier_name: &str) -> String {
+        format!("outbox-{notifier_name}.json")
+    }
+
+    /// Builds the path and the content which would be stored for an outbox
+    pub fn build_outbox(
+        &self,
+        notifier_name: &str,
+        outbox: &Outbox,
+    ) -> Result<(path::PathBuf, String), LibError> {
+        let name = Self::get_outbox_name(notifier_name);
+        let json = serde_json::to_string(outbox)?;
+        Ok((self.backend.location(&name), json))
+    }
+
+    /// Stores the changes which could not be notified through a notifier
+    pub fn put_outbox(&self, notifier_name: &str, outbox: &Outbox) -> Result<(), LibError> {
+        let json = serde_json::to_string(outbox)?;
+        self.backend
+            .write(&Self::get_outbox_name(notifier_name), &json)
+    }
+
+    /// Gets the changes which could not be notified through a notifier
+    ///
+    /// Same behaviour as `get_hash()`: None if the file was simply not found,
+    /// or could not be parsed, and an Err if it could not be read.
+    pub fn get_outbox(&self, notifier_name: &str) -> Result<Option<Outbox>, LibError> {
+        self.read_json(&Self::get_outbox_name(notifier_name))
+    }
+
+    /// Builds the entry name for the last runs of the availability hook of a provider
+    fn get_hook_name(provider_name: &str) -> String {
+        format!("hook-{provider_name}.json")
+    }
+
+    /// Stores when the availability hook last ran, per server
+    pub fn put_hook_runs(
+        &self,
+        provider_name: &str,
+        runs: &BTreeMap<String, u64>,
+    ) -> Result<(), LibError> {
+        let json = serde_json::to_string(runs)?;
+        self.backend
+            .write(&Self::get_hook_name(provider_name), &json)
+    }
+
+    /// Gets when the availability hook last ran, per server
+    ///
+    /// Same behaviour as `get_hash()`: None if the file was simply not found,
+    /// or could not be parsed, and an Err if it could not be read.
+    pub fn get_hook_runs(
+        &self,
+        provider_name: &str,
+    ) -> Result<Option<BTreeMap<String, u64>>, LibError> {
+        self.read_json(&Self::get_hook_name(provider_name))
+    }
+
+    /// Builds the entry name for the provider API metrics of the latest checks of a provider
+    fn get_metrics_name(provider_name: &str) -> String {
+        format!("metrics-{provider_name}.json")
+    }
+
+    /// Builds the path and the content which would be stored for provider API metrics
+    pub fn build_metrics(
+        &self,
+        provider_name: &str,
+        history: &[CheckMetrics],
+    ) -> Result<(path::PathBuf, String), LibError> {
+        let name = Self::get_metrics_name(provider_name);
+        let json = serde_json::to_string(history)?;
+        Ok((self.backend.location(&name), json))
+    }
+
+    /// Stores the provider API metrics of the latest checks, oldest first
+    pub fn put_metrics(
+        &self,
+        provider_name: &str,
+        history: &[CheckMetrics],
+    ) -> Result<(), LibError> {
+        let json = serde_json::to_string(history)?;
+        self.backend
+            .write(&Self::get_metrics_name(provider_name), &json)
+    }
+
+    /// Gets the provider API metrics of the latest checks, oldest first
+    ///
+    /// Same behaviour as `get_hash()`: None if the file was simply not found,
+    /// or could not be parsed, and an Err if it could not be read.
+    pub fn get_metrics(&self, provider_name: &str) -> Result<Option<Vec<CheckMetrics>>, LibError> {
+        self.read_json(&Self::get_metrics_name(provider_name))
+    }
+
+    /// Builds the entry name for the latest notification deliveries of a provider
+    fn get_deliveries_name(provider_name: &str) -> String {
+        format!("deliveries-{provider_name}.json")
+    }
+
+    /// Builds the path and the content which would be stored for notification deliveries
+    pub fn build_deliveries(
+        &self,
+        provider_name: &str,
+        history: &[DeliveryReport],
+    ) -> Result<(path::PathBuf, String), LibError> {
+        let name = Self::get_deliveries_name(provider_name);
+        let json = serde_json::to_string(history)?;
+        Ok((self.backend.location(&name), json))
+    }
+
+    /// Stores the latest notification deliveries, oldest first
+    pub fn put_deliveries(
+        &self,
+        provider_name: &str,
+        history: &[DeliveryReport],
+    ) -> Result<(), LibError> {
+        let json = serde_json::to_string(history)?;
+        self.backend
+            .write(&Self::get_deliveries_name(provider_name), &json)
+    }
+
+    /// Gets the latest notification deliveries, oldest first
+    ///
+    /// Same behaviour as `get_hash()`: None if the file was simply not found,
+    /// or could not be parsed, and an Err if it could not be read.
+    pub fn get_deliveries(
+        &self,
+        provider_name: &str,
+    ) -> Result<Option<Vec<DeliveryReport>>, LibError> {
+        self.read_json(&Self::get_deliveries_name(provider_name))
+    }
+
+    /// Builds the entry name for the last successful check of a provider
+    fn get_last_success_name(provider_name: &str) -> String {
+        format!("success-{provider_name}.json")
+    }
+
+    /// Stores the timestamp of the last check of a provider which succeeded
+    pub fn put_last_success(&self, provider_name: &str, timestamp: u64) -> Result<(), LibError> {
+        let json = serde_json::to_string(&timestamp)?;
+        self.backend
+            .write(&Self::get_last_success_name(provider_name), &json)
+    }
+
+    /// Gets the timestamp of the last check of a provider which succeeded
+    ///
+    /// Same behaviour as `get_hash()`: None if the file was simply not found,
+    /// or could not be parsed, and an Err if it could not be read.
+    pub fn get_last_success(&self, provider_name: &str) -> Result<Option<u64>, LibError> {
+        self.read_json(&Self::get_last_success_name(provider_name))
+    }
+}
+
+/// An implementation for the ListRunner, which shows what a storage directory tracks
+pub struct ListRunner {
+    storage: CheckResultStorage,
+}
+
+impl ListRunner {
+    /// Builds an instance for the selected storage directory, or the default one
+    pub fn new(storage_dir: &Option<String>) -> anyhow::Result<Self> {
+        Ok(Self {
+            storage: CheckResultStorage::from_dir(storage_dir)
+                .context("while initializing CheckResultStorage")?,
+        })
+    }
+
+    /// Prints every stored entry, oldest first
+    pub fn list(&self) -> anyhow::Result<()> {
+        let entries = self
+            .storage
+            .list_entries()
+            .context("while listing storage")?;
+        if entries.is_empty() {
+            println!("Nothing stored in {}", self.storage.location().display());
+        }
+        for entry in entries.iter() {
+            let name = match &entry.server {
+                Some(server) => format!("{} {server}", entry.handler),
+                None => entry.handler.clone(),
+            };
+            println!(
+                "{:<12} {:<40} {:<16} {}",
+                entry.kind.to_string(),
+                name,
+                crate::format_age(entry.age()),
+                entry
+                    .path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .dimmed()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// An implementation for the PruneRunner, which deletes the states nothing checks anymore
+pub struct PruneRunner {
+    storage: CheckResultStorage,
+    ttl: Duration,
+    dry_run: bool,
+}
+
+impl PruneRunner {
+    /// Builds an instance for the selected storage directory, or the default one
+    pub fn new(storage_dir: &Option<String>, ttl: Duration, dry_run: bool) -> anyhow::Result<Self> {
+        Ok(Self {
+            storage: CheckResultStorage::from_dir(storage_dir)
+                .context("while initializing CheckResultStorage")?,
+            ttl,
+            dry_run,
+        })
+    }
+
+    /// Deletes the expired entries, holding the storage lock so that no check races with it
+    pub fn prune(&self) -> anyhow::Result<()> {
+        let _lock = match self.dry_run {
+            true => None,
+            false => Some(self.storage.lock().context("while locking storage")?),
+        };
+        let pruned = self
+            .storage
+            .prune(self.ttl, self.dry_run)
+            .context("while pruning storage")?;
+        let verb = if self.dry_run {
+            "Would prune"
+        } else {
+            "Pruned"
+        };
+        for entry in pruned.iter() {
+            println!(
+                "{verb} {} (last checked {})",
+                entry.path.display(),
+                crate::format_age(entry.age())
+            );
+        }
+        println!("{verb} {} entries", pruned.len());
+        Ok(())
+    }
 }
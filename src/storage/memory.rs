@@ -0,0 +1,114 @@
+use super::{StorageLock, StorageTrait};
+use crate::LibError;
+use std::collections::BTreeMap;
+use std::path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+// MEMORY implementation (no filesystem access, for library consumers)
+
+/// A stored entry, with the last time it was written or touched
+struct MemoryEntry {
+    content: String,
+    modified: SystemTime,
+}
+
+/// Keeps every entry in memory, for programs embedding the library which
+/// have their own persistence: they export the entries after the checks,
+/// and import them back before the next ones.
+///
+/// Clones share the same entries, so that one can be kept to export them
+/// while another is used by a CheckRunner.
+#[derive(Clone, Default)]
+pub struct MemoryStorage {
+    entries: Arc<Mutex<BTreeMap<String, MemoryEntry>>>,
+}
+
+impl MemoryStorage {
+    /// Builds a new empty instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the entries, even if a thread panicked while holding them.
+    fn entries(&self) -> MutexGuard<'_, BTreeMap<String, MemoryEntry>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Exports every entry, by name, with its content.
+    pub fn export(&self) -> BTreeMap<String, String> {
+        self.entries()
+            .iter()
+            .map(|(name, entry)| (name.clone(), entry.content.clone()))
+            .collect()
+    }
+
+    /// Imports entries, as exported before, replacing the current ones.
+    /// They are deemed written right now.
+    pub fn import(&self, entries: BTreeMap<String, String>) {
+        let modified = SystemTime::now();
+        *self.entries() = entries
+            .into_iter()
+            .map(|(name, content)| (name, MemoryEntry { content, modified }))
+            .collect();
+    }
+}
+
+impl StorageTrait for MemoryStorage {
+    /// Gets the entry name, as there is no actual location.
+    fn location(&self, name: &str) -> path::PathBuf {
+        path::PathBuf::from(name)
+    }
+
+    /// Reads an entry.
+    fn read(&self, name: &str) -> Result<Option<String>, LibError> {
+        Ok(self.entries().get(name).map(|entry| entry.content.clone()))
+    }
+
+    /// Writes an entry.
+    fn write(&self, name: &str, content: &str) -> Result<(), LibError> {
+        self.entries().insert(
+            name.to_string(),
+            MemoryEntry {
+                content: content.to_string(),
+                modified: SystemTime::now(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Marks an entry as still in use.
+    fn touch(&self, name: &str) -> Result<(), LibError> {
+        if let Some(entry) = self.entries().get_mut(name) {
+            entry.modified = SystemTime::now();
+        }
+        Ok(())
+    }
+
+    /// Deletes an entry.
+    fn remove(&self, name: &str) -> Result<(), LibError> {
+        self.entries().remove(name);
+        Ok(())
+    }
+
+    /// Lists the entries.
+    fn list(&self) -> Result<Vec<(String, SystemTime)>, LibError> {
+        Ok(self
+            .entries()
+            .iter()
+            .map(|(name, entry)| (name.clone(), entry.modified))
+            .collect())
+    }
+
+    /// Locks nothing, as no other process can access the entries.
+    fn try_lock(&self) -> Result<StorageLock, LibError> {
+        Ok(StorageLock::none())
+    }
+
+    /// Locks nothing, as no other process can access the entries.
+    fn lock(&self) -> Result<StorageLock, LibError> {
+        Ok(StorageLock::none())
+    }
+}
@@ -0,0 +1,84 @@
+// Text templates
+
+/// Replaces every `{name}` placeholder of the template with its value.
+///
+/// Unknown placeholders and lone braces are kept as they are.
+pub fn render(template: &str, values: &[(&str, &str)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest.find('}').and_then(|end| {
+            values
+                .iter()
+                .find(|(name, _)| *name == &rest[1..end])
+                .map(|(_, value)| (end, value))
+        });
+        match value {
+            Some((end, value)) => {
+                output.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                output.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Escapes the characters which have a meaning in HTML.
+pub fn escape_html(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+/// Percent-encodes everything but the unreserved characters, for use in URLs.
+pub fn escape_url(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                output.push(byte as char)
+            }
+            byte => output.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_replaces_known_placeholders() {
+        let values = [("provider", "ovh"), ("server", "22sk010")];
+        assert_eq!(render("{provider}/{server}", &values), "ovh/22sk010");
+        assert_eq!(render("{server}-{server}", &values), "22sk010-22sk010");
+        assert_eq!(render("no placeholder", &values), "no placeholder");
+    }
+
+    #[test]
+    fn render_keeps_unknown_placeholders_and_lone_braces() {
+        let values = [("server", "22sk010")];
+        assert_eq!(render("{unknown} {server}", &values), "{unknown} 22sk010");
+        assert_eq!(render("{ {server} }", &values), "{ 22sk010 }");
+        assert_eq!(render("{server", &values), "{server");
+        assert_eq!(render("{{server}}", &values), "{22sk010}");
+    }
+}
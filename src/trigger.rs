@@ -0,0 +1,282 @@
+use crate::{metrics, LibError};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// On-demand checks, requested over HTTP
+
+/// Environment variable holding the token which trigger requests must provide.
+pub const ENV_TRIGGER_TOKEN: &str = "DSAW_TRIGGER_TOKEN";
+
+/// Path prefix of trigger requests, followed by the provider name.
+const TRIGGER_PATH: &str = "/trigger/";
+
+/// Path of the provider API metrics, in the Prometheus text format.
+const METRICS_PATH: &str = "/metrics";
+
+/// How long a client may take to send its whole request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Most connections answered at once, the others being turned down right away.
+const MAX_CONNECTIONS: usize = 16;
+
+/// Maximum size of the request line and headers, the body being ignored.
+const MAX_REQUEST_SIZE: usize = 8192;
+
+/// Set by the listener, cleared once the check has been started.
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Name of the provider being watched, which trigger requests must target.
+static TARGET: Mutex<Option<String>> = Mutex::new(None);
+
+/// Selects the provider which trigger requests must target.
+pub fn set_target(provider_name: &str) {
+    *TARGET
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(provider_name.to_string());
+}
+
+/// Tells if the provider is the one being watched.
+fn is_target(provider_name: &str) -> bool {
+    TARGET
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .as_deref()
+        == Some(provider_name)
+}
+
+/// Tells if a check was requested since the last call.
+pub fn take() -> bool {
+    REQUESTED.swap(false, Ordering::Relaxed)
+}
+
+/// Compares tokens in a time which does not depend on where they differ.
+fn is_same_token(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected
+            .bytes()
+            .zip(provided.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Reads a stream until a deadline, however slowly its client sends it.
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(std::io::ErrorKind::TimedOut.into());
+        }
+        let mut stream = self.stream;
+        stream.set_read_timeout(Some(remaining))?;
+        stream.read(buffer)
+    }
+}
+
+/// A parsed request, with only what matters to triggers.
+struct Request {
+    method: String,
+    path: String,
+    query_token: Option<String>,
+    bearer_token: Option<String>,
+}
+
+impl Request {
+    /// Reads the request line and the headers, ignoring any body,
+    /// unless the client takes more than `REQUEST_TIMEOUT` to send them.
+    fn read(stream: &TcpStream) -> Option<Self> {
+        let deadline = Instant::now() + REQUEST_TIMEOUT;
+        let reader = DeadlineReader { stream, deadline };
+        let mut reader = BufReader::new(reader.take(MAX_REQUEST_SIZE as u64));
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let mut parts = line.split_whitespace();
+        let method = parts.next()?.to_string();
+        let target = parts.next()?;
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query_token = query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("token="))
+            .map(str::to_string);
+
+        let mut bearer_token = None;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).ok()? == 0 || header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("authorization") {
+                    bearer_token = value
+                        .trim()
+                        .strip_prefix("Bearer ")
+                        .map(|token| token.trim().to_string());
+                }
+            }
+        }
+        Some(Self {
+            method,
+            path: path.to_string(),
+            query_token,
+            bearer_token,
+        })
+    }
+}
+
+/// Answers trigger requests: authenticates them, limits their rate,
+/// and requests a check of the watched provider.
+struct TriggerServer {
+    token: String,
+    min_interval: Duration,
+    last: Mutex<Option<Instant>>,
+}
+
+impl TriggerServer {
+    /// Tells if the request provides the expected token.
+    fn is_authorized(&self, request: &Request) -> bool {
+        request
+            .bearer_token
+            .as_deref()
+            .or(request.query_token.as_deref())
+            .is_some_and(|provided| is_same_token(&self.token, provided))
+    }
+
+    /// Handles a metrics request, and gets the status, the extra headers and the body of the response.
+    fn handle_metrics(&self, request: &Request) -> (u16, &'static str, Vec<String>, String) {
+        if request.method != "GET" {
+            let reason = "Method Not Allowed";
+            return (
+                405,
+                reason,
+                vec!["Allow: GET".to_string()],
+                format!("{reason}\n"),
+            );
+        }
+        if !self.is_authorized(request) {
+            let reason = "Unauthorized";
+            return (401, reason, Vec::new(), format!("{reason}\n"));
+        }
+        (200, "OK", Vec::new(), metrics::render())
+    }
+
+    /// Handles a request, and gets the status and the extra headers of the response.
+    fn handle(&self, request: Option<Request>) -> (u16, &'static str, Vec<String>) {
+        let Some(request) = request else {
+            return (400, "Bad Request", Vec::new());
+        };
+        let Some(provider_name) = request.path.strip_prefix(TRIGGER_PATH) else {
+            return (404, "Not Found", Vec::new());
+        };
+        if request.method != "POST" {
+            return (405, "Method Not Allowed", vec!["Allow: POST".to_string()]);
+        }
+        if !self.is_authorized(&request) {
+            return (401, "Unauthorized", Vec::new());
+        }
+        if !is_target(provider_name) {
+            return (404, "Not Found", Vec::new());
+        }
+        let mut last = self
+            .last
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(last) = *last {
+            let elapsed = last.elapsed();
+            if elapsed < self.min_interval {
+                let retry = (self.min_interval - elapsed).as_secs().max(1);
+                return (
+                    429,
+                    "Too Many Requests",
+                    vec![format!("Retry-After: {retry}")],
+                );
+            }
+        }
+        *last = Some(Instant::now());
+        REQUESTED.store(true, Ordering::Relaxed);
+        (202, "Accepted", Vec::new())
+    }
+
+    /// Answers a single connection, any failure only affecting this client.
+    fn serve(&self, stream: TcpStream) {
+        let _ = stream.set_write_timeout(Some(REQUEST_TIMEOUT));
+        let request = Request::read(&stream);
+        let (status, reason, headers, body) = match request {
+            Some(request) if request.path == METRICS_PATH => self.handle_metrics(&request),
+            request => {
+                let (status, reason, headers) = self.handle(request);
+                (status, reason, headers, format!("{reason}\n"))
+            }
+        };
+        Self::respond(stream, status, reason, headers, body);
+    }
+
+    /// Writes a response, and closes the connection.
+    fn respond(
+        mut stream: TcpStream,
+        status: u16,
+        reason: &str,
+        headers: Vec<String>,
+        body: String,
+    ) {
+        let mut response = format!("HTTP/1.1 {status} {reason}\r\n");
+        for header in headers {
+            response.push_str(&header);
+            response.push_str("\r\n");
+        }
+        response.push_str(&format!(
+            "Content-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        ));
+        let _ = stream.write_all(response.as_bytes());
+    }
+}
+
+/// Listens for trigger requests in the background, for the lifetime of the process.
+///
+/// `POST /trigger/{provider}` requests a check of the watched provider, right away.
+/// The token is expected as a bearer token, or as a `token` query parameter for
+/// clients which cannot set headers. Accepted requests are at least `min_interval` apart.
+/// Up to `MAX_CONNECTIONS` clients are answered at once, each having `REQUEST_TIMEOUT` to send its request.
+/// `GET /metrics` gets the provider API metrics, with the same token.
+pub fn listen(address: &str, token: &str, min_interval: Duration) -> Result<(), LibError> {
+    if token.is_empty() {
+        return Err(LibError::ValueError {
+            name: format!("found empty {ENV_TRIGGER_TOKEN}"),
+            value: String::new(),
+        });
+    }
+    let listener = TcpListener::bind(address)?;
+    let server = Arc::new(TriggerServer {
+        token: token.to_string(),
+        min_interval,
+        last: Mutex::new(None),
+    });
+    // every connection is answered on its own thread, so that a slow client delays no other
+    let connections = Arc::new(AtomicUsize::new(0));
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                connections.fetch_sub(1, Ordering::SeqCst);
+                let _ = stream.set_write_timeout(Some(REQUEST_TIMEOUT));
+                let reason = "Service Unavailable";
+                TriggerServer::respond(stream, 503, reason, Vec::new(), format!("{reason}\n"));
+                continue;
+            }
+            let server = Arc::clone(&server);
+            let connections = Arc::clone(&connections);
+            thread::spawn(move || {
+                server.serve(stream);
+                connections.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,319 @@
+use crate::providers::CheckRunner;
+use crate::schedule::Scheduler;
+use crate::storage::{get_timestamp, StoredKind};
+use crate::{format_age, CheckResult, LibError};
+use anyhow::Context;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, List, Row, Table};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::VecDeque;
+use std::io;
+use std::time::{Duration, Instant, SystemTime};
+
+// Terminal monitor
+
+/// Separates the provider from its servers, like `ovh=24sk10,24sk20`.
+const WATCH_SEPARATOR: char = '=';
+
+/// Separates the provider from the profile of the job, like `scaleway@work=EM-A210R-HDD`.
+const PROFILE_SEPARATOR: char = '@';
+
+/// How many notifications the log pane remembers.
+const LOG_LENGTH: usize = 100;
+
+/// How often the screen is refreshed while waiting for the next check.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Parses a watch, like `ovh=24sk10,24sk20` or `scaleway@work=EM-A210R-HDD`, into
+/// its provider, the profile of the environment file it uses, if any, and its servers.
+pub fn parse_watch(watch: &str) -> Result<(String, Option<String>, Vec<String>), LibError> {
+    let error = || LibError::ValueError {
+        name: "watch (PROVIDER[@PROFILE]=SERVER,...)".into(),
+        value: watch.to_string(),
+    };
+    let (provider, servers) = watch.split_once(WATCH_SEPARATOR).ok_or_else(error)?;
+    let servers = crate::tokenize_optional_csv_str(&Some(servers.to_string()))?;
+    let (provider, profile) = match provider.split_once(PROFILE_SEPARATOR) {
+        Some((provider, profile)) => (provider.trim(), Some(profile.trim())),
+        None => (provider.trim(), None),
+    };
+    if provider.is_empty() || profile.is_some_and(str::is_empty) || servers.is_empty() {
+        return Err(error());
+    }
+    Ok((provider.to_string(), profile.map(str::to_string), servers))
+}
+
+/// A line of the table, describing the latest state of a server.
+struct ServerRow {
+    provider: &'static str,
+    server: String,
+    status: (&'static str, Color),
+    last_check: String,
+    last_available: String,
+}
+
+/// An implementation for the TuiRunner, which checks several providers on the same schedule,
+/// and shows their servers in a live table, with a log of the recent notifications.
+pub struct TuiRunner {
+    runners: Vec<CheckRunner>,
+    scheduler: Scheduler,
+    log: VecDeque<Line<'static>>,
+    started: SystemTime,
+}
+
+impl TuiRunner {
+    /// Builds an instance around CheckRunners, which notify as usual
+    pub fn new(runners: Vec<CheckRunner>, scheduler: Scheduler) -> Self {
+        Self {
+            runners,
+            scheduler,
+            log: VecDeque::new(),
+            started: SystemTime::now(),
+        }
+    }
+
+    /// Shows the monitor until the user quits, restoring the terminal afterwards.
+    pub fn run(&mut self) -> anyhow::Result<()> {
+        // raw mode and alternate screen, restored on panics too
+        let mut terminal = ratatui::init();
+        let result = self.run_loop(&mut terminal);
+        ratatui::restore();
+        result
+    }
+
+    /// Checks on schedule, refreshing the screen meanwhile.
+    fn run_loop(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
+        let mut first = true;
+        loop {
+            let delay = self
+                .scheduler
+                .next_delay(first)
+                .context("while computing the next check time")?;
+            first = false;
+
+            let deadline = Instant::now() + delay;
+            loop {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    break;
+                }
+                self.draw(
+                    terminal,
+                    &format!("next check in {}", format_countdown(remaining)),
+                )?;
+                if quit_requested(remaining.min(REFRESH_INTERVAL))? {
+                    return Ok(());
+                }
+            }
+
+            if !self.check(terminal)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Checks every provider, logging what was notified, and tells if the monitor goes on.
+    /// Failures are logged too, as the other providers are still worth watching.
+    fn check(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<bool> {
+        for index in 0..self.runners.len() {
+            self.draw(terminal, "checking...")?;
+            let runner = &self.runners[index];
+            let provider = runner.provider_name();
+            match runner.check_once_changes() {
+                Ok(None) => {}
+                Ok(Some(changes)) => {
+                    for (line, color) in describe_changes(&changes) {
+                        self.push_log(line, color);
+                    }
+                }
+                Err(error) => self.push_log(format!("{provider}: {error:#}"), Color::Red),
+            }
+            // checks may be long, so the user may have quit meanwhile
+            if quit_requested(Duration::ZERO)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Adds a timestamped line to the log, forgetting the oldest ones.
+    fn push_log(&mut self, line: String, color: Color) {
+        let time = chrono::Local::now().format("%H:%M:%S");
+        self.log
+            .push_front(Line::from(format!("{time} {line}")).fg(color));
+        self.log.truncate(LOG_LENGTH);
+    }
+
+    /// Gets the servers checked since the monitor started, from their stored states.
+    /// This includes the servers which patterns expanded to.
+    fn get_rows(&self) -> anyhow::Result<Vec<ServerRow>> {
+        let now = get_timestamp();
+        let age = |timestamp: Option<u64>, never: &str| match timestamp {
+            Some(timestamp) => format_age(Duration::from_secs(now.saturating_sub(timestamp))),
+            None => never.to_string(),
+        };
+
+        let mut rows = Vec::new();
+        for runner in self.runners.iter() {
+            let provider = runner.provider_name();
+            let storage = runner.storage();
+            let entries = storage.list_entries().context("while listing storage")?;
+            let mut servers: Vec<String> = entries
+                .into_iter()
+                .filter(|entry| {
+                    entry.kind == StoredKind::ServerState
+                        && entry.handler == provider
+                        && entry.modified >= self.started
+                })
+                .filter_map(|entry| entry.server)
+                .collect();
+            servers.sort();
+
+            for server in servers {
+                let Some(state) = storage
+                    .get_server_state(provider, &server)
+                    .with_context(|| format!("while reading state of server {server}"))?
+                else {
+                    continue;
+                };
+                let status = match (state.available, state.observed) {
+                    (true, true) => ("available", Color::Green),
+                    (false, false) => ("unavailable", Color::Red),
+                    (false, true) => ("appearing", Color::Yellow),
+                    (true, false) => ("disappearing", Color::Yellow),
+                };
+                rows.push(ServerRow {
+                    provider,
+                    server,
+                    status,
+                    last_check: age(state.last_check, "never"),
+                    last_available: age(state.last_available, "never"),
+                });
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Draws the whole screen, with the current status of the monitor.
+    fn draw(&self, terminal: &mut DefaultTerminal, status: &str) -> anyhow::Result<()> {
+        let rows = self.get_rows()?;
+        terminal.draw(|frame| self.render(frame, &rows, status))?;
+        Ok(())
+    }
+
+    /// Renders the table first, then as much of the log as fits.
+    fn render(&self, frame: &mut Frame, rows: &[ServerRow], status: &str) {
+        // the borders and the header of the table, then a line per server
+        let table_height = rows.len().max(1) as u16 + 3;
+        let [title_area, table_area, log_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Max(table_height),
+            Constraint::Min(3),
+        ])
+        .areas(frame.area());
+
+        let title = Line::from(vec![
+            "dsaw monitor".bold(),
+            Span::raw(format!(" - {} servers - {status} - q to quit", rows.len())),
+        ]);
+        frame.render_widget(title, title_area);
+
+        let mut table_rows: Vec<Row> = rows
+            .iter()
+            .map(|row| {
+                let (status, color) = row.status;
+                Row::new(vec![
+                    Cell::from(row.provider),
+                    Cell::from(row.server.as_str()),
+                    Cell::from(status).fg(color),
+                    Cell::from(row.last_check.as_str()),
+                    Cell::from(row.last_available.as_str()),
+                ])
+            })
+            .collect();
+        if table_rows.is_empty() {
+            table_rows.push(Row::new(vec![Cell::from("waiting for the first check")]).dim());
+        }
+        let widths = [
+            Constraint::Length(16),
+            Constraint::Length(24),
+            Constraint::Length(13),
+            Constraint::Length(18),
+            Constraint::Fill(1),
+        ];
+        let table = Table::new(table_rows, widths)
+            .header(
+                Row::new(vec![
+                    "PROVIDER",
+                    "SERVER",
+                    "STATUS",
+                    "LAST CHECK",
+                    "LAST SEEN AVAILABLE",
+                ])
+                .style(Style::new().bold()),
+            )
+            .block(Block::bordered().title("Servers"));
+        frame.render_widget(table, table_area);
+
+        let log = match self.log.is_empty() {
+            true => List::new(vec![Line::from("nothing notified yet").dim()]),
+            false => List::new(self.log.iter().cloned()),
+        };
+        frame.render_widget(
+            log.block(Block::bordered().title("Recent notifications")),
+            log_area,
+        );
+    }
+}
+
+/// Waits for a key press for up to `timeout`, and tells if it asks to quit,
+/// with `q`, `Esc` or `Ctrl-C`, as raw mode does not turn the latter into a signal.
+fn quit_requested(timeout: Duration) -> io::Result<bool> {
+    if !event::poll(timeout)? {
+        return Ok(false);
+    }
+    match event::read()? {
+        Event::Key(key) if key.kind == KeyEventKind::Press => Ok(match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => true,
+            KeyCode::Char('c') => key.modifiers.contains(KeyModifiers::CONTROL),
+            _ => false,
+        }),
+        _ => Ok(false),
+    }
+}
+
+/// Formats a countdown as minutes and seconds, or hours and minutes.
+fn format_countdown(remaining: Duration) -> String {
+    let seconds = remaining.as_secs();
+    match seconds {
+        0..3600 => format!("{:02}:{:02}", seconds / 60, seconds % 60),
+        _ => format!("{}h{:02}", seconds / 3600, seconds / 60 % 60),
+    }
+}
+
+/// Describes every notified change, one line per server, with its color.
+fn describe_changes(changes: &CheckResult) -> Vec<(String, Color)> {
+    let provider = &changes.provider_name;
+    let now = get_timestamp();
+    let mut lines = Vec::new();
+    for server in changes.available_servers.iter() {
+        let seen = match changes.last_seen_available.get(server) {
+            Some(timestamp) => format!(
+                " (last seen in stock {})",
+                format_age(Duration::from_secs(now.saturating_sub(*timestamp)))
+            ),
+            None => String::new(),
+        };
+        lines.push((
+            format!("{provider}: {server} available{seen}"),
+            Color::Green,
+        ));
+    }
+    for server in changes.unavailable_servers.iter() {
+        lines.push((format!("{provider}: {server} unavailable"), Color::Red));
+    }
+    lines
+}
@@ -0,0 +1,220 @@
+use crate::notifiers::NotifierTrait;
+use crate::providers::{CheckRunner, ProviderTrait, StockLevel, WatchRunner};
+use crate::schedule::Scheduler;
+use crate::storage::CheckResultStorage;
+use crate::CheckResult;
+use anyhow::Context;
+use serde::Serialize;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+// Programmatic watching, for programs embedding the library
+
+/// What happens during the checks of a `Watcher`, for programs reacting to it themselves,
+/// like user interfaces or bots, instead of going through a notifier or parsing its output.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AvailabilityEvent {
+    /// A provider is about to be checked
+    CheckStarted { provider_name: String },
+    /// A server became available, or its stock or its price changed as watched
+    ServerBecameAvailable {
+        provider_name: String,
+        server: String,
+        order_url: Option<String>,
+        stock: Option<StockLevel>,
+        locations: Vec<String>,
+        price: Option<f64>,
+        previous_price: Option<f64>,
+        /// When the server was last seen available before, as a unix timestamp
+        last_seen_available: Option<u64>,
+    },
+    /// A server is not available anymore
+    ServerBecameUnavailable {
+        provider_name: String,
+        server: String,
+    },
+    /// A check failed, with its error chain
+    CheckFailed {
+        provider_name: String,
+        message: String,
+        /// Tells if the next checks may succeed, otherwise the watch gives up
+        transient: bool,
+    },
+    /// A provider was checked, after its changes, if any
+    CheckSucceeded { provider_name: String },
+    /// Changes could not be notified through a notifier, which is not retried
+    NotifierFailed {
+        notifier_name: String,
+        provider_name: String,
+        message: String,
+    },
+}
+
+impl AvailabilityEvent {
+    /// Splits the changes notified by a check into one event per server
+    pub fn from_changes(changes: &CheckResult) -> Vec<Self> {
+        let provider_name = &changes.provider_name;
+        let available = changes.available_servers.iter().map(|server| {
+            AvailabilityEvent::ServerBecameAvailable {
+                provider_name: provider_name.clone(),
+                server: server.clone(),
+                order_url: changes.order_urls.get(server).cloned(),
+                stock: changes.stock_levels.get(server).copied(),
+                locations: changes.locations.get(server).cloned().unwrap_or_default(),
+                price: changes.prices.get(server).copied(),
+                previous_price: changes.previous_prices.get(server).copied(),
+                last_seen_available: changes.last_seen_available.get(server).copied(),
+            }
+        });
+        let unavailable = changes.unavailable_servers.iter().map(|server| {
+            AvailabilityEvent::ServerBecameUnavailable {
+                provider_name: provider_name.clone(),
+                server: server.clone(),
+            }
+        });
+        available.chain(unavailable).collect()
+    }
+}
+
+/// Handles every event of a `Watcher`, on the thread running it
+type EventHandler = Box<dyn FnMut(&AvailabilityEvent) + Send>;
+
+/// Watches several sets of servers, possibly of different providers, and hands
+/// the changes over to callbacks or channels as typed events, for programs embedding
+/// the library. Changes are detected as by the `check` command, from the stored states,
+/// and are only notified through the notifiers which were added.
+/// It can be moved to a worker thread, to run its checks aside of the program.
+#[derive(Default)]
+pub struct Watcher {
+    runners: Vec<CheckRunner>,
+    notifiers: Vec<Box<dyn NotifierTrait>>,
+    handlers: Vec<EventHandler>,
+}
+
+impl Watcher {
+    /// Builds an instance without any server nor handler
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watches servers of a provider, which was already built, like by its builder.
+    /// Patterns are supported, and an empty list of servers watches the whole inventory.
+    pub fn add_servers(
+        &mut self,
+        provider: Box<dyn ProviderTrait>,
+        servers: &[String],
+        storage: CheckResultStorage,
+    ) -> anyhow::Result<()> {
+        let provider_name = provider.name();
+        let runner = CheckRunner::from_provider(provider, servers, storage)
+            .with_context(|| format!("while setting up watch of {provider_name}"))?
+            .with_print(false);
+        self.runners.push(runner);
+        Ok(())
+    }
+
+    /// Calls a handler with every event, in the order they happen
+    pub fn add_handler(&mut self, handler: impl FnMut(&AvailabilityEvent) + Send + 'static) {
+        self.handlers.push(Box::new(handler));
+    }
+
+    /// Sends every event through a channel. Events are dropped once its receiver is gone.
+    pub fn add_channel(&mut self, sender: Sender<AvailabilityEvent>) {
+        self.add_handler(move |event| {
+            let _ = sender.send(event.clone());
+        });
+    }
+
+    /// Gets a receiver of every event from now on, which can be moved to another thread.
+    pub fn events(&mut self) -> Receiver<AvailabilityEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.add_channel(sender);
+        receiver
+    }
+
+    /// Also notifies the changes through a notifier, its failures being handed over as events.
+    pub fn add_notifier(&mut self, notifier: Box<dyn NotifierTrait>) {
+        self.notifiers.push(notifier);
+    }
+
+    /// Hands an event over to every handler
+    fn emit(handlers: &mut [EventHandler], event: &AvailabilityEvent) {
+        for handler in handlers.iter_mut() {
+            handler(event);
+        }
+    }
+
+    /// Checks every set of servers once, and hands over their changes.
+    /// A failed check is handed over too, and does not prevent checking the others,
+    /// but the first permanent failure (invalid credentials, unknown server, ...) is returned.
+    pub fn run_once(&mut self) -> anyhow::Result<()> {
+        let mut permanent = None;
+        for runner in self.runners.iter() {
+            let provider_name = runner.provider_name().to_string();
+            Self::emit(
+                &mut self.handlers,
+                &AvailabilityEvent::CheckStarted {
+                    provider_name: provider_name.clone(),
+                },
+            );
+            let changes = match runner.check_once_changes() {
+                Ok(changes) => changes,
+                Err(error) => {
+                    let transient = WatchRunner::is_transient(&error);
+                    Self::emit(
+                        &mut self.handlers,
+                        &AvailabilityEvent::CheckFailed {
+                            provider_name,
+                            message: format!("{error:#}"),
+                            transient,
+                        },
+                    );
+                    if !transient && permanent.is_none() {
+                        permanent = Some(error);
+                    }
+                    continue;
+                }
+            };
+
+            if let Some(changes) = changes {
+                for event in AvailabilityEvent::from_changes(&changes).iter() {
+                    Self::emit(&mut self.handlers, event);
+                }
+                for notifier in self.notifiers.iter() {
+                    if let Err(error) = notifier.notify(&changes) {
+                        Self::emit(
+                            &mut self.handlers,
+                            &AvailabilityEvent::NotifierFailed {
+                                notifier_name: notifier.name().to_string(),
+                                provider_name: provider_name.clone(),
+                                message: format!("{:#}", anyhow::Error::new(error)),
+                            },
+                        );
+                    }
+                }
+            }
+            Self::emit(
+                &mut self.handlers,
+                &AvailabilityEvent::CheckSucceeded { provider_name },
+            );
+        }
+        match permanent {
+            Some(error) => Err(error.context("permanent error, giving up")),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks every set of servers as the scheduler tells, until a permanent failure.
+    pub fn run_forever(&mut self, scheduler: &Scheduler) -> anyhow::Result<()> {
+        let mut first = true;
+        loop {
+            let delay = scheduler
+                .next_delay(first)
+                .context("while computing the next check time")?;
+            first = false;
+            thread::sleep(delay);
+            self.run_once()?;
+        }
+    }
+}
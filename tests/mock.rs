@@ -0,0 +1,226 @@
+// End-to-end tests of the checks, storage and notifications, with the mock handlers
+#![cfg(feature = "testing")]
+
+use dedicated_server_availability_watcher::digest::DigestBuffer;
+use dedicated_server_availability_watcher::outbox::Outbox;
+use dedicated_server_availability_watcher::providers::mock::MockProvider;
+use dedicated_server_availability_watcher::providers::CheckRunner;
+use dedicated_server_availability_watcher::storage::{CheckResultStorage, Debounce, ServerState};
+use dedicated_server_availability_watcher::{with_env_overrides, CheckResult};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs, process};
+
+/// Builds an empty directory of its own for a test
+fn test_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("dsaw-test-{}-{name}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Writes the inventory of the mock provider, with the availability of each server
+fn write_fixture(path: &Path, servers: &[(&str, bool)]) {
+    let servers: Vec<String> = servers
+        .iter()
+        .map(|(reference, available)| {
+            format!(r#"{{"reference": "{reference}", "available": {available}}}"#)
+        })
+        .collect();
+    fs::write(path, format!("[{}]", servers.join(","))).unwrap();
+}
+
+/// Builds a runner checking the fixture, and notifying through the mock notifier
+fn build_runner(dir: &Path, servers: &[&str], debounce: Debounce) -> CheckRunner {
+    let fixture = dir.join("inventory.json");
+    let storage = dir.join("storage");
+    fs::create_dir_all(&storage).unwrap();
+    let vars = BTreeMap::from([(
+        "MOCK_NOTIFIER_PATH".to_string(),
+        dir.join("calls.jsonl").display().to_string(),
+    )]);
+    let servers: Vec<String> = servers.iter().map(|server| server.to_string()).collect();
+    with_env_overrides(&vars, || {
+        CheckRunner::new_with_provider(
+            Box::new(MockProvider::from_file(&fixture.display().to_string()).unwrap()),
+            &servers,
+            &["mock".to_string()],
+            CheckResultStorage::new(&storage).unwrap(),
+            false,
+            debounce,
+            false,
+        )
+    })
+    .unwrap()
+    .with_print(false)
+}
+
+/// Reads the calls recorded by the mock notifier
+fn read_calls(dir: &Path) -> Vec<String> {
+    fs::read_to_string(dir.join("calls.jsonl"))
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Builds a result from its available and unavailable servers
+fn build_result(provider: &str, available: &[&str], unavailable: &[&str]) -> CheckResult {
+    serde_json::from_value(serde_json::json!({
+        "provider_name": provider,
+        "available_servers": available,
+        "unavailable_servers": unavailable,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn check_runner_notifies_changes_only() {
+    let dir = test_dir("changes");
+    let fixture = dir.join("inventory.json");
+    write_fixture(&fixture, &[("small-box", false), ("big-box", true)]);
+    let runner = build_runner(&dir, &["small-box", "big-box"], Debounce::default());
+
+    // servers never seen before are deemed unavailable
+    let changes = runner.check_once_changes().unwrap().unwrap();
+    assert_eq!(changes.available_servers, vec!["big-box"]);
+    assert!(changes.unavailable_servers.is_empty());
+
+    // nothing changed
+    assert!(runner.check_once_changes().unwrap().is_none());
+
+    write_fixture(&fixture, &[("small-box", true), ("big-box", false)]);
+    let changes = runner.check_once_changes().unwrap().unwrap();
+    assert_eq!(changes.available_servers, vec!["small-box"]);
+    assert_eq!(changes.unavailable_servers, vec!["big-box"]);
+
+    assert_eq!(
+        read_calls(&dir),
+        vec![
+            r#"{"call":"notify","provider":"mock","servers":["big-box"]}"#,
+            r#"{"call":"notify","provider":"mock","servers":["small-box"]}"#,
+        ]
+    );
+}
+
+#[test]
+fn check_runner_confirms_changes_after_debounce() {
+    let dir = test_dir("debounce");
+    let fixture = dir.join("inventory.json");
+    write_fixture(&fixture, &[("small-box", true)]);
+    let debounce = Debounce {
+        checks: Some(2),
+        duration: None,
+    };
+    let runner = build_runner(&dir, &["small-box"], debounce);
+
+    assert!(runner.check_once_changes().unwrap().is_none());
+    let changes = runner.check_once_changes().unwrap().unwrap();
+    assert_eq!(changes.available_servers, vec!["small-box"]);
+
+    // a single unavailable check is not confirmed, and resets the streak
+    write_fixture(&fixture, &[("small-box", false)]);
+    assert!(runner.check_once_changes().unwrap().is_none());
+    write_fixture(&fixture, &[("small-box", true)]);
+    assert!(runner.check_once_changes().unwrap().is_none());
+
+    assert_eq!(read_calls(&dir).len(), 1);
+}
+
+#[test]
+fn server_state_observe_debounces_by_checks() {
+    let debounce = Debounce {
+        checks: Some(3),
+        duration: None,
+    };
+    let mut state = ServerState::new();
+    assert!(!state.observe(true, 100, &debounce));
+    assert!(!state.observe(true, 200, &debounce));
+    assert!(state.observe(true, 300, &debounce));
+    assert!(state.available);
+
+    // confirmed availability does not change again
+    assert!(!state.observe(true, 400, &debounce));
+
+    // a different observation restarts the streak
+    assert!(!state.observe(false, 500, &debounce));
+    assert!(!state.observe(true, 600, &debounce));
+    assert!(state.available);
+    assert_eq!(state.last_available, Some(600));
+}
+
+#[test]
+fn server_state_observe_debounces_by_duration() {
+    let debounce = Debounce {
+        checks: None,
+        duration: Some(Duration::from_secs(60)),
+    };
+    let mut state = ServerState::new();
+    assert!(!state.observe(true, 1000, &debounce));
+    assert!(!state.observe(true, 1059, &debounce));
+    assert!(state.observe(true, 1060, &debounce));
+
+    // without any threshold, every change is confirmed immediately
+    assert!(state.observe(false, 1070, &Debounce::default()));
+    assert!(!state.available);
+}
+
+#[test]
+fn digest_buffer_merges_changes() {
+    let mut digest = DigestBuffer::default();
+    assert!(digest.is_empty());
+
+    digest.add(&build_result("ovh", &["a", "b"], &[]), 100);
+    digest.add(&build_result("ovh", &["c"], &["a"]), 200);
+    assert_eq!(digest.started(), 100);
+
+    // the latest availability of a server replaces any previous one
+    let result = digest.to_result();
+    assert_eq!(result.provider_name, "ovh");
+    assert_eq!(result.available_servers, vec!["b", "c"]);
+    assert_eq!(result.unavailable_servers, vec!["a"]);
+
+    // several providers prefix their servers
+    digest.add(&build_result("online", &["d"], &[]), 300);
+    let result = digest.to_result();
+    assert_eq!(result.provider_name, "online,ovh");
+    assert_eq!(result.available_servers, vec!["online:d", "ovh:b", "ovh:c"]);
+    assert_eq!(result.unavailable_servers, vec!["ovh:a"]);
+}
+
+#[test]
+fn outbox_backs_off_and_expires() {
+    let mut outbox = Outbox::default();
+    assert!(!outbox.is_due(0));
+
+    outbox.add(&build_result("ovh", &["a"], &[]), 1000);
+    assert!(outbox.is_due(1000));
+
+    // the delay doubles after every failure
+    outbox.failed(1000);
+    assert!(!outbox.is_due(1059));
+    assert!(outbox.is_due(1060));
+    outbox.failed(1060);
+    assert!(!outbox.is_due(1179));
+    assert!(outbox.is_due(1180));
+    assert_eq!(outbox.attempts(), 2);
+
+    // up to an hour
+    for _ in 0..10 {
+        outbox.failed(2000);
+    }
+    assert!(!outbox.is_due(5599));
+    assert!(outbox.is_due(5600));
+
+    // changes failing in a row are delivered together
+    outbox.add(&build_result("ovh", &["b"], &["a"]), 2000);
+    let result = outbox.to_result();
+    assert_eq!(result.available_servers, vec!["b"]);
+    assert_eq!(result.unavailable_servers, vec!["a"]);
+
+    // given up from the first pending change
+    let max_age = Duration::from_secs(3600);
+    assert!(!outbox.is_expired(max_age, 4599));
+    assert!(outbox.is_expired(max_age, 4600));
+}